@@ -0,0 +1,126 @@
+#[cfg(feature = "3d")]
+use std::cell::RefCell;
+use std::convert::Infallible;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dmfg_embedded_graphics_polygon::polygon::{Polygon, PolygonOwned, PolygonSet};
+#[cfg(feature = "3d")]
+use dmfg_embedded_graphics_polygon::polygon_3d::{DepthMap, Polygon3d};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle, Rectangle};
+use embedded_graphics::{Drawable, Pixel};
+
+/// A target that only counts what it is asked to draw.
+struct Sink(u64);
+
+impl OriginDimensions for Sink {
+    fn size(&self) -> Size {
+        Size::new(512, 512)
+    }
+}
+
+impl DrawTarget for Sink {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+        self.0 += pixels.into_iter().count() as u64;
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, _color: Self::Color) -> Result<(), Self::Error> {
+        self.0 += area.size.width as u64 * area.size.height as u64;
+        Ok(())
+    }
+}
+
+fn regular(count: usize, radius: f64) -> Vec<Point> {
+    (0..count).map(|i| {
+        let angle = i as f64 * std::f64::consts::TAU / count as f64;
+        Point::new((256.0 + radius * angle.cos()).round() as i32, (256.0 + radius * angle.sin()).round() as i32)
+    }).collect()
+}
+
+fn fill(c: &mut Criterion) {
+    let convex = regular(50, 200.0);
+    let style = PrimitiveStyle::with_fill(BinaryColor::On);
+    c.bench_function("fill convex 50 vertices", |b| b.iter(|| {
+        let mut sink = Sink(0);
+        Polygon::new(black_box(&convex)).into_styled(style).draw(&mut sink).unwrap();
+        sink.0
+    }));
+    c.bench_function("pixel count convex 50 vertices", |b| b.iter(|| Polygon::new(black_box(&convex)).pixel_count()));
+}
+
+#[cfg(feature = "3d")]
+fn fill_3d(c: &mut Criterion) {
+    // a tilted 100 by 100 quad, so the depth changes across every span
+    let quad = [(Point::new(100, 100), 1.0), (Point::new(200, 100), 2.0), (Point::new(200, 200), 3.0), (Point::new(100, 200), 2.0)];
+    let style = PrimitiveStyle::with_fill(BinaryColor::On);
+    let mut buffer = DepthMap::new(512, 512);
+    c.bench_function("fill 3d quad 100 by 100", |b| b.iter(|| {
+        buffer.clear();
+        let mut sink = Sink(0);
+        Polygon3d::new(black_box(&quad)).draw_3d(&style, &mut buffer, &mut sink).unwrap();
+        sink.0
+    }));
+    // the deprecated shared buffer, borrowed once a draw
+    let shared = RefCell::new(buffer);
+    c.bench_function("fill 3d quad 100 by 100 shared depth map", |b| b.iter(|| {
+        shared.borrow_mut().clear();
+        let mut sink = Sink(0);
+        #[allow(deprecated)]
+        Polygon3d::with_depth_map(black_box(&quad), &shared).into_styled(style).draw(&mut sink).unwrap();
+        sink.0
+    }));
+}
+
+fn bounding_box(c: &mut Criterion) {
+    let vertices = regular(500, 200.0);
+    c.bench_function("bounding box borrowed 500 vertices", |b| b.iter(|| Polygon::new(black_box(&vertices)).bounding_box()));
+    let owned = PolygonOwned::new(vertices.clone());
+    c.bench_function("bounding box owned 500 vertices", |b| b.iter(|| black_box(&owned).bounding_box()));
+}
+
+fn hit_test(c: &mut Criterion) {
+    // 500 small hexagons in a 25 by 20 layout over a 1000 by 800 area
+    let hexagon = regular(6, 15.0);
+    let mut set = PolygonSet::new();
+    for i in 0..500 {
+        set.push(Polygon::with_translate(&hexagon, Point::new((i % 25) * 40 - 256, (i / 25) * 40 - 256)));
+    }
+    let points: Vec<Point> = (0..100).map(|i| Point::new((i * 397) % 1000, (i * 211) % 800)).collect();
+    c.bench_function("hit test 500 polygons indexed", |b| b.iter(|| points.iter().map(|p| set.query_point(black_box(*p)).count()).sum::<usize>()));
+    c.bench_function("hit test 500 polygons linear", |b| b.iter(|| points.iter().map(|p| set.iter().filter(|polygon| polygon.contains(black_box(*p))).count()).sum::<usize>()));
+    let area = Rectangle::new(Point::new(300, 300), Size::new(100, 100));
+    c.bench_function("rectangle query 500 polygons", |b| b.iter(|| set.query_rect(black_box(area)).count()));
+}
+
+fn simplified(c: &mut Criterion) {
+    // a 3000 vertex wobbly ring scaled down to about 30 pixels across
+    let coastline: Vec<Point> = (0..3000).map(|i| {
+        let angle = i as f64 * std::f64::consts::TAU / 3000.0;
+        let radius = 1500.0 + 60.0 * (angle * 37.0).sin() + 25.0 * (angle * 211.0).cos();
+        Point::new((radius * angle.cos()).round() as i32, (radius * angle.sin()).round() as i32)
+    }).collect();
+    let mut polygon = PolygonOwned::with_translate(coastline, Point::new(20, 20));
+    polygon.scale(0.01);
+    let style = PrimitiveStyle::with_fill(BinaryColor::On);
+    c.bench_function("fill 3000 vertices drawn 30 px", |b| b.iter(|| {
+        let mut sink = Sink(0);
+        black_box(&polygon).as_polygon().into_styled(style).draw(&mut sink).unwrap();
+        sink.0
+    }));
+    c.bench_function("fill simplified 3000 vertices drawn 30 px", |b| b.iter(|| {
+        let mut sink = Sink(0);
+        black_box(&polygon).draw_simplified(&style, 0.5, &mut sink).unwrap();
+        sink.0
+    }));
+}
+
+#[cfg(feature = "3d")]
+criterion_group!(benches, fill, fill_3d, bounding_box, hit_test, simplified);
+#[cfg(not(feature = "3d"))]
+criterion_group!(benches, fill, bounding_box, hit_test, simplified);
+criterion_main!(benches);
@@ -1,404 +1,650 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 pub mod polygon {
-    use std::cmp::Ordering;
-    use std::collections::{BTreeMap, HashMap, VecDeque};
-    use std::fmt::Debug;
-    use std::iter;
+    mod affected;
+    mod bands;
+    mod clip;
+    mod collision;
+    mod compact;
+    mod compressed;
+    mod containment;
+    mod count;
+    mod coverage;
+    mod decompose;
+    mod diff;
+    mod distance;
+    mod flood;
+    #[cfg(feature = "geo")]
+    mod geo;
+    mod geometry;
+    mod interior;
+    mod job;
+    mod intersection;
+    mod mapping;
+    mod markers;
+    mod merge;
+    pub(crate) mod math;
+    mod morph;
+    mod multi;
+    mod outline;
+    mod perimeter;
+    mod raster;
+    #[cfg(feature = "verify")]
+    pub mod reference;
+    mod rle;
+    mod rotation;
+    mod scanline;
+    mod set;
+    mod shadow;
+    mod shapes;
+    mod simplify;
+    mod snap;
+    mod split;
+    mod sprite;
+    mod stroke;
+    mod style;
+    mod subpixel;
+    #[cfg(feature = "svg")]
+    mod svg;
+    mod tiles;
+    mod trace;
+    mod visibility;
+    mod waveform;
+    #[cfg(feature = "wkt")]
+    mod wkt;
+
+    use core::cell::{Cell, RefCell};
+    use core::fmt::{self, Debug};
+    use core::iter;
+    use alloc::vec::Vec;
     use embedded_graphics::draw_target::DrawTarget;
     use embedded_graphics::geometry::{Dimensions, Point};
     use embedded_graphics::pixelcolor::PixelColor;
     use embedded_graphics::prelude::Size;
-    use embedded_graphics::primitives::{Line, Polyline, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
+    use embedded_graphics::primitives::{ContainsPoint, Line, Polyline, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
     use embedded_graphics::transform::Transform;
-    use itertools::Itertools;
+    use affected::AffectedArea;
+    pub(crate) use scanline::Scanlines;
+
+    pub use compact::Polygon16;
+    pub use compressed::{CompressedPolygon, CompressedVertices, DecodeError};
+    pub use diff::draw_diff;
+    pub use flood::{flood_fill, flood_fill_bounded, FloodError, FloodMode, GetPixel};
+    pub use job::{FillJob, Progress};
+    pub use mapping::{CoordinateMapping, CoordinateOutOfRange};
+    pub use markers::{MarkerColor, VertexMarker, VertexMarkerStyle};
+    pub use multi::MultiPolygon;
+    pub use raster::BitOrder;
+    pub use rle::RleError;
+    pub use scanline::{Span, Spans};
+    pub use set::PolygonSet;
+    pub use shadow::ShadowStyle;
+    pub use shapes::ShapeError;
+    pub use snap::{SnapCollapsed, SnapMode};
+    pub use sprite::PolygonSprite;
+    pub use stroke::{stroke_polyline, CapStyle, JoinStyle};
+    pub use style::{DashPattern, FillRule, PolygonStyle, PolygonStyleBuilder};
+    pub use subpixel::{SubpixelPolygon, SUBPIXEL_SCALE};
+    #[cfg(feature = "svg")]
+    pub use svg::{SvgPathError, SvgPathErrorKind};
+    #[cfg(feature = "wkt")]
+    pub use wkt::{WktError, WktErrorKind, WktGeometry};
+    #[cfg(feature = "3d")]
+    pub(crate) use outline::blend;
 
+    /// A closed polygon over a borrowed list of vertices.
+    ///
+    /// Filling covers every pixel whose coordinate lies inside the polygon or exactly on its
+    /// boundary, so polygons sharing an edge tile without gaps.
+    ///
+    /// The vertices and translate offset are read through [`vertices`](Self::vertices) and
+    /// [`translate`](Self::translate), the same methods [`PolygonOwned`] has. Build one with
+    /// [`new`](Self::new) or [`with_translate`](Self::with_translate) and move it with
+    /// [`set_translate`](Self::set_translate).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub struct Polygon<'a> {
-        pub translate: Point,
-        pub vertices: &'a [Point],
+        translate: Point,
+        vertices: &'a [Point],
     }
 
     impl<'a> Polygon<'a> {
-        pub fn new(vertices: &'a [Point]) -> Self{
+        /// Creates a polygon without offset. This is a `const fn`, so a polygon over a `static`
+        /// vertex array can itself be a `static` and live in flash.
+        pub const fn new(vertices: &'a [Point]) -> Self{
+            Self::with_translate(vertices, Point::zero())
+        }
+
+        pub const fn with_translate(vertices: &'a [Point], translate: Point) -> Self {
             Polygon{
-                translate: Point::zero(),
+                translate,
                 vertices,
             }
         }
     }
 
+    impl<'a> Polygon<'a> {
+        /// Returns the vertices, without the translate offset.
+        pub const fn vertices(&self) -> &'a [Point] {
+            self.vertices
+        }
+
+        /// Returns the offset added to every vertex when drawing and measuring.
+        pub const fn translate(&self) -> Point {
+            self.translate
+        }
+
+        pub fn set_translate(&mut self, translate: Point) {
+            self.translate = translate;
+        }
+
+        /// Returns the edges of the closed outline with the translate offset applied, from each
+        /// vertex to the next and finally from the last vertex back to the first.
+        ///
+        /// Fewer than two vertices have no edges, and exactly two give the single segment between
+        /// them.
+        pub fn edges(&self) -> impl Iterator<Item = Line> + 'a {
+            let translate = self.translate();
+            ring_edges(self.vertices().iter().map(move |v| *v + translate))
+        }
+
+        /// Returns the horizontal runs of pixels the fill covers, top to bottom and left to right
+        /// within a row, with the translate offset applied. Runs on the same row never touch, so
+        /// each pixel belongs to exactly one span.
+        pub fn spans(&self) -> Spans {
+            Spans::new(self.edges())
+        }
+
+        /// Fills the polygon with `color`, skipping every pixel for which `mask` returns `false`.
+        ///
+        /// `mask` is called exactly once per pixel inside the polygon, and each run of accepted
+        /// pixels is still written with a single `fill_solid`.
+        pub fn fill_masked<C, D, F>(&self, color: C, mask: F, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C>, F: FnMut(Point) -> bool {
+            fill_scanlines_masked(Scanlines::new(self.edges()), color, mask, target)
+        }
+    }
+
+    /// Fills the spans produced by `scanlines` as [`Polygon::fill_masked`] does.
+    fn fill_scanlines_masked<C, D, F>(mut scanlines: Scanlines, color: C, mut mask: F, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C>, F: FnMut(Point) -> bool {
+        let mut spans = Vec::new();
+        while let Some(y) = scanlines.next_row(&mut spans) {
+            for &(start, end) in spans.iter() {
+                let mut run_start = None;
+                for x in start..=end {
+                    match (mask(Point::new(x, y)), run_start) {
+                        (true, None) => run_start = Some(x),
+                        (false, Some(run)) => {
+                            target.fill_solid(&Rectangle::new(Point::new(run, y), Size::new(x.abs_diff(run), 1)), color)?;
+                            run_start = None;
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(run) = run_start {
+                    target.fill_solid(&Rectangle::new(Point::new(run, y), Size::new(end.abs_diff(run) + 1, 1)), color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     impl<'a> Dimensions for Polygon<'a> {
+        /// Returns the box around the vertices, moved by the translate offset.
         fn bounding_box(&self) -> Rectangle {
-            let (min_x, max_x, min_y, max_y) = self.vertices.iter().fold((i32::max_value(), i32::min_value(), i32::max_value(), i32::min_value()), |mut old, point|{
-                old.0 = old.0.min(point.x);
-                old.1 = old.1.max(point.x);
-                old.2 = old.2.min(point.y);
-                old.3 = old.3.max(point.y);
-                old
-            });
-            let width = (max_x - min_x) as u32;
-            let height = (max_y - min_y) as u32;
-            Rectangle::new(Point::new(min_x, min_y), Size::new(width, height))
+            vertex_bounds(self.vertices().iter().map(|v| *v + self.translate()))
         }
     }
 
     impl<'a> Primitive for Polygon<'a> {}
 
+    impl<'a> ContainsPoint for Polygon<'a> {
+        /// Even-odd containment that counts points on the boundary as inside, matching the pixels
+        /// covered by the fill.
+        fn contains(&self, point: Point) -> bool {
+            edges_contain(self.edges(), point)
+        }
+    }
+
     impl<'a, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for Polygon<'a> {
         type Color = C;
-        type Output = ();
+        type Output = Rectangle;
 
+        /// Draws the polygon and returns the smallest rectangle holding every pixel written inside
+        /// the target's bounds, or a zero-sized rectangle if there were none.
         fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color=Self::Color> {
+            let mut target = AffectedArea::new(target);
             match style.stroke_width {
                 0 => {
-                    let mut global_edge_table = Vec::new();
-                    self.vertices.iter().enumerate().map(|(i, vertex)|{
-                        let next_vertex = &self.vertices[(i+1) % self.vertices.len()];
-                        let min_y_and_corresponding_x = if vertex.y < next_vertex.y {vertex} else {next_vertex};
-                        let max_y = vertex.y.max(next_vertex.y);
-                        // let min_x = vertex.x.min(next_vertex.x);
-                        // let max_x = vertex.x.max(next_vertex.x);
-                        let y_diff = next_vertex.y - vertex.y;
-                        let x_diff = next_vertex.x - vertex.x;
-                        let slope_inv = x_diff as f32 / y_diff as f32;
-                        //println!("{slope_inv} ({vertex}) ({next_vertex})");
-                        (min_y_and_corresponding_x, max_y, slope_inv)
-                    })
-                        .filter(|(_, _, slope)|slope.is_finite())
-                        .for_each(|v|{
-                            if global_edge_table.len() == 0 {
-                                global_edge_table.push(v);
-                                return;
-                            }
-                            let (min_y_and_corresponding_x, _max_y, _slope_inv) = v;
-                            let mut insertion_index = 0;
-                            while insertion_index < global_edge_table.len() && min_y_and_corresponding_x.y > global_edge_table[insertion_index].0.y {
-                                if insertion_index < global_edge_table.len() {
-                                    insertion_index += 1;
-                                }
-                            }
-
-                            while insertion_index < global_edge_table.len() && min_y_and_corresponding_x.x > global_edge_table[insertion_index].0.x && min_y_and_corresponding_x.y == global_edge_table[insertion_index].0.y {
-                                if insertion_index < global_edge_table.len() {
-                                    insertion_index += 1;
-                                }
-                            }
-                            global_edge_table.insert(insertion_index, v);
-                            //println!("global {:?}", global_edge_table);
-                        });
-                    let mut active_edge_table = Vec::new();
-                    if global_edge_table.len() > 1 {
-                        let mut scan_line = global_edge_table[0].0.y;
-                        // populate active edge table
-                        loop {
-                            if let Some((edge, max_y, slope_inv)) = global_edge_table.get(0).and_then(|edge| { if edge.0.y <= scan_line { Some(edge) } else { None } }) {
-                                // remove element and add to active edge table if within scan line range
-                                active_edge_table.push((*max_y, edge.x as f32, *slope_inv));
-                                let _ = global_edge_table.remove(0);
-                            } else {
-                                break;
-                            }
-                        }
-
-                        loop {
-                            //println!("scan line {scan_line}");
-                            //println!("active edge {:?}", active_edge_table);
-                            for (start, end) in active_edge_table.iter().tuples() {
-                                //println!("from {} to {}", start.1, end.1);
-                                let _ = Line::new(Point::new(start.1.round() as i32, scan_line), Point::new(end.1.round() as i32, scan_line))
-                                    .draw_styled(&PrimitiveStyle::with_stroke(style.fill_color.unwrap(), 1), target);
-                            }
-
-                            scan_line += 1;
-
-                            active_edge_table.retain_mut(|(max_y, x, slope_inverse)| {
-                                //println!("{x} {slope_inverse}");
-                                if *max_y != scan_line {
-                                    *x += *slope_inverse;
-                                    true
-                                } else {
-                                    false
-                                }
-                            });
-
-                            loop {
-                                if let Some((edge, max_y, slope_inv)) = global_edge_table.get(0).and_then(|edge| { if edge.0.y == scan_line { Some(edge) } else { None } }) {
-                                    // remove element and add to active edge table if within scan line range
-                                    active_edge_table.push((*max_y, edge.x as f32, *slope_inv));
-                                    let _ = global_edge_table.remove(0);
-                                } else {
-                                    break;
-                                }
-                            }
-
-                            if active_edge_table.is_empty() {
-                                break;
-                            }
-                            active_edge_table.sort_by(|a, b| { a.1.total_cmp(&b.1) })
-                        }
+                    if let Some(color) = style.fill_color {
+                        fill_ring(self.edges(), color, &mut target)?;
                     }
-                    //println!("{} {}", active_edge_table.len(), global_edge_table.len());
-                    Ok(())
                 } // fill
+                1 => {
+                    // a one pixel outline has no joints, so closing it with a separate line draws
+                    // the same pixels without collecting the ring
+                    Polyline::new(self.vertices()).translate(self.translate()).draw_styled(style, &mut target)?;
+                    if let [first, .., last] = self.vertices() {
+                        Line::new(*last + self.translate(), *first + self.translate()).draw_styled(style, &mut target)?;
+                    }
+                }
                 _ => {
-                    let complete_points = self.vertices.iter().cloned().chain(iter::once(self.vertices[0])).collect::<Vec<Point>>();
-                    Polyline::new(&complete_points).translate(self.translate).draw_styled(style, target)
+                    // thicker outlines join each edge to the next, which needs the closed ring in
+                    // one slice
+                    let edges = self.edges().collect::<Vec<Line>>();
+                    let complete_points = edges.iter().map(|edge| edge.start).chain(edges.last().map(|edge| edge.end)).collect::<Vec<Point>>();
+                    Polyline::new(&complete_points).draw_styled(style, &mut target)?;
                 }
             }
+            Ok(target.area())
         }
     }
 
-    #[cfg(test)]
-    mod tests {
-        use std::ops::{Add, Sub};
-        use std::time::{Duration, Instant};
-        use colored::Colorize;
-        use embedded_graphics::Drawable;
-        use embedded_graphics::pixelcolor::Rgb888;
-        use embedded_graphics::prelude::{Point, Size};
-        use embedded_graphics::primitives::{Circle, Line, Polyline, Primitive, PrimitiveStyle};
-        use embedded_graphics_core::prelude::DrawTarget;
-        use embedded_graphics_simulator::{BinaryColorTheme, OutputSettings, SimulatorEvent};
-        use embedded_graphics_simulator::sdl2::Keycode;
-        use itertools::Itertools;
-        use rand::{Rng, thread_rng};
-        use crate::polygon::Polygon;
+    /// A closed polygon that owns its vertices, as produced by the operations that build new
+    /// shapes. Borrow it as a [`Polygon`] with [`as_polygon`](Self::as_polygon) for everything
+    /// else.
+    ///
+    /// The bounding box is computed on first use and kept until the polygon is next changed, which
+    /// is why the vertices and offset are only reachable through methods.
+    #[derive(Clone, Default)]
+    pub struct PolygonOwned {
+        translate: Point,
+        vertices: Vec<Point>,
+        bounds: Cell<Option<Rectangle>>,
+        /// The vertices last simplified by [`draw_simplified`](Self::draw_simplified), with the
+        /// bits of the error they were simplified to.
+        simplified: RefCell<Option<(u32, Vec<Point>)>>,
+    }
 
-        fn test_polyline() {
-            let points = [[16, 20], [28, 10], [28, 16], [22, 10], [10, 10], [10, 16]].iter().map(|p|Point::from(p)).collect_vec();
-            let mut surface = embedded_graphics::mock_display::MockDisplay::new();
-            surface.set_allow_overdraw(true);
-            let _ = Polygon::new(&points).into_styled(PrimitiveStyle::with_fill(Rgb888::new(255, 255, 255))).draw(&mut surface);
-            //println!("{surface:?}");
-            surface = embedded_graphics::mock_display::MockDisplay::new();
-            surface.set_allow_overdraw(true);
-            let _ = Polyline::new(&points).into_styled(PrimitiveStyle::with_stroke(Rgb888::new(255, 255, 255), 1)).draw(&mut surface);
-            //println!("{surface:?}");
+    impl PolygonOwned {
+        pub const fn new(vertices: Vec<Point>) -> Self {
+            Self::with_translate(vertices, Point::zero())
         }
 
-        #[test]
-        fn test_random_shapes() {
-            let mut display = embedded_graphics_simulator::SimulatorDisplay::new(Size::new(100, 75));
-            let mut window = embedded_graphics_simulator::Window::new("Polygon_tester", &OutputSettings{
-                scale: 4,
-                pixel_spacing: 0,
-                theme: BinaryColorTheme::Default,
-                max_fps: 30,
-            });
+        /// Creates a polygon with the given offset. The bounding box is left to be computed on
+        /// first use, which keeps this a `const fn`.
+        pub const fn with_translate(vertices: Vec<Point>, translate: Point) -> Self {
+            PolygonOwned {
+                translate,
+                vertices,
+                bounds: Cell::new(None),
+                simplified: RefCell::new(None),
+            }
+        }
 
-            let mut next_draw = Instant::now();
-            let mut draw_again = true;
-            'running: loop {
-                if draw_again {
-                    //println!("{}", "======NEW DRAW======".red());
-                    //println!("{}", "======NEW DRAW======".red());
-                    //println!("{}", "======NEW DRAW======".red());
-                    draw_again = false;
-                    display.clear(Rgb888::new(0, 0, 0));
-                    let mut points = Vec::new();
-                    let colors = [
-                        Rgb888::new(255, 0, 0),
-                        Rgb888::new(0, 255, 0),
-                        Rgb888::new(0, 0, 255),
-                        Rgb888::new(255, 255, 0)
-                    ];
-                    for i in 0..4 {
-                        points.push(Point::new(thread_rng().gen_range(10..90), thread_rng().gen_range(10..65)))
-                    }
-                    Polygon::new(&points).into_styled(PrimitiveStyle::with_fill(Rgb888::new(255, 255, 255))).draw(&mut display);
-                    Polyline::new(&points).into_styled(PrimitiveStyle::with_stroke(Rgb888::new(255, 0, 255), 1)).draw(&mut display);
-                    for (point, color) in points.iter().zip(colors.iter()) {
-                        Circle::new(point.sub(Point::new(2, 2)), 5).into_styled(PrimitiveStyle::with_fill(*color)).draw(&mut display);
+        pub fn as_polygon(&self) -> Polygon<'_> {
+            Polygon::with_translate(&self.vertices, self.translate)
+        }
+
+        pub fn translate(&self) -> Point {
+            self.translate
+        }
+
+        pub fn vertices(&self) -> &[Point] {
+            &self.vertices
+        }
+
+        pub fn into_vertices(self) -> Vec<Point> {
+            self.vertices
+        }
+
+        pub fn set_translate(&mut self, translate: Point) {
+            self.translate = translate;
+            self.bounds.set(None);
+        }
+
+        /// Moves the polygon by `by`, adding it to the translate offset.
+        pub fn translate_mut(&mut self, by: Point) -> &mut Self {
+            self.set_translate(self.translate + by);
+            self
+        }
+
+        /// Gives mutable access to the vertices. The cached bounding box and simplified outline
+        /// are dropped up front, so any change made through the returned reference is picked up
+        /// by the next [`bounding_box`](Dimensions::bounding_box) or
+        /// [`draw_simplified`](Self::draw_simplified).
+        pub fn vertices_mut(&mut self) -> &mut Vec<Point> {
+            self.bounds.set(None);
+            self.simplified.take();
+            &mut self.vertices
+        }
+
+        /// Inserts `vertex` before the vertex at `index`, or at the end if `index` is the vertex
+        /// count.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `index` is greater than the vertex count.
+        pub fn insert_vertex(&mut self, index: usize, vertex: Point) {
+            self.vertices_mut().insert(index, vertex);
+        }
+
+        /// Removes and returns the vertex at `index`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `index` is out of bounds.
+        pub fn remove_vertex(&mut self, index: usize) -> Point {
+            self.vertices_mut().remove(index)
+        }
+
+        /// Scales every vertex by `factor` about the polygon's own origin, so the translate offset
+        /// is unchanged, rounding to the nearest pixel.
+        pub fn scale(&mut self, factor: f32) {
+            for v in self.vertices_mut() {
+                *v = Point::new(math::round(v.x as f32 * factor) as i32, math::round(v.y as f32 * factor) as i32);
+            }
+        }
+    }
+
+    impl Debug for PolygonOwned {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PolygonOwned").field("translate", &self.translate).field("vertices", &self.vertices).finish()
+        }
+    }
+
+    impl PartialEq for PolygonOwned {
+        fn eq(&self, other: &Self) -> bool {
+            self.translate == other.translate && self.vertices == other.vertices
+        }
+    }
+
+    impl Eq for PolygonOwned {}
+
+    impl<'a> From<Polygon<'a>> for PolygonOwned {
+        fn from(polygon: Polygon<'a>) -> Self {
+            PolygonOwned::with_translate(polygon.vertices().to_vec(), polygon.translate())
+        }
+    }
+
+    impl Dimensions for PolygonOwned {
+        /// Returns the bounding box, computed only on the first call after a change.
+        fn bounding_box(&self) -> Rectangle {
+            if let Some(bounds) = self.bounds.get() {
+                return bounds;
+            }
+            let bounds = self.as_polygon().bounding_box();
+            self.bounds.set(Some(bounds));
+            bounds
+        }
+    }
+
+    impl Primitive for PolygonOwned {}
+
+    impl ContainsPoint for PolygonOwned {
+        fn contains(&self, point: Point) -> bool {
+            self.as_polygon().contains(point)
+        }
+    }
+
+    impl<C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for PolygonOwned {
+        type Color = C;
+        type Output = Rectangle;
+
+        fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color=Self::Color> {
+            self.as_polygon().draw_styled(style, target)
+        }
+    }
+
+    /// A polygon with holes cut out of it, owning its exterior ring and one ring per hole.
+    ///
+    /// All rings are filled together under the even-odd rule, so the holes stay empty whichever
+    /// way they wind. Pixels on a hole's outline belong to the boundary and are still covered.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct PolygonWithHoles {
+        pub translate: Point,
+        pub exterior: Vec<Point>,
+        pub holes: Vec<Vec<Point>>,
+    }
+
+    impl PolygonWithHoles {
+        pub fn new(exterior: Vec<Point>, holes: Vec<Vec<Point>>) -> Self {
+            PolygonWithHoles {
+                translate: Point::zero(),
+                exterior,
+                holes,
+            }
+        }
+
+        /// Borrows the exterior ring as a [`Polygon`], carrying the translate offset.
+        pub fn exterior(&self) -> Polygon<'_> {
+            Polygon::with_translate(&self.exterior, self.translate)
+        }
+
+        /// Borrows each hole as a [`Polygon`], carrying the translate offset.
+        pub fn holes(&self) -> impl Iterator<Item = Polygon<'_>> {
+            self.holes.iter().map(move |hole| Polygon::with_translate(hole, self.translate))
+        }
+
+        /// Returns the edges of the exterior followed by the edges of every hole, with the
+        /// translate offset applied.
+        pub fn edges(&self) -> impl Iterator<Item = Line> + '_ {
+            iter::once(self.exterior()).chain(self.holes()).flat_map(|ring| ring.edges())
+        }
+    }
+
+    impl From<PolygonOwned> for PolygonWithHoles {
+        fn from(polygon: PolygonOwned) -> Self {
+            PolygonWithHoles {
+                translate: polygon.translate(),
+                exterior: polygon.into_vertices(),
+                holes: Vec::new(),
+            }
+        }
+    }
+
+    impl Dimensions for PolygonWithHoles {
+        fn bounding_box(&self) -> Rectangle {
+            self.exterior().bounding_box()
+        }
+    }
+
+    impl Primitive for PolygonWithHoles {}
+
+    impl ContainsPoint for PolygonWithHoles {
+        fn contains(&self, point: Point) -> bool {
+            edges_contain(self.edges(), point)
+        }
+    }
+
+    impl<C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for PolygonWithHoles {
+        type Color = C;
+        type Output = Rectangle;
+
+        /// Draws the polygon and returns the affected area as [`Polygon`] does.
+        fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color=Self::Color> {
+            let mut target = AffectedArea::new(target);
+            match style.stroke_width {
+                0 => {
+                    if let Some(color) = style.fill_color {
+                        fill_ring(self.edges(), color, &mut target)?;
                     }
-                }
-                window.update(&display);
-                for event in window.events() {
-                    match event {
-                        SimulatorEvent::KeyUp { .. } => {}
-                        SimulatorEvent::KeyDown { keycode, keymod, repeat } => {
-                            if keycode == Keycode::Space {
-                                draw_again = true;
-                            }
-                        }
-                        SimulatorEvent::MouseButtonUp { .. } => {}
-                        SimulatorEvent::MouseButtonDown { .. } => {}
-                        SimulatorEvent::MouseWheel { .. } => {}
-                        SimulatorEvent::MouseMove { .. } => {}
-                        SimulatorEvent::Quit => break 'running
+                } // fill
+                _ => {
+                    for ring in iter::once(self.exterior()).chain(self.holes()) {
+                        ring.draw_styled(style, &mut target)?;
                     }
                 }
             }
+            Ok(target.area())
         }
     }
-}
-
-#[cfg(feature="3d")]
-pub mod polygon_3d {
-    use std::cmp::Ordering;
-    use std::collections::{BTreeMap, HashMap, VecDeque};
-    use std::fmt::Debug;
-    use std::iter;
-    use std::cell::RefCell;
-    use embedded_graphics::draw_target::DrawTarget;
-    use embedded_graphics::geometry::{Dimensions, Point};
-    use embedded_graphics::pixelcolor::PixelColor;
-    use embedded_graphics::prelude::Size;
-    use embedded_graphics::primitives::{Line, Polyline, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
-    use embedded_graphics::transform::Transform;
-    use embedded_graphics_core::Pixel;
-    use itertools::Itertools;
-    use nalgebra::{DMatrix, Matrix, OMatrix, Point3, U1, U4, Vector3};
 
-    pub struct Polygon3d<'a> {
+    /// A closed polygon whose vertices are produced by an iterator instead of a slice.
+    ///
+    /// Each draw walks a clone of the iterator once, so vertices decoded on the fly never have to
+    /// be collected; the edge table is the only allocation and is proportional to the edge count.
+    /// Filling produces exactly the same pixels as [`Polygon`] over the same vertices.
+    #[derive(Clone)]
+    pub struct StreamingPolygon<I> {
         pub translate: Point,
-        pub vertices: &'a [(Point, f32)],
-        pub depth_map: &'a RefCell<DMatrix<f32>>
+        pub vertices: I,
     }
 
-    impl<'a> Polygon3d<'a> {
-        pub fn new(vertices: &'a [(Point, f32)], depth_map: &'a RefCell<DMatrix<f32>>) -> Self{
-            Polygon3d{
+    impl<I: Iterator<Item = Point> + Clone> StreamingPolygon<I> {
+        pub fn new(vertices: I) -> Self {
+            StreamingPolygon {
                 translate: Point::zero(),
                 vertices,
-                depth_map
             }
         }
     }
 
-    impl<'a> Dimensions for Polygon3d<'a> {
+    impl<I: Iterator<Item = Point> + Clone> StreamingPolygon<I> {
+        /// Returns the edges of the closed outline with the translate offset applied, in the same
+        /// way as [`Polygon::edges`].
+        pub fn edges(&self) -> impl Iterator<Item = Line> {
+            let translate = self.translate;
+            ring_edges(self.vertices.clone().map(move |v| v + translate))
+        }
+    }
+
+    impl<I: Iterator<Item = Point> + Clone> Dimensions for StreamingPolygon<I> {
         fn bounding_box(&self) -> Rectangle {
-            let (min_x, max_x, min_y, max_y) = self.vertices.iter().fold((i32::max_value(), i32::min_value(), i32::max_value(), i32::min_value()), |mut old, (point, depth)|{
-                old.0 = old.0.min(point.x);
-                old.1 = old.1.max(point.x);
-                old.2 = old.2.min(point.y);
-                old.3 = old.3.max(point.y);
-                old
-            });
-            let width = (max_x - min_x) as u32;
-            let height = (max_y - min_y) as u32;
-            Rectangle::new(Point::new(min_x, min_y),    Size::new(width, height))
+            let translate = self.translate;
+            vertex_bounds(self.vertices.clone().map(move |v| v + translate))
         }
     }
 
-    impl<'a> Primitive for Polygon3d<'a> {}
+    impl<I: Iterator<Item = Point> + Clone> Primitive for StreamingPolygon<I> {}
 
-    impl<'a, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for Polygon3d<'a> {
+    impl<I: Iterator<Item = Point> + Clone, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for StreamingPolygon<I> {
         type Color = C;
-        type Output = ();
+        type Output = Rectangle;
 
+        /// Draws the polygon and returns the affected area as [`Polygon`] does.
         fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color=Self::Color> {
+            let mut target = AffectedArea::new(target);
             match style.stroke_width {
                 0 => {
-                    let colour = style.fill_color.unwrap();
-                    let mut global_edge_table = Vec::new();
-                    self.vertices.iter().enumerate().map(|(i, (vertex, depth))|{
-                        let (next_vertex, _depth) = &self.vertices[(i+1) % self.vertices.len()];
-                        let min_y_and_corresponding_x = if vertex.y < next_vertex.y {vertex} else {next_vertex};
-                        let max_y = vertex.y.max(next_vertex.y);
-                        let y_diff = next_vertex.y - vertex.y;
-                        let x_diff = next_vertex.x - vertex.x;
-                        let slope_inv = x_diff as f32 / y_diff as f32;
-                        //println!("{slope_inv} ({vertex}) ({next_vertex})");
-                        (min_y_and_corresponding_x, max_y, slope_inv)
-                    })
-                        .filter(|(_, _, slope)|slope.is_finite())
-                        .for_each(|v|{
-                            if global_edge_table.len() == 0 {
-                                global_edge_table.push(v);
-                                return;
-                            }
-                            let (min_y_and_corresponding_x, _max_y, _slope_inv) = v;
-                            let mut insertion_index = 0;
-                            while insertion_index < global_edge_table.len() && min_y_and_corresponding_x.y > global_edge_table[insertion_index].0.y {
-                                if insertion_index < global_edge_table.len() {
-                                    insertion_index += 1;
-                                }
-                            }
+                    if let Some(color) = style.fill_color {
+                        fill_ring(self.edges(), color, &mut target)?;
+                    }
+                } // fill
+                _ => {
+                    for edge in self.edges() {
+                        edge.draw_styled(style, &mut target)?;
+                    }
+                }
+            }
+            Ok(target.area())
+        }
+    }
 
-                            while insertion_index < global_edge_table.len() && min_y_and_corresponding_x.x > global_edge_table[insertion_index].0.x && min_y_and_corresponding_x.y == global_edge_table[insertion_index].0.y {
-                                if insertion_index < global_edge_table.len() {
-                                    insertion_index += 1;
-                                }
-                            }
-                            global_edge_table.insert(insertion_index, v);
-                            //println!("global {:?}", global_edge_table);
-                        });
-                    let mut active_edge_table = Vec::new();
-                    if global_edge_table.len() > 1 {
-                        let mut scan_line = global_edge_table[0].0.y;
-                        // populate active edge table
-                        loop {
-                            if let Some((edge, max_y, slope_inv)) = global_edge_table.get(0).and_then(|edge| { if edge.0.y <= scan_line { Some(edge) } else { None } }) {
-                                // remove element and add to active edge table if within scan line range
-                                active_edge_table.push((*max_y, edge.x as f32, *slope_inv));
-                                let _ = global_edge_table.remove(0);
-                            } else {
-                                break;
-                            }
-                        }
+    /// Returns the smallest rectangle holding every point, so a single point has a box one pixel
+    /// across, or a zero-sized rectangle at the origin if there are no points.
+    pub(crate) fn vertex_bounds(mut vertices: impl Iterator<Item = Point>) -> Rectangle {
+        let Some(first) = vertices.next() else {
+            return Rectangle::zero();
+        };
+        let (min, max) = vertices.fold((first, first), |(min, max), point| (min.component_min(point), max.component_max(point)));
+        Rectangle::with_corners(min, max)
+    }
 
-                        loop {
-                            // println!("scan line {scan_line}");
-                            // println!("active edge {:?}", active_edge_table);
-                            for (start, end) in active_edge_table.iter().tuples() {
-                                //println!("from {} to {}", start.1, end.1);
-                                for x in (start.1.round() as i32) .. (end.1.round() as i32) {
-                                    let x_f = x as f32;
-                                    let y_f = scan_line as f32;
-                                    let distances = self.vertices.iter().map(|(v, depth)|(v.x as f32-x_f).powi(2)+(v.y as f32-y_f).powi(2)).collect::<Vec<f32>>();
-                                    let sum = distances.iter().sum::<f32>();
-                                    let point_depth = self.vertices.iter().zip(distances.iter()).map(|((v, depth), d)|depth * d/sum).sum::<f32>();
-                                    if let Some(d) = self.depth_map.borrow_mut().get_mut((x as usize, scan_line as usize)) {
-                                        if *d < point_depth{
-                                            target.draw_iter(iter::once(Pixel(Point::new(x, scan_line), colour)));
-                                            *d = point_depth;
-                                        }
-                                    }
-                                };
-                            }
+    /// Iterator over the edges of the closed ring through a sequence of vertices.
+    ///
+    /// This is the only place the ring is closed, everything that walks the outline goes through
+    /// it.
+    #[derive(Clone)]
+    pub(crate) struct RingEdges<I> {
+        vertices: iter::Fuse<I>,
+        first: Option<Point>,
+        previous: Option<Point>,
+        count: usize,
+    }
 
-                            scan_line += 1;
-
-                            active_edge_table.retain_mut(|(max_y, x, slope_inverse)| {
-                                //println!("{x} {slope_inverse}");
-                                if *max_y != scan_line {
-                                    *x += *slope_inverse;
-                                    true
-                                } else {
-                                    false
-                                }
-                            });
-
-                            loop {
-                                if let Some((edge, max_y, slope_inv)) = global_edge_table.get(0).and_then(|edge| { if edge.0.y == scan_line { Some(edge) } else { None } }) {
-                                    // remove element and add to active edge table if within scan line range
-                                    active_edge_table.push((*max_y, edge.x as f32, *slope_inv));
-                                    let _ = global_edge_table.remove(0);
-                                } else {
-                                    break;
-                                }
-                            }
+    pub(crate) fn ring_edges<I: Iterator<Item = Point>>(vertices: I) -> RingEdges<I> {
+        RingEdges {
+            vertices: vertices.fuse(),
+            first: None,
+            previous: None,
+            count: 0,
+        }
+    }
 
-                            if active_edge_table.is_empty() {
-                                break;
-                            }
-                            active_edge_table.sort_by(|a, b| { a.1.total_cmp(&b.1) })
+    impl<I: Iterator<Item = Point>> Iterator for RingEdges<I> {
+        type Item = Line;
+
+        fn next(&mut self) -> Option<Line> {
+            match self.vertices.next() {
+                Some(vertex) => {
+                    self.count += 1;
+                    match self.previous.replace(vertex) {
+                        Some(previous) => Some(Line::new(previous, vertex)),
+                        None => {
+                            self.first = Some(vertex);
+                            self.next()
                         }
                     }
-                    //println!("{} {}", active_edge_table.len(), global_edge_table.len());
-                    Ok(())
-                } // fill
-                _ => {
-                    let complete_points = self.vertices.iter().cloned().chain(iter::once(self.vertices[0])).map(|(v, depth)|v).collect::<Vec<Point>>();
-                    Polyline::new(&complete_points).translate(self.translate).draw_styled(style, target)
+                }
+                None => {
+                    // two vertices already form their only segment, closing it would trace it back
+                    let first = self.first.take()?;
+                    let last = self.previous?;
+                    (self.count > 2).then_some(Line::new(last, first))
                 }
             }
         }
     }
 
-    #[cfg(test)]
-    mod tests {
-        use std::ops::{Add, Sub};
-        use std::time::{Duration, Instant};
-        use colored::Colorize;
+    /// Even-odd containment over a set of closed edges that counts points on an edge as inside,
+    /// matching the pixels covered by the fill.
+    fn edges_contain(edges: impl Iterator<Item = Line>, point: Point) -> bool {
+        edges_locate(edges, point, FillRule::EvenOdd) != Some(false)
+    }
+
+    /// Returns `None` if `point` lies on the outline of the ring bounded by `edges`, and otherwise
+    /// whether it lies inside under `rule`.
+    fn edges_locate(edges: impl Iterator<Item = Line>, point: Point, rule: FillRule) -> Option<bool> {
+        let mut winding = 0;
+        for Line { start: a, end: b } in edges {
+            let cross = (b.x as i64 - a.x as i64) * (point.y as i64 - a.y as i64) - (point.x as i64 - a.x as i64) * (b.y as i64 - a.y as i64);
+            if cross == 0 && point.x >= a.x.min(b.x) && point.x <= a.x.max(b.x) && point.y >= a.y.min(b.y) && point.y <= a.y.max(b.y) {
+                return None;
+            }
+            // the crossing lies right of the point when the cross product agrees with the edge direction
+            if (a.y > point.y) != (b.y > point.y) && (cross > 0) == (b.y > a.y) {
+                winding += if b.y > a.y { 1 } else { -1 };
+            }
+        }
+        Some(match rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        })
+    }
+
+    /// Fills the ring bounded by `edges` one scanline span at a time.
+    ///
+    /// Only the rows and columns within the target's bounds are walked, so even a ring spanning
+    /// the whole coordinate range costs no more than the target's area.
+    fn fill_ring<C, D>(edges: impl Iterator<Item = Line>, color: C, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+        fill_scanlines(Scanlines::new(edges), color, target)
+    }
+
+    /// Fills the spans produced by `scanlines`, as [`fill_ring`] does.
+    fn fill_scanlines<C, D>(mut scanlines: Scanlines, color: C, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+        let bounds = target.bounding_box();
+        let Some(bottom_right) = bounds.bottom_right() else {
+            return Ok(());
+        };
+        scanlines.skip_to(bounds.top_left.y);
+        let mut spans = Vec::new();
+        while let Some(y) = scanlines.next_row(&mut spans).filter(|y| *y <= bottom_right.y) {
+            for &(start, end) in spans.iter() {
+                let (start, end) = (start.max(bounds.top_left.x), end.min(bottom_right.x));
+                if start <= end {
+                    target.fill_solid(&Rectangle::new(Point::new(start, y), Size::new(end.abs_diff(start) + 1, 1)), color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) mod tests {
+        use std::ops::{Add, Sub};
+        use std::time::{Duration, Instant};
+        use colored::Colorize;
         use embedded_graphics::Drawable;
         use embedded_graphics::pixelcolor::Rgb888;
         use embedded_graphics::prelude::{Point, Size};
@@ -408,7 +654,3537 @@ pub mod polygon_3d {
         use embedded_graphics_simulator::sdl2::Keycode;
         use itertools::Itertools;
         use rand::{Rng, thread_rng};
-        use crate::polygon::Polygon;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::mock_display::MockDisplay;
+        use embedded_graphics::pixelcolor::BinaryColor;
+        use embedded_graphics::primitives::{ContainsPoint, PointsIter, Rectangle};
+        use embedded_graphics_core::Pixel;
+        use std::convert::Infallible;
+        use crate::polygon::{Polygon, PolygonOwned, PolygonWithHoles, StreamingPolygon};
+
+        /// Collects every pixel a draw writes, without the size limits of `MockDisplay`.
+        #[derive(Default)]
+        pub(crate) struct PixelCollector {
+            pub(crate) pixels: Vec<Point>,
+        }
+
+        impl Dimensions for PixelCollector {
+            fn bounding_box(&self) -> Rectangle {
+                Rectangle::new(Point::new(-(1 << 20), -(1 << 20)), Size::new(1 << 21, 1 << 21))
+            }
+        }
+
+        impl DrawTarget for PixelCollector {
+            type Color = BinaryColor;
+            type Error = Infallible;
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+                self.pixels.extend(pixels.into_iter().map(|Pixel(p, _)| p));
+                Ok(())
+            }
+        }
+
+        /// The sorted pixels covered by filling `polygon`.
+        pub(crate) fn fill_pixels(polygon: &Polygon) -> Vec<Point> {
+            let mut collector = PixelCollector::default();
+            polygon.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+            collector.pixels.sort_by_key(|p| (p.y, p.x));
+            collector.pixels
+        }
+
+        /// The points of a ring written as `[x, y]` pairs.
+        pub(crate) fn ring(points: &[[i32; 2]]) -> Vec<Point> {
+            points.iter().map(Point::from).collect()
+        }
+
+        /// Checks the bounding box `bounds` gives for vertices moved by a translate offset, shared
+        /// by every polygon type built on a slice of points.
+        pub(crate) fn check_bounding_box(bounds: impl Fn(&[Point], Point) -> Rectangle) {
+            assert_eq!(bounds(&[], Point::new(5, 5)), Rectangle::zero());
+            // a single vertex covers its own pixel
+            assert_eq!(bounds(&[Point::new(4, 2)], Point::new(1, 1)), Rectangle::new(Point::new(5, 3), Size::new(1, 1)));
+            let triangle = [Point::new(3, 4), Point::new(23, 9), Point::new(10, 14)];
+            assert_eq!(bounds(&triangle, Point::zero()), Rectangle::new(Point::new(3, 4), Size::new(21, 11)));
+            assert_eq!(bounds(&triangle, Point::new(-40, 7)), Rectangle::new(Point::new(-37, 11), Size::new(21, 11)));
+
+            // every filled pixel lies inside, and the fill reaches each side
+            let filled = fill_pixels(&Polygon::with_translate(&triangle, Point::new(-40, 7)));
+            let (min, max) = filled.iter().fold((filled[0], filled[0]), |(min, max), p| (min.component_min(*p), max.component_max(*p)));
+            assert_eq!(Rectangle::with_corners(min, max), bounds(&triangle, Point::new(-40, 7)));
+        }
+
+        /// Accepts `remaining` pixels, then fails, counting any draw attempted after the failure.
+        pub(crate) struct FailAfter {
+            pub(crate) remaining: usize,
+            pub(crate) written: usize,
+            pub(crate) failed: bool,
+            pub(crate) calls_after_failure: usize,
+        }
+
+        impl FailAfter {
+            pub(crate) fn new(remaining: usize) -> Self {
+                FailAfter { remaining, written: 0, failed: false, calls_after_failure: 0 }
+            }
+        }
+
+        impl Dimensions for FailAfter {
+            fn bounding_box(&self) -> Rectangle {
+                PixelCollector::default().bounding_box()
+            }
+        }
+
+        impl DrawTarget for FailAfter {
+            type Color = BinaryColor;
+            type Error = ();
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+                if self.failed {
+                    self.calls_after_failure += 1;
+                    return Err(());
+                }
+                for _ in pixels {
+                    if self.remaining == 0 {
+                        self.failed = true;
+                        return Err(());
+                    }
+                    self.remaining -= 1;
+                    self.written += 1;
+                }
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn test_draw_errors_stop_the_draw() {
+            let vertices = [Point::new(2, 2), Point::new(60, 10), Point::new(20, 60)];
+            let hole = vec![Point::new(20, 15), Point::new(30, 15), Point::new(25, 25)];
+            for style in [PrimitiveStyle::with_fill(BinaryColor::On), PrimitiveStyle::with_stroke(BinaryColor::On, 1), PrimitiveStyle::with_stroke(BinaryColor::On, 4)] {
+                let mut target = FailAfter::new(40);
+                assert_eq!(Polygon::new(&vertices).into_styled(style).draw(&mut target), Err(()), "{style:?}");
+                assert_eq!((target.written, target.calls_after_failure), (40, 0), "{style:?}");
+
+                let mut target = FailAfter::new(40);
+                assert_eq!(StreamingPolygon::new(vertices.iter().copied()).into_styled(style).draw(&mut target), Err(()));
+                assert_eq!((target.written, target.calls_after_failure), (40, 0));
+
+                let mut target = FailAfter::new(40);
+                let with_hole = PolygonWithHoles::new(vertices.to_vec(), vec![hole.clone()]);
+                assert_eq!(with_hole.into_styled(style).draw(&mut target), Err(()));
+                assert_eq!((target.written, target.calls_after_failure), (40, 0));
+            }
+        }
+
+        fn test_polyline() {
+            let points = [[16, 20], [28, 10], [28, 16], [22, 10], [10, 10], [10, 16]].iter().map(|p|Point::from(p)).collect_vec();
+            let mut surface = embedded_graphics::mock_display::MockDisplay::new();
+            surface.set_allow_overdraw(true);
+            let _ = Polygon::new(&points).into_styled(PrimitiveStyle::with_fill(Rgb888::new(255, 255, 255))).draw(&mut surface);
+            //println!("{surface:?}");
+            surface = embedded_graphics::mock_display::MockDisplay::new();
+            surface.set_allow_overdraw(true);
+            let _ = Polyline::new(&points).into_styled(PrimitiveStyle::with_stroke(Rgb888::new(255, 255, 255), 1)).draw(&mut surface);
+            //println!("{surface:?}");
+        }
+
+        #[test]
+        fn test_sliver_tiling_leaves_no_gaps() {
+            let quad = [Point::new(2, 2), Point::new(42, 38), Point::new(40, 40), Point::new(2, 40)];
+            let midpoint = Point::new(22, 20);
+            let big_triangle = [quad[0], quad[2], quad[3]];
+            let slivers = [[quad[0], midpoint, quad[2]], [midpoint, quad[1], quad[2]]];
+
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            Polygon::new(&big_triangle).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            for sliver in slivers.iter() {
+                let mut sliver_display = MockDisplay::<BinaryColor>::new();
+                Polygon::new(sliver).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut sliver_display).unwrap();
+                assert!(!sliver_display.affected_area().is_zero_sized());
+                Polygon::new(sliver).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            }
+
+            let inside_quad = |p: Point| quad.iter().zip(quad.iter().cycle().skip(1)).all(|(a, b)| {
+                (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x) >= 0
+            });
+            for p in display.bounding_box().points().filter(|p| inside_quad(*p)) {
+                assert_eq!(display.get_pixel(p), Some(BinaryColor::On), "gap at {p}");
+            }
+        }
+
+        #[test]
+        fn test_fill_covers_boundary_pixels() {
+            let points = [Point::new(1, 1), Point::new(4, 1), Point::new(4, 3), Point::new(1, 3)];
+            let mut display = MockDisplay::new();
+            Polygon::new(&points).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            display.assert_pattern(&[
+                "     ",
+                " ####",
+                " ####",
+                " ####",
+            ]);
+        }
+
+        #[test]
+        fn test_streaming_fill_matches_slice_fill() {
+            let packed: [u8; 12] = [16, 20, 28, 10, 28, 16, 22, 10, 10, 10, 10, 16];
+            let points = packed.chunks_exact(2).map(|c| Point::new(c[0] as i32, c[1] as i32)).collect_vec();
+            let style = PrimitiveStyle::with_fill(BinaryColor::On);
+
+            let mut expected = MockDisplay::new();
+            Polygon::new(&points).into_styled(style).draw(&mut expected).unwrap();
+            let mut display = MockDisplay::new();
+            let streaming = StreamingPolygon::new(packed.chunks_exact(2).map(|c| Point::new(c[0] as i32, c[1] as i32)));
+            streaming.clone().into_styled(style).draw(&mut display).unwrap();
+
+            display.assert_eq(&expected);
+            assert_eq!(streaming.bounding_box(), Polygon::new(&points).bounding_box());
+        }
+
+        #[test]
+        fn test_fill_masked_cuts_exact_hole() {
+            let points = [Point::new(2, 2), Point::new(60, 10), Point::new(20, 60)];
+            let hole = Rectangle::new(Point::new(15, 12), Size::new(10, 7));
+
+            let mut full = MockDisplay::new();
+            Polygon::new(&points).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut full).unwrap();
+            let mut calls = 0;
+            let mut display = MockDisplay::new();
+            Polygon::new(&points).fill_masked(BinaryColor::On, |p| {
+                calls += 1;
+                !hole.contains(p)
+            }, &mut display).unwrap();
+
+            let filled = full.bounding_box().points().filter(|p| full.get_pixel(*p).is_some()).count();
+            assert_eq!(calls, filled);
+            for p in full.bounding_box().points() {
+                let expected = full.get_pixel(p).filter(|_| !hole.contains(p));
+                assert_eq!(display.get_pixel(p), expected, "{p}");
+            }
+            assert!(hole.points().all(|p| full.get_pixel(p).is_some()), "hole should lie inside the triangle");
+        }
+
+        #[test]
+        fn test_contains_matches_fill() {
+            let points = [[16, 20], [28, 10], [28, 16], [22, 10], [10, 10], [10, 16], [3, 30]].iter().map(Point::from).collect_vec();
+            let mut polygon = Polygon::new(&points);
+            polygon.set_translate(Point::new(4, 7));
+            let mut display = MockDisplay::new();
+            polygon.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            for p in Rectangle::new(Point::zero(), Size::new(64, 64)).points() {
+                assert_eq!(polygon.contains(p), display.get_pixel(p).is_some(), "{p}");
+            }
+        }
+
+        #[test]
+        fn test_edges_close_the_ring() {
+            let points = [Point::new(0, 0), Point::new(10, 0), Point::new(5, 8)];
+            let mut polygon = Polygon::new(&points);
+            polygon.set_translate(Point::new(3, -1));
+            assert_eq!(polygon.edges().collect_vec(), [
+                Line::new(Point::new(3, -1), Point::new(13, -1)),
+                Line::new(Point::new(13, -1), Point::new(8, 7)),
+                Line::new(Point::new(8, 7), Point::new(3, -1)),
+            ]);
+            assert_eq!(StreamingPolygon { translate: polygon.translate(), vertices: points.iter().copied() }.edges().collect_vec(), polygon.edges().collect_vec());
+
+            assert_eq!(Polygon::new(&points[..2]).edges().collect_vec(), [Line::new(points[0], points[1])]);
+            assert_eq!(Polygon::new(&points[..1]).edges().count(), 0);
+            assert_eq!(Polygon::new(&[]).edges().count(), 0);
+
+            // a two-vertex polygon fills the pixels of its only segment
+            let mut display = MockDisplay::new();
+            Polygon::new(&points[..2]).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            display.assert_pattern(&["###########"]);
+            let mut display = MockDisplay::new();
+            Polygon::new(&[Point::new(0, 0), Point::new(3, 3)]).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            display.assert_pattern(&["#   ", " #  ", "  # ", "   #"]);
+        }
+
+        #[test]
+        fn test_polygon_with_holes_leaves_holes_empty() {
+            let exterior = vec![Point::new(0, 0), Point::new(8, 0), Point::new(8, 6), Point::new(0, 6)];
+            // the hole winds the same way as the exterior, which the even-odd rule does not mind
+            let hole = vec![Point::new(2, 2), Point::new(6, 2), Point::new(6, 4), Point::new(2, 4)];
+            let mut polygon = PolygonWithHoles::new(exterior, vec![hole]);
+            polygon.translate = Point::new(1, 1);
+            let mut display = MockDisplay::new();
+            polygon.clone().into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            display.assert_pattern(&[
+                "          ",
+                " #########",
+                " #########",
+                " #########",
+                " ###   ###",
+                " #########",
+                " #########",
+                " #########",
+            ]);
+            for p in Rectangle::new(Point::zero(), Size::new(12, 10)).points() {
+                assert_eq!(polygon.contains(p), display.get_pixel(p).is_some(), "{p}");
+            }
+            assert_eq!(polygon.bounding_box(), polygon.exterior().bounding_box());
+        }
+
+        #[test]
+        fn test_thin_stroke_matches_closed_polyline() {
+            let fixtures = [
+                vec![Point::new(1, 1), Point::new(20, 4), Point::new(14, 25), Point::new(3, 17)],
+                vec![Point::new(30, 2), Point::new(2, 9), Point::new(25, 30), Point::new(9, 3), Point::new(31, 21)],
+                vec![Point::new(4, 4), Point::new(27, 13)],
+                vec![Point::new(5, 5)],
+            ];
+            for vertices in fixtures.iter() {
+                let mut polygon = Polygon::new(vertices);
+                polygon.set_translate(Point::new(2, 1));
+                let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                polygon.into_styled(style).draw(&mut display).unwrap();
+
+                let closed: Vec<Point> = vertices.iter().chain(vertices.first()).map(|v| *v + polygon.translate()).collect();
+                let mut expected = MockDisplay::new();
+                expected.set_allow_overdraw(true);
+                Polyline::new(if vertices.len() > 1 { &closed } else { &[] }).into_styled(style).draw(&mut expected).unwrap();
+                display.assert_eq(&expected);
+            }
+        }
+
+        #[test]
+        fn test_bounding_box() {
+            check_bounding_box(|vertices, translate| Polygon::with_translate(vertices, translate).bounding_box());
+        }
+
+        #[test]
+        fn test_bounding_box_includes_translate() {
+            let vertices = [Point::new(3, 4), Point::new(23, 9), Point::new(10, 14)];
+            let mut polygon = Polygon::new(&vertices);
+            let untranslated = polygon.bounding_box();
+            polygon.set_translate(Point::new(40, -7));
+            let mut expected = untranslated;
+            expected.top_left += Point::new(40, -7);
+            assert_eq!(polygon.bounding_box(), expected);
+            assert_eq!(PolygonOwned::from(polygon).bounding_box(), expected);
+            let mut streaming = StreamingPolygon::new(vertices.iter().copied());
+            streaming.translate = polygon.translate();
+            assert_eq!(streaming.bounding_box(), expected);
+        }
+
+        const ARROW_POINTS: [Point; 3] = [Point::new(0, 0), Point::new(4, 2), Point::new(0, 4)];
+        static ARROW: Polygon<'static> = Polygon::with_translate(&ARROW_POINTS, Point::new(1, 0));
+        const fn empty() -> PolygonOwned {
+            PolygonOwned::new(Vec::new())
+        }
+
+        #[test]
+        fn test_const_constructed_polygons() {
+            let mut display = MockDisplay::new();
+            ARROW.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+            display.assert_pattern(&[
+                " #    ",
+                " ###  ",
+                " #####",
+                " ###  ",
+                " #    ",
+            ]);
+            assert_eq!(Polygon::new(&ARROW_POINTS).translate(), Point::zero());
+            assert_eq!(empty(), PolygonOwned::default());
+        }
+
+        #[test]
+        fn test_owned_bounding_box_cache_follows_changes() {
+            let mut polygon = PolygonOwned::new(vec![Point::new(0, 0), Point::new(10, 0), Point::new(10, 6)]);
+            let fresh = |polygon: &PolygonOwned| polygon.as_polygon().bounding_box();
+            assert_eq!(polygon.bounding_box(), fresh(&polygon));
+            assert!(polygon.bounds.get().is_some());
+
+            polygon.insert_vertex(1, Point::new(4, -8));
+            assert!(polygon.bounds.get().is_none());
+            assert_eq!(polygon.bounding_box(), Rectangle::new(Point::new(0, -8), Size::new(11, 15)));
+
+            polygon.translate_mut(Point::new(3, 3));
+            assert!(polygon.bounds.get().is_none());
+            assert_eq!(polygon.bounding_box(), Rectangle::new(Point::new(3, -5), Size::new(11, 15)));
+            assert_eq!(polygon.translate(), Point::new(3, 3));
+
+            polygon.scale(2.0);
+            assert_eq!(polygon.bounding_box(), Rectangle::new(Point::new(3, -13), Size::new(21, 29)));
+            assert_eq!(polygon.bounding_box(), fresh(&polygon));
+
+            polygon.vertices_mut().push(Point::new(-30, 0));
+            assert_eq!(polygon.bounding_box().top_left, Point::new(-27, -13));
+            assert_eq!(polygon.remove_vertex(4), Point::new(-30, 0));
+            assert_eq!(polygon.bounding_box(), fresh(&polygon));
+
+            // the cache takes no part in equality
+            assert_eq!(polygon, PolygonOwned::with_translate(polygon.vertices().to_vec(), Point::new(3, 3)));
+        }
+
+        #[test]
+        fn test_random_shapes() {
+            let mut display = embedded_graphics_simulator::SimulatorDisplay::new(Size::new(100, 75));
+            let mut window = embedded_graphics_simulator::Window::new("Polygon_tester", &OutputSettings{
+                scale: 4,
+                pixel_spacing: 0,
+                theme: BinaryColorTheme::Default,
+                max_fps: 30,
+            });
+
+            let mut next_draw = Instant::now();
+            let mut draw_again = true;
+            'running: loop {
+                if draw_again {
+                    //println!("{}", "======NEW DRAW======".red());
+                    //println!("{}", "======NEW DRAW======".red());
+                    //println!("{}", "======NEW DRAW======".red());
+                    draw_again = false;
+                    display.clear(Rgb888::new(0, 0, 0));
+                    let mut points = Vec::new();
+                    let colors = [
+                        Rgb888::new(255, 0, 0),
+                        Rgb888::new(0, 255, 0),
+                        Rgb888::new(0, 0, 255),
+                        Rgb888::new(255, 255, 0)
+                    ];
+                    for i in 0..4 {
+                        points.push(Point::new(thread_rng().gen_range(10..90), thread_rng().gen_range(10..65)))
+                    }
+                    Polygon::new(&points).into_styled(PrimitiveStyle::with_fill(Rgb888::new(255, 255, 255))).draw(&mut display);
+                    Polyline::new(&points).into_styled(PrimitiveStyle::with_stroke(Rgb888::new(255, 0, 255), 1)).draw(&mut display);
+                    for (point, color) in points.iter().zip(colors.iter()) {
+                        Circle::new(point.sub(Point::new(2, 2)), 5).into_styled(PrimitiveStyle::with_fill(*color)).draw(&mut display);
+                    }
+                }
+                window.update(&display);
+                for event in window.events() {
+                    match event {
+                        SimulatorEvent::KeyUp { .. } => {}
+                        SimulatorEvent::KeyDown { keycode, keymod, repeat } => {
+                            if keycode == Keycode::Space {
+                                draw_again = true;
+                            }
+                        }
+                        SimulatorEvent::MouseButtonUp { .. } => {}
+                        SimulatorEvent::MouseButtonDown { .. } => {}
+                        SimulatorEvent::MouseWheel { .. } => {}
+                        SimulatorEvent::MouseMove { .. } => {}
+                        SimulatorEvent::Quit => break 'running
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature="3d")]
+pub mod polygon_3d {
+    use core::convert::Infallible;
+    use core::fmt::{self, Debug};
+    use core::iter;
+    use core::cell::RefCell;
+    #[cfg(feature = "nalgebra")]
+    use core::cell::Cell;
+    use core::ops::{Index, IndexMut, Range};
+    use alloc::{vec, vec::Vec};
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{Dimensions, Point};
+    use embedded_graphics::pixelcolor::{PixelColor, Rgb888, RgbColor};
+    use embedded_graphics::prelude::Size;
+    use embedded_graphics::primitives::{PointsIter, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
+    use embedded_graphics_core::Pixel;
+    #[cfg(feature = "nalgebra")]
+    use nalgebra::{DMatrix, Matrix4, Orthographic3, Perspective3, Point3, Vector3, Vector4};
+    use crate::polygon::{blend, math, ring_edges, vertex_bounds, Polygon, Scanlines};
+
+    /// Per-pixel depths that [`Polygon3d`] tests against and updates while filling. The greater
+    /// depth is the one in front, so a buffer starts out cleared to [`DepthMap::FAR`].
+    ///
+    /// Pixels are addressed as on the display: `x` is the column counted from the left and `y`
+    /// the row counted from the top. How they are laid out in memory is up to the buffer.
+    pub trait DepthBuffer {
+        /// Returns the width and height in pixels.
+        fn size(&self) -> Size;
+
+        /// Returns the depth at `x`, `y`, or `None` outside the buffer.
+        fn depth_at(&self, x: u32, y: u32) -> Option<f32>;
+
+        /// Stores `depth` at `x`, `y` whatever is there. Outside the buffer nothing is stored.
+        fn set_depth(&mut self, x: u32, y: u32, depth: f32);
+
+        /// Returns `true` if `depth` is greater than the depth at `x`, `y`, without storing it.
+        /// Outside the buffer the result is `false`.
+        fn passes(&self, x: u32, y: u32, depth: f32) -> bool {
+            self.depth_at(x, y).is_some_and(|there| there < depth)
+        }
+
+        /// Stores `depth` at `x`, `y` and returns `true` if it is greater than the depth there.
+        /// Outside the buffer nothing is stored and the result is `false`.
+        fn test_and_set(&mut self, x: u32, y: u32, depth: f32) -> bool {
+            let nearer = self.passes(x, y, depth);
+            if nearer {
+                self.set_depth(x, y, depth);
+            }
+            nearer
+        }
+
+        /// Sets every pixel to `depth`.
+        fn clear(&mut self, depth: f32);
+    }
+
+    /// A depth buffer bound for drawing with the depth test, which draws only the pixels in front
+    /// of what the buffer holds, and the depth write, which stores the depth of the pixels drawn,
+    /// each turned on or off. Drawing through `&mut buffer` itself does both.
+    ///
+    /// Decals and overlays are drawn [`DepthTestOnly`], so they hide behind the scene without
+    /// hiding each other. Which of the four is used is fixed by the type, so the draw loops have
+    /// no extra branch for it. Pixels outside the buffer are never drawn, whatever the flags.
+    #[derive(Debug)]
+    pub struct DepthAccess<'b, B: ?Sized, const TEST: bool, const WRITE: bool> {
+        buffer: &'b mut B,
+        size: Size,
+    }
+
+    /// Draws only in front of the depths held, and leaves them as they are.
+    pub type DepthTestOnly<'b, B> = DepthAccess<'b, B, true, false>;
+    /// Draws every pixel and stores its depth, as the first layer of a scene might be.
+    pub type DepthWriteOnly<'b, B> = DepthAccess<'b, B, false, true>;
+    /// Draws every pixel and leaves the depths as they are.
+    pub type DepthIgnored<'b, B> = DepthAccess<'b, B, false, false>;
+
+    impl<'b, B: DepthBuffer + ?Sized, const TEST: bool, const WRITE: bool> DepthAccess<'b, B, TEST, WRITE> {
+        pub fn new(buffer: &'b mut B) -> Self {
+            let size = buffer.size();
+            DepthAccess { buffer, size }
+        }
+    }
+
+    impl<'b, B: DepthBuffer + ?Sized, const TEST: bool, const WRITE: bool> DepthBuffer for DepthAccess<'b, B, TEST, WRITE> {
+        fn size(&self) -> Size {
+            self.size
+        }
+
+        fn depth_at(&self, x: u32, y: u32) -> Option<f32> {
+            self.buffer.depth_at(x, y)
+        }
+
+        /// Stores `depth` only if the depth write is on.
+        fn set_depth(&mut self, x: u32, y: u32, depth: f32) {
+            if WRITE {
+                self.buffer.set_depth(x, y, depth);
+            }
+        }
+
+        /// Tests `depth` only if the depth test is on, otherwise passes anywhere in the buffer.
+        fn passes(&self, x: u32, y: u32, depth: f32) -> bool {
+            match TEST {
+                true => self.buffer.passes(x, y, depth),
+                false => x < self.size.width && y < self.size.height,
+            }
+        }
+
+        fn test_and_set(&mut self, x: u32, y: u32, depth: f32) -> bool {
+            match (TEST, WRITE) {
+                (true, true) => self.buffer.test_and_set(x, y, depth),
+                (true, false) => self.buffer.passes(x, y, depth),
+                (false, write) => {
+                    let inside = x < self.size.width && y < self.size.height;
+                    if inside && write {
+                        self.buffer.set_depth(x, y, depth);
+                    }
+                    inside
+                }
+            }
+        }
+
+        /// Clears the buffer only if the depth write is on.
+        fn clear(&mut self, depth: f32) {
+            if WRITE {
+                self.buffer.clear(depth);
+            }
+        }
+    }
+
+    /// A matrix row for each row of pixels and a column for each column, as nalgebra indexes
+    /// `(row, column)`: a 160 by 80 display takes `DMatrix::from_element(80, 160, DepthMap::FAR)`.
+    #[cfg(feature = "nalgebra")]
+    impl DepthBuffer for DMatrix<f32> {
+        fn size(&self) -> Size {
+            let (rows, columns) = self.shape();
+            Size::new(columns as u32, rows as u32)
+        }
+
+        fn depth_at(&self, x: u32, y: u32) -> Option<f32> {
+            self.get((y as usize, x as usize)).copied()
+        }
+
+        fn set_depth(&mut self, x: u32, y: u32, depth: f32) {
+            if let Some(there) = self.get_mut((y as usize, x as usize)) {
+                *there = depth;
+            }
+        }
+
+        fn test_and_set(&mut self, x: u32, y: u32, depth: f32) -> bool {
+            match self.get_mut((y as usize, x as usize)) {
+                Some(d) if *d < depth => {
+                    *d = depth;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        fn clear(&mut self, depth: f32) {
+            self.fill(depth);
+        }
+    }
+
+    /// A depth buffer owned by the crate, stored a row at a time.
+    ///
+    /// New and cleared maps hold [`FAR`](Self::FAR), which every depth is in front of, so the
+    /// first polygon drawn over a pixel always shows. Pixels are indexed by `(x, y)`.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct DepthMap {
+        width: u32,
+        depths: Vec<f32>,
+    }
+
+    impl DepthMap {
+        /// The depth of a pixel nothing has been drawn over.
+        pub const FAR: f32 = f32::NEG_INFINITY;
+
+        /// Creates a map of `width` by `height` pixels, all [`FAR`](Self::FAR).
+        pub fn new(width: u32, height: u32) -> Self {
+            DepthMap { width, depths: vec![Self::FAR; width as usize * height as usize] }
+        }
+
+        /// Sets every pixel back to [`FAR`](Self::FAR), ready for the next frame.
+        pub fn clear(&mut self) {
+            self.clear_to(Self::FAR);
+        }
+
+        /// Sets every pixel to `value`, so only depths greater than it are drawn.
+        pub fn clear_to(&mut self, value: f32) {
+            self.depths.fill(value);
+        }
+
+        pub fn width(&self) -> u32 {
+            self.width
+        }
+
+        pub fn height(&self) -> u32 {
+            if self.width == 0 { 0 } else { (self.depths.len() / self.width as usize) as u32 }
+        }
+
+        /// Returns the depth at `x`, `y`, or `None` outside the map.
+        pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+            self.index_of(x, y).map(|i| self.depths[i])
+        }
+
+        fn index_of(&self, x: u32, y: u32) -> Option<usize> {
+            (x < self.width && y < self.height()).then(|| y as usize * self.width as usize + x as usize)
+        }
+    }
+
+    impl Index<(u32, u32)> for DepthMap {
+        type Output = f32;
+
+        /// # Panics
+        ///
+        /// Panics if the pixel is outside the map.
+        fn index(&self, (x, y): (u32, u32)) -> &f32 {
+            let i = self.index_of(x, y).expect("pixel outside the depth map");
+            &self.depths[i]
+        }
+    }
+
+    impl IndexMut<(u32, u32)> for DepthMap {
+        fn index_mut(&mut self, (x, y): (u32, u32)) -> &mut f32 {
+            let i = self.index_of(x, y).expect("pixel outside the depth map");
+            &mut self.depths[i]
+        }
+    }
+
+    impl DepthBuffer for DepthMap {
+        fn size(&self) -> Size {
+            Size::new(self.width, self.height())
+        }
+
+        fn depth_at(&self, x: u32, y: u32) -> Option<f32> {
+            self.get(x, y)
+        }
+
+        fn set_depth(&mut self, x: u32, y: u32, depth: f32) {
+            if let Some(i) = self.index_of(x, y) {
+                self.depths[i] = depth;
+            }
+        }
+
+        fn test_and_set(&mut self, x: u32, y: u32, depth: f32) -> bool {
+            match self.index_of(x, y) {
+                Some(i) if self.depths[i] < depth => {
+                    self.depths[i] = depth;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        fn clear(&mut self, depth: f32) {
+            self.clear_to(depth);
+        }
+    }
+
+    /// A depth buffer over a borrowed slice, such as an array in a `static`, stored a row at a
+    /// time without allocating.
+    ///
+    /// Pixels outside `width` by `height` are never drawn, whatever the length of the slice.
+    #[derive(Debug)]
+    pub struct SliceDepthBuffer<'a> {
+        data: &'a mut [f32],
+        width: u32,
+        height: u32,
+    }
+
+    impl<'a> SliceDepthBuffer<'a> {
+        /// Takes `data` as a `width` by `height` buffer and clears it to [`DepthMap::FAR`], or
+        /// returns `None` if it is too short.
+        pub fn new(data: &'a mut [f32], width: u32, height: u32) -> Option<Self> {
+            let len = (width as usize).checked_mul(height as usize)?;
+            let data = data.get_mut(..len)?;
+            data.fill(DepthMap::FAR);
+            Some(SliceDepthBuffer { data, width, height })
+        }
+
+        /// Returns the depth at `x`, `y`, or `None` outside the buffer.
+        pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+            self.index_of(x, y).map(|i| self.data[i])
+        }
+
+        fn index_of(&self, x: u32, y: u32) -> Option<usize> {
+            (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+        }
+    }
+
+    impl<'a> DepthBuffer for SliceDepthBuffer<'a> {
+        fn size(&self) -> Size {
+            Size::new(self.width, self.height)
+        }
+
+        fn depth_at(&self, x: u32, y: u32) -> Option<f32> {
+            self.get(x, y)
+        }
+
+        fn set_depth(&mut self, x: u32, y: u32, depth: f32) {
+            if let Some(i) = self.index_of(x, y) {
+                self.data[i] = depth;
+            }
+        }
+
+        fn test_and_set(&mut self, x: u32, y: u32, depth: f32) -> bool {
+            match self.index_of(x, y) {
+                Some(i) if self.data[i] < depth => {
+                    self.data[i] = depth;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        fn clear(&mut self, depth: f32) {
+            self.data.fill(depth);
+        }
+    }
+
+    /// A polygon whose vertices each carry a depth, filled only where it is in front of what a
+    /// [`DepthBuffer`] already holds. The buffer is handed to [`draw_3d`](Self::draw_3d) for each
+    /// draw rather than kept in the polygon.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Polygon3d<'a> {
+        pub translate: Point,
+        pub vertices: &'a [(Point, f32)],
+        /// Which way round the vertices of a face turned toward the viewer go, so that faces
+        /// turned away can be skipped without drawing.
+        pub culling: Culling,
+    }
+
+    /// Whether [`Polygon3d`] skips faces turned away from the viewer, as told by which way round
+    /// their vertices go on the screen. The faces of a closed mesh seen from outside all go the
+    /// same way round, so half of them can be skipped without drawing a pixel or testing a depth.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum Culling {
+        /// Every polygon is drawn, whichever way round it goes, as for a face seen from both sides.
+        #[default]
+        None,
+        /// Faces going clockwise on the screen are turned toward the viewer, the others skipped.
+        ClockwiseFront,
+        /// Faces going counterclockwise on the screen are turned toward the viewer, the others
+        /// skipped.
+        CounterClockwiseFront,
+    }
+
+    impl<'a> Polygon3d<'a> {
+        pub fn new(vertices: &'a [(Point, f32)]) -> Self{
+            Polygon3d{
+                translate: Point::zero(),
+                vertices,
+                culling: Culling::None,
+            }
+        }
+
+        pub fn with_culling(self, culling: Culling) -> Self {
+            Polygon3d { culling, ..self }
+        }
+
+        /// Returns `true` if [`culling`](Self::culling) skips the polygon, as one turned away from
+        /// the viewer or seen edge on.
+        pub fn is_culled(&self) -> bool {
+            // twice the area enclosed on the screen, positive going clockwise as y points down
+            let area: i64 = self.vertices.iter().enumerate().map(|(i, (a, _))| {
+                let (b, _) = self.vertices[(i + 1) % self.vertices.len()];
+                a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64
+            }).sum();
+            match self.culling {
+                Culling::None => false,
+                Culling::ClockwiseFront => area <= 0,
+                Culling::CounterClockwiseFront => area >= 0,
+            }
+        }
+
+        /// Wraps the polygon with a shared depth buffer, as `Polygon3d::new` used to take, so it
+        /// can be drawn with `into_styled`.
+        #[deprecated(since = "0.2.0", note = "pass the depth buffer to `Polygon3d::draw_3d` instead")]
+        pub fn with_depth_map<B: DepthBuffer>(vertices: &'a [(Point, f32)], depth_map: &'a RefCell<B>) -> Polygon3dWithDepth<'a, B> {
+            Polygon3dWithDepth { polygon: Polygon3d::new(vertices), depth_map }
+        }
+    }
+
+    /// A [`Polygon3d`] bound to a depth buffer in a `RefCell`, from the deprecated
+    /// [`Polygon3d::with_depth_map`]. The buffer is borrowed once at the start of each draw, not
+    /// for every pixel, which panics before anything is drawn if it is already borrowed.
+    pub struct Polygon3dWithDepth<'a, B> {
+        pub polygon: Polygon3d<'a>,
+        pub depth_map: &'a RefCell<B>,
+    }
+
+    impl<'a, B> Dimensions for Polygon3dWithDepth<'a, B> {
+        fn bounding_box(&self) -> Rectangle {
+            self.polygon.bounding_box()
+        }
+    }
+
+    impl<'a, B> Primitive for Polygon3dWithDepth<'a, B> {}
+
+    impl<'a, B: DepthBuffer, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for Polygon3dWithDepth<'a, B> {
+        type Color = C;
+        type Output = ();
+
+        fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color=Self::Color> {
+            let Ok(mut depth_map) = self.depth_map.try_borrow_mut() else {
+                panic!("the depth map of a Polygon3dWithDepth is already borrowed, release it before drawing");
+            };
+            self.polygon.draw_3d(style, &mut *depth_map, target)
+        }
+    }
+
+    impl<'a> Dimensions for Polygon3d<'a> {
+        /// Returns the box around the vertices on the screen, moved by the translate offset, as
+        /// [`Polygon`](crate::polygon::Polygon) does.
+        fn bounding_box(&self) -> Rectangle {
+            vertex_bounds(self.vertices.iter().map(|(v, _)| *v + self.translate))
+        }
+    }
+
+    /// How [`Polygon3d`] reads the depth each vertex carries. Every polygon drawn into one
+    /// [`DepthBuffer`] should use the same mode, as the two store different values.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum DepthMode {
+        /// The depth is interpolated straight across the screen and stored as it is, greater in
+        /// front. Exact under an orthographic projection, and the cheaper of the two.
+        #[default]
+        Linear,
+        /// The depth is the distance from the camera, `w`, which must be positive, under a
+        /// perspective projection. `1 / w` varies linearly across the screen where `w` does not,
+        /// so that is what is interpolated and stored, and nearer is still greater.
+        Perspective,
+    }
+
+    impl DepthMode {
+        /// The value interpolated and stored for a vertex depth.
+        fn stored(self, depth: f32) -> f64 {
+            match self {
+                DepthMode::Linear => depth as f64,
+                DepthMode::Perspective => 1.0 / depth as f64,
+            }
+        }
+
+        /// The vertex depth that a stored depth, such as a [`Fragment::depth`], stands for.
+        pub fn vertex_depth(self, stored: f32) -> f32 {
+            match self {
+                DepthMode::Linear => stored,
+                DepthMode::Perspective => 1.0 / stored,
+            }
+        }
+    }
+
+    /// A value across a polygon's plane, such as its depth, `dx * x + dy * y + at_origin`.
+    #[derive(Clone, Copy, Debug)]
+    struct Plane {
+        dx: f64,
+        dy: f64,
+        at_origin: f64,
+    }
+
+    impl Plane {
+        /// Fits the plane through the vertices, at the values `value` gives them, with Newell's
+        /// method, which is exact for a flat polygon and averages over every vertex of a slightly
+        /// bent one. A polygon seen edge-on has no such plane and gets its mean value throughout.
+        fn fit<T>(vertices: &[T], value: impl Fn(&T) -> (Point, f64)) -> Self {
+            if vertices.is_empty() {
+                return Plane { dx: 0.0, dy: 0.0, at_origin: 0.0 };
+            }
+            let (mut nx, mut ny, mut nz) = (0.0, 0.0, 0.0);
+            let (mut cx, mut cy, mut cz) = (0.0, 0.0, 0.0);
+            for (i, vertex) in vertices.iter().enumerate() {
+                let ((p, z0), (q, z1)) = (value(vertex), value(&vertices[(i + 1) % vertices.len()]));
+                let (x0, y0, x1, y1) = (p.x as f64, p.y as f64, q.x as f64, q.y as f64);
+                nx += (y0 - y1) * (z0 + z1);
+                ny += (z0 - z1) * (x0 + x1);
+                nz += (x0 - x1) * (y0 + y1);
+                (cx, cy, cz) = (cx + x0, cy + y0, cz + z0);
+            }
+            let n = vertices.len() as f64;
+            let (cx, cy, cz) = (cx / n, cy / n, cz / n);
+            if nz == 0.0 {
+                return Plane { dx: 0.0, dy: 0.0, at_origin: cz };
+            }
+            let (dx, dy) = (-nx / nz, -ny / nz);
+            Plane { dx, dy, at_origin: cz - dx * cx - dy * cy }
+        }
+
+        fn at(&self, x: i32, y: i32) -> f64 {
+            self.dx * x as f64 + self.dy * y as f64 + self.at_origin
+        }
+    }
+
+    /// The pixels both `buffer` and `target` have, the only ones a polygon can be drawn on.
+    fn drawable_area<B: DepthBuffer + ?Sized>(buffer: &B, target: &impl Dimensions) -> Rectangle {
+        Rectangle::new(Point::zero(), buffer.size()).intersection(&target.bounding_box())
+    }
+
+    /// Walks the rows of the polygon through `vertices` from top to bottom and hands each span of
+    /// pixels inside it to `span` as its first column, the column past its last and its row.
+    /// Spans are cut to `area`, so none reaches left of or above it.
+    ///
+    /// The spans are those [`Scanlines`] gives the 2D fill, so a polygon covers the same pixels,
+    /// its right and bottom edges included, whether it is drawn with a depth buffer or without.
+    fn for_each_span<T, E>(vertices: &[T], point: impl Fn(&T) -> Point, area: Rectangle, mut span: impl FnMut(i32, i32, i32) -> Result<(), E>) -> Result<(), E> {
+        let Some(bottom_right) = area.bottom_right() else {
+            return Ok(());
+        };
+        let mut scanlines = Scanlines::new(ring_edges(vertices.iter().map(&point)));
+        scanlines.skip_to(area.top_left.y);
+        let mut spans = Vec::new();
+        while let Some(y) = scanlines.next_row(&mut spans).filter(|y| *y <= bottom_right.y) {
+            for &(start, end) in spans.iter() {
+                let (first, last) = (start.max(area.top_left.x), end.min(bottom_right.x));
+                if first <= last {
+                    span(first, last + 1, y)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// What 3D draws did, added up over every draw it is passed to, for measuring where the time
+    /// of a frame goes. Only the `_counted` draws fill it in, the others count nothing.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct RenderStats {
+        /// Runs of pixels on one row that a fill walked.
+        pub spans: u32,
+        /// Pixels in front of the depth buffer, which were drawn.
+        pub drawn: u32,
+        /// Pixels behind the depth buffer, which were skipped.
+        pub rejected: u32,
+        /// Faces turned away and [culled](Polygon3d::is_culled).
+        pub culled: u32,
+        /// Filled faces that left no span inside the buffer and target, lying wholly outside them,
+        /// having been cut away at the near plane, or seen edge on.
+        pub clipped: u32,
+    }
+
+    /// Where the 3D draws count what they do, `()` when nothing is asked for so the counting
+    /// compiles away.
+    trait Counter {
+        fn spans(&mut self, spans: u32);
+        fn pixel(&mut self, drawn: bool);
+        fn culled(&mut self);
+    }
+
+    impl Counter for () {
+        fn spans(&mut self, _: u32) {}
+        fn pixel(&mut self, _: bool) {}
+        fn culled(&mut self) {}
+    }
+
+    impl Counter for RenderStats {
+        fn spans(&mut self, spans: u32) {
+            self.spans += spans;
+            if spans == 0 {
+                self.clipped += 1;
+            }
+        }
+
+        fn pixel(&mut self, drawn: bool) {
+            match drawn {
+                true => self.drawn += 1,
+                false => self.rejected += 1,
+            }
+        }
+
+        fn culled(&mut self) {
+            self.culled += 1;
+        }
+    }
+
+    /// A pixel of a polygon being filled, handed to the shader of
+    /// [`Polygon3d::draw_with_shader`].
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Fragment<A = ()> {
+        pub point: Point,
+        /// The depth stored for the pixel if it is drawn.
+        pub depth: f32,
+        /// Whatever else the polygon interpolates across its face, such as the color of a
+        /// [`ShadedPolygon3d`].
+        pub attributes: A,
+    }
+
+    /// Draws `fragment` in the color `shader` gives it, if it passes the depth test and is not
+    /// discarded.
+    fn shade<A, B, C, D>(fragment: Fragment<A>, buffer: &mut B, target: &mut D, shader: &mut impl FnMut(Fragment<A>) -> Option<C>) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+        let Point { x, y } = fragment.point;
+        if !buffer.passes(x as u32, y as u32, fragment.depth) {
+            return Ok(());
+        }
+        let (point, depth) = (fragment.point, fragment.depth);
+        match shader(fragment) {
+            Some(color) => {
+                buffer.set_depth(x as u32, y as u32, depth);
+                target.draw_iter(iter::once(Pixel(point, color)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    impl<'a> Polygon3d<'a> {
+        /// Draws the polygon with `style`. Every pixel is tested against `buffer` and drawn only
+        /// where it is in front, storing its depth. A style with a stroke draws just the outline,
+        /// as [`draw_outline_3d`](Self::draw_outline_3d) does without a bias. A polygon
+        /// [culled](Self::is_culled) is not drawn at all.
+        ///
+        /// The depth of a pixel is read off the plane through the vertices, so polygons passing
+        /// through each other meet along a straight line. A fill writes the pixels in front a run
+        /// at a time with `fill_solid`, one call per row where nothing nearer breaks it up. This
+        /// is [`draw_3d_with`](Self::draw_3d_with) in [`DepthMode::Linear`].
+        pub fn draw_3d<B, C, D>(&self, style: &PrimitiveStyle<C>, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.draw_3d_with(style, DepthMode::Linear, buffer, target)
+        }
+
+        /// Draws the polygon as [`draw_3d`](Self::draw_3d) does, reading the vertex depths as
+        /// `mode` says.
+        pub fn draw_3d_with<B, C, D>(&self, style: &PrimitiveStyle<C>, mode: DepthMode, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.draw_counting(style, mode, buffer, target, &mut ())
+        }
+
+        /// Draws the polygon as [`draw_3d_with`](Self::draw_3d_with) does, adding what the draw
+        /// did to `stats`.
+        pub fn draw_3d_counted<B, C, D>(&self, style: &PrimitiveStyle<C>, mode: DepthMode, buffer: &mut B, target: &mut D, stats: &mut RenderStats) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.draw_counting(style, mode, buffer, target, stats)
+        }
+
+        fn draw_counting<B, C, D>(&self, style: &PrimitiveStyle<C>, mode: DepthMode, buffer: &mut B, target: &mut D, stats: &mut impl Counter) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            if self.is_culled() {
+                stats.culled();
+                return Ok(());
+            }
+            match style.stroke_width {
+                0 => {
+                    // nothing to fill with, so the buffer is left as it is too
+                    let Some(colour) = style.fill_color else {
+                        return Ok(());
+                    };
+                    let area = drawable_area(buffer, target);
+                    self.fill_depth(mode, buffer, area, stats, |run| target.fill_solid(&run, colour))
+                } // fill
+                _ => self.outline_counting(style, mode, 0.0, buffer, target, stats),
+            }
+        }
+
+        /// Fills the polygon into `buffer` as [`draw_3d_with`](Self::draw_3d_with) does, without
+        /// drawing any color. Whatever is drawn behind it afterwards is hidden, so this lays down
+        /// invisible occluders, or the depth of a scene before its costly color pass.
+        ///
+        /// Only a depth strictly in front passes the test, so the color pass over a prepass of the
+        /// same polygons has to move their depths forward a little to show.
+        pub fn draw_depth<B>(&self, mode: DepthMode, buffer: &mut B) where B: DepthBuffer + ?Sized {
+            if self.is_culled() {
+                return;
+            }
+            let area = Rectangle::new(Point::zero(), buffer.size());
+            self.fill_depth(mode, buffer, area, &mut (), |_| Ok::<(), Infallible>(())).unwrap_or_else(|never| match never {});
+        }
+
+        /// Fills the polygon as [`draw_3d_with`](Self::draw_3d_with) does, but in the color
+        /// `shader` returns for each pixel, such as a procedural pattern or a debug view of the
+        /// depth. A pixel for which it returns `None` is discarded, neither drawn nor stored in
+        /// `buffer`.
+        ///
+        /// Each pixel is tested against `buffer` before `shader` is called, so pixels hidden
+        /// behind what is already there never reach it. The fragment's depth is the value stored,
+        /// as `mode` says.
+        pub fn draw_with_shader<B, C, D>(&self, mode: DepthMode, buffer: &mut B, target: &mut D, mut shader: impl FnMut(Fragment) -> Option<C>) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            if self.is_culled() {
+                return Ok(());
+            }
+            let plane = Plane::fit(self.vertices, |(p, depth)| (*p + self.translate, mode.stored(*depth)));
+            for_each_span(self.vertices, |(p, _)| *p + self.translate, drawable_area(buffer, target), |first, last, y| {
+                let mut depth = plane.at(first, y);
+                for x in first..last {
+                    shade(Fragment { point: Point::new(x, y), depth: depth as f32, attributes: () }, buffer, target, &mut shader)?;
+                    depth += plane.dx;
+                }
+                Ok(())
+            })
+        }
+
+        /// Fills the polygon in `color` as [`draw_3d_with`](Self::draw_3d_with) does, with each
+        /// pixel that passes the depth test blended toward the fog color as [`Fog::apply`] blends
+        /// it. For a lit face pass the color its lighting gives it, so the fog covers the lighting.
+        pub fn draw_3d_fogged<B, C, D>(&self, color: C, mode: DepthMode, fog: &Fog<C>, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor + Into<Rgb888> + From<Rgb888>, D: DrawTarget<Color = C> {
+            self.draw_with_shader(mode, buffer, target, |fragment| Some(fog.apply(color, mode.vertex_depth(fragment.depth))))
+        }
+
+        /// Walks the fill of the polygon inside `area`, calling `drawn` with each run of pixels on
+        /// a row that pass the depth test one after another, so a target is written a run at a
+        /// time rather than a pixel at a time.
+        fn fill_depth<B, E>(&self, mode: DepthMode, buffer: &mut B, area: Rectangle, stats: &mut impl Counter, mut drawn: impl FnMut(Rectangle) -> Result<(), E>) -> Result<(), E> where B: DepthBuffer + ?Sized {
+            let plane = Plane::fit(self.vertices, |(p, depth)| (*p + self.translate, mode.stored(*depth)));
+            let mut spans = 0;
+            for_each_span(self.vertices, |(p, _)| *p + self.translate, area, |first, last, y| {
+                spans += 1;
+                let run = |start: i32, end: i32| Rectangle::new(Point::new(start, y), Size::new((end - start) as u32, 1));
+                // the depth changes by the same step from one column to the next
+                let mut depth = plane.at(first, y);
+                let mut start = None;
+                for x in first..last {
+                    let passed = buffer.test_and_set(x as u32, y as u32, depth as f32);
+                    stats.pixel(passed);
+                    match (passed, start) {
+                        (true, None) => start = Some(x),
+                        (false, Some(from)) => {
+                            drawn(run(from, x))?;
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                    depth += plane.dx;
+                }
+                match start {
+                    Some(from) => drawn(run(from, last)),
+                    None => Ok(()),
+                }
+            })?;
+            stats.spans(spans);
+            Ok(())
+        }
+
+        /// Draws the edges in the stroke color and width of `style`, testing each pixel against
+        /// `buffer` as a fill does, so an outline behind nearer polygons is hidden by them.
+        ///
+        /// The depth is stepped along each edge with its pixels, and `bias` is added to it before
+        /// the test, in the values `mode` stores. An outline drawn over its own fill ties with it
+        /// and is hidden in places, as a pixel's depth on the edge and in the fill can differ by
+        /// what the depth changes over half a pixel. A bias of at least that lays it on top. A
+        /// stroke wider than a pixel is drawn as a run of pixels across each edge, all at the
+        /// depth of the pixel at its middle. A polygon [culled](Self::is_culled) is not drawn.
+        pub fn draw_outline_3d<B, C, D>(&self, style: &PrimitiveStyle<C>, mode: DepthMode, bias: f32, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.outline_counting(style, mode, bias, buffer, target, &mut ())
+        }
+
+        fn outline_counting<B, C, D>(&self, style: &PrimitiveStyle<C>, mode: DepthMode, bias: f32, buffer: &mut B, target: &mut D, stats: &mut impl Counter) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            let Some(colour) = style.stroke_color.filter(|_| style.stroke_width > 0 && !self.is_culled()) else {
+                return Ok(());
+            };
+            let width = style.stroke_width as i32;
+            let bias = bias as f64;
+            let area = drawable_area(buffer, target);
+            for (i, (start, start_depth)) in self.vertices.iter().enumerate() {
+                let (end, end_depth) = self.vertices[(i + 1) % self.vertices.len()];
+                let (start, end) = (*start + self.translate, end + self.translate);
+                let (dx, dy) = ((end.x - start.x).abs(), -(end.y - start.y).abs());
+                let step = Point::new((end.x - start.x).signum(), (end.y - start.y).signum());
+                // a run across the edge goes along the axis the edge moves less on
+                let across = if dx >= -dy { Point::new(0, 1) } else { Point::new(1, 0) };
+                let steps = dx.max(-dy).max(1) as f64;
+                let (mut depth, depth_step) = (mode.stored(*start_depth), (mode.stored(end_depth) - mode.stored(*start_depth)) / steps);
+                let (mut p, mut error) = (start, dx + dy);
+                loop {
+                    for offset in -(width - 1) / 2..=width / 2 {
+                        let pixel = p + across * offset;
+                        // so never left of or above the origin either
+                        if !area.contains(pixel) {
+                            continue;
+                        }
+                        let passed = buffer.test_and_set(pixel.x as u32, pixel.y as u32, (depth + bias) as f32);
+                        stats.pixel(passed);
+                        if passed {
+                            target.draw_iter(iter::once(Pixel(pixel, colour)))?;
+                        }
+                    }
+                    if p == end {
+                        break;
+                    }
+                    let doubled = 2 * error;
+                    if doubled >= dy {
+                        error += dy;
+                        p.x += step.x;
+                    }
+                    if doubled <= dx {
+                        error += dx;
+                        p.y += step.y;
+                    }
+                    depth += depth_step;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Why a [`Mesh3d`] could not be built.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum MeshError {
+        /// Face `face` names vertex `index`, past the end of the vertices.
+        IndexOutOfRange { face: usize, index: u16 },
+        /// The vertices could not be projected, from [`Mesh3d::project`].
+        #[cfg(feature = "nalgebra")]
+        Projection(ProjectionError),
+        /// There are `normals` normals for `vertices` vertices, from [`Mesh3d::with_normals`].
+        #[cfg(feature = "nalgebra")]
+        NormalCount { vertices: usize, normals: usize },
+    }
+
+    impl fmt::Display for MeshError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                MeshError::IndexOutOfRange { face, index } => write!(f, "face {face} uses vertex {index}, which does not exist"),
+                #[cfg(feature = "nalgebra")]
+                MeshError::Projection(error) => write!(f, "{error}"),
+                #[cfg(feature = "nalgebra")]
+                MeshError::NormalCount { vertices, normals } => write!(f, "{normals} normals for {vertices} vertices, there must be one per vertex"),
+            }
+        }
+    }
+
+    impl core::error::Error for MeshError {}
+
+    #[cfg(feature = "nalgebra")]
+    impl From<ProjectionError> for MeshError {
+        fn from(error: ProjectionError) -> Self {
+            MeshError::Projection(error)
+        }
+    }
+
+    /// Faces sharing a buffer of projected vertices, each face a list of indices into it, such as
+    /// `[u16; 3]` for a mesh of triangles.
+    ///
+    /// Every face is drawn as a [`Polygon3d`] through its vertices would be, so a vertex shared by
+    /// several faces is projected and stored once.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Mesh3d<'a, F> {
+        vertices: &'a [(Point, f32)],
+        faces: &'a [F],
+        culling: Culling,
+        #[cfg(feature = "nalgebra")]
+        normals: Option<&'a [Vector3<f32>]>,
+    }
+
+    impl<'a, F: AsRef<[u16]>> Mesh3d<'a, F> {
+        /// Builds a mesh drawing every face, or returns an error naming the first face with an
+        /// index past the end of `vertices`.
+        pub fn new(vertices: &'a [(Point, f32)], faces: &'a [F]) -> Result<Self, MeshError> {
+            for (face, indices) in faces.iter().enumerate() {
+                if let Some(index) = indices.as_ref().iter().find(|i| **i as usize >= vertices.len()) {
+                    return Err(MeshError::IndexOutOfRange { face, index: *index });
+                }
+            }
+            Ok(Mesh3d {
+                vertices,
+                faces,
+                culling: Culling::None,
+                #[cfg(feature = "nalgebra")]
+                normals: None,
+            })
+        }
+
+        /// Skips the faces `culling` says are turned away, as [`Polygon3d::culling`] does.
+        pub fn with_culling(self, culling: Culling) -> Self {
+            Mesh3d { culling, ..self }
+        }
+
+        pub fn vertices(&self) -> &'a [(Point, f32)] {
+            self.vertices
+        }
+
+        pub fn faces(&self) -> &'a [F] {
+            self.faces
+        }
+
+        /// Draws every face with `style`, as [`Polygon3d::draw_3d`] draws each.
+        pub fn draw_3d<B, C, D>(&self, style: &PrimitiveStyle<C>, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.draw_faces(DepthMode::Linear, |_| *style, buffer, target)
+        }
+
+        /// Draws each face in the style `style` returns for its position in the faces, such as a
+        /// material looked up per face, reading the depths as `mode` says.
+        pub fn draw_faces<B, C, D>(&self, mode: DepthMode, style: impl FnMut(usize) -> PrimitiveStyle<C>, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.faces_counting(mode, style, buffer, target, &mut ())
+        }
+
+        /// Draws each face as [`draw_faces`](Self::draw_faces) does, adding what the draw did to
+        /// `stats`.
+        pub fn draw_faces_counted<B, C, D>(&self, mode: DepthMode, style: impl FnMut(usize) -> PrimitiveStyle<C>, buffer: &mut B, target: &mut D, stats: &mut RenderStats) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            self.faces_counting(mode, style, buffer, target, stats)
+        }
+
+        fn faces_counting<B, C, D>(&self, mode: DepthMode, mut style: impl FnMut(usize) -> PrimitiveStyle<C>, buffer: &mut B, target: &mut D, stats: &mut impl Counter) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor, D: DrawTarget<Color = C> {
+            // one list of vertices for every face in turn
+            let mut face_vertices = Vec::new();
+            for (face, indices) in self.faces.iter().enumerate() {
+                face_vertices.clear();
+                face_vertices.extend(indices.as_ref().iter().map(|i| self.vertices[*i as usize]));
+                let polygon = Polygon3d::new(&face_vertices).with_culling(self.culling);
+                match polygon.is_culled() {
+                    true => stats.culled(),
+                    false => polygon.draw_counting(&style(face), mode, buffer, target, stats)?,
+                }
+            }
+            Ok(())
+        }
+
+        /// Fills every face into `buffer` without drawing any color, as
+        /// [`Polygon3d::draw_depth`] fills each.
+        pub fn draw_depth<B>(&self, mode: DepthMode, buffer: &mut B) where B: DepthBuffer + ?Sized {
+            let mut face_vertices = Vec::new();
+            for indices in self.faces {
+                face_vertices.clear();
+                face_vertices.extend(indices.as_ref().iter().map(|i| self.vertices[*i as usize]));
+                Polygon3d::new(&face_vertices).with_culling(self.culling).draw_depth(mode, buffer);
+            }
+        }
+    }
+
+    /// A target wrapped with what is already shown on it, which translucent polygons are blended
+    /// over as the target itself cannot be read back.
+    ///
+    /// A [solid](Self::solid) backdrop takes every pixel to be the background color. A
+    /// [recording](Self::recording) one keeps a copy of each pixel drawn through it inside the
+    /// target's bounds, so polygons drawn through it first, and translucent ones drawn over them,
+    /// show through in their own colors.
+    pub struct Backdrop<'d, D: DrawTarget> {
+        target: &'d mut D,
+        background: D::Color,
+        area: Rectangle,
+        colors: Option<Vec<D::Color>>,
+    }
+
+    impl<'d, D: DrawTarget> Backdrop<'d, D> {
+        pub fn solid(target: &'d mut D, background: D::Color) -> Self {
+            let area = target.bounding_box();
+            Backdrop { target, background, area, colors: None }
+        }
+
+        /// Starts a copy of the target filled with `background`, so it should be cleared to it.
+        pub fn recording(target: &'d mut D, background: D::Color) -> Self {
+            let area = target.bounding_box();
+            let colors = vec![background; area.size.width as usize * area.size.height as usize];
+            Backdrop { target, background, area, colors: Some(colors) }
+        }
+
+        /// The color behind `point`, the background wherever nothing has been recorded.
+        pub fn color_at(&self, point: Point) -> D::Color {
+            match (&self.colors, index_in(self.area, point)) {
+                (Some(colors), Some(i)) => colors[i],
+                _ => self.background,
+            }
+        }
+    }
+
+    impl<'d, D: DrawTarget> Dimensions for Backdrop<'d, D> {
+        fn bounding_box(&self) -> Rectangle {
+            self.area
+        }
+    }
+
+    impl<'d, D: DrawTarget> DrawTarget for Backdrop<'d, D> {
+        type Color = D::Color;
+        type Error = D::Error;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+            if self.colors.is_none() {
+                return self.target.draw_iter(pixels);
+            }
+            let (area, colors) = (self.area, &mut self.colors);
+            self.target.draw_iter(pixels.into_iter().inspect(|Pixel(point, color)| {
+                if let (Some(colors), Some(i)) = (colors.as_mut(), index_in(area, *point)) {
+                    colors[i] = *color;
+                }
+            }))
+        }
+    }
+
+    /// Where `point` is in the rows of `area`, if it is inside.
+    fn index_in(area: Rectangle, point: Point) -> Option<usize> {
+        let offset = point - area.top_left;
+        area.contains(point).then(|| offset.y as usize * area.size.width as usize + offset.x as usize)
+    }
+
+    /// A polygon waiting in a [`TranslucentQueue`].
+    #[derive(Clone, Copy, Debug)]
+    struct Translucent<'a, C> {
+        polygon: Polygon3d<'a>,
+        color: C,
+        alpha: u8,
+        /// The mean of the stored vertex depths, which the queue is sorted by.
+        depth: f32,
+        order: usize,
+    }
+
+    /// [`TranslucentQueue::push`] was called with all `N` places taken.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct QueueFull;
+
+    impl fmt::Display for QueueFull {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "the translucent queue is full")
+        }
+    }
+
+    impl core::error::Error for QueueFull {}
+
+    /// Up to `N` translucent polygons held back until the opaque ones are drawn, then drawn
+    /// farthest first over them, without allocating.
+    ///
+    /// A frame draws every opaque polygon as usual, which fills the depth buffer, and pushes the
+    /// translucent ones here. [`draw`](Self::draw) then sorts them by the mean depth of their
+    /// vertices and fills each where it is in front of the buffer, without storing its depth, so
+    /// the ones behind still show through. Each pixel is blended from the color the [`Backdrop`]
+    /// has behind it towards the polygon's color by its alpha. Polygons passing through each other
+    /// cannot be put in one order, and blend as if the one with the farther mean were behind.
+    #[derive(Clone, Debug)]
+    pub struct TranslucentQueue<'a, C, const N: usize> {
+        entries: [Option<Translucent<'a, C>>; N],
+        len: usize,
+    }
+
+    impl<'a, C: PixelColor + Into<Rgb888> + From<Rgb888>, const N: usize> TranslucentQueue<'a, C, N> {
+        pub fn new() -> Self {
+            TranslucentQueue { entries: [None; N], len: 0 }
+        }
+
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// Queues `polygon` to be drawn in `color` with an opacity of `alpha` out of 255, reading
+        /// its depths as the draw's `mode` will. A polygon [culled](Polygon3d::is_culled) is
+        /// left out.
+        pub fn push(&mut self, polygon: Polygon3d<'a>, mode: DepthMode, color: C, alpha: u8) -> Result<(), QueueFull> {
+            if self.len == N {
+                return Err(QueueFull);
+            }
+            if !polygon.is_culled() {
+                let total: f32 = polygon.vertices.iter().map(|(_, depth)| mode.stored(*depth) as f32).sum();
+                let depth = total / polygon.vertices.len().max(1) as f32;
+                self.entries[self.len] = Some(Translucent { polygon, color, alpha, depth, order: self.len });
+                self.len += 1;
+            }
+            Ok(())
+        }
+
+        /// Draws the queued polygons farthest first, each tested against `buffer` without
+        /// changing it, and empties the queue.
+        pub fn draw<B, D>(&mut self, mode: DepthMode, buffer: &mut B, target: &mut Backdrop<D>) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, D: DrawTarget<Color = C> {
+            let queued = &mut self.entries[..self.len];
+            // ties keep the order they were pushed in
+            queued.sort_unstable_by(|a, b| {
+                let (a, b) = (a.as_ref().unwrap(), b.as_ref().unwrap());
+                a.depth.total_cmp(&b.depth).then(a.order.cmp(&b.order))
+            });
+            self.len = 0;
+            let mut buffer = DepthTestOnly::new(buffer);
+            let area = drawable_area(&buffer, target);
+            for entry in queued.iter_mut() {
+                let Some(Translucent { polygon, color, alpha, .. }) = entry.take() else {
+                    continue;
+                };
+                polygon.fill_depth(mode, &mut buffer, area, &mut (), |run| {
+                    for point in run.points() {
+                        let blended = C::from(blend(color, target.color_at(point), alpha));
+                        target.draw_iter(iter::once(Pixel(point, blended)))?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<'a, C: PixelColor + Into<Rgb888> + From<Rgb888>, const N: usize> Default for TranslucentQueue<'a, C, N> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// Which depth stands for a whole polygon when [`PolygonQueue::draw_sorted`] orders them.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum SortDepth {
+        /// The mean of the vertex depths.
+        #[default]
+        Mean,
+        /// The depth of the nearest vertex.
+        Nearest,
+    }
+
+    /// A polygon waiting in a [`PolygonQueue`], its vertices a range of the queue's.
+    #[derive(Clone, Debug)]
+    struct Queued<C: PixelColor> {
+        vertices: Range<usize>,
+        style: PrimitiveStyle<C>,
+    }
+
+    /// Polygons and meshes collected for a frame, to be drawn either against a depth buffer or,
+    /// where there is no memory for one, in the painter's order.
+    ///
+    /// The queue keeps its own copy of the vertices, with the translate offset applied, so the
+    /// faces of a mesh and polygons projected into a reused list can be pushed alike. The same
+    /// polygons can be drawn with [`draw_3d`](Self::draw_3d) or [`draw_sorted`](Self::draw_sorted),
+    /// which is all that changes between the two. Both fill the same pixels of each polygon, its
+    /// right and bottom edges included.
+    ///
+    /// Drawn in the painter's order, polygons are sorted farthest first by one depth each and
+    /// filled with the plain 2D fill, each over those before it. That is exact for polygons that
+    /// do not pass through or overlap each other in depth, such as the faces of a convex object,
+    /// but where two intersect the nearer by its sorting depth is drawn whole over the other,
+    /// instead of the two meeting along the line where they cross.
+    #[derive(Clone, Debug)]
+    pub struct PolygonQueue<C: PixelColor> {
+        vertices: Vec<(Point, f32)>,
+        polygons: Vec<Queued<C>>,
+    }
+
+    impl<C: PixelColor> PolygonQueue<C> {
+        pub fn new() -> Self {
+            PolygonQueue { vertices: Vec::new(), polygons: Vec::new() }
+        }
+
+        pub fn len(&self) -> usize {
+            self.polygons.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.polygons.is_empty()
+        }
+
+        pub fn clear(&mut self) {
+            self.vertices.clear();
+            self.polygons.clear();
+        }
+
+        /// Queues `polygon` to be drawn with `style`, leaving it out if it is
+        /// [culled](Polygon3d::is_culled).
+        pub fn push(&mut self, polygon: &Polygon3d, style: PrimitiveStyle<C>) {
+            if polygon.is_culled() {
+                return;
+            }
+            let start = self.vertices.len();
+            self.vertices.extend(polygon.vertices.iter().map(|(p, depth)| (*p + polygon.translate, *depth)));
+            self.polygons.push(Queued { vertices: start..self.vertices.len(), style });
+        }
+
+        /// Queues every face of `mesh` that is not culled, in the style `style` returns for its
+        /// position in the faces, as [`Mesh3d::draw_faces`] draws them.
+        pub fn push_mesh<F: AsRef<[u16]>>(&mut self, mesh: &Mesh3d<F>, mut style: impl FnMut(usize) -> PrimitiveStyle<C>) {
+            for (face, indices) in mesh.faces.iter().enumerate() {
+                let start = self.vertices.len();
+                self.vertices.extend(indices.as_ref().iter().map(|i| mesh.vertices[*i as usize]));
+                if Polygon3d::new(&self.vertices[start..]).with_culling(mesh.culling).is_culled() {
+                    self.vertices.truncate(start);
+                } else {
+                    self.polygons.push(Queued { vertices: start..self.vertices.len(), style: style(face) });
+                }
+            }
+        }
+
+        /// Draws the queued polygons in the order they were pushed, each tested against `buffer`
+        /// as [`Polygon3d::draw_3d_with`] does, and empties the queue.
+        pub fn draw_3d<B, D>(&mut self, mode: DepthMode, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, D: DrawTarget<Color = C> {
+            for queued in self.polygons.iter() {
+                Polygon3d::new(&self.vertices[queued.vertices.clone()]).draw_3d_with(&queued.style, mode, buffer, target)?;
+            }
+            self.clear();
+            Ok(())
+        }
+
+        /// Draws the queued polygons farthest first by the depth `sort` picks for each, read as
+        /// `mode` says, without a depth buffer, and empties the queue. Polygons at the same depth
+        /// are drawn in the order they were pushed.
+        pub fn draw_sorted<D>(&mut self, mode: DepthMode, sort: SortDepth, target: &mut D) -> Result<(), D::Error> where D: DrawTarget<Color = C> {
+            let depth = |queued: &Queued<C>| {
+                let depths = self.vertices[queued.vertices.clone()].iter().map(|(_, depth)| mode.stored(*depth));
+                match sort {
+                    SortDepth::Mean => depths.sum::<f64>() / queued.vertices.len().max(1) as f64,
+                    SortDepth::Nearest => depths.fold(f64::NEG_INFINITY, f64::max),
+                }
+            };
+            let mut order: Vec<(f64, usize)> = self.polygons.iter().map(depth).zip(0..).collect();
+            order.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut points = Vec::new();
+            for (_, i) in order {
+                let queued = &self.polygons[i];
+                points.clear();
+                points.extend(self.vertices[queued.vertices.clone()].iter().map(|(p, _)| *p));
+                Polygon::new(&points).draw_styled(&queued.style, target)?;
+            }
+            self.clear();
+            Ok(())
+        }
+    }
+
+    impl<C: PixelColor> Default for PolygonQueue<C> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// Why [`project_vertices`] could not project every point onto the screen.
+    #[cfg(feature = "nalgebra")]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ProjectionError {
+        /// Point `index` is level with or behind the camera, so the faces through it have to be
+        /// clipped, as [`project_polygon`] does, before they can be drawn.
+        BehindCamera { index: usize },
+        /// The output holds fewer vertices than there are points.
+        OutputTooShort,
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl fmt::Display for ProjectionError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ProjectionError::BehindCamera { index } => write!(f, "point {index} is behind the camera"),
+                ProjectionError::OutputTooShort => write!(f, "the output is shorter than the points"),
+            }
+        }
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl core::error::Error for ProjectionError {}
+
+    /// Projects `points` through the model-view-projection matrix `mvp` onto `viewport`, writing
+    /// the vertices for a [`Polygon3d`] or [`Mesh3d`] to the start of `out`.
+    ///
+    /// `mvp` takes points to clip space as OpenGL does, and nalgebra's `Perspective3` and
+    /// `Orthographic3` build: after dividing by `w`, `x` runs from -1 at the left of `viewport` to
+    /// 1 at its right, `y` from -1 at its bottom to 1 at its top, and `z` from -1 at the near
+    /// plane to 1 at the far one. The depth stored is that `z` negated, which puts the nearer
+    /// points in front and varies straight across the screen even under perspective, so draw
+    /// them in the default [`DepthMode::Linear`].
+    ///
+    /// Every point is projected, but a point with `w` not above zero, level with or behind the
+    /// camera, has no place on the screen, and the first is returned as an error.
+    #[cfg(feature = "nalgebra")]
+    pub fn project_vertices(points: &[Point3<f32>], mvp: &Matrix4<f32>, viewport: Rectangle, out: &mut [(Point, f32)]) -> Result<(), ProjectionError> {
+        let out = out.get_mut(..points.len()).ok_or(ProjectionError::OutputTooShort)?;
+        let mut behind = None;
+        for (index, (point, vertex)) in points.iter().zip(out.iter_mut()).enumerate() {
+            let clip = mvp * point.to_homogeneous();
+            if clip.w <= 0.0 {
+                behind = behind.or(Some(ProjectionError::BehindCamera { index }));
+            }
+            *vertex = to_screen(clip, viewport);
+        }
+        behind.map_or(Ok(()), Err)
+    }
+
+    /// Divides a point in clip space by its `w` and places it on `viewport`, with its depth.
+    #[cfg(feature = "nalgebra")]
+    fn to_screen(clip: Vector4<f32>, viewport: Rectangle) -> (Point, f32) {
+        let (width, height) = (viewport.size.width as f32, viewport.size.height as f32);
+        let (x, y, z) = (clip.x / clip.w, clip.y / clip.w, clip.z / clip.w);
+        let screen = Point::new(math::round((x + 1.0) * 0.5 * width) as i32, math::round((1.0 - y) * 0.5 * height) as i32);
+        (viewport.top_left + screen, -z)
+    }
+
+    /// Projects the polygon through `points` as [`project_vertices`] does, after cutting away
+    /// what lies nearer than the near plane, and leaves its vertices in `out`.
+    ///
+    /// The cut is made in clip space, before dividing by `w`, so the new vertices where an edge
+    /// crosses the near plane sit straight on it with the depth of the near plane, and the
+    /// polygon keeps straight edges along the cut. A polygon wholly nearer than the near plane
+    /// leaves `out` empty.
+    #[cfg(feature = "nalgebra")]
+    pub fn project_polygon(points: &[Point3<f32>], mvp: &Matrix4<f32>, viewport: Rectangle, out: &mut Vec<(Point, f32)>) {
+        out.clear();
+        let clip: Vec<Vector4<f32>> = points.iter().map(|point| mvp * point.to_homogeneous()).collect();
+        // how far a point is past the near plane, where z is -w in clip space
+        let past_near = |v: &Vector4<f32>| v.z + v.w;
+        for (i, a) in clip.iter().enumerate() {
+            let b = &clip[(i + 1) % clip.len()];
+            let (from, to) = (past_near(a), past_near(b));
+            if from >= 0.0 {
+                out.push(to_screen(*a, viewport));
+            }
+            if (from > 0.0 && to < 0.0) || (from < 0.0 && to > 0.0) {
+                out.push(to_screen(a.lerp(b, from / (from - to)), viewport));
+            }
+        }
+    }
+
+    /// Projects `points` as [`project_vertices`] does into a new list of vertices.
+    #[cfg(feature = "nalgebra")]
+    pub fn projected_vertices(points: &[Point3<f32>], mvp: &Matrix4<f32>, viewport: Rectangle) -> Result<Vec<(Point, f32)>, ProjectionError> {
+        let mut out = vec![(Point::zero(), 0.0); points.len()];
+        project_vertices(points, mvp, viewport, &mut out).map(|_| out)
+    }
+
+    /// The model, view and projection matrices and the viewport that take points in a model's
+    /// own space to the screen, as [`project_vertices`] takes them through one matrix.
+    ///
+    /// The matrices are multiplied together when first needed after one changes, and the product
+    /// of the view and projection is kept apart, so moving each model of a frame in turn costs a
+    /// single product each.
+    #[cfg(feature = "nalgebra")]
+    #[derive(Clone, Debug)]
+    pub struct Pipeline3d {
+        model: Matrix4<f32>,
+        view: Matrix4<f32>,
+        projection: Matrix4<f32>,
+        viewport: Rectangle,
+        view_projection: Cell<Option<Matrix4<f32>>>,
+        model_view_projection: Cell<Option<Matrix4<f32>>>,
+        /// How many times the view and projection have been multiplied.
+        #[cfg(test)]
+        view_projections: Cell<usize>,
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl Pipeline3d {
+        /// A pipeline with every matrix the identity, showing clip space as it is on `viewport`.
+        pub fn new(viewport: Rectangle) -> Self {
+            Pipeline3d {
+                model: Matrix4::identity(),
+                view: Matrix4::identity(),
+                projection: Matrix4::identity(),
+                viewport,
+                view_projection: Cell::new(None),
+                model_view_projection: Cell::new(None),
+                #[cfg(test)]
+                view_projections: Cell::new(0),
+            }
+        }
+
+        pub fn model(&self) -> &Matrix4<f32> {
+            &self.model
+        }
+
+        pub fn view(&self) -> &Matrix4<f32> {
+            &self.view
+        }
+
+        pub fn projection(&self) -> &Matrix4<f32> {
+            &self.projection
+        }
+
+        pub fn viewport(&self) -> Rectangle {
+            self.viewport
+        }
+
+        /// Sets the matrix placing the model in the world.
+        pub fn set_model(&mut self, model: Matrix4<f32>) {
+            self.model = model;
+            self.model_view_projection.set(None);
+        }
+
+        /// Sets the matrix taking the world to the camera's view.
+        pub fn set_view(&mut self, view: Matrix4<f32>) {
+            self.view = view;
+            self.view_projection.set(None);
+            self.model_view_projection.set(None);
+        }
+
+        /// Sets the view to a camera at `eye` looking at `target`, with `up` pointing up on the
+        /// screen, in right-handed coordinates.
+        pub fn set_view_look_at(&mut self, eye: &Point3<f32>, target: &Point3<f32>, up: &Vector3<f32>) {
+            self.set_view(Matrix4::look_at_rh(eye, target, up));
+        }
+
+        /// Sets the matrix taking the camera's view to clip space.
+        pub fn set_projection(&mut self, projection: Matrix4<f32>) {
+            self.projection = projection;
+            self.view_projection.set(None);
+            self.model_view_projection.set(None);
+        }
+
+        /// Sets a perspective projection through a lens `fovy` radians high, with `aspect` the
+        /// width over the height, showing what lies between `near` and `far` from the camera.
+        pub fn set_projection_perspective(&mut self, aspect: f32, fovy: f32, near: f32, far: f32) {
+            self.set_projection(Perspective3::new(aspect, fovy, near, far).to_homogeneous());
+        }
+
+        /// Sets an orthographic projection of the box between the given planes of the view.
+        pub fn set_projection_orthographic(&mut self, left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) {
+            self.set_projection(Orthographic3::new(left, right, bottom, top, near, far).to_homogeneous());
+        }
+
+        pub fn set_viewport(&mut self, viewport: Rectangle) {
+            self.viewport = viewport;
+        }
+
+        /// The projection times the view times the model, which takes the model to clip space.
+        pub fn model_view_projection(&self) -> Matrix4<f32> {
+            if let Some(matrix) = self.model_view_projection.get() {
+                return matrix;
+            }
+            let view_projection = self.view_projection.get().unwrap_or_else(|| {
+                #[cfg(test)]
+                self.view_projections.set(self.view_projections.get() + 1);
+                let matrix = self.projection * self.view;
+                self.view_projection.set(Some(matrix));
+                matrix
+            });
+            let matrix = view_projection * self.model;
+            self.model_view_projection.set(Some(matrix));
+            matrix
+        }
+
+        /// Projects `points` onto the viewport as [`project_vertices`] does.
+        pub fn project(&self, points: &[Point3<f32>], out: &mut [(Point, f32)]) -> Result<(), ProjectionError> {
+            project_vertices(points, &self.model_view_projection(), self.viewport, out)
+        }
+
+        /// Projects `points` onto the viewport into a new list of vertices.
+        pub fn projected(&self, points: &[Point3<f32>]) -> Result<Vec<(Point, f32)>, ProjectionError> {
+            projected_vertices(points, &self.model_view_projection(), self.viewport)
+        }
+
+        /// Projects the polygon through `points` onto the viewport, cut at the near plane, as
+        /// [`project_polygon`] does.
+        pub fn project_polygon(&self, points: &[Point3<f32>], out: &mut Vec<(Point, f32)>) {
+            project_polygon(points, &self.model_view_projection(), self.viewport, out)
+        }
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl<'a, F: AsRef<[u16]>> Mesh3d<'a, F> {
+        /// Builds a mesh of `faces` over `points` projected through `pipeline`, keeping the
+        /// projected vertices in `vertices` so the list can be reused from one frame to the next.
+        pub fn project(pipeline: &Pipeline3d, points: &[Point3<f32>], faces: &'a [F], vertices: &'a mut Vec<(Point, f32)>) -> Result<Self, MeshError> {
+            vertices.clear();
+            vertices.resize(points.len(), (Point::zero(), 0.0));
+            pipeline.project(points, vertices)?;
+            Mesh3d::new(vertices, faces)
+        }
+    }
+
+    /// A directional light for [`flat_shade`], such as the sun, shining the same way everywhere.
+    /// See [`Lighting`] for several lights or colored ones.
+    #[cfg(feature = "nalgebra")]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Light {
+        /// Points from the scene toward the light, in the space of the face normals. Its length
+        /// does not matter.
+        pub direction: Vector3<f32>,
+        /// How much of the base color a face turned straight at the light gets on top of
+        /// `ambient`.
+        pub intensity: f32,
+        /// How much of the base color every face gets, lit or not.
+        pub ambient: f32,
+    }
+
+    /// Scales each channel of `base` by how directly the face with `normal` is turned to `light`,
+    /// from `light.ambient` for a face side on or turned away up to `ambient + intensity`, and
+    /// saturates each channel rather than overflowing it. The length of `normal` does not matter,
+    /// and a zero normal gets only the ambient light.
+    #[cfg(feature = "nalgebra")]
+    pub fn flat_shade<C>(base: C, normal: Vector3<f32>, light: Light) -> C where C: PixelColor + Into<Rgb888> + From<Rgb888> {
+        Lighting::new([DirectionalLight::white(light.direction, light.intensity)], light.ambient).shade(normal, base)
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl<'a> Polygon3d<'a> {
+        /// The normal of the plane through the first three vertices, in pixels across and down
+        /// the screen and in depth out of it, turned toward the viewer so the winding of the
+        /// vertices does not matter. `None` for fewer than three vertices.
+        pub fn normal(&self) -> Option<Vector3<f32>> {
+            let [a, b, c] = [self.vertices.first()?, self.vertices.get(1)?, self.vertices.get(2)?].map(|(p, depth)| Vector3::new(p.x as f32, p.y as f32, *depth));
+            let normal = (b - a).cross(&(c - a));
+            Some(if normal.z < 0.0 { -normal } else { normal })
+        }
+
+        /// Fills the polygon in `base` lit by `light`, as [`flat_shade`] colors it, with depth
+        /// tested as [`draw_3d`](Self::draw_3d) fills. Without a `normal` the polygon's own
+        /// [`normal`](Self::normal) is used, which takes the depths to be in pixels as they are in
+        /// [`DepthMode::Linear`].
+        pub fn draw_3d_lit<B, C, D>(&self, base: C, normal: Option<Vector3<f32>>, light: Light, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor + Into<Rgb888> + From<Rgb888>, D: DrawTarget<Color = C> {
+            let normal = normal.or_else(|| self.normal()).unwrap_or_else(Vector3::zeros);
+            self.draw_3d(&PrimitiveStyle::with_fill(flat_shade(base, normal, light)), buffer, target)
+        }
+    }
+
+    /// One of the lights of a [`Lighting`], shining the same way everywhere.
+    #[cfg(feature = "nalgebra")]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct DirectionalLight {
+        /// Points from the scene toward the light, in the space of the normals. Its length does
+        /// not matter.
+        pub direction: Vector3<f32>,
+        /// The color of the light, which scales each channel it adds, white for a plain light.
+        pub color: Rgb888,
+        /// How much of the base color a face turned straight at the light gets from it.
+        pub intensity: f32,
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl DirectionalLight {
+        pub fn new(direction: Vector3<f32>, color: Rgb888, intensity: f32) -> Self {
+            DirectionalLight { direction, color, intensity }
+        }
+
+        /// A white light, adding to every channel alike.
+        pub fn white(direction: Vector3<f32>, intensity: f32) -> Self {
+            DirectionalLight::new(direction, Rgb888::WHITE, intensity)
+        }
+    }
+
+    /// A fixed set of `N` directional lights over an ambient floor, such as a key and a fill
+    /// light, evaluated per face or per vertex.
+    #[cfg(feature = "nalgebra")]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Lighting<const N: usize> {
+        pub lights: [DirectionalLight; N],
+        /// How much of the base color everything gets, lit or not.
+        pub ambient: f32,
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl<const N: usize> Lighting<N> {
+        pub fn new(lights: [DirectionalLight; N], ambient: f32) -> Self {
+            Lighting { lights, ambient }
+        }
+
+        /// Scales each channel of `base` by the ambient level plus what every light adds for how
+        /// directly `normal` is turned to it, and saturates each channel rather than overflowing
+        /// it. A light side on or behind adds nothing, and a zero normal gets only the ambient
+        /// light. The length of `normal` does not matter.
+        pub fn shade<C>(&self, normal: Vector3<f32>, base: C) -> C where C: PixelColor + Into<Rgb888> + From<Rgb888> {
+            let mut brightness = [self.ambient; 3];
+            if let Some(normal) = normal.try_normalize(0.0) {
+                for light in self.lights.iter() {
+                    let facing = light.direction.try_normalize(0.0).map_or(0.0, |direction| normal.dot(&direction).max(0.0));
+                    let color = [light.color.r(), light.color.g(), light.color.b()];
+                    for (channel, tint) in brightness.iter_mut().zip(color) {
+                        *channel += light.intensity * facing * tint as f32 / 255.0;
+                    }
+                }
+            }
+            let base: Rgb888 = base.into();
+            let scale = |channel: u8, brightness: f32| math::round(channel as f32 * brightness.max(0.0)).min(255.0) as u8;
+            Rgb888::new(scale(base.r(), brightness[0]), scale(base.g(), brightness[1]), scale(base.b(), brightness[2])).into()
+        }
+    }
+
+    #[cfg(feature = "nalgebra")]
+    impl<'a, F: AsRef<[u16]>> Mesh3d<'a, F> {
+        /// Gives every vertex a normal for [`draw_lit`](Self::draw_lit) to light it by, or
+        /// returns an error if there is not exactly one per vertex.
+        pub fn with_normals(self, normals: &'a [Vector3<f32>]) -> Result<Self, MeshError> {
+            match normals.len() == self.vertices.len() {
+                true => Ok(Mesh3d { normals: Some(normals), ..self }),
+                false => Err(MeshError::NormalCount { vertices: self.vertices.len(), normals: normals.len() }),
+            }
+        }
+
+        /// Draws each face in the color `material` returns for its position in the faces, lit by
+        /// `lighting`, faded by `fog` if there is any, and reading the depths as `mode` says.
+        ///
+        /// Without [normals](Self::with_normals) every face is lit flat by its own
+        /// [`Polygon3d::normal`]. With them, each vertex is lit by its own and the colors are
+        /// blended across the face as [`ShadedPolygon3d`] blends them (Gouraud shading). Fog is
+        /// blended in last, pixel by pixel, over either.
+        pub fn draw_lit<B, C, D, const N: usize>(&self, mode: DepthMode, mut material: impl FnMut(usize) -> C, lighting: &Lighting<N>, fog: Option<&Fog<C>>, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, C: PixelColor + Into<Rgb888> + From<Rgb888>, D: DrawTarget<Color = C> {
+            let mut face_vertices = Vec::new();
+            let mut shaded = Vec::new();
+            for (face, indices) in self.faces.iter().enumerate() {
+                face_vertices.clear();
+                face_vertices.extend(indices.as_ref().iter().map(|i| self.vertices[*i as usize]));
+                let polygon = Polygon3d::new(&face_vertices).with_culling(self.culling);
+                if polygon.is_culled() {
+                    continue;
+                }
+                let base = material(face);
+                match self.normals {
+                    None => {
+                        let color = lighting.shade(polygon.normal().unwrap_or_else(Vector3::zeros), base);
+                        match fog {
+                            Some(fog) => polygon.draw_3d_fogged(color, mode, fog, buffer, target)?,
+                            None => polygon.draw_3d_with(&PrimitiveStyle::with_fill(color), mode, buffer, target)?,
+                        }
+                    }
+                    Some(normals) => {
+                        shaded.clear();
+                        shaded.extend(indices.as_ref().iter().map(|i| {
+                            let (point, depth) = self.vertices[*i as usize];
+                            (point, depth, lighting.shade(normals[*i as usize], base))
+                        }));
+                        let polygon = ShadedPolygon3d::new(&shaded);
+                        match fog {
+                            Some(fog) => polygon.draw_3d_fogged(mode, fog, buffer, target)?,
+                            None => polygon.draw_3d(mode, buffer, target)?,
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// A [`Polygon3d`] whose vertices each carry a color too, blended smoothly across the face
+    /// (Gouraud shading) where a `Polygon3d` is filled in one color.
+    ///
+    /// Each color channel is read off the plane through the vertices as the depth is, which is
+    /// exact for a triangle. Past three vertices the colors only match where they lie on a plane,
+    /// so draw a mesh with colors that differ sharply across a face as triangles. In
+    /// [`DepthMode::Perspective`] the channels are divided by `w` before interpolating and
+    /// multiplied back per pixel, as [`TexturedPolygon3d`] does its coordinates, so a blend does
+    /// not bunch up toward the far side of a receding face.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ShadedPolygon3d<'a, C> {
+        pub vertices: &'a [(Point, f32, C)],
+    }
+
+    impl<'a, C> ShadedPolygon3d<'a, C> where C: PixelColor + Into<Rgb888> + From<Rgb888> {
+        pub fn new(vertices: &'a [(Point, f32, C)]) -> Self {
+            ShadedPolygon3d { vertices }
+        }
+
+        /// Fills the polygon, testing every pixel against `buffer` and drawing the ones in front
+        /// in their blended color, as [`Polygon3d::draw_3d_with`] fills.
+        pub fn draw_3d<B, D>(&self, mode: DepthMode, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, D: DrawTarget<Color = C> {
+            self.draw_with_shader(mode, buffer, target, |fragment| Some(fragment.attributes))
+        }
+
+        /// Fills the polygon as [`draw_3d`](Self::draw_3d) does, with each blended color then
+        /// blended toward the fog color as [`Fog::apply`] blends it.
+        pub fn draw_3d_fogged<B, D>(&self, mode: DepthMode, fog: &Fog<C>, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, D: DrawTarget<Color = C> {
+            self.draw_with_shader(mode, buffer, target, |fragment| Some(fog.apply(fragment.attributes, mode.vertex_depth(fragment.depth))))
+        }
+
+        /// Fills the polygon as [`Polygon3d::draw_with_shader`] does, with the blended color of
+        /// each pixel as the fragment's attributes.
+        pub fn draw_with_shader<B, D>(&self, mode: DepthMode, buffer: &mut B, target: &mut D, mut shader: impl FnMut(Fragment<C>) -> Option<C>) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, D: DrawTarget<Color = C> {
+            let depth_plane = Plane::fit(self.vertices, |(p, depth, _)| (*p, mode.stored(*depth)));
+            // under perspective the channels go across the screen as `c / w`, as texture
+            // coordinates do, and `1 / w` is the stored depth to divide by
+            let weight = move |depth: f32| match mode {
+                DepthMode::Linear => 1.0,
+                DepthMode::Perspective => mode.stored(depth),
+            };
+            let channel = |channel: fn(Rgb888) -> u8| Plane::fit(self.vertices, move |(p, depth, color)| (*p, channel((*color).into()) as f64 * weight(*depth)));
+            let planes = [channel(|c| c.r()), channel(|c| c.g()), channel(|c| c.b())];
+            for_each_span(self.vertices, |(p, ..)| *p, drawable_area(buffer, target), |first, last, y| {
+                // each value starts at the span's first pixel and steps by the same amount per pixel
+                let mut depth = depth_plane.at(first, y);
+                let mut rgb = planes.map(|plane| plane.at(first, y));
+                for x in first..last {
+                    let divisor = match mode {
+                        DepthMode::Linear => 1.0,
+                        DepthMode::Perspective => depth,
+                    };
+                    let [r, g, b] = rgb.map(|value| math::round(value / divisor).clamp(0.0, 255.0) as u8);
+                    let fragment = Fragment { point: Point::new(x, y), depth: depth as f32, attributes: Rgb888::new(r, g, b).into() };
+                    shade(fragment, buffer, target, &mut shader)?;
+                    depth += depth_plane.dx;
+                    for (value, plane) in rgb.iter_mut().zip(planes.iter()) {
+                        *value += plane.dx;
+                    }
+                }
+                Ok(())
+            })
+        }
+    }
+
+    /// How [`Fog`] thickens between its start and end depths.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub enum FogFalloff {
+        /// In even steps.
+        #[default]
+        Linear,
+        /// Quickly at first and more slowly toward the end, as `1 - e^(-density * t)` for the
+        /// fraction `t` of the way from start to end, scaled to reach full fog at the end. The
+        /// greater the density the sooner near things fade. A density of zero or less is linear.
+        Exponential(f32),
+    }
+
+    /// Fades pixels toward a fog color the farther away they are, so distant geometry recedes.
+    ///
+    /// The depths are vertex depths, as given to the polygons whatever the [`DepthMode`]: no fog
+    /// at `start` or anywhere on the near side of it, full fog at `end` and past it. In
+    /// [`DepthMode::Linear`], where greater is in front, `start` is then the greater of the two.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Fog<C> {
+        pub color: C,
+        pub start: f32,
+        pub end: f32,
+        pub falloff: FogFalloff,
+    }
+
+    impl<C> Fog<C> where C: PixelColor + Into<Rgb888> + From<Rgb888> {
+        /// Fog thickening linearly from `start` to `end`.
+        pub fn new(color: C, start: f32, end: f32) -> Self {
+            Fog { color, start, end, falloff: FogFalloff::Linear }
+        }
+
+        pub fn with_falloff(self, falloff: FogFalloff) -> Self {
+            Fog { falloff, ..self }
+        }
+
+        /// How much of the fog color a pixel at `depth` takes, from 0 at the start to 1 at the
+        /// end. Fog whose start and end are the same depth has no effect.
+        pub fn mix(&self, depth: f32) -> f32 {
+            if self.start == self.end {
+                return 0.0;
+            }
+            let t = ((depth - self.start) / (self.end - self.start)).clamp(0.0, 1.0);
+            match self.falloff {
+                FogFalloff::Exponential(density) if density > 0.0 => (1.0 - math::exp(-density * t)) / (1.0 - math::exp(-density)),
+                _ => t,
+            }
+        }
+
+        /// Blends `color` toward the fog color by [`mix`](Self::mix) at `depth`.
+        pub fn apply(&self, color: C, depth: f32) -> C {
+            let mix = self.mix(depth);
+            if mix <= 0.0 {
+                return color;
+            }
+            blend(self.color, color, math::round(mix * 255.0).min(255.0) as u8).into()
+        }
+    }
+
+    /// An image that [`TexturedPolygon3d`] reads its pixels from.
+    pub trait Texture {
+        type Color: PixelColor;
+
+        /// Returns the width and height in pixels.
+        fn size(&self) -> Size;
+
+        /// Returns the pixel at `x`, `y`, which is always inside [`size`](Self::size).
+        fn texel(&self, x: u32, y: u32) -> Self::Color;
+    }
+
+    /// A texture over a borrowed slice of colors, stored a row at a time, such as one decoded
+    /// from a bitmap or held in a `static`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct SliceTexture<'a, C> {
+        pixels: &'a [C],
+        width: u32,
+        height: u32,
+    }
+
+    impl<'a, C> SliceTexture<'a, C> {
+        /// Takes `pixels` as a `width` by `height` texture, or returns `None` if it is empty or
+        /// `pixels` is too short.
+        pub fn new(pixels: &'a [C], width: u32, height: u32) -> Option<Self> {
+            let len = (width as usize).checked_mul(height as usize)?;
+            (len > 0 && pixels.len() >= len).then_some(SliceTexture { pixels, width, height })
+        }
+    }
+
+    impl<'a, C: PixelColor> Texture for SliceTexture<'a, C> {
+        type Color = C;
+
+        fn size(&self) -> Size {
+            Size::new(self.width, self.height)
+        }
+
+        fn texel(&self, x: u32, y: u32) -> C {
+            self.pixels[y as usize * self.width as usize + x as usize]
+        }
+    }
+
+    /// What [`TexturedPolygon3d`] samples for texture coordinates outside `0.0..1.0`.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum TextureAddressing {
+        /// The texture repeats, so `1.25` samples where `0.25` does.
+        #[default]
+        Wrap,
+        /// The edge pixels stretch out past the texture.
+        Clamp,
+    }
+
+    impl TextureAddressing {
+        /// The pixel of a texture `size` pixels across that `coordinate` lands on.
+        fn texel(self, coordinate: f64, size: u32) -> u32 {
+            let texel = math::floor(coordinate * size as f64) as i64;
+            match self {
+                TextureAddressing::Wrap => texel.rem_euclid(size as i64) as u32,
+                TextureAddressing::Clamp => texel.clamp(0, size as i64 - 1) as u32,
+            }
+        }
+    }
+
+    /// A [`Polygon3d`] filled from a [`Texture`], its vertices each carrying the coordinates
+    /// `(u, v)` of the point on the texture they show, from `(0.0, 0.0)` at its top left corner
+    /// to `(1.0, 1.0)` at its bottom right.
+    ///
+    /// In [`DepthMode::Linear`] the coordinates are interpolated straight across the screen
+    /// (affine mapping), which bends the texture on faces that recede under a perspective
+    /// projection. In [`DepthMode::Perspective`] they are divided by `w` before interpolating and
+    /// multiplied back per pixel, so the texture lies flat on the face.
+    #[derive(Clone, Copy, Debug)]
+    pub struct TexturedPolygon3d<'a, T> {
+        pub vertices: &'a [(Point, f32, (f32, f32))],
+        pub texture: &'a T,
+        pub addressing: TextureAddressing,
+    }
+
+    impl<'a, T: Texture> TexturedPolygon3d<'a, T> {
+        /// A polygon showing `texture` with [`TextureAddressing::Wrap`].
+        pub fn new(vertices: &'a [(Point, f32, (f32, f32))], texture: &'a T) -> Self {
+            TexturedPolygon3d { vertices, texture, addressing: TextureAddressing::default() }
+        }
+
+        pub fn with_addressing(self, addressing: TextureAddressing) -> Self {
+            TexturedPolygon3d { addressing, ..self }
+        }
+
+        /// Fills the polygon, testing every pixel against `buffer` and drawing the ones in front
+        /// in the texture's pixel under them, as [`Polygon3d::draw_3d_with`] fills. Each pixel
+        /// takes the texture's nearest pixel.
+        pub fn draw_3d<B, D>(&self, mode: DepthMode, buffer: &mut B, target: &mut D) -> Result<(), D::Error> where B: DepthBuffer + ?Sized, D: DrawTarget<Color = T::Color> {
+            let Size { width, height } = self.texture.size();
+            if width == 0 || height == 0 {
+                return Ok(());
+            }
+            let depth_plane = Plane::fit(self.vertices, |(p, depth, _)| (*p, mode.stored(*depth)));
+            // under perspective the coordinates go across the screen as `u / w`, and `1 / w` is
+            // the stored depth to divide by
+            let weight = |depth: f32| match mode {
+                DepthMode::Linear => 1.0,
+                DepthMode::Perspective => mode.stored(depth),
+            };
+            let u_plane = Plane::fit(self.vertices, |(p, depth, (u, _))| (*p, *u as f64 * weight(*depth)));
+            let v_plane = Plane::fit(self.vertices, |(p, depth, (_, v))| (*p, *v as f64 * weight(*depth)));
+            for_each_span(self.vertices, |(p, ..)| *p, drawable_area(buffer, target), |first, last, y| {
+                let (mut depth, mut u, mut v) = (depth_plane.at(first, y), u_plane.at(first, y), v_plane.at(first, y));
+                for x in first..last {
+                    if buffer.test_and_set(x as u32, y as u32, depth as f32) {
+                        let divisor = match mode {
+                            DepthMode::Linear => 1.0,
+                            DepthMode::Perspective => depth,
+                        };
+                        let texel = self.texture.texel(self.addressing.texel(u / divisor, width), self.addressing.texel(v / divisor, height));
+                        target.draw_iter(iter::once(Pixel(Point::new(x, y), texel)))?;
+                    }
+                    depth += depth_plane.dx;
+                    u += u_plane.dx;
+                    v += v_plane.dx;
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::cell::RefCell;
+        use std::ops::{Add, Sub};
+        use std::time::{Duration, Instant};
+        use colored::Colorize;
+        use embedded_graphics::Drawable;
+        use embedded_graphics::mock_display::MockDisplay;
+        use embedded_graphics::pixelcolor::{BinaryColor, Rgb565, Rgb888, RgbColor};
+        use embedded_graphics::prelude::{Dimensions, Point, Size};
+        use embedded_graphics::primitives::{Circle, Line, Polyline, Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle};
+        use embedded_graphics::Pixel;
+        use embedded_graphics_core::prelude::DrawTarget;
+        use embedded_graphics_simulator::{BinaryColorTheme, OutputSettings, SimulatorEvent};
+        use embedded_graphics_simulator::sdl2::Keycode;
+        use itertools::Itertools;
+        #[cfg(feature = "nalgebra")]
+        use nalgebra::DMatrix;
+        use rand::{Rng, thread_rng};
+        use crate::polygon::{vertex_bounds, Polygon};
+        use crate::polygon::tests::{check_bounding_box, fill_pixels, FailAfter, PixelCollector};
+        use crate::polygon::math;
+        use super::{Backdrop, Culling, DepthBuffer, DepthIgnored, DepthMap, DepthMode, DepthTestOnly, DepthWriteOnly, Fog, FogFalloff, Fragment, Mesh3d, MeshError, Polygon3d, PolygonQueue, QueueFull, RenderStats, ShadedPolygon3d, SortDepth, SliceDepthBuffer, SliceTexture, TextureAddressing, TexturedPolygon3d, TranslucentQueue};
+
+        /// Draws two triangles crossing each other in depth, and a square behind both.
+        fn render_scene<B: DepthBuffer>(buffer: &mut B) -> MockDisplay<Rgb888> {
+            let scene: [&[(Point, f32)]; 3] = [
+                &[(Point::new(5, 5), 1.0), (Point::new(55, 10), 5.0), (Point::new(15, 50), 1.0)],
+                &[(Point::new(50, 5), 4.0), (Point::new(40, 55), 0.0), (Point::new(5, 30), 4.0)],
+                &[(Point::new(0, 0), -1.0), (Point::new(63, 0), -1.0), (Point::new(63, 40), -1.0), (Point::new(0, 40), -1.0)],
+            ];
+            let colors = [Rgb888::new(255, 0, 0), Rgb888::new(0, 255, 0), Rgb888::new(0, 0, 255)];
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            for (vertices, color) in scene.iter().zip(colors) {
+                Polygon3d::new(vertices).draw_3d(&PrimitiveStyle::with_fill(color), buffer, &mut display).unwrap();
+            }
+            display
+        }
+
+        #[test]
+        fn test_depth_buffers_render_alike() {
+            let mut map = DepthMap::new(64, 48);
+            let expected = render_scene(&mut map);
+            // the square shows only where neither triangle is in front of it
+            assert_eq!(expected.get_pixel(Point::new(60, 38)), Some(Rgb888::new(0, 0, 255)));
+            assert_eq!(expected.get_pixel(Point::new(5, 45)), None);
+
+            let mut storage = [0.0; 64 * 48];
+            render_scene(&mut SliceDepthBuffer::new(&mut storage, 64, 48).unwrap()).assert_eq(&expected);
+            assert!((0..64).all(|x| (0..48).all(|y| map[(x, y)] == storage[y as usize * 64 + x as usize])));
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_matrix_renders_like_depth_map() {
+            let mut matrix = DMatrix::from_element(48, 64, 0.0);
+            matrix.clear(DepthMap::FAR);
+            assert_eq!(matrix.size(), Size::new(64, 48));
+            render_scene(&mut matrix).assert_eq(&render_scene(&mut DepthMap::new(64, 48)));
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_wide_matrix_occludes_across_its_width() {
+            // a 160 by 80 display, with a near band over a far one reaching past both sides
+            let far = [(Point::new(-10, 10), 1.0), (Point::new(170, 10), 1.0), (Point::new(170, 70), 1.0), (Point::new(-10, 70), 1.0)];
+            let near = [(Point::new(-10, 20), 2.0), (Point::new(170, 20), 2.0), (Point::new(170, 60), 2.0), (Point::new(-10, 60), 2.0)];
+            let style = PrimitiveStyle::with_fill(BinaryColor::On);
+            let draw = |vertices: &[(Point, f32)], matrix: &mut DMatrix<f32>| {
+                let mut collector = PixelCollector::default();
+                Polygon3d::new(vertices).draw_3d(&style, matrix, &mut collector).unwrap();
+                collector.pixels
+            };
+            let mut matrix = DMatrix::from_element(80, 160, DepthMap::FAR);
+            let far_pixels = draw(&far, &mut matrix);
+            let near_pixels = draw(&near, &mut matrix);
+            // every column of the display is reached, and the near band wins wherever it is
+            assert_eq!(near_pixels.iter().map(|p| p.x).collect::<std::collections::BTreeSet<_>>().len(), 160);
+            assert!(near_pixels.iter().all(|p| far_pixels.contains(p)));
+            assert_eq!(matrix[(40, 150)], 2.0);
+
+            let mut matrix = DMatrix::from_element(80, 160, DepthMap::FAR);
+            assert_eq!(draw(&near, &mut matrix), near_pixels);
+            let behind = draw(&far, &mut matrix);
+            assert!(behind.iter().all(|p| !near_pixels.contains(p)));
+            assert_eq!(behind.len() + near_pixels.len(), far_pixels.len());
+        }
+
+        #[test]
+        fn test_slice_buffer_occludes() {
+            // as a buffer in a `static` would be, touched only through the slice
+            let mut storage = [0.0f32; 32 * 24];
+            assert!(SliceDepthBuffer::new(&mut storage, 32, 25).is_none());
+            let near: [(Point, f32); 3] = [(Point::new(2, 2), 2.0), (Point::new(20, 2), 2.0), (Point::new(2, 20), 2.0)];
+            let far: [(Point, f32); 3] = [(Point::new(8, 8), 1.0), (Point::new(30, 8), 1.0), (Point::new(8, 22), 1.0)];
+            let mut displays = Vec::new();
+            for order in [[(&near, BinaryColor::On), (&far, BinaryColor::Off)], [(&far, BinaryColor::Off), (&near, BinaryColor::On)]] {
+                let mut buffer = SliceDepthBuffer::new(&mut storage, 32, 24).unwrap();
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                for (vertices, color) in order {
+                    Polygon3d::new(vertices).draw_3d(&PrimitiveStyle::with_fill(color), &mut buffer, &mut display).unwrap();
+                }
+                assert!(buffer.get(10, 9).is_some_and(|depth| depth > 1.5));
+                displays.push(display);
+            }
+            // the near triangle covers the overlap whichever was drawn first
+            assert_eq!(displays[0].get_pixel(Point::new(10, 9)), Some(BinaryColor::On));
+            assert_eq!(displays[0].get_pixel(Point::new(20, 12)), Some(BinaryColor::Off));
+            displays[1].assert_eq(&displays[0]);
+            assert!(storage.contains(&DepthMap::FAR));
+        }
+
+        #[test]
+        fn test_style_without_fill_draws_nothing() {
+            let vertices = [(Point::new(2, 2), 1.0), (Point::new(20, 4), 2.0), (Point::new(6, 18), 3.0)];
+            let styles = [
+                PrimitiveStyleBuilder::<BinaryColor>::new().build(),
+                PrimitiveStyleBuilder::new().stroke_color(BinaryColor::On).stroke_width(0).build(),
+            ];
+            for style in styles {
+                let mut buffer = DepthMap::new(24, 24);
+                let mut collector = PixelCollector::default();
+                Polygon3d::new(&vertices).draw_3d(&style, &mut buffer, &mut collector).unwrap();
+                Polygon3d::new(&vertices).draw_outline_3d(&style, DepthMode::Linear, 0.0, &mut buffer, &mut collector).unwrap();
+                assert!(collector.pixels.is_empty(), "{style:?}");
+                assert_eq!(buffer, DepthMap::new(24, 24));
+            }
+        }
+
+        #[test]
+        fn test_translucent_quad_over_opaque_one() {
+            let square = |left: i32, top: i32, right: i32, bottom: i32, depth: f32| {
+                [(Point::new(left, top), depth), (Point::new(right, top), depth), (Point::new(right, bottom), depth), (Point::new(left, bottom), depth)]
+            };
+            let red_quad = square(2, 2, 9, 9, 1.0);
+            let (glass, hidden) = (square(6, 6, 13, 13, 2.0), square(0, 0, 3, 3, 0.5));
+            let (over_red, over_black) = (Rgb888::new(127, 0, 128), Rgb888::new(0, 0, 128));
+
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(16, 16);
+            let mut backdrop = Backdrop::recording(&mut display, Rgb888::BLACK);
+            Polygon3d::new(&red_quad).draw_3d(&PrimitiveStyle::with_fill(Rgb888::RED), &mut buffer, &mut backdrop).unwrap();
+            let opaque = buffer.clone();
+            let mut queue = TranslucentQueue::<Rgb888, 2>::new();
+            queue.push(Polygon3d::new(&glass), DepthMode::Linear, Rgb888::BLUE, 128).unwrap();
+            queue.push(Polygon3d::new(&hidden), DepthMode::Linear, Rgb888::GREEN, 128).unwrap();
+            assert_eq!(queue.push(Polygon3d::new(&glass), DepthMode::Linear, Rgb888::BLUE, 128), Err(QueueFull));
+            queue.draw(DepthMode::Linear, &mut buffer, &mut backdrop).unwrap();
+            assert!(queue.is_empty());
+            assert_eq!(buffer, opaque);
+
+            for y in 0..16 {
+                for x in 0..16 {
+                    let (in_red, in_glass) = ((2..10).contains(&x) && (2..10).contains(&y), (6..14).contains(&x) && (6..14).contains(&y));
+                    let expected = match (in_red, in_glass) {
+                        (true, true) => Some(over_red),
+                        (false, true) => Some(over_black),
+                        (true, false) => Some(Rgb888::RED),
+                        // the green quad is behind the red one, and only shows around it
+                        (false, false) if x < 4 && y < 4 => Some(Rgb888::new(0, 128, 0)),
+                        (false, false) => None,
+                    };
+                    assert_eq!(display.get_pixel(Point::new(x, y)), expected, "{x}, {y}");
+                }
+            }
+
+            // over a solid backdrop the glass blends towards the background everywhere
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(16, 16);
+            let mut backdrop = Backdrop::solid(&mut display, Rgb888::BLACK);
+            Polygon3d::new(&red_quad).draw_3d(&PrimitiveStyle::with_fill(Rgb888::RED), &mut buffer, &mut backdrop).unwrap();
+            let mut queue = TranslucentQueue::<Rgb888, 1>::new();
+            queue.push(Polygon3d::new(&glass), DepthMode::Linear, Rgb888::BLUE, 128).unwrap();
+            queue.draw(DepthMode::Linear, &mut buffer, &mut backdrop).unwrap();
+            assert_eq!(display.get_pixel(Point::new(8, 8)), Some(over_black));
+        }
+
+        #[test]
+        fn test_painter_order_stacks_separate_quads() {
+            let square = |left: i32, top: i32, right: i32, bottom: i32, depth: f32| {
+                [(Point::new(left, top), depth), (Point::new(right, top), depth), (Point::new(right, bottom), depth), (Point::new(left, bottom), depth)]
+            };
+            // pushed nearest first, so only sorting puts them right
+            let quads = [(square(3, 8, 12, 13, 3.0), Rgb888::BLUE), (square(0, 0, 10, 10, 1.0), Rgb888::RED), (square(5, 5, 15, 15, 2.0), Rgb888::GREEN)];
+            let vertices: Vec<(Point, f32)> = quads.iter().flat_map(|(quad, _)| *quad).collect();
+            let faces = [[0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11]];
+            let mesh = Mesh3d::new(&vertices, &faces).unwrap();
+
+            let mut queue = PolygonQueue::new();
+            queue.push_mesh(&mesh, |face| PrimitiveStyle::with_fill(quads[face].1));
+            let turned_away = Polygon3d::new(&quads[0].0).with_culling(Culling::CounterClockwiseFront);
+            queue.push(&turned_away, PrimitiveStyle::with_fill(Rgb888::WHITE));
+            assert_eq!(queue.len(), 3);
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            queue.draw_sorted(DepthMode::Linear, SortDepth::Mean, &mut display).unwrap();
+            assert!(queue.is_empty());
+
+            // each pixel shows the nearest quad the 2D fill covers it with
+            let mut nearest_first: Vec<_> = quads.iter().map(|(quad, color)| {
+                let points = quad.map(|(p, _)| p);
+                (quad[0].1, fill_pixels(&Polygon::new(&points)), *color)
+            }).collect();
+            nearest_first.sort_by(|a, b| b.0.total_cmp(&a.0));
+            for y in 0..20 {
+                for x in 0..20 {
+                    let p = Point::new(x, y);
+                    let expected = nearest_first.iter().find(|(_, pixels, _)| pixels.contains(&p)).map(|(_, _, color)| *color);
+                    assert_eq!(display.get_pixel(p), expected, "{p:?}");
+                }
+            }
+
+            // the same queue drawn against a depth buffer covers the same pixels, edges included,
+            // and as the quads do not cross in depth, shows the same one at each
+            let mut queue = PolygonQueue::new();
+            queue.push_mesh(&mesh, |face| PrimitiveStyle::with_fill(quads[face].1));
+            let mut buffered = MockDisplay::new();
+            buffered.set_allow_overdraw(true);
+            queue.draw_3d(DepthMode::Linear, &mut DepthMap::new(20, 20), &mut buffered).unwrap();
+            buffered.assert_eq(&display);
+        }
+
+        #[test]
+        fn test_painter_order_cannot_split_crossing_quads() {
+            let wall = [(Point::new(0, 0), 2.0), (Point::new(16, 0), 2.0), (Point::new(16, 16), 2.0), (Point::new(0, 16), 2.0)];
+            // passes through the wall halfway across, behind it on the left and in front on the right
+            let tilted = [(Point::new(0, 4), 0.5), (Point::new(16, 4), 3.1), (Point::new(16, 12), 3.1), (Point::new(0, 12), 0.5)];
+            let draw = |sort: Option<SortDepth>| {
+                let mut queue = PolygonQueue::new();
+                queue.push(&Polygon3d::new(&wall), PrimitiveStyle::with_fill(Rgb888::RED));
+                queue.push(&Polygon3d::new(&tilted), PrimitiveStyle::with_fill(Rgb888::GREEN));
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                match sort {
+                    Some(sort) => queue.draw_sorted(DepthMode::Linear, sort, &mut display).unwrap(),
+                    None => queue.draw_3d(DepthMode::Linear, &mut DepthMap::new(16, 16), &mut display).unwrap(),
+                }
+                [Point::new(2, 8), Point::new(14, 8)].map(|p| display.get_pixel(p).unwrap())
+            };
+            // the depth buffer meets the two along the line where they cross
+            assert_eq!(draw(None), [Rgb888::RED, Rgb888::GREEN]);
+            // sorted whole, one of the two always lies over the other
+            assert_eq!(draw(Some(SortDepth::Mean)), [Rgb888::RED, Rgb888::RED]);
+            assert_eq!(draw(Some(SortDepth::Nearest)), [Rgb888::GREEN, Rgb888::GREEN]);
+        }
+
+        #[test]
+        fn test_screen_door_shader() {
+            let square = |left: i32, top: i32, right: i32, bottom: i32, depth: f32| {
+                [(Point::new(left, top), depth), (Point::new(right, top), depth), (Point::new(right, bottom), depth), (Point::new(left, bottom), depth)]
+            };
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(16, 16);
+            Polygon3d::new(&square(0, 0, 15, 15, 1.0)).draw_3d(&PrimitiveStyle::with_fill(Rgb565::RED), &mut buffer, &mut display).unwrap();
+
+            // every other pixel of the door is cut away, letting the background show through
+            let door = square(4, 4, 11, 11, 2.0);
+            let mut shaded = 0;
+            Polygon3d::new(&door).draw_with_shader(DepthMode::Linear, &mut buffer, &mut display, |fragment: Fragment| {
+                shaded += 1;
+                assert_eq!(fragment.depth, 2.0);
+                let Point { x, y } = fragment.point;
+                ((x + y) % 2 == 0).then_some(Rgb565::BLUE)
+            }).unwrap();
+            assert_eq!(shaded, 64);
+            for y in 0..16 {
+                for x in 0..16 {
+                    let open = (4..12).contains(&x) && (4..12).contains(&y) && (x + y) % 2 == 0;
+                    assert_eq!(display.get_pixel(Point::new(x as i32, y as i32)), Some(if open { Rgb565::BLUE } else { Rgb565::RED }), "{x}, {y}");
+                    assert_eq!(buffer.get(x, y), Some(if open { 2.0 } else { 1.0 }), "discarded pixels keep their depth");
+                }
+            }
+
+            // pixels failing the depth test never reach the shader
+            Polygon3d::new(&square(0, 0, 15, 15, 0.5)).draw_with_shader(DepthMode::Linear, &mut buffer, &mut display, |_| -> Option<Rgb565> {
+                panic!("shaded a hidden pixel")
+            }).unwrap();
+        }
+
+        #[test]
+        fn test_render_stats_count_exactly() {
+            let square = |left: i32, top: i32, right: i32, bottom: i32, depth: f32| {
+                [(Point::new(left, top), depth), (Point::new(right, top), depth), (Point::new(right, bottom), depth), (Point::new(left, bottom), depth)]
+            };
+            let fill = PrimitiveStyle::with_fill(Rgb565::WHITE);
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(16, 16);
+            let mut stats = RenderStats::default();
+
+            // 4 rows of 8 pixels
+            Polygon3d::new(&square(0, 0, 7, 3, 1.0)).draw_3d_counted(&fill, DepthMode::Linear, &mut buffer, &mut display, &mut stats).unwrap();
+            assert_eq!(stats, RenderStats { spans: 4, drawn: 32, ..RenderStats::default() });
+            // 4 rows of 8 more, 2 rows of 4 of them behind the first
+            Polygon3d::new(&square(4, 2, 11, 5, 0.5)).draw_3d_counted(&fill, DepthMode::Linear, &mut buffer, &mut display, &mut stats).unwrap();
+            assert_eq!(stats, RenderStats { spans: 8, drawn: 56, rejected: 8, ..RenderStats::default() });
+
+            let mut reversed = square(0, 8, 7, 11, 1.0);
+            reversed.reverse();
+            Polygon3d::new(&reversed).with_culling(Culling::ClockwiseFront).draw_3d_counted(&fill, DepthMode::Linear, &mut buffer, &mut display, &mut stats).unwrap();
+            Polygon3d::new(&square(20, 20, 30, 30, 1.0)).draw_3d_counted(&fill, DepthMode::Linear, &mut buffer, &mut display, &mut stats).unwrap();
+            // what the near plane leaves of a polygon wholly in front of it
+            Polygon3d::new(&[]).draw_3d_counted(&fill, DepthMode::Linear, &mut buffer, &mut display, &mut stats).unwrap();
+            assert_eq!(stats, RenderStats { spans: 8, drawn: 56, rejected: 8, culled: 1, clipped: 2 });
+
+            // a one pixel outline tests each of its pixels, the corners twice
+            let mut stats = RenderStats::default();
+            Polygon3d::new(&square(2, 10, 5, 13, 2.0)).draw_3d_counted(&PrimitiveStyle::with_stroke(Rgb565::RED, 1), DepthMode::Linear, &mut buffer, &mut display, &mut stats).unwrap();
+            assert_eq!(stats, RenderStats { drawn: 12, rejected: 4, ..RenderStats::default() });
+
+            // the back half of a cube is culled
+            let corners = cube_corners();
+            let mesh = Mesh3d::new(&corners, &CUBE_FACES).unwrap().with_culling(Culling::ClockwiseFront);
+            let mut stats = RenderStats::default();
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            mesh.draw_faces_counted(DepthMode::Linear, |_| fill, &mut DepthMap::new(64, 64), &mut display, &mut stats).unwrap();
+            assert_eq!((stats.culled, stats.clipped), (3, 0));
+            let lit = (0..64).flat_map(|y| (0..64).map(move |x| Point::new(x, y))).filter(|p| display.get_pixel(*p).is_some()).count();
+            assert!(stats.drawn as usize >= lit && lit > 0);
+        }
+
+        /// Counts the allocations each thread makes, so tests running alongside do not disturb
+        /// the count.
+        struct CountingAllocator;
+
+        thread_local! {
+            static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+
+        unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+                let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+                std::alloc::System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+                std::alloc::System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+        #[test]
+        fn test_fill_allocates_nothing_per_pixel() {
+            let allocations = |size: i32| {
+                let quad = [(Point::new(0, 0), 1.0), (Point::new(size, 0), 2.0), (Point::new(size, size), 3.0), (Point::new(0, size), 2.0)];
+                let mut buffer = DepthMap::new(64, 64);
+                let mut display = MockDisplay::new();
+                let before = ALLOCATIONS.with(|count| count.get());
+                Polygon3d::new(&quad).draw_3d(&PrimitiveStyle::with_fill(Rgb565::WHITE), &mut buffer, &mut display).unwrap();
+                ALLOCATIONS.with(|count| count.get()) - before
+            };
+            // the edge table is built once per polygon, whatever its size
+            let small = allocations(4);
+            assert_eq!(allocations(60), small);
+            assert!((1..=4).contains(&small), "{small} allocations");
+        }
+
+        /// Counts the calls made to draw into a mock display.
+        struct CallCounter {
+            display: MockDisplay<Rgb565>,
+            calls: usize,
+        }
+
+        impl Dimensions for CallCounter {
+            fn bounding_box(&self) -> Rectangle {
+                self.display.bounding_box()
+            }
+        }
+
+        impl DrawTarget for CallCounter {
+            type Color = Rgb565;
+            type Error = core::convert::Infallible;
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Rgb565>> {
+                self.calls += 1;
+                self.display.draw_iter(pixels)
+            }
+
+            fn fill_solid(&mut self, area: &Rectangle, color: Rgb565) -> Result<(), Self::Error> {
+                self.calls += 1;
+                self.display.fill_solid(area, color)
+            }
+        }
+
+        #[test]
+        fn test_fill_draws_a_run_at_a_time() {
+            let square = |left: i32, top: i32, right: i32, bottom: i32, depth: f32| {
+                [(Point::new(left, top), depth), (Point::new(right, top), depth), (Point::new(right, bottom), depth), (Point::new(left, bottom), depth)]
+            };
+            // the second quad is behind the first, so two of its rows start partway along
+            let scene = [(square(0, 0, 7, 3, 2.0), Rgb565::RED), (square(4, 2, 11, 5, 1.0), Rgb565::BLUE)];
+            let mut target = CallCounter { display: MockDisplay::new(), calls: 0 };
+            let mut buffer = DepthMap::new(16, 16);
+            let mut expected = MockDisplay::new();
+            let mut expected_buffer = DepthMap::new(16, 16);
+            for (quad, color) in scene.iter() {
+                Polygon3d::new(quad).draw_3d(&PrimitiveStyle::with_fill(*color), &mut buffer, &mut target).unwrap();
+                // the shader path still draws a pixel at a time
+                Polygon3d::new(quad).draw_with_shader(DepthMode::Linear, &mut expected_buffer, &mut expected, |_| Some(*color)).unwrap();
+            }
+            target.display.assert_eq(&expected);
+            assert_eq!(buffer, expected_buffer);
+            // one call for each of the 8 rows, where the 56 pixels drawn once took one each
+            assert_eq!(target.calls, 8);
+        }
+
+        #[test]
+        fn test_bounding_box_matches_2d() {
+            check_bounding_box(|vertices, translate| {
+                let vertices: Vec<(Point, f32)> = vertices.iter().map(|v| (*v, 1.0)).collect();
+                let mut polygon = Polygon3d::new(&vertices);
+                polygon.translate = translate;
+                polygon.bounding_box()
+            });
+        }
+
+        #[test]
+        fn test_translate_moves_fill_outline_and_bounds() {
+            let vertices = [(Point::new(2, 1), 1.0), (Point::new(12, 4), 2.0), (Point::new(6, 10), 3.0)];
+            let translate = Point::new(5, 3);
+            let moved: Vec<(Point, f32)> = vertices.iter().map(|(p, depth)| (*p + translate, *depth)).collect();
+            let mut polygon = Polygon3d::new(&vertices);
+            polygon.translate = translate;
+            let draw = |polygon: &Polygon3d, style: &PrimitiveStyle<BinaryColor>| {
+                let mut buffer = DepthMap::new(32, 32);
+                let mut collector = PixelCollector::default();
+                polygon.draw_3d(style, &mut buffer, &mut collector).unwrap();
+                collector.pixels.sort_by_key(|p| (p.y, p.x));
+                (collector.pixels, buffer)
+            };
+            let (fill, outline) = (PrimitiveStyle::with_fill(BinaryColor::On), PrimitiveStyle::with_stroke(BinaryColor::On, 1));
+
+            // drawn as if the offset were added to the vertices, depths and all
+            let (filled, outlined) = (draw(&polygon, &fill), draw(&polygon, &outline));
+            assert_eq!(filled, draw(&Polygon3d::new(&moved), &fill));
+            assert_eq!(outlined, draw(&Polygon3d::new(&moved), &outline));
+            let (mut depths, mut expected) = (DepthMap::new(32, 32), DepthMap::new(32, 32));
+            polygon.draw_depth(DepthMode::Linear, &mut depths);
+            Polygon3d::new(&moved).draw_depth(DepthMode::Linear, &mut expected);
+            assert_eq!(depths, expected);
+
+            // and the fill, the outline and the bounding box all agree on where that is
+            let bounds = polygon.bounding_box();
+            assert_eq!(vertex_bounds(filled.0.iter().copied()), bounds);
+            assert_eq!(vertex_bounds(outlined.0.iter().copied()), bounds);
+        }
+
+        #[test]
+        fn test_decals_drawn_without_depth_writes() {
+            let square = |left: i32, top: i32, right: i32, bottom: i32, depth: f32| {
+                [(Point::new(left, top), depth), (Point::new(right, top), depth), (Point::new(right, bottom), depth), (Point::new(left, bottom), depth)]
+            };
+            let (wall, block) = (square(0, 0, 16, 16, 1.0), square(9, 0, 16, 16, 2.0));
+            let (first, second) = (square(2, 2, 8, 8, 1.5), square(5, 5, 12, 12, 1.5));
+            let scene = |display: &mut MockDisplay<Rgb565>| {
+                display.set_allow_overdraw(true);
+                let mut buffer = DepthMap::new(16, 16);
+                Polygon3d::new(&wall).draw_3d(&PrimitiveStyle::with_fill(Rgb565::BLUE), &mut buffer, display).unwrap();
+                Polygon3d::new(&block).draw_3d(&PrimitiveStyle::with_fill(Rgb565::WHITE), &mut buffer, display).unwrap();
+                buffer
+            };
+
+            // written as they are drawn, the second decal loses to the first where they overlap
+            let mut display = MockDisplay::new();
+            let mut buffer = scene(&mut display);
+            Polygon3d::new(&first).draw_3d(&PrimitiveStyle::with_fill(Rgb565::RED), &mut buffer, &mut display).unwrap();
+            Polygon3d::new(&second).draw_3d(&PrimitiveStyle::with_fill(Rgb565::GREEN), &mut buffer, &mut display).unwrap();
+            assert_eq!(display.get_pixel(Point::new(6, 6)), Some(Rgb565::RED));
+
+            let mut display = MockDisplay::new();
+            let mut buffer = scene(&mut display);
+            let before = buffer.clone();
+            Polygon3d::new(&first).draw_3d(&PrimitiveStyle::with_fill(Rgb565::RED), &mut DepthTestOnly::new(&mut buffer), &mut display).unwrap();
+            Polygon3d::new(&second).draw_3d(&PrimitiveStyle::with_fill(Rgb565::GREEN), &mut DepthTestOnly::new(&mut buffer), &mut display).unwrap();
+            assert_eq!(display.get_pixel(Point::new(3, 3)), Some(Rgb565::RED));
+            assert_eq!(display.get_pixel(Point::new(6, 6)), Some(Rgb565::GREEN));
+            // still hidden behind the nearer block
+            assert_eq!(display.get_pixel(Point::new(10, 6)), Some(Rgb565::WHITE));
+            assert_eq!(display.get_pixel(Point::new(14, 14)), Some(Rgb565::WHITE));
+            assert_eq!(buffer, before);
+
+            // with the test off everything is drawn, and only a write moves the depths
+            let behind = square(0, 0, 16, 16, 0.5);
+            let mut display = MockDisplay::new();
+            Polygon3d::new(&behind).draw_3d(&PrimitiveStyle::with_fill(Rgb565::RED), &mut DepthIgnored::new(&mut buffer), &mut display).unwrap();
+            assert_eq!(display.get_pixel(Point::new(10, 6)), Some(Rgb565::RED));
+            assert_eq!(buffer, before);
+            Polygon3d::new(&behind).draw_3d(&PrimitiveStyle::with_fill(Rgb565::RED), &mut DepthWriteOnly::new(&mut buffer), &mut MockDisplay::new()).unwrap();
+            assert_eq!(buffer.get(10, 6), Some(0.5));
+            assert_eq!(buffer.depth_at(16, 6), None);
+        }
+
+        #[test]
+        fn test_shaded_triangle_blends_vertex_colors() {
+            let (red, green, blue) = (Rgb888::new(255, 0, 0), Rgb888::new(0, 255, 0), Rgb888::new(0, 0, 255));
+            let triangle = [(Point::new(0, 0), 1.0, red), (Point::new(30, 0), 1.0, green), (Point::new(0, 30), 1.0, blue)];
+            let mut display = MockDisplay::new();
+            ShadedPolygon3d::new(&triangle).draw_3d(DepthMode::Linear, &mut DepthMap::new(64, 64), &mut display).unwrap();
+
+            // each channel falls off in a straight line from its own corner
+            let expected = |x: i32, y: i32| {
+                let channel = |weight: f32| (255.0 * weight).round() as u8;
+                Rgb888::new(channel(1.0 - (x + y) as f32 / 30.0), channel(x as f32 / 30.0), channel(y as f32 / 30.0))
+            };
+            assert_eq!(display.get_pixel(Point::new(0, 0)), Some(red));
+            assert_eq!(display.get_pixel(Point::new(15, 0)), Some(Rgb888::new(128, 128, 0)));
+            let mut drawn = 0;
+            for (x, y) in (0..30).flat_map(|y| (0..30).map(move |x| (x, y))) {
+                let Some(color) = display.get_pixel(Point::new(x, y)) else { continue };
+                let target = expected(x, y);
+                let close = |a: u8, b: u8| a.abs_diff(b) <= 1;
+                assert!(close(color.r(), target.r()) && close(color.g(), target.g()) && close(color.b(), target.b()), "{x} {y} {color:?}");
+                drawn += 1;
+            }
+            assert!(drawn > 400);
+
+            // the same blend in 16 bit color, nearer than the first so it shows over it
+            let narrow = triangle.map(|(p, _, color)| (p, 2.0, Rgb565::from(color)));
+            let mut narrow_display = MockDisplay::new();
+            let mut buffer = DepthMap::new(64, 64);
+            ShadedPolygon3d::new(&narrow).draw_3d(DepthMode::Linear, &mut buffer, &mut narrow_display).unwrap();
+            assert_eq!(narrow_display.get_pixel(Point::new(0, 0)), Some(Rgb565::RED));
+            for (x, y) in (0..30).flat_map(|y| (0..30).map(move |x| (x, y))) {
+                let color = display.get_pixel(Point::new(x, y)).map(Rgb565::from);
+                assert_eq!(narrow_display.get_pixel(Point::new(x, y)), color);
+            }
+            // a farther copy is hidden behind it
+            let mut hidden = MockDisplay::<Rgb888>::new();
+            ShadedPolygon3d::new(&triangle).draw_3d(DepthMode::Linear, &mut buffer, &mut hidden).unwrap();
+            assert_eq!(hidden, MockDisplay::new());
+        }
+
+        #[test]
+        fn test_shaded_receding_quad_blends_in_perspective() {
+            // three times as far on the right, where the color goes from black to red
+            let (near, far) = (Rgb888::new(0, 0, 0), Rgb888::new(255, 0, 0));
+            let quad = [(Point::new(8, 8), 1.0, near), (Point::new(24, 8), 3.0, far), (Point::new(24, 24), 3.0, far), (Point::new(8, 24), 1.0, near)];
+            let draw = |mode| {
+                let mut display = MockDisplay::new();
+                ShadedPolygon3d::new(&quad).draw_3d(mode, &mut DepthMap::new(32, 32), &mut display).unwrap();
+                display
+            };
+            let receding = draw(DepthMode::Perspective);
+            // a fraction `s` across the screen is `s / (3 - 2s)` of the way along the face
+            for x in 8..24 {
+                let s = (x - 8) as f32 / 16.0;
+                let red = (255.0 * s / (3.0 - 2.0 * s)).round() as u8;
+                let color = receding.get_pixel(Point::new(x, 16)).unwrap();
+                assert!(color.r().abs_diff(red) <= 1 && color.g() == 0 && color.b() == 0, "{x} {color:?}");
+            }
+            assert_eq!(receding.get_pixel(Point::new(8, 16)), Some(near));
+            assert_eq!(receding.get_pixel(Point::new(16, 16)), Some(Rgb888::new(64, 0, 0)));
+            // straight across the screen it is halfway to red halfway across
+            assert_eq!(draw(DepthMode::Linear).get_pixel(Point::new(16, 16)), Some(Rgb888::new(128, 0, 0)));
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_lit_cube_shows_three_faces() {
+            use nalgebra::{Rotation3, Vector3};
+            use super::{flat_shade, Light};
+            // a cube turned to show its front, right and top, projected straight onto the screen
+            let turn = Rotation3::<f32>::from_euler_angles(-0.5, -0.6, 0.0);
+            let corners: Vec<(Point, f32)> = (0..8).map(|i| {
+                let corner = turn * Vector3::new([-1.0, 1.0][i & 1], [-1.0, 1.0][i >> 1 & 1], [-1.0, 1.0][i >> 2 & 1]) * 12.0;
+                (Point::new(32 + corner.x.round() as i32, 32 + corner.y.round() as i32), corner.z)
+            }).collect();
+            let faces = [[0, 1, 3, 2], [4, 5, 7, 6], [0, 1, 5, 4], [2, 3, 7, 6], [0, 2, 6, 4], [1, 3, 7, 5]];
+            let light = Light { direction: Vector3::new(0.5, -1.0, 1.5), intensity: 0.8, ambient: 0.2 };
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(64, 64);
+            for face in faces {
+                let vertices = face.map(|i| corners[i]);
+                Polygon3d::new(&vertices).draw_3d_lit(Rgb888::new(200, 120, 40), None, light, &mut buffer, &mut display).unwrap();
+            }
+            let mut shades: Vec<Rgb888> = (0..64).flat_map(|y| (0..64).map(move |x| Point::new(x, y))).filter_map(|p| display.get_pixel(p)).collect();
+            shades.sort_by_key(|c| (c.r(), c.g(), c.b()));
+            shades.dedup();
+            assert_eq!(shades.len(), 3, "{shades:?}");
+
+            // turned away or side on it gets the ambient light alone, and bright light saturates
+            let base = Rgb565::new(20, 40, 10);
+            assert_eq!(flat_shade(base, Vector3::new(0.0, 1.0, 0.0), light), flat_shade(base, Vector3::new(1.0, 0.0, 0.0), Light { direction: Vector3::new(0.0, 1.0, 0.0), ..light }));
+            assert_eq!(flat_shade(Rgb888::new(100, 100, 100), -light.direction, light), Rgb888::new(20, 20, 20));
+            assert_eq!(flat_shade(Rgb888::new(100, 100, 100), Vector3::zeros(), light), Rgb888::new(20, 20, 20));
+            assert_eq!(flat_shade(Rgb888::new(100, 200, 250), light.direction * 3.0, Light { intensity: 2.0, ..light }), Rgb888::new(220, 255, 255));
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_lights_add_up_and_saturate() {
+            use nalgebra::Vector3;
+            use super::{DirectionalLight, Lighting};
+            let key = Vector3::new(0.5, -1.0, 1.5);
+            let fill = Vector3::new(-1.0, 0.0, 0.5);
+            let base = Rgb888::new(200, 120, 40);
+            // two half lights from the same way light as much as one whole one
+            let whole = Lighting::new([DirectionalLight::white(key, 0.8)], 0.1);
+            let halves = Lighting::new([DirectionalLight::white(key, 0.4), DirectionalLight::white(key * 2.0, 0.4)], 0.1);
+            for normal in [key, fill, Vector3::new(0.2, 0.3, 1.0), -key] {
+                let [a, b] = [whole.shade(normal, base), halves.shade(normal, base)];
+                assert!(a.r().abs_diff(b.r()) <= 1 && a.g().abs_diff(b.g()) <= 1 && a.b().abs_diff(b.b()) <= 1, "{a:?} {b:?}");
+            }
+            // lights from different ways add their shares, each only on the faces turned to it
+            let both = Lighting::new([DirectionalLight::white(key, 0.5), DirectionalLight::white(fill, 0.5)], 0.0);
+            let gray = Rgb888::new(100, 100, 100);
+            let sum = |normal| Lighting::new([DirectionalLight::white(key, 0.5)], 0.0).shade(normal, gray).r() + Lighting::new([DirectionalLight::white(fill, 0.5)], 0.0).shade(normal, gray).r();
+            let between = key.normalize() + fill.normalize();
+            assert!(both.shade(between, gray).r().abs_diff(sum(between)) <= 1);
+            assert_eq!(both.shade(-between, gray), Rgb888::BLACK);
+            assert_eq!(both.shade(Vector3::zeros(), gray), Rgb888::BLACK);
+
+            // every channel saturates at white rather than wrapping, and a colored light tints
+            let bright = Lighting::new([DirectionalLight::white(key, 2.0), DirectionalLight::white(key, 2.0)], 0.5);
+            assert_eq!(bright.shade(key, Rgb888::new(100, 200, 250)), Rgb888::WHITE);
+            assert_eq!(bright.shade(key, Rgb565::new(20, 40, 10)), Rgb565::WHITE);
+            let red = Lighting::new([DirectionalLight::new(key, Rgb888::RED, 1.0)], 0.2);
+            assert_eq!(red.shade(key, gray), Rgb888::new(120, 20, 20));
+
+            // a lit mesh, flat per face or blended from per vertex normals
+            let vertices = [(Point::new(4, 4), 1.0), (Point::new(28, 4), 1.0), (Point::new(28, 28), 1.0), (Point::new(4, 28), 1.0)];
+            let faces = [[0u16, 1, 2], [0, 2, 3]];
+            let mesh = Mesh3d::new(&vertices, &faces).unwrap();
+            let lighting = Lighting::new([DirectionalLight::white(Vector3::new(1.0, 0.0, 1.0), 0.6), DirectionalLight::white(Vector3::new(-1.0, 0.0, 1.0), 0.6)], 0.1);
+            let draw = |mesh: Mesh3d<[u16; 3]>| {
+                let mut display = MockDisplay::new();
+                mesh.draw_lit(DepthMode::Linear, |_| gray, &lighting, None, &mut DepthMap::new(32, 32), &mut display).unwrap();
+                display
+            };
+            let flat = draw(mesh);
+            assert_eq!(flat.get_pixel(Point::new(20, 8)), Some(lighting.shade(Vector3::z(), gray)));
+            assert_eq!(flat.get_pixel(Point::new(8, 20)), flat.get_pixel(Point::new(20, 8)));
+            // the left edge faces one light head on, the right edge takes a share of both
+            let normals = [Vector3::new(-1.0, 0.0, 1.0), Vector3::z(), Vector3::z(), Vector3::new(-1.0, 0.0, 1.0)];
+            assert_eq!(mesh.with_normals(&normals[..3]), Err(MeshError::NormalCount { vertices: 4, normals: 3 }));
+            let smooth = draw(mesh.with_normals(&normals).unwrap());
+            let [left, middle, right] = [4, 16, 27].map(|x| smooth.get_pixel(Point::new(x, 16)).unwrap().r());
+            assert_eq!(left, lighting.shade(normals[0], gray).r());
+            assert!(left < middle && middle < right, "{left} {middle} {right}");
+        }
+
+        #[test]
+        fn test_fog_thickens_down_a_corridor() {
+            // quads side by side, each twice as far away as the one before
+            let distances = [1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+            let quads: Vec<[(Point, f32); 4]> = distances.iter().enumerate().map(|(i, w)| {
+                let x = i as i32 * 10;
+                [(Point::new(x, 0), *w), (Point::new(x + 8, 0), *w), (Point::new(x + 8, 8), *w), (Point::new(x, 8), *w)]
+            }).collect();
+            let corridor = |fog: Fog<Rgb888>| -> Vec<u8> {
+                let mut display = MockDisplay::new();
+                let mut buffer = DepthMap::new(64, 64);
+                for quad in quads.iter() {
+                    Polygon3d::new(quad).draw_3d_fogged(Rgb888::new(200, 0, 0), DepthMode::Perspective, &fog, &mut buffer, &mut display).unwrap();
+                }
+                // how far each quad moved from its red toward the fog's blue
+                (0..quads.len() as i32).map(|i| display.get_pixel(Point::new(i * 10 + 4, 4)).unwrap().b()).collect()
+            };
+            for falloff in [FogFalloff::Linear, FogFalloff::Exponential(3.0)] {
+                let mixes = corridor(Fog::new(Rgb888::BLUE, 2.0, 16.0).with_falloff(falloff));
+                assert_eq!([mixes[0], mixes[1], mixes[4], mixes[5]], [0, 0, 255, 255], "{falloff:?}");
+                assert!(mixes.windows(2).all(|pair| pair[0] <= pair[1]) && mixes[2] < mixes[3], "{falloff:?} {mixes:?}");
+            }
+            let linear = Fog::new(Rgb888::BLUE, 2.0, 16.0);
+            let exponential = linear.with_falloff(FogFalloff::Exponential(3.0));
+            assert!(exponential.mix(4.0) > linear.mix(4.0) && exponential.mix(8.0) > linear.mix(8.0));
+            assert_eq!(linear.with_falloff(FogFalloff::Exponential(0.0)).mix(9.0), 0.5);
+
+            // with greater depths in front, the fog starts at the greater depth
+            let linear_depths = Fog::new(Rgb565::WHITE, 10.0, 0.0);
+            assert_eq!([12.0, 10.0, 5.0, 0.0, -3.0].map(|depth| linear_depths.mix(depth)), [0.0, 0.0, 0.5, 1.0, 1.0]);
+            assert_eq!(linear_depths.apply(Rgb565::BLACK, 5.0), Rgb565::new(16, 32, 16));
+            assert_eq!(Fog::new(Rgb565::WHITE, 3.0, 3.0).apply(Rgb565::BLACK, 10.0), Rgb565::BLACK);
+
+            // over a Gouraud face the fog is blended into each blended color
+            let triangle = [(Point::new(0, 0), 0.0, Rgb888::RED), (Point::new(12, 0), 4.0, Rgb888::GREEN), (Point::new(0, 12), 8.0, Rgb888::BLUE)];
+            let fog = Fog::new(Rgb888::WHITE, 0.0, 12.0);
+            let [mut plain, mut fogged] = [MockDisplay::new(), MockDisplay::new()];
+            ShadedPolygon3d::new(&triangle).draw_3d(DepthMode::Linear, &mut DepthMap::new(16, 16), &mut plain).unwrap();
+            let mut buffer = DepthMap::new(16, 16);
+            ShadedPolygon3d::new(&triangle).draw_3d_fogged(DepthMode::Linear, &fog, &mut buffer, &mut fogged).unwrap();
+            for p in (0..16).flat_map(|y| (0..16).map(move |x| Point::new(x, y))) {
+                assert_eq!(fogged.get_pixel(p), plain.get_pixel(p).map(|color| fog.apply(color, buffer.get(p.x as u32, p.y as u32).unwrap())), "{p:?}");
+            }
+        }
+
+        #[test]
+        fn test_checkerboard_texture_quadrants() {
+            use BinaryColor::{Off, On};
+            let pixels = [On, Off, Off, On];
+            let checkerboard = SliceTexture::new(&pixels, 2, 2).unwrap();
+            assert!(SliceTexture::new(&pixels, 2, 3).is_none());
+            let square = |w: [f32; 2], uv: [f32; 2]| [
+                (Point::new(8, 8), w[0], (uv[0], uv[0])),
+                (Point::new(24, 8), w[1], (uv[1], uv[0])),
+                (Point::new(24, 24), w[1], (uv[1], uv[1])),
+                (Point::new(8, 24), w[0], (uv[0], uv[1])),
+            ];
+            let draw = |polygon: TexturedPolygon3d<SliceTexture<BinaryColor>>, mode| {
+                let mut display = MockDisplay::new();
+                polygon.draw_3d(mode, &mut DepthMap::new(32, 32), &mut display).unwrap();
+                display
+            };
+            let quadrant = |p: Point| if (p.x < 16) == (p.y < 16) { On } else { Off };
+            let vertices = square([1.0, 1.0], [0.0, 1.0]);
+            let display = draw(TexturedPolygon3d::new(&vertices, &checkerboard), DepthMode::Linear);
+            for p in (8..24).flat_map(|y| (8..24).map(move |x| Point::new(x, y))) {
+                assert_eq!(display.get_pixel(p), Some(quadrant(p)), "{p:?}");
+            }
+            // the same face at one depth throughout maps alike under perspective
+            draw(TexturedPolygon3d::new(&vertices, &checkerboard), DepthMode::Perspective).assert_eq(&display);
+
+            // twice over, the texture repeats into four squares a side, or stretches its edges
+            let vertices = square([1.0, 1.0], [-0.5, 1.5]);
+            let wrapped = draw(TexturedPolygon3d::new(&vertices, &checkerboard), DepthMode::Linear);
+            let clamped = draw(TexturedPolygon3d::new(&vertices, &checkerboard).with_addressing(TextureAddressing::Clamp), DepthMode::Linear);
+            for (x, y, wrap, clamp) in [(9, 9, On, On), (13, 9, Off, On), (17, 9, On, Off), (21, 9, Off, Off), (21, 21, On, On), (13, 21, On, Off)] {
+                assert_eq!((wrapped.get_pixel(Point::new(x, y)), clamped.get_pixel(Point::new(x, y))), (Some(wrap), Some(clamp)), "{x} {y}");
+            }
+
+            // three times as far on the right, the middle of the texture lands three quarters
+            // across, where it is halfway across without perspective; a single row, as a square on
+            // the screen is not a flat face when it recedes
+            let stripes = SliceTexture::new(&pixels[..2], 2, 1).unwrap();
+            let vertices = square([1.0, 3.0], [0.0, 1.0]);
+            let receding = draw(TexturedPolygon3d::new(&vertices, &stripes), DepthMode::Perspective);
+            assert_eq!(receding.get_pixel(Point::new(19, 10)), Some(On));
+            assert_eq!(receding.get_pixel(Point::new(21, 20)), Some(Off));
+            let affine = draw(TexturedPolygon3d::new(&vertices, &stripes), DepthMode::Linear);
+            assert_eq!(affine.get_pixel(Point::new(17, 10)), Some(Off));
+
+            // and it is depth tested as any fill
+            let mut buffer = DepthMap::new(32, 32);
+            let near = [(Point::new(0, 0), 2.0), (Point::new(32, 0), 2.0), (Point::new(32, 32), 2.0), (Point::new(0, 32), 2.0)];
+            Polygon3d::new(&near).draw_3d(&PrimitiveStyle::with_fill(On), &mut buffer, &mut PixelCollector::default()).unwrap();
+            let mut hidden = PixelCollector::default();
+            TexturedPolygon3d::new(&square([1.0, 1.0], [0.0, 1.0]), &checkerboard).draw_3d(DepthMode::Linear, &mut buffer, &mut hidden).unwrap();
+            assert!(hidden.pixels.is_empty());
+        }
+
+        /// The corners of a cube turned to show three faces, projected onto a 64 pixel square.
+        fn cube_corners() -> Vec<(Point, f32)> {
+            let (sin_a, cos_a, sin_b, cos_b) = (0.5f32.sin(), 0.5f32.cos(), 0.6f32.sin(), 0.6f32.cos());
+            (0..8).map(|i| {
+                let (x, y, z) = ([-12.0, 12.0][i & 1], [-12.0, 12.0][i >> 1 & 1], [-12.0, 12.0][i >> 2 & 1]);
+                let (x, z) = (x * cos_b + z * sin_b, z * cos_b - x * sin_b);
+                let (y, z) = (y * cos_a - z * sin_a, z * cos_a + y * sin_a);
+                (Point::new(32 + x.round() as i32, 32 + y.round() as i32), z)
+            }).collect()
+        }
+
+        /// The faces of [`cube_corners`], each going counterclockwise seen from outside.
+        const CUBE_FACES: [[u16; 4]; 6] = [[0, 4, 6, 2], [1, 3, 7, 5], [0, 1, 5, 4], [2, 6, 7, 3], [0, 2, 3, 1], [4, 5, 7, 6]];
+
+        #[test]
+        fn test_culling_skips_the_back_of_a_cube() {
+            let corners = cube_corners();
+            let faces = CUBE_FACES.map(|face| face.map(|i| corners[i as usize]));
+
+            let render = |culling| {
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                let mut buffer = DepthMap::new(64, 64);
+                let mut drawn = 0;
+                for (face, color) in faces.iter().zip([0x20, 0x40, 0x60, 0x80, 0xa0, 0xc0]) {
+                    let polygon = Polygon3d::new(face).with_culling(culling);
+                    drawn += usize::from(!polygon.is_culled());
+                    polygon.draw_3d(&PrimitiveStyle::with_fill(Rgb888::new(color, 0, 0)), &mut buffer, &mut display).unwrap();
+                }
+                (drawn, display)
+            };
+            let (all, expected) = render(Culling::None);
+            assert_eq!(all, 6);
+            // with greater depth nearer and y pointing down, the faces toward the viewer go
+            // clockwise, and leaving out the others covers the same pixels; only a few along the
+            // edges between faces, where a face behind tied with one in front, change color
+            let (front, display) = render(Culling::ClockwiseFront);
+            assert_eq!(front, 3);
+            let pixels: Vec<Point> = (0..64).flat_map(|y| (0..64).map(move |x| Point::new(x, y))).collect();
+            assert!(pixels.iter().all(|p| display.get_pixel(*p).is_some() == expected.get_pixel(*p).is_some()));
+            assert!(pixels.iter().filter(|p| display.get_pixel(**p) != expected.get_pixel(**p)).count() < 32);
+            let (back, display) = render(Culling::CounterClockwiseFront);
+            assert_eq!(back, 3);
+            assert_ne!(display, expected);
+
+            // an outline is skipped as the fill is, and an edge-on face has no front
+            let mut collector = PixelCollector::default();
+            let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+            Polygon3d::new(&faces[0]).with_culling(Culling::CounterClockwiseFront).draw_3d(&style, &mut DepthMap::new(64, 64), &mut collector).unwrap();
+            Polygon3d::new(&faces[0]).with_culling(Culling::ClockwiseFront).draw_3d(&style, &mut DepthMap::new(64, 64), &mut collector).unwrap();
+            assert!(!collector.pixels.is_empty());
+            let edge_on = [(Point::new(0, 0), 0.0), (Point::new(10, 10), 1.0), (Point::new(20, 20), 2.0)];
+            assert!(Polygon3d::new(&edge_on).with_culling(Culling::ClockwiseFront).is_culled());
+            assert!(!Polygon3d::new(&edge_on).is_culled());
+        }
+
+        #[test]
+        fn test_mesh_draws_like_its_polygons() {
+            let corners = cube_corners();
+            let triangles: Vec<[u16; 3]> = CUBE_FACES.iter().flat_map(|[a, b, c, d]| [[*a, *b, *c], [*a, *c, *d]]).collect();
+            assert_eq!(triangles.len(), 12);
+            let material = |face: usize| PrimitiveStyle::with_fill(Rgb888::new(20 * face as u8, 0, 255 - 20 * face as u8));
+            for culling in [Culling::None, Culling::ClockwiseFront] {
+                let mut expected = MockDisplay::new();
+                expected.set_allow_overdraw(true);
+                let mut expected_buffer = DepthMap::new(64, 64);
+                for (face, triangle) in triangles.iter().enumerate() {
+                    let vertices = triangle.map(|i| corners[i as usize]);
+                    Polygon3d::new(&vertices).with_culling(culling).draw_3d(&material(face), &mut expected_buffer, &mut expected).unwrap();
+                }
+
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                let mut buffer = DepthMap::new(64, 64);
+                let mesh = Mesh3d::new(&corners, &triangles).unwrap().with_culling(culling);
+                mesh.draw_faces(DepthMode::Linear, material, &mut buffer, &mut display).unwrap();
+                display.assert_eq(&expected);
+                assert_eq!(buffer, expected_buffer);
+            }
+
+            // faces of any size, and one style for all
+            let style = PrimitiveStyle::with_fill(Rgb888::WHITE);
+            let mut quads = MockDisplay::new();
+            quads.set_allow_overdraw(true);
+            Mesh3d::new(&corners, &CUBE_FACES).unwrap().draw_3d(&style, &mut DepthMap::new(64, 64), &mut quads).unwrap();
+            let mut expected = MockDisplay::new();
+            expected.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(64, 64);
+            for face in CUBE_FACES {
+                Polygon3d::new(&face.map(|i| corners[i as usize])).draw_3d(&style, &mut buffer, &mut expected).unwrap();
+            }
+            quads.assert_eq(&expected);
+        }
+
+        #[test]
+        fn test_depth_prepass_hides_what_is_behind() {
+            let occluder = [(Point::new(4, 4), 2.0), (Point::new(12, 4), 2.0), (Point::new(12, 12), 2.0), (Point::new(4, 12), 2.0)];
+            let far = [(Point::new(0, 0), 1.0), (Point::new(16, 0), 1.0), (Point::new(16, 16), 1.0), (Point::new(0, 16), 1.0)];
+            let mut buffer = DepthMap::new(16, 16);
+            Polygon3d::new(&occluder).draw_depth(DepthMode::Linear, &mut buffer);
+            let mut prepass = DepthMap::new(16, 16);
+            Polygon3d::new(&occluder).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut prepass, &mut PixelCollector::default()).unwrap();
+            assert_eq!(buffer, prepass);
+
+            let mut display = MockDisplay::new();
+            Polygon3d::new(&far).draw_3d(&PrimitiveStyle::with_fill(Rgb565::BLUE), &mut buffer, &mut display).unwrap();
+            for y in 0..16 {
+                for x in 0..16 {
+                    let hidden = buffer.get(x, y) == Some(2.0);
+                    assert_eq!(display.get_pixel(Point::new(x as i32, y as i32)), if hidden { None } else { Some(Rgb565::BLUE) }, "{x}, {y}");
+                }
+            }
+            assert!(display.get_pixel(Point::new(8, 8)).is_none());
+
+            // a mesh fills the same depths as drawing it
+            let corners = cube_corners();
+            let mesh = Mesh3d::new(&corners, &CUBE_FACES).unwrap().with_culling(Culling::ClockwiseFront);
+            let (mut depths, mut drawn) = (DepthMap::new(64, 64), DepthMap::new(64, 64));
+            mesh.draw_depth(DepthMode::Linear, &mut depths);
+            mesh.draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut drawn, &mut PixelCollector::default()).unwrap();
+            assert_eq!(depths, drawn);
+            assert_ne!(depths, DepthMap::new(64, 64));
+        }
+
+        #[test]
+        fn test_mesh_rejects_missing_vertices() {
+            let corners = cube_corners();
+            assert_eq!(Mesh3d::new(&corners, &[[0, 1, 2], [2, 3, 8]]), Err(MeshError::IndexOutOfRange { face: 1, index: 8 }));
+            assert_eq!(Mesh3d::new(&corners[..4], &CUBE_FACES).unwrap_err().to_string(), "face 0 uses vertex 4, which does not exist");
+            assert!(Mesh3d::<[u16; 3]>::new(&[], &[]).is_ok());
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_project_cube_through_camera() {
+            use nalgebra::{Perspective3, Point3, Translation3};
+            use embedded_graphics::primitives::Rectangle;
+            use super::{project_vertices, projected_vertices, ProjectionError};
+            // a cube two units across at the origin, seen from five units along z through a 90
+            // degree lens, near and far planes at 1 and 10
+            let cube: Vec<Point3<f32>> = (0..8).map(|i| Point3::new([-1.0, 1.0][i & 1], [-1.0, 1.0][i >> 1 & 1], [-1.0, 1.0][i >> 2 & 1])).collect();
+            let camera = Perspective3::new(1.0, std::f32::consts::FRAC_PI_2, 1.0, 10.0).to_homogeneous() * Translation3::new(0.0, 0.0, -5.0).to_homogeneous();
+            let viewport = Rectangle::new(Point::new(10, 20), Size::new(80, 80));
+            let vertices = projected_vertices(&cube, &camera, viewport).unwrap();
+
+            // the near face is 4 away, so its corners are a quarter of the way out from the
+            // middle, the far face 6 away and a sixth of the way; z in clip space is
+            // (11 d - 20) / 9 at distance d, over w = d
+            let (near_depth, far_depth) = (-(11.0 * 4.0 - 20.0) / 36.0, -(11.0 * 6.0 - 20.0) / 54.0);
+            let expected = [
+                ((43, 67), far_depth), ((57, 67), far_depth), ((43, 53), far_depth), ((57, 53), far_depth),
+                ((40, 70), near_depth), ((60, 70), near_depth), ((40, 50), near_depth), ((60, 50), near_depth),
+            ];
+            for ((point, depth), ((x, y), expected_depth)) in vertices.iter().zip(expected) {
+                assert_eq!(*point, Point::new(x, y));
+                assert!((depth - expected_depth).abs() < 1e-5, "{depth} {expected_depth}");
+            }
+            assert!(near_depth > far_depth);
+
+            // a point behind the camera still leaves the others projected
+            let mut out = [(Point::zero(), 0.0); 3];
+            let points = [cube[7], Point3::new(0.0, 0.0, 6.0), cube[0]];
+            assert_eq!(project_vertices(&points, &camera, viewport, &mut out), Err(ProjectionError::BehindCamera { index: 1 }));
+            assert_eq!((out[0].0, out[2].0), (Point::new(60, 50), Point::new(43, 67)));
+            assert_eq!(project_vertices(&cube, &camera, viewport, &mut out), Err(ProjectionError::OutputTooShort));
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_pipeline_projects_and_caches() {
+            use nalgebra::{Matrix4, Point3, Vector3};
+            use embedded_graphics::primitives::Rectangle;
+            use super::{MeshError, Pipeline3d, ProjectionError};
+            let mut pipeline = Pipeline3d::new(Rectangle::new(Point::new(0, 0), Size::new(64, 48)));
+            pipeline.set_view_look_at(&Point3::new(3.0, 4.0, 10.0), &Point3::new(1.0, 2.0, 0.0), &Vector3::y());
+            pipeline.set_projection_perspective(4.0 / 3.0, 1.0, 1.0, 50.0);
+            // what the camera looks at lands in the middle of the screen
+            assert_eq!(pipeline.projected(&[Point3::new(1.0, 2.0, 0.0)]).unwrap()[0].0, Point::new(32, 24));
+            assert_eq!(pipeline.view_projections.get(), 1);
+
+            // moving the model keeps the view and projection product, and the point moved onto
+            // what the camera looks at lands in the middle again
+            for step in 0..4 {
+                pipeline.set_model(Matrix4::new_translation(&Vector3::new(step as f32, 0.0, 0.0)));
+                assert_eq!(pipeline.projected(&[Point3::new(1.0 - step as f32, 2.0, 0.0)]).unwrap()[0].0, Point::new(32, 24));
+            }
+            assert_eq!(pipeline.view_projections.get(), 1);
+            pipeline.set_projection_orthographic(-4.0, 4.0, -3.0, 3.0, 1.0, 50.0);
+            pipeline.set_model(Matrix4::identity());
+            assert_eq!(pipeline.projected(&[Point3::new(1.0, 2.0, 0.0)]).unwrap()[0].0, Point::new(32, 24));
+            assert_eq!(pipeline.view_projections.get(), 2);
+
+            // a mesh straight from the pipeline draws as its projected vertices do
+            let cube: Vec<Point3<f32>> = (0..8).map(|i| Point3::new([0.0, 2.0][i & 1], [1.0, 3.0][i >> 1 & 1], [-1.0, 1.0][i >> 2 & 1])).collect();
+            let mut vertices = Vec::new();
+            let mesh = Mesh3d::project(&pipeline, &cube, &CUBE_FACES, &mut vertices).unwrap();
+            let expected = pipeline.projected(&cube).unwrap();
+            assert_eq!(mesh.vertices(), expected);
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            mesh.with_culling(Culling::ClockwiseFront).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut DepthMap::new(64, 48), &mut display).unwrap();
+            assert_eq!(display.get_pixel(Point::new(32, 24)), Some(BinaryColor::On));
+
+            // standing inside the cube under perspective, some of it is behind the camera
+            pipeline.set_projection_perspective(4.0 / 3.0, 1.0, 0.1, 50.0);
+            pipeline.set_view_look_at(&Point3::new(1.0, 2.0, 0.5), &Point3::new(1.0, 2.0, 10.0), &Vector3::y());
+            let mut vertices = Vec::new();
+            assert!(matches!(Mesh3d::project(&pipeline, &cube, &CUBE_FACES, &mut vertices), Err(MeshError::Projection(ProjectionError::BehindCamera { .. }))));
+        }
+
+        #[cfg(feature = "nalgebra")]
+        #[test]
+        fn test_ground_cut_at_the_near_plane() {
+            use nalgebra::{Point3, Vector3};
+            use embedded_graphics::primitives::Rectangle;
+            use super::{Pipeline3d, ProjectionError};
+            // a ground a unit below a camera looking along -z, reaching 20 units ahead and behind
+            let mut pipeline = Pipeline3d::new(Rectangle::new(Point::zero(), Size::new(64, 48)));
+            pipeline.set_view_look_at(&Point3::origin(), &Point3::new(0.0, 0.0, -1.0), &Vector3::y());
+            pipeline.set_projection_perspective(64.0 / 48.0, std::f32::consts::FRAC_PI_2, 1.0, 100.0);
+            let ground = [Point3::new(-10.0, -1.0, -20.0), Point3::new(10.0, -1.0, -20.0), Point3::new(10.0, -1.0, 20.0), Point3::new(-10.0, -1.0, 20.0)];
+            assert!(matches!(pipeline.projected(&ground), Err(ProjectionError::BehindCamera { index: 2 })));
+
+            let mut vertices = Vec::new();
+            pipeline.project_polygon(&ground, &mut vertices);
+            // the far corners, then the cut at the near plane, where the ground is level with the
+            // bottom of the screen
+            assert_eq!(vertices.len(), 4);
+            assert_eq!((vertices[0].0, vertices[1].0), (Point::new(20, 25), Point::new(44, 25)));
+            assert!(vertices[2..].iter().all(|(p, depth)| p.y == 48 && (depth - 1.0).abs() < 1e-4));
+            assert_eq!((vertices[2].0.x, vertices[3].0.x), (272, -208));
+
+            let mut display = MockDisplay::new();
+            Polygon3d::new(&vertices).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut DepthMap::new(64, 48), &mut display).unwrap();
+            // nothing above the far edge, and every row below it one run, widening to the
+            // whole screen along the bottom
+            let row = |y: i32| (0..64).filter(|x| display.get_pixel(Point::new(*x, y)).is_some()).collect::<Vec<i32>>();
+            assert!((0..25).all(|y| row(y).is_empty()));
+            let mut width = 0;
+            for y in 25..48 {
+                let row = row(y);
+                assert!(row.len() >= width && row.windows(2).all(|pair| pair[1] == pair[0] + 1), "{y} {row:?}");
+                width = row.len();
+            }
+            assert_eq!(width, 64);
+
+            // a polygon wholly behind the camera is dropped
+            pipeline.project_polygon(&[Point3::new(0.0, 0.0, 1.0), Point3::new(1.0, 0.0, 2.0), Point3::new(0.0, 1.0, 0.5)], &mut vertices);
+            assert!(vertices.is_empty());
+        }
+
+        #[test]
+        fn test_triangle_off_the_top_left_corner() {
+            let place = |vertices: &[(Point, f32)], offset: Point| vertices.iter().map(|(p, depth)| (*p + offset, *depth)).collect::<Vec<_>>();
+            // half of the near triangle is off the corner, the far one overlaps it on screen; the
+            // edges' slopes are exact in binary, so moving them rounds their ends alike
+            let near = [(Point::new(-20, -20), 3.0), (Point::new(24, -4), 2.0), (Point::new(-4, 12), 2.0)];
+            let far = [(Point::new(-10, 6), 1.0), (Point::new(30, -2), 1.5), (Point::new(14, 30), 1.5)];
+            let render = |offset: Point, size: u32| {
+                let mut collector = PixelCollector::default();
+                let mut buffer = DepthMap::new(size, size);
+                for (vertices, color) in [(&far, BinaryColor::Off), (&near, BinaryColor::On), (&far, BinaryColor::Off)] {
+                    let mut pixels = PixelCollector::default();
+                    Polygon3d::new(&place(vertices, offset)).draw_3d(&PrimitiveStyle::with_fill(color), &mut buffer, &mut pixels).unwrap();
+                    collector.pixels.extend(pixels.pixels.into_iter().map(|p| p - offset));
+                }
+                (collector.pixels, buffer)
+            };
+            // the same scene moved wholly onto a larger buffer, with what lands off the corner cut
+            let offset = Point::new(32, 32);
+            let (whole, whole_buffer) = render(offset, 96);
+            let (pixels, buffer) = render(Point::zero(), 64);
+            assert_eq!(pixels, whole.into_iter().filter(|p| p.x >= 0 && p.y >= 0).collect::<Vec<_>>());
+            assert!(pixels.contains(&Point::new(0, 0)) && pixels.contains(&Point::new(20, 0)));
+            let alike = |a: f32, b: f32| a == b || (a - b).abs() < 1e-4;
+            assert!((0..64).all(|x| (0..64).all(|y| alike(buffer[(x, y)], whole_buffer[(x + 32, y + 32)]))));
+            // the far triangle shows only past the near one
+            assert!(buffer[(4, 4)] > 2.0 && (buffer[(14, 20)] - 1.5).abs() < 0.1);
+
+            // nor is a depth stored where the target has no pixel
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let mut buffer = DepthMap::new(96, 96);
+            let big = [(Point::new(0, 0), 1.0), (Point::new(90, 0), 1.0), (Point::new(0, 90), 1.0)];
+            Polygon3d::new(&big).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut buffer, &mut display).unwrap();
+            Polygon3d::new(&big).draw_outline_3d(&PrimitiveStyle::with_stroke(BinaryColor::On, 1), DepthMode::Linear, 1.0, &mut buffer, &mut display).unwrap();
+            assert_eq!(buffer[(63, 0)], 2.0);
+            assert!((64..96).all(|x| buffer[(x, 0)] == DepthMap::FAR && buffer[(0, x)] == DepthMap::FAR));
+        }
+
+        #[test]
+        fn test_outline_hidden_behind_nearer_face() {
+            // a cube seen from the front and a little to the side, its rear face up and left
+            let front = [(Point::new(14, 14), 2.0), (Point::new(40, 14), 2.0), (Point::new(40, 40), 2.0), (Point::new(14, 40), 2.0)];
+            let rear = [(Point::new(4, 4), 1.0), (Point::new(30, 4), 1.0), (Point::new(30, 30), 1.0), (Point::new(4, 30), 1.0)];
+            let outline = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+            let mut everywhere = PixelCollector::default();
+            Polygon3d::new(&rear).draw_3d(&outline, &mut DepthMap::new(48, 48), &mut everywhere).unwrap();
+            // every pixel of the square's edges once, corners included
+            assert_eq!(everywhere.pixels.len(), 4 * 26);
+
+            let mut buffer = DepthMap::new(48, 48);
+            Polygon3d::new(&front).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::Off), &mut buffer, &mut PixelCollector::default()).unwrap();
+            let mut behind = PixelCollector::default();
+            Polygon3d::new(&rear).draw_3d(&outline, &mut buffer, &mut behind).unwrap();
+            let covered = |p: &Point| (14..40).contains(&p.x) && (14..40).contains(&p.y);
+            assert_eq!(behind.pixels, everywhere.pixels.iter().copied().filter(|p| !covered(p)).collect::<Vec<_>>());
+            assert!(behind.pixels.len() < everywhere.pixels.len());
+        }
+
+        #[test]
+        fn test_biased_outline_over_its_own_fill() {
+            // tilted, so the depth along the edges changes with every step
+            let corners = [Point::new(3, 3), Point::new(28, 5), Point::new(25, 27), Point::new(5, 24)];
+            let tilt = |p: Point| 0.1 + 0.01 * p.x as f32 + 0.005 * p.y as f32;
+            let face = corners.map(|p| (p, 10.0 * tilt(p)));
+            let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+            let mut alone = PixelCollector::default();
+            Polygon3d::new(&face).draw_3d(&style, &mut DepthMap::new(32, 32), &mut alone).unwrap();
+
+            // flat in the values each mode stores, and each bias covers half a pixel's change in them
+            let distant = corners.map(|p| (p, 1.0 / tilt(p)));
+            for (vertices, bias, mode) in [(&face, 0.1, DepthMode::Linear), (&distant, 0.01, DepthMode::Perspective)] {
+                let mut buffer = DepthMap::new(32, 32);
+                let polygon = Polygon3d::new(vertices);
+                polygon.draw_3d_with(&PrimitiveStyle::with_fill(BinaryColor::Off), mode, &mut buffer, &mut PixelCollector::default()).unwrap();
+                let mut over = PixelCollector::default();
+                polygon.draw_outline_3d(&style, mode, bias, &mut buffer, &mut over).unwrap();
+                assert_eq!(over.pixels, alone.pixels, "{mode:?}");
+            }
+
+            // a wide stroke is a run across each edge, three pixels tall along the top
+            let mut wide = PixelCollector::default();
+            Polygon3d::new(&face).draw_3d(&PrimitiveStyle::with_stroke(BinaryColor::On, 3), &mut DepthMap::new(32, 32), &mut wide).unwrap();
+            assert_eq!(wide.pixels.iter().filter(|p| p.x == 10 && p.y < 10).count(), 3);
+        }
+
+        #[test]
+        fn test_no_interior_mutability() {
+            // nothing in a polygon can change behind a shared reference, so it can be shared
+            // between threads and copied freely
+            fn assert_shareable<T: Copy + Send + Sync>() {}
+            assert_shareable::<Polygon3d>();
+            assert_shareable::<&DepthMap>();
+
+            // the buffer is only written through the `&mut` handed to each draw, and can be read
+            // in between with no borrow left to release
+            let vertices = [(Point::new(2, 2), 1.0), (Point::new(30, 4), 1.0), (Point::new(10, 20), 1.0)];
+            let polygon = Polygon3d::new(&vertices);
+            let mut map = DepthMap::new(32, 24);
+            let style = PrimitiveStyle::with_fill(BinaryColor::On);
+            let inspect = &map;
+            assert_eq!(inspect.get(8, 8), Some(DepthMap::FAR));
+            let mut first = PixelCollector::default();
+            polygon.draw_3d(&style, &mut map, &mut first).unwrap();
+            let inspect = &map;
+            assert!(inspect.get(8, 8).is_some_and(|depth| depth > 0.5));
+            let mut second = PixelCollector::default();
+            polygon.draw_3d(&style, &mut map, &mut second).unwrap();
+            assert!(!first.pixels.is_empty() && second.pixels.is_empty());
+        }
+
+        #[test]
+        fn test_crossing_triangles_meet_on_a_line() {
+            // one triangle at depth 0, the other tilted to depth 2x - y - 10, so it is in front
+            // right of the line 2x - y = 10
+            let corners = [Point::new(0, 0), Point::new(60, 0), Point::new(0, 60)];
+            let flat: Vec<(Point, f32)> = corners.iter().map(|p| (*p, 0.0)).collect();
+            let tilted: Vec<(Point, f32)> = corners.iter().map(|p| (*p, (2 * p.x - p.y - 10) as f32)).collect();
+            let (red, green) = (Rgb888::new(255, 0, 0), Rgb888::new(0, 255, 0));
+            for order in [[(&flat, green), (&tilted, red)], [(&tilted, red), (&flat, green)]] {
+                let mut map = DepthMap::new(64, 64);
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                for (vertices, color) in order {
+                    Polygon3d::new(vertices).draw_3d(&PrimitiveStyle::with_fill(color), &mut map, &mut display).unwrap();
+                }
+                let mut seen = (0, 0);
+                for y in 0..64 {
+                    for x in 0..64 {
+                        let Some(color) = display.get_pixel(Point::new(x, y)) else { continue };
+                        // the line crosses each row at x = (y + 10) / 2
+                        let side = 2 * x - y - 10;
+                        if side.abs() > 2 {
+                            assert_eq!(color, if side > 0 { red } else { green }, "({x}, {y})");
+                            if side > 0 { seen.0 += 1 } else { seen.1 += 1 }
+                        }
+                    }
+                }
+                assert!(seen.0 > 100 && seen.1 > 100, "{seen:?}");
+            }
+        }
+
+        #[test]
+        fn test_perspective_ground_occludes_along_its_length() {
+            // a camera one unit above flat ground, the horizon on row 20 and a focal length of 100
+            // pixels, so ground `w` from the camera is on row 20 + 100 / w
+            let at = |x: f32, y: f32, w: f32| (Point::new(math::round(64.0 + 100.0 * x / w) as i32, math::round(20.0 + 100.0 * y / w) as i32), w);
+            let ground = [at(-0.5, 1.0, 1.0), at(0.5, 1.0, 1.0), at(0.5, 1.0, 80.0), at(-0.5, 1.0, 80.0)];
+            let style = PrimitiveStyle::with_fill(BinaryColor::On);
+            let draw = |vertices: &[(Point, f32)], map: &mut DepthMap| {
+                let mut collector = PixelCollector::default();
+                Polygon3d::new(vertices).draw_3d_with(&style, DepthMode::Perspective, map, &mut collector).unwrap();
+                collector.pixels
+            };
+            for w in [3.0, 8.0, 15.0, 30.0] {
+                // a post standing on the ground, against ground farther away, and a slab sunk into
+                // the ground in front of it, below nearer ground
+                let post = [at(-0.3, 0.0, w), at(0.3, 0.0, w), at(0.3, 0.95, w), at(-0.3, 0.95, w)];
+                let slab = [at(-0.3, 1.15, w), at(0.3, 1.15, w), at(0.3, 1.6, w), at(-0.3, 1.6, w)];
+                let mut map = DepthMap::new(128, 128);
+                let ground_pixels = draw(&ground, &mut map);
+                let post_pixels = draw(&post, &mut map);
+                assert!(!post_pixels.is_empty());
+                assert!(post_pixels.iter().any(|p| ground_pixels.contains(p)), "the post overlaps the ground at {w}");
+                assert_eq!(draw(&slab, &mut map), [], "the slab shows through the ground at {w}");
+
+                // drawn the other way round the ground leaves the post alone and covers the slab
+                let mut map = DepthMap::new(128, 128);
+                let (post_first, slab_first) = (draw(&post, &mut map), draw(&slab, &mut map));
+                let ground_pixels = draw(&ground, &mut map);
+                assert_eq!(post_first, post_pixels);
+                assert!(ground_pixels.iter().all(|p| !post_first.contains(p)));
+                assert!(slab_first.iter().all(|p| ground_pixels.contains(p)));
+            }
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_shared_depth_map_still_draws() {
+            let vertices = [(Point::new(2, 2), 1.0), (Point::new(30, 4), 3.0), (Point::new(10, 20), 2.0)];
+            let style = PrimitiveStyle::with_fill(BinaryColor::On);
+            let shared = RefCell::new(DepthMap::new(32, 24));
+            let mut expected = PixelCollector::default();
+            Polygon3d::with_depth_map(&vertices, &shared).into_styled(style).draw(&mut expected).unwrap();
+            let mut map = DepthMap::new(32, 24);
+            let mut collector = PixelCollector::default();
+            Polygon3d::new(&vertices).draw_3d(&style, &mut map, &mut collector).unwrap();
+            assert_eq!(collector.pixels, expected.pixels);
+            assert_eq!(shared.into_inner(), map);
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_shared_depth_map_borrowed_elsewhere_fails_before_drawing() {
+            let vertices = [(Point::new(2, 2), 1.0), (Point::new(30, 4), 3.0), (Point::new(10, 20), 2.0)];
+            let shared = RefCell::new(DepthMap::new(32, 24));
+            let held = shared.borrow();
+            let mut collector = PixelCollector::default();
+            let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                Polygon3d::with_depth_map(&vertices, &shared).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+            }));
+            let message = *outcome.unwrap_err().downcast::<&str>().unwrap();
+            assert!(message.contains("already borrowed"), "{message}");
+            assert!(collector.pixels.is_empty());
+            drop(held);
+        }
+
+        #[test]
+        fn test_cleared_depth_map_lets_the_first_polygon_through() {
+            let ring = [Point::new(2, 2), Point::new(40, 8), Point::new(30, 28), Point::new(6, 20)];
+            let draw = |depth: f32, map: &mut DepthMap| {
+                let vertices: Vec<(Point, f32)> = ring.iter().map(|p| (*p, depth)).collect();
+                let mut collector = PixelCollector::default();
+                Polygon3d::new(&vertices).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), map, &mut collector).unwrap();
+                collector.pixels
+            };
+            let mut map = DepthMap::new(48, 32);
+            assert_eq!((map.width(), map.height()), (48, 32));
+            let near = draw(0.0, &mut map);
+            assert!(!near.is_empty());
+            // even a depth far behind any scene is in front of a cleared map
+            let mut far = DepthMap::new(48, 32);
+            assert_eq!(draw(-1e30, &mut far), near);
+            assert!(far[(20, 15)] < -1e29);
+
+            // drawn over again at the same depth nothing shows until the map is cleared
+            assert!(draw(0.0, &mut map).is_empty());
+            map.clear();
+            assert_eq!(draw(0.0, &mut map), near);
+            assert_eq!(map.get(20, 15), Some(0.0));
+            assert_eq!(map.get(48, 0), None);
+
+            map.clear_to(1.0);
+            assert!(draw(0.5, &mut map).is_empty());
+            map[(20, 15)] = 0.0;
+            assert_eq!(draw(0.5, &mut map), [Point::new(20, 15)]);
+        }
+
+        #[test]
+        fn test_draw_errors_stop_the_fill() {
+            let vertices = [(Point::new(2, 2), 1.0), (Point::new(60, 10), 2.0), (Point::new(20, 60), 3.0)];
+            let mut target = FailAfter::new(25);
+            assert_eq!(Polygon3d::new(&vertices).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut DepthMap::new(64, 64), &mut target), Err(()));
+            assert_eq!((target.written, target.calls_after_failure), (25, 0));
+        }
+
+        #[test]
+        fn test_near_horizontal_edges_do_not_streak() {
+            let vertices: Vec<(Point, f32)> = [[0, 0], [200, 2], [200, 10], [100, 9], [0, 10]].iter().map(|p| (Point::from(p), 1.0)).collect();
+            let mut collector = PixelCollector::default();
+            Polygon3d::new(&vertices).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut DepthMap::new(256, 16), &mut collector).unwrap();
+            // every row stays between the edges crossing it, which are at most 100 pixels from x = 0 on row 1
+            assert!(!collector.pixels.is_empty());
+            assert!(collector.pixels.iter().all(|p| p.x <= 200 && (p.y != 1 || p.x <= 101)), "{:?}", collector.pixels);
+        }
+
+        #[test]
+        fn test_malformed_rings_terminate() {
+            let huge = i32::MAX;
+            let fixtures: [&[[i32; 2]]; 6] = [
+                &[[3, 3], [3, 3], [3, 3]],
+                &[[0, 0], [10, 10], [0, 0], [10, 10]],
+                &[[0, 0], [0, 20], [0, 5], [0, 40]],
+                &[[10, 10], [0, 10], [5, 0], [5, 0]],
+                &[[0, 0], [huge, 1], [1, huge]],
+                &[[huge, huge], [huge, huge - 1], [huge - 1, huge]],
+            ];
+            for fixture in fixtures {
+                for reversed in [false, true] {
+                    let mut vertices: Vec<(Point, f32)> = fixture.iter().map(|p| (Point::from(p), 1.0)).collect();
+                    if reversed {
+                        vertices.reverse();
+                    }
+                    let mut target = FailAfter::new(64 * 64);
+                    Polygon3d::new(&vertices).draw_3d(&PrimitiveStyle::with_fill(BinaryColor::On), &mut DepthMap::new(64, 64), &mut target).unwrap();
+                }
+            }
+        }
 
         fn test_polyline() {
             let points = [[16, 20], [28, 10], [28, 16], [22, 10], [10, 10], [10, 16]].iter().map(|p|Point::from(p)).collect_vec();
@@ -0,0 +1,138 @@
+//! Tracking the area a draw actually writes to.
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+/// Forwards every draw to `target` while tracking the bounding box of the pixels that land inside
+/// the target's bounds.
+pub(crate) struct AffectedArea<'d, D> {
+    target: &'d mut D,
+    bounds: Rectangle,
+    corners: Option<(Point, Point)>,
+}
+
+impl<'d, D: DrawTarget> AffectedArea<'d, D> {
+    pub(crate) fn new(target: &'d mut D) -> Self {
+        let bounds = target.bounding_box();
+        AffectedArea { target, bounds, corners: None }
+    }
+
+    /// The smallest rectangle holding every pixel written so far, zero-sized if there were none.
+    pub(crate) fn area(&self) -> Rectangle {
+        self.corners.map_or(Rectangle::zero(), |(min, max)| Rectangle::with_corners(min, max))
+    }
+}
+
+fn include(corners: &mut Option<(Point, Point)>, min: Point, max: Point) {
+    *corners = Some(match *corners {
+        Some((low, high)) => (low.component_min(min), high.component_max(max)),
+        None => (min, max),
+    });
+}
+
+fn include_area(corners: &mut Option<(Point, Point)>, bounds: &Rectangle, area: &Rectangle) {
+    let area = area.intersection(bounds);
+    if let Some(bottom_right) = area.bottom_right() {
+        include(corners, area.top_left, bottom_right);
+    }
+}
+
+impl<'d, D: DrawTarget> Dimensions for AffectedArea<'d, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+impl<'d, D: DrawTarget> DrawTarget for AffectedArea<'d, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+        let AffectedArea { target, bounds, corners } = self;
+        target.draw_iter(pixels.into_iter().inspect(|Pixel(p, _)| {
+            if bounds.contains(*p) {
+                include(corners, *p, *p);
+            }
+        }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Self::Color> {
+        include_area(&mut self.corners, &self.bounds, area);
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        include_area(&mut self.corners, &self.bounds, area);
+        self.target.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let bounds = self.bounds;
+        include_area(&mut self.corners, &bounds, &bounds);
+        self.target.clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle};
+    use crate::polygon::{Polygon, PolygonWithHoles};
+    use crate::polygon::tests::{PixelCollector, ring};
+
+    fn assert_tight(area: Rectangle, pixels: &[Point]) {
+        assert!(pixels.iter().all(|p| area.contains(*p)), "{area:?}");
+        let bottom_right = area.bottom_right().unwrap();
+        assert!(pixels.iter().any(|p| p.x == area.top_left.x) && pixels.iter().any(|p| p.y == area.top_left.y));
+        assert!(pixels.iter().any(|p| p.x == bottom_right.x) && pixels.iter().any(|p| p.y == bottom_right.y));
+    }
+
+    #[test]
+    fn test_draw_returns_the_affected_area() {
+        let vertices = ring(&[[3, 2], [40, 9], [22, 30], [5, 21]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(-7, 4));
+        let styles = [
+            PrimitiveStyle::with_fill(BinaryColor::On),
+            PrimitiveStyle::with_stroke(BinaryColor::On, 1),
+            PrimitiveStyle::with_stroke(BinaryColor::On, 5),
+        ];
+        for style in styles {
+            let mut collector = PixelCollector::default();
+            let area = polygon.into_styled(style).draw(&mut collector).unwrap();
+            assert_tight(area, &collector.pixels);
+        }
+
+        let with_hole = PolygonWithHoles::new(ring(&[[0, 0], [20, 0], [20, 20], [0, 20]]), vec![ring(&[[5, 5], [15, 5], [15, 15]])]);
+        let mut collector = PixelCollector::default();
+        let area = with_hole.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+        assert_eq!(area, Rectangle::new(Point::zero(), Size::new(21, 21)));
+    }
+
+    #[test]
+    fn test_affected_area_is_clipped_to_the_target() {
+        let vertices = ring(&[[-20, 10], [30, -15], [90, 40], [20, 50]]);
+        let polygon = Polygon::new(&vertices);
+        for style in [PrimitiveStyle::with_fill(BinaryColor::On), PrimitiveStyle::with_stroke(BinaryColor::On, 3)] {
+            let mut display = MockDisplay::new();
+            display.set_allow_out_of_bounds_drawing(true);
+            display.set_allow_overdraw(true);
+            let area = polygon.into_styled(style).draw(&mut display).unwrap();
+            assert_eq!(area, display.affected_area());
+        }
+
+        // nothing drawn
+        let mut display = MockDisplay::<BinaryColor>::new();
+        let transparent = PrimitiveStyleBuilder::new().build();
+        assert_eq!(polygon.into_styled(transparent).draw(&mut display).unwrap(), Rectangle::zero());
+        let mut off_screen = polygon;
+        off_screen.set_translate(Point::new(200, 0));
+        display.set_allow_out_of_bounds_drawing(true);
+        assert_eq!(off_screen.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap(), Rectangle::zero());
+    }
+}
@@ -0,0 +1,101 @@
+//! Drawing the part of a polygon within a band of rows.
+
+use core::ops::Range;
+use alloc::vec::Vec;
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, StyledDrawable};
+use super::Polygon;
+use super::affected::AffectedArea;
+use super::scanline::Scanlines;
+
+impl<'a> Polygon<'a> {
+    /// Draws only the pixels of rows `rows` that a full draw would produce, and returns the
+    /// affected area as a full draw does.
+    ///
+    /// A fill starts at the first row of the band by computing the edge crossings there directly,
+    /// without walking the rows above it, and stops after the last, so drawing every band of a
+    /// screen in turn writes exactly the pixels of a single draw. A stroke is drawn clipped to the
+    /// band.
+    pub fn draw_rows<C, D>(&self, style: &PrimitiveStyle<C>, rows: Range<i32>, target: &mut D) -> Result<Rectangle, D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+        let mut target = AffectedArea::new(target);
+        if rows.is_empty() {
+            return Ok(target.area());
+        }
+        if style.stroke_width > 0 {
+            let bounds = target.bounding_box();
+            let band = Rectangle::new(Point::new(bounds.top_left.x, rows.start), Size::new(bounds.size.width, rows.start.abs_diff(rows.end)));
+            self.draw_styled(style, &mut target.clipped(&band))?;
+            return Ok(target.area());
+        }
+        if let Some(color) = style.fill_color {
+            let mut scanlines = Scanlines::new(self.edges());
+            scanlines.skip_to(rows.start);
+            let mut spans = Vec::new();
+            while let Some(y) = scanlines.next_row(&mut spans).filter(|y| rows.contains(y)) {
+                for &(start, end) in spans.iter() {
+                    target.fill_solid(&Rectangle::new(Point::new(start, y), Size::new(end.abs_diff(start) + 1, 1)), color)?;
+                }
+            }
+        }
+        Ok(target.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::{fill_pixels, PixelCollector, ring};
+
+    fn banded(polygon: &Polygon, style: PrimitiveStyle<BinaryColor>, height: i32) -> Vec<Point> {
+        let mut collector = PixelCollector::default();
+        for top in (-40..80).step_by(height as usize) {
+            polygon.draw_rows(&style, top..top + height, &mut collector).unwrap();
+        }
+        collector.pixels.sort_by_key(|p| (p.y, p.x));
+        collector.pixels
+    }
+
+    #[test]
+    fn test_bands_concatenate_to_a_full_draw() {
+        let fixtures = [
+            ring(&[[-6, 3], [50, -4], [70, 30], [31, 23], [35, 52], [2, 40]]),
+            // horizontal edges and vertices on band boundaries
+            ring(&[[0, 0], [20, 0], [20, 8], [10, 8], [10, 16], [30, 24], [0, 24]]),
+            ring(&[[0, -20], [10, 50], [20, -20], [0, 30], [20, 30]]),
+        ];
+        for vertices in fixtures.iter() {
+            let mut polygon = Polygon::new(vertices);
+            polygon.set_translate(Point::new(3, -5));
+            let full = fill_pixels(&polygon);
+            for height in [1, 2, 7, 8, 13, 120] {
+                assert_eq!(banded(&polygon, PrimitiveStyle::with_fill(BinaryColor::On), height), full, "{vertices:?} in bands of {height}");
+            }
+
+            let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+            let mut collector = PixelCollector::default();
+            polygon.into_styled(stroke).draw(&mut collector).unwrap();
+            collector.pixels.sort_by_key(|p| (p.y, p.x));
+            assert_eq!(banded(&polygon, stroke, 5), collector.pixels);
+        }
+    }
+
+    #[test]
+    fn test_band_outside_the_polygon() {
+        let vertices = ring(&[[0, 0], [10, 0], [5, 10]]);
+        let polygon = Polygon::new(&vertices);
+        let mut collector = PixelCollector::default();
+        let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+        assert!(polygon.draw_rows(&fill, 11..20, &mut collector).unwrap().is_zero_sized());
+        assert!(polygon.draw_rows(&fill, -5..0, &mut collector).unwrap().is_zero_sized());
+        assert!(polygon.draw_rows(&fill, 4..4, &mut collector).unwrap().is_zero_sized());
+        assert!(collector.pixels.is_empty());
+        polygon.draw_rows(&fill, 10..11, &mut collector).unwrap();
+        assert_eq!(collector.pixels, [Point::new(5, 10)]);
+    }
+}
@@ -0,0 +1,327 @@
+//! Clipping polygons against arbitrary, possibly concave, clip polygons.
+//!
+//! Both rings are split wherever they meet, then the parts of each ring lying inside the other
+//! are kept, together with the stretches where both rings run along each other in the same
+//! direction. Linking those parts back into rings is the Weiler–Atherton traversal without its
+//! entry and exit bookkeeping, so vertices lying on the other ring and collinear overlaps need no
+//! special handling. Intersections are computed exactly and only rounded to pixels in the output.
+
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::ContainsPoint;
+use super::{MultiPolygon, Polygon, PolygonWithHoles};
+use super::math;
+use super::geometry::twice_signed_area;
+
+/// The exact point `(x / d, y / d)`, in lowest terms with `d > 0` so that equal points compare
+/// equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Node {
+    x: i128,
+    y: i128,
+    d: i128,
+}
+
+pub(crate) fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Node {
+    fn new(x: i128, y: i128, d: i128) -> Self {
+        let g = gcd(gcd(x, y), d) * d.signum();
+        Node { x: x / g, y: y / g, d: d / g }
+    }
+
+    fn vertex(p: Point) -> Self {
+        Node { x: p.x as i128, y: p.y as i128, d: 1 }
+    }
+
+    fn to_f64(self) -> (f64, f64) {
+        (self.x as f64 / self.d as f64, self.y as f64 / self.d as f64)
+    }
+
+    /// The nearest pixel, halves rounding up.
+    fn round(self) -> Point {
+        let round = |v: i128| (2 * v + self.d).div_euclid(2 * self.d) as i32;
+        Point::new(round(self.x), round(self.y))
+    }
+}
+
+/// One edge of a ring, split at every point where it meets the other ring.
+struct SplitEdge {
+    /// Split points along the edge as `(t, node)`, from `t = 0` at its start to `t = 1` at its end.
+    nodes: Vec<(f64, Node)>,
+    /// Stretches `(t_start, t_end, same_direction)` running along an edge of the other ring.
+    shared: Vec<(f64, f64, bool)>,
+}
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn delta(from: Point, to: Point) -> (i128, i128) {
+    (to.x as i128 - from.x as i128, to.y as i128 - from.y as i128)
+}
+
+fn ring_edges(ring: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    ring.iter().zip(ring.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+}
+
+fn split_edges(ring: &[Point], other: &[Point]) -> Vec<SplitEdge> {
+    ring_edges(ring).map(|(a, b)| {
+        let r = delta(a, b);
+        let mut split = SplitEdge { nodes: vec![(0.0, Node::vertex(a)), (1.0, Node::vertex(b))], shared: Vec::new() };
+        for (c, e) in ring_edges(other) {
+            let (s, ca) = (delta(c, e), delta(a, c));
+            let denominator = cross(r, s);
+            if denominator != 0 {
+                let sign = denominator.signum();
+                let (t, u, denominator) = (cross(ca, s) * sign, cross(ca, r) * sign, denominator * sign);
+                if (0..=denominator).contains(&t) && (0..=denominator).contains(&u) {
+                    let node = Node::new(a.x as i128 * denominator + r.0 * t, a.y as i128 * denominator + r.1 * t, denominator);
+                    split.nodes.push((t as f64 / denominator as f64, node));
+                }
+            } else if cross(ca, r) == 0 {
+                // collinear, the edges meet where either one's endpoints lie on the other
+                let length = dot(r, r);
+                let (tc, te) = (dot(ca, r), dot(delta(a, e), r));
+                for (t, p) in [(tc, c), (te, e)] {
+                    if (0..=length).contains(&t) {
+                        split.nodes.push((t as f64 / length as f64, Node::vertex(p)));
+                    }
+                }
+                let (start, end) = (tc.min(te).max(0), tc.max(te).min(length));
+                if start < end {
+                    split.shared.push((start as f64 / length as f64, end as f64 / length as f64, dot(r, s) > 0));
+                }
+            }
+        }
+        split.nodes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        split.nodes.dedup_by_key(|(_, node)| *node);
+        split
+    }).collect()
+}
+
+/// Even-odd containment of a point that is known not to lie on the ring.
+fn inside(ring: &[Point], (x, y): (f64, f64)) -> bool {
+    ring_edges(ring).filter(|(a, b)| {
+        let (ax, ay, bx, by) = (a.x as f64, a.y as f64, b.x as f64, b.y as f64);
+        (ay > y) != (by > y) && x < ax + (y - ay) * (bx - ax) / (by - ay)
+    }).count() % 2 == 1
+}
+
+/// The parts of `ring` that bound the intersection with `other`, as directed `(from, to)` pieces.
+fn kept_pieces(ring: &[Point], other: &[Point], keep_shared: bool, pieces: &mut Vec<(Node, Node)>) {
+    for (split, (a, b)) in split_edges(ring, other).iter().zip(ring_edges(ring)) {
+        for pair in split.nodes.windows(2) {
+            let ((t0, from), (t1, to)) = (pair[0], pair[1]);
+            let t = (t0 + t1) / 2.0;
+            let keep = match split.shared.iter().find(|(start, end, _)| (*start..=*end).contains(&t)) {
+                Some(&(_, _, same_direction)) => keep_shared && same_direction,
+                None => inside(other, (a.x as f64 + (b.x - a.x) as f64 * t, a.y as f64 + (b.y - a.y) as f64 * t)),
+            };
+            if keep {
+                pieces.push((from, to));
+            }
+        }
+    }
+}
+
+/// The translated ring without repeated vertices, wound with a positive area, or `None` if it
+/// encloses no area.
+fn normalized_ring(polygon: &Polygon) -> Option<Vec<Point>> {
+    let mut ring: Vec<Point> = polygon.vertices().iter().map(|v| *v + polygon.translate()).collect();
+    ring.dedup();
+    while ring.len() > 1 && ring.first() == ring.last() {
+        let _ = ring.pop();
+    }
+    match twice_signed_area(&ring) {
+        0 => None,
+        area if area < 0 => {
+            ring.reverse();
+            Some(ring)
+        }
+        _ => Some(ring),
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the parts of this polygon inside `clip`, which may be concave, as one polygon per
+    /// disjoint piece.
+    ///
+    /// Both polygons must be simple, though either may be wound either way. The pieces carry the
+    /// translate offsets in their vertices and have none of their own. Intersection points are
+    /// rounded to the nearest pixel, and pieces that round away to nothing are left out.
+    /// Where the polygons only share a boundary without overlapping, nothing is returned. Should
+    /// the pieces enclose a hole, its ring, wound opposite to the others, is added to the
+    /// smallest piece around it.
+    pub fn clip_to(&self, clip: &Polygon) -> MultiPolygon {
+        let (Some(subject), Some(clip)) = (normalized_ring(self), normalized_ring(clip)) else {
+            return MultiPolygon::default();
+        };
+        let mut pieces = Vec::new();
+        kept_pieces(&subject, &clip, true, &mut pieces);
+        kept_pieces(&clip, &subject, false, &mut pieces);
+
+        let mut outgoing: BTreeMap<Node, Vec<usize>> = BTreeMap::new();
+        for (i, (from, _)) in pieces.iter().enumerate() {
+            outgoing.entry(*from).or_default().push(i);
+        }
+        let mut used = vec![false; pieces.len()];
+        let mut rings = Vec::new();
+        for first in 0..pieces.len() {
+            if used[first] {
+                continue;
+            }
+            let mut ring = Vec::new();
+            let mut current = first;
+            loop {
+                used[current] = true;
+                let (from, to) = pieces[current];
+                ring.push(to.round());
+                if to == pieces[first].0 {
+                    break;
+                }
+                // where pieces touch at a point, turn as far left as possible to keep them apart
+                let (fx, fy) = from.to_f64();
+                let (tx, ty) = to.to_f64();
+                let next = outgoing.get(&to).into_iter().flatten().copied().filter(|i| !used[*i]).max_by(|i, j| {
+                    let turn = |k: usize| {
+                        let (nx, ny) = pieces[k].1.to_f64();
+                        let (incoming, out) = ((tx - fx, ty - fy), (nx - tx, ny - ty));
+                        math::atan2(incoming.0 * out.1 - incoming.1 * out.0, incoming.0 * out.0 + incoming.1 * out.1)
+                    };
+                    turn(*i).total_cmp(&turn(*j))
+                });
+                match next {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+            ring.dedup();
+            while ring.len() > 1 && ring.first() == ring.last() {
+                let _ = ring.pop();
+            }
+            if twice_signed_area(&ring) != 0 {
+                rings.push(ring);
+            }
+        }
+
+        let (exteriors, holes): (Vec<_>, Vec<_>) = rings.into_iter().partition(|ring| twice_signed_area(ring) > 0);
+        let mut parts: Vec<PolygonWithHoles> = exteriors.into_iter().map(|ring| PolygonWithHoles::new(ring, Vec::new())).collect();
+        for hole in holes {
+            let around = parts.iter_mut().filter(|part| hole.iter().all(|v| part.exterior().contains(*v))).min_by_key(|part| twice_signed_area(&part.exterior));
+            match around {
+                Some(part) => part.holes.push(hole),
+                None => parts.push(PolygonWithHoles::new(hole, Vec::new())),
+            }
+        }
+        MultiPolygon::new(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::Polygon;
+    use crate::polygon::geometry::twice_signed_area;
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    type Pixels = BTreeSet<(i32, i32)>;
+
+    fn pixels(polygon: &Polygon) -> Pixels {
+        fill_pixels(polygon).into_iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    /// The pixels of the clipped pieces and the pixels both fills cover.
+    fn clipped_and_expected(subject: &Polygon, clip: &Polygon) -> (Pixels, Pixels) {
+        let clipped = subject.clip_to(clip).iter().flat_map(|piece| pixels(&piece.exterior())).collect();
+        let expected = pixels(subject).intersection(&pixels(clip)).copied().collect();
+        (clipped, expected)
+    }
+
+    /// A U opening towards positive y, with a notch over 10..20 from y = 10.
+    fn u_shape() -> Vec<Point> {
+        ring(&[[0, 0], [30, 0], [30, 30], [20, 30], [20, 10], [10, 10], [10, 30], [0, 30]])
+    }
+
+    #[test]
+    fn test_clip_splits_across_a_concave_clip() {
+        let clip = u_shape();
+        let bar = ring(&[[-5, 20], [35, 20], [35, 25], [-5, 25]]);
+        let mut pieces: Vec<Vec<Point>> = Polygon::new(&bar).clip_to(&Polygon::new(&clip)).into_iter().map(|p| p.exterior).collect();
+        pieces.iter_mut().for_each(|piece| piece.sort_by_key(|p| (p.x, p.y)));
+        pieces.sort_by_key(|piece| piece[0].x);
+        assert_eq!(pieces, vec![ring(&[[0, 20], [0, 25], [10, 20], [10, 25]]), ring(&[[20, 20], [20, 25], [30, 20], [30, 25]])]);
+
+        let (clipped, expected) = clipped_and_expected(&Polygon::new(&bar), &Polygon::new(&clip));
+        assert_eq!(clipped, expected);
+        // clipping the clip by the subject covers the same pixels
+        let (clipped, _) = clipped_and_expected(&Polygon::new(&clip), &Polygon::new(&bar));
+        assert_eq!(clipped, expected);
+    }
+
+    #[test]
+    fn test_clip_vertices_on_clip_edges() {
+        let clip = u_shape();
+        // the diamond's edges pass exactly through the notch's corners, where the three pieces
+        // left of, above and right of the notch touch
+        let diamond = ring(&[[15, 5], [25, 15], [15, 25], [5, 15]]);
+        let pieces = Polygon::new(&diamond).clip_to(&Polygon::new(&clip));
+        assert_eq!(pieces.len(), 3);
+        assert!(pieces.iter().all(|piece| piece.exterior.len() == 3 && piece.holes.is_empty()));
+        let (clipped, expected) = clipped_and_expected(&Polygon::new(&diamond), &Polygon::new(&clip));
+        assert_eq!(clipped, expected);
+
+        // a vertex touching the clip boundary from inside leaves the subject whole
+        let triangle = ring(&[[25, 5], [30, 15], [25, 25]]);
+        let pieces = Polygon::new(&triangle).clip_to(&Polygon::new(&clip));
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(twice_signed_area(&pieces.parts[0].exterior).abs(), twice_signed_area(&triangle).abs());
+    }
+
+    #[test]
+    fn test_clip_collinear_overlaps() {
+        let clip = u_shape();
+        // sharing the top edge of the clip, and wound the other way
+        let inside = ring(&[[0, 5], [15, 5], [15, 0], [0, 0]]);
+        let (clipped, expected) = clipped_and_expected(&Polygon::new(&inside), &Polygon::new(&clip));
+        assert_eq!(clipped, expected);
+        assert_eq!(clipped.len(), 16 * 6);
+
+        // overlapping the clip's right arm along its inner edge and beyond its bottom
+        let straddling = ring(&[[20, 20], [25, 20], [25, 40], [20, 40]]);
+        let (clipped, expected) = clipped_and_expected(&Polygon::new(&straddling), &Polygon::new(&clip));
+        assert_eq!(clipped, expected);
+
+        // touching along an edge from outside encloses nothing
+        let above = ring(&[[5, -10], [25, -10], [25, 0], [5, 0]]);
+        assert!(Polygon::new(&above).clip_to(&Polygon::new(&clip)).is_empty());
+        assert_eq!(Polygon::new(&clip).clip_to(&Polygon::new(&clip)).len(), 1);
+        assert!(Polygon::new(&ring(&[[40, 40], [50, 40], [45, 50]])).clip_to(&Polygon::new(&clip)).is_empty());
+    }
+
+    #[test]
+    fn test_clip_rounded_intersections() {
+        let star = ring(&[[15, -3], [19, 9], [33, 9], [22, 17], [26, 33], [15, 23], [4, 33], [8, 17], [-3, 9], [11, 9]]);
+        let clip = u_shape();
+        let mut clip_polygon = Polygon::new(&clip);
+        clip_polygon.set_translate(Point::new(1, 2));
+        let (clipped, expected) = clipped_and_expected(&Polygon::new(&star), &clip_polygon);
+        // rounding the intersections only moves the outline by up to a pixel
+        let near = |set: &Pixels, (x, y): (i32, i32)| (-1..=1).any(|dx| (-1..=1).any(|dy| set.contains(&(x + dx, y + dy))));
+        assert!(clipped.difference(&expected).all(|p| near(&expected, *p)));
+        assert!(expected.difference(&clipped).all(|p| near(&clipped, *p)));
+        assert!(clipped.intersection(&expected).count() * 10 > expected.len() * 9);
+    }
+}
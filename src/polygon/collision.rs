@@ -0,0 +1,224 @@
+//! Overlap tests between convex polygons using the separating axis theorem.
+
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Rectangle;
+use super::Polygon;
+use super::math;
+
+/// Projects the translated vertices onto `axis`, returning the covered `(min, max)` interval.
+fn project(vertices: &[Point], translate: Point, axis: (i64, i64)) -> (i128, i128) {
+    vertices.iter().fold((i128::MAX, i128::MIN), |(min, max), v| {
+        let v = *v + translate;
+        let d = v.x as i128 * axis.0 as i128 + v.y as i128 * axis.1 as i128;
+        (min.min(d), max.max(d))
+    })
+}
+
+/// Edge normals of a ring, skipping zero-length edges.
+fn axes<'v>(vertices: &'v [Point]) -> impl Iterator<Item = (i64, i64)> + 'v {
+    vertices.iter().enumerate().filter_map(move |(i, a)| {
+        let b = vertices[(i + 1) % vertices.len()];
+        let edge = (b.x as i64 - a.x as i64, b.y as i64 - a.y as i64);
+        (edge != (0, 0)).then_some((-edge.1, edge.0))
+    })
+}
+
+fn rectangle_corners(rectangle: &Rectangle) -> Option<[Point; 4]> {
+    rectangle.bottom_right().map(|bottom_right| {
+        let top_left = rectangle.top_left;
+        [top_left, Point::new(bottom_right.x, top_left.y), bottom_right, Point::new(top_left.x, bottom_right.y)]
+    })
+}
+
+/// Overlap of the projections of two shapes onto `axis`, negative when separated.
+fn overlap(a: &Polygon, b: &Polygon, axis: (i64, i64)) -> (i128, (i128, i128), (i128, i128)) {
+    let (min_a, max_a) = project(a.vertices(), a.translate(), axis);
+    let (min_b, max_b) = project(b.vertices(), b.translate(), axis);
+    (max_a.min(max_b) - min_a.max(min_b), (min_a, max_a), (min_b, max_b))
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns `true` if this polygon and `other` overlap, using the separating axis theorem over
+    /// the edge normals of both shapes.
+    ///
+    /// Both polygons must be convex (see [`is_convex`](Self::is_convex)), concave inputs give
+    /// unspecified results. Shapes that only touch along an edge or at a vertex count as
+    /// colliding, consistent with the fill covering boundary pixels: the fills of touching
+    /// polygons share those pixels.
+    pub fn collides_with(&self, other: &Polygon) -> bool {
+        debug_assert!(self.is_convex() && other.is_convex(), "collides_with requires convex polygons");
+        if self.vertices().is_empty() || other.vertices().is_empty() {
+            return false;
+        }
+        axes(self.vertices()).chain(axes(other.vertices())).chain([(1, 0), (0, 1)])
+            .all(|axis| overlap(self, other, axis).0 >= 0)
+    }
+
+    /// Returns `true` if this convex polygon overlaps the pixels covered by `rectangle`, following
+    /// the same touching convention as [`collides_with`](Self::collides_with). A zero-sized
+    /// rectangle covers nothing and never collides.
+    pub fn collides_with_rectangle(&self, rectangle: &Rectangle) -> bool {
+        match rectangle_corners(rectangle) {
+            Some(corners) => self.collides_with(&Polygon::new(&corners)),
+            None => false,
+        }
+    }
+
+    /// Returns the minimum translation vector that separates this polygon from `other`, or `None`
+    /// if they do not collide.
+    ///
+    /// The vector lies along the axis of least overlap, points away from `other`, and is the
+    /// shortest whole-pixel offset after which [`collides_with`](Self::collides_with) is `false`.
+    /// The depth is the overlap along that axis in pixels, rounded up, so shapes that merely touch
+    /// report a depth of 0 and are still pushed one pixel apart. Both polygons must be convex.
+    pub fn penetration(&self, other: &Polygon) -> Option<(Point, i32)> {
+        if !self.collides_with(other) {
+            return None;
+        }
+        // edge normals are not unit length, so compare overlaps as distances
+        let (axis, overlap, self_range, other_range) = axes(self.vertices()).chain(axes(other.vertices())).chain([(1, 0), (0, 1)])
+            .map(|axis| {
+                let (overlap, self_range, other_range) = overlap(self, other, axis);
+                (axis, overlap as f64 / math::sqrt((axis.0 * axis.0 + axis.1 * axis.1) as f64), self_range, other_range)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        let length = math::sqrt((axis.0 * axis.0 + axis.1 * axis.1) as f64);
+        let direction = if self_range.0 + self_range.1 >= other_range.0 + other_range.1 { 1.0 } else { -1.0 };
+        let (unit_x, unit_y) = (direction * axis.0 as f64 / length, direction * axis.1 as f64 / length);
+
+        // a push longer than the overlap along the axis separates the shapes along it
+        let mut distance = math::ceil(overlap).max(1.0);
+        loop {
+            let push = Point::new(math::round(unit_x * distance) as i32, math::round(unit_y * distance) as i32);
+            let mut moved = *self;
+            moved.set_translate(moved.translate() + push);
+            if !moved.collides_with(other) {
+                return Some((push, math::ceil(overlap) as i32));
+            }
+            distance += 1.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::primitives::{ContainsPoint, Line, Rectangle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    /// Exact geometric overlap of two convex polygons: a vertex of one inside the other, or
+    /// crossing outlines.
+    fn geometric_overlap(a: &Polygon, b: &Polygon) -> bool {
+        a.vertices().iter().any(|v| b.contains(*v + a.translate()))
+            || b.vertices().iter().any(|v| a.contains(*v + b.translate()))
+            || a.vertices().iter().enumerate().any(|(i, v)| {
+                let line = Line::new(*v + a.translate(), a.vertices()[(i + 1) % a.vertices().len()] + a.translate());
+                b.intersect_segment(&line).next().is_some()
+            })
+    }
+
+    #[test]
+    fn test_collides_with_fixed_cases() {
+        let square = ring(&[[0, 0], [10, 0], [10, 10], [0, 10]]);
+        let triangle = ring(&[[5, 5], [20, 5], [5, 20]]);
+        let far = ring(&[[30, 30], [40, 30], [35, 40]]);
+        let touching = ring(&[[10, 0], [20, 0], [20, 10], [10, 10]]);
+        let corner = ring(&[[10, 10], [20, 10], [20, 20]]);
+        // the diagonal edge of this triangle separates it from the square
+        let diagonal = ring(&[[11, 0], [22, 0], [22, 11]]);
+
+        let square = Polygon::new(&square);
+        assert!(square.collides_with(&Polygon::new(&triangle)));
+        assert!(!square.collides_with(&Polygon::new(&far)));
+        assert!(square.collides_with(&Polygon::new(&touching)));
+        assert!(square.collides_with(&Polygon::new(&corner)));
+        assert!(!square.collides_with(&Polygon::new(&diagonal)));
+
+        let mut moved = Polygon::new(&far);
+        moved.set_translate(Point::new(-28, -28));
+        assert!(square.collides_with(&moved));
+
+        assert!(square.collides_with_rectangle(&Rectangle::new(Point::new(10, 10), Size::new(3, 3))));
+        assert!(!square.collides_with_rectangle(&Rectangle::new(Point::new(11, 0), Size::new(3, 3))));
+        assert!(!square.collides_with_rectangle(&Rectangle::new(Point::new(5, 5), Size::zero())));
+    }
+
+    fn assert_separates(a: &Polygon, b: &Polygon) -> (Point, i32) {
+        let (push, depth) = a.penetration(b).expect("shapes should collide");
+        let mut moved = *a;
+        moved.set_translate(moved.translate() + push);
+        assert!(!moved.collides_with(b), "{push} does not separate {a:?} from {b:?}");
+        // the push is minimal: one pixel less along the same direction still collides
+        let shorter = Point::new(push.x - push.x.signum(), push.y - push.y.signum());
+        let mut moved = *a;
+        moved.set_translate(moved.translate() + shorter);
+        assert!(moved.collides_with(b), "{push} is longer than needed");
+        (push, depth)
+    }
+
+    #[test]
+    fn test_penetration_deep_overlap() {
+        let square = ring(&[[0, 0], [20, 0], [20, 20], [0, 20]]);
+        let inner = ring(&[[12, 4], [30, 4], [30, 16], [12, 16]]);
+        let (push, depth) = assert_separates(&Polygon::new(&inner), &Polygon::new(&square));
+        assert_eq!((push, depth), (Point::new(9, 0), 8));
+        let (push, depth) = assert_separates(&Polygon::new(&square), &Polygon::new(&inner));
+        assert_eq!((push, depth), (Point::new(-9, 0), 8));
+    }
+
+    #[test]
+    fn test_penetration_shallow_and_touching_contact() {
+        let square = ring(&[[0, 0], [20, 0], [20, 20], [0, 20]]);
+        let shallow = ring(&[[5, 19], [15, 19], [15, 30], [5, 30]]);
+        assert_eq!(assert_separates(&Polygon::new(&shallow), &Polygon::new(&square)), (Point::new(0, 2), 1));
+
+        let touching = ring(&[[20, 5], [30, 5], [30, 15], [20, 15]]);
+        assert_eq!(assert_separates(&Polygon::new(&touching), &Polygon::new(&square)), (Point::new(1, 0), 0));
+
+        // a triangle whose tip rests on the square's top edge
+        let vertex_on_edge = ring(&[[10, 0], [4, -10], [16, -10]]);
+        assert_eq!(assert_separates(&Polygon::new(&vertex_on_edge), &Polygon::new(&square)), (Point::new(0, -1), 0));
+
+        let diamond = ring(&[[30, 10], [35, 5], [40, 10], [35, 15]]);
+        let mut slanted = Polygon::new(&diamond);
+        slanted.set_translate(Point::new(-13, 0));
+        let (push, _) = assert_separates(&slanted, &Polygon::new(&square));
+        assert!(push.x > 0);
+
+        assert_eq!(Polygon::new(&square).penetration(&Polygon::new(&ring(&[[30, 30], [40, 30], [35, 40]]))), None);
+    }
+
+    #[test]
+    fn test_collides_with_matches_rasterized_overlap() {
+        let mut rng = StdRng::seed_from_u64(334);
+        let mut collisions = 0;
+        for _ in 0..2000 {
+            // triangles are always convex
+            let mut random_triangle = || (0..3).map(|_| Point::new(rng.gen_range(0..16), rng.gen_range(0..16))).collect::<Vec<_>>();
+            let (a, b) = (random_triangle(), random_triangle());
+            let (a, b) = (Polygon::new(&a), Polygon::new(&b));
+            let collides = a.collides_with(&b);
+            assert_eq!(collides, b.collides_with(&a));
+
+            let pixels_a: HashSet<Point> = fill_pixels(&a).into_iter().collect();
+            let shares_pixel = fill_pixels(&b).iter().any(|p| pixels_a.contains(p));
+            // shared pixels imply overlap, but convex shapes may also overlap between pixel centers
+            if shares_pixel {
+                assert!(collides, "{a:?} {b:?}");
+            }
+            assert_eq!(collides, geometric_overlap(&a, &b), "{a:?} {b:?}");
+            if collides {
+                let (push, _) = a.penetration(&b).unwrap();
+                let mut moved = a;
+                moved.set_translate(moved.translate() + push);
+                assert!(!moved.collides_with(&b), "{a:?} {b:?} {push}");
+            }
+            collisions += collides as u32;
+        }
+        assert!(collisions > 200 && collisions < 1800, "{collisions}");
+    }
+}
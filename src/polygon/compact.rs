@@ -0,0 +1,165 @@
+//! Polygons over vertices stored as `i16` pairs.
+
+use alloc::vec::Vec;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{ContainsPoint, Line, Polyline, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
+use super::{edges_contain, fill_ring, ring_edges, vertex_bounds, PolygonOwned, StreamingPolygon};
+use super::affected::AffectedArea;
+
+/// A closed polygon over a borrowed list of vertices stored as `(x, y)` pairs of `i16`, half the
+/// size of [`Polygon`](super::Polygon)'s `Point`s, which is plenty for any display.
+///
+/// Vertices are widened to `Point`s one at a time as they are read, so drawing, bounds and
+/// containment work straight off the stored pairs, and draw exactly what a `Polygon` over the
+/// same vertices does. The rest of the API is reached through [`streaming`](Self::streaming), or
+/// through [`to_polygon_owned`](Self::to_polygon_owned), which does copy the vertices.
+///
+/// The vertices and translate offset are read through [`vertices`](Self::vertices) and
+/// [`translate`](Self::translate), and the polygon is moved with
+/// [`set_translate`](Self::set_translate), as for a `Polygon`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Polygon16<'a> {
+    translate: Point,
+    vertices: &'a [(i16, i16)],
+}
+
+impl<'a> Polygon16<'a> {
+    /// Creates a polygon without offset. Like [`Polygon::new`](super::Polygon::new) this is a
+    /// `const fn`, so the polygon can be a `static` in flash along with its vertices.
+    pub const fn new(vertices: &'a [(i16, i16)]) -> Self {
+        Self::with_translate(vertices, Point::zero())
+    }
+
+    pub const fn with_translate(vertices: &'a [(i16, i16)], translate: Point) -> Self {
+        Polygon16 { translate, vertices }
+    }
+
+    /// Returns the stored vertex pairs, without the translate offset.
+    pub const fn vertices(&self) -> &'a [(i16, i16)] {
+        self.vertices
+    }
+
+    /// Returns the offset added to every vertex when drawing and measuring.
+    pub const fn translate(&self) -> Point {
+        self.translate
+    }
+
+    pub fn set_translate(&mut self, translate: Point) {
+        self.translate = translate;
+    }
+
+    /// Returns the vertices as `Point`s with the translate offset applied.
+    pub fn points(&self) -> impl Iterator<Item = Point> + Clone + 'a {
+        let translate = self.translate;
+        self.vertices.iter().map(move |&(x, y)| Point::new(x as i32, y as i32) + translate)
+    }
+
+    /// Returns the edges of the closed outline with the translate offset applied, in the same
+    /// way as [`Polygon::edges`](super::Polygon::edges).
+    pub fn edges(&self) -> impl Iterator<Item = Line> + 'a {
+        ring_edges(self.points())
+    }
+
+    /// Returns the polygon as a [`StreamingPolygon`] reading the same vertices.
+    pub fn streaming(&self) -> StreamingPolygon<impl Iterator<Item = Point> + Clone + 'a> {
+        StreamingPolygon::new(self.points())
+    }
+
+    /// Copies the vertices into a [`PolygonOwned`] with the same translate offset, for the
+    /// operations that need them as a slice of `Point`s.
+    pub fn to_polygon_owned(&self) -> PolygonOwned {
+        let translate = self.translate;
+        PolygonOwned::with_translate(self.points().map(|p| p - translate).collect(), translate)
+    }
+}
+
+impl<'a> Dimensions for Polygon16<'a> {
+    /// Returns the box around the vertices, moved by the translate offset.
+    fn bounding_box(&self) -> Rectangle {
+        vertex_bounds(self.points())
+    }
+}
+
+impl<'a> Primitive for Polygon16<'a> {}
+
+impl<'a> ContainsPoint for Polygon16<'a> {
+    fn contains(&self, point: Point) -> bool {
+        edges_contain(self.edges(), point)
+    }
+}
+
+impl<'a, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for Polygon16<'a> {
+    type Color = C;
+    type Output = Rectangle;
+
+    /// Draws the polygon and returns the affected area as [`Polygon`](super::Polygon) does.
+    fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color = Self::Color> {
+        let mut target = AffectedArea::new(target);
+        match style.stroke_width {
+            0 => {
+                if let Some(color) = style.fill_color {
+                    fill_ring(self.edges(), color, &mut target)?;
+                }
+            } // fill
+            1 => {
+                for edge in self.edges() {
+                    edge.draw_styled(style, &mut target)?;
+                }
+            }
+            _ => {
+                // thicker outlines join each edge to the next, which needs the closed ring in one
+                // slice
+                let points: Vec<Point> = self.points().chain(self.points().take(1)).collect();
+                Polyline::new(&points).draw_styled(style, &mut target)?;
+            }
+        }
+        Ok(target.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Dimensions, Point};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle};
+    use crate::polygon::{Polygon, Polygon16};
+
+    static STAR: [(i16, i16); 10] = [(30, 2), (36, 22), (58, 22), (40, 35), (48, 60), (30, 45), (12, 60), (20, 35), (2, 22), (24, 22)];
+    static COMPACT_STAR: Polygon16<'static> = Polygon16::new(&STAR);
+
+    #[test]
+    fn test_compact_storage_draws_like_points() {
+        assert_eq!(mem::size_of_val(&STAR), 40);
+        let points: Vec<Point> = STAR.iter().map(|&(x, y)| Point::new(x as i32, y as i32)).collect();
+        assert_eq!(mem::size_of_val(points.as_slice()), 80);
+
+        let mut wide = Polygon::new(&points);
+        let mut compact = COMPACT_STAR;
+        for translate in [Point::zero(), Point::new(3, 2)] {
+            wide.set_translate(translate);
+            compact.set_translate(translate);
+            assert_eq!((compact.translate(), compact.vertices()), (wide.translate(), &STAR[..]));
+            for style in [PrimitiveStyle::with_fill(BinaryColor::On), PrimitiveStyle::with_stroke(BinaryColor::On, 1), PrimitiveStyle::with_stroke(BinaryColor::On, 3)] {
+                let (mut expected, mut display) = (MockDisplay::new(), MockDisplay::new());
+                for display in [&mut expected, &mut display] {
+                    // the miters of the wide outline reach past the edges
+                    display.set_allow_overdraw(true);
+                    display.set_allow_out_of_bounds_drawing(true);
+                }
+                let expected_area = wide.into_styled(style).draw(&mut expected).unwrap();
+                let area = compact.into_styled(style).draw(&mut display).unwrap();
+                display.assert_eq(&expected);
+                assert_eq!(area, expected_area);
+            }
+            assert_eq!(compact.bounding_box(), wide.bounding_box());
+            assert_eq!(compact.edges().collect::<Vec<_>>(), wide.edges().collect::<Vec<_>>());
+            assert!((0..64).all(|x| (0..64).all(|y| compact.contains(Point::new(x, y)) == wide.contains(Point::new(x, y)))));
+            assert_eq!(compact.to_polygon_owned().as_polygon(), wide);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Polygons decoded on the fly from delta-encoded vertex bytes.
+
+use core::fmt;
+use alloc::vec::Vec;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{ContainsPoint, Line, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
+use super::{edges_contain, ring_edges, vertex_bounds, StreamingPolygon};
+
+/// Why bytes are not a valid [`CompressedPolygon`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes end inside a number, or after an x delta with no y delta, at `offset`.
+    Truncated { offset: usize },
+    /// The number starting at byte `offset` does not fit 32 bits.
+    Overflow { offset: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { offset } => write!(f, "vertex data truncated at byte {offset}"),
+            DecodeError::Overflow { offset } => write!(f, "delta at byte {offset} does not fit 32 bits"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Reads the number at the start of `bytes`, returning it and the bytes it took.
+fn read_varint(bytes: &[u8], offset: usize) -> Result<(u32, usize), DecodeError> {
+    let mut value = 0u32;
+    for (i, byte) in bytes.iter().enumerate().take(5) {
+        // the fifth byte holds only the top four bits
+        if i == 4 && *byte > 0x0f {
+            return Err(DecodeError::Overflow { offset });
+        }
+        value |= ((byte & 0x7f) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    if bytes.len() < 5 { Err(DecodeError::Truncated { offset: offset + bytes.len() }) } else { Err(DecodeError::Overflow { offset }) }
+}
+
+fn write_varint(mut value: u32, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Maps small negative numbers to small unsigned ones: 0, -1, 1, -2 to 0, 1, 2, 3.
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+fn unzigzag(value: u32) -> i32 {
+    (value >> 1) as i32 ^ -((value & 1) as i32)
+}
+
+/// The vertices of a [`CompressedPolygon`], decoded one at a time.
+#[derive(Clone)]
+pub struct CompressedVertices<'a> {
+    next: Option<Point>,
+    deltas: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> CompressedVertices<'a> {
+    fn read_delta(&mut self) -> Result<Option<Point>, DecodeError> {
+        if self.offset == self.deltas.len() {
+            return Ok(None);
+        }
+        let (dx, taken) = read_varint(&self.deltas[self.offset..], self.offset)?;
+        self.offset += taken;
+        if self.offset == self.deltas.len() {
+            return Err(DecodeError::Truncated { offset: self.offset });
+        }
+        let (dy, taken) = read_varint(&self.deltas[self.offset..], self.offset)?;
+        self.offset += taken;
+        Ok(Some(Point::new(unzigzag(dx), unzigzag(dy))))
+    }
+}
+
+impl<'a> Iterator for CompressedVertices<'a> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let vertex = self.next?;
+        // the bytes were checked up front, so an error can only end the vertices early
+        self.next = self.read_delta().ok().flatten().map(|delta| Point::new(vertex.x.wrapping_add(delta.x), vertex.y.wrapping_add(delta.y)));
+        Some(vertex)
+    }
+}
+
+/// A closed polygon whose vertices are kept as bytes: a starting vertex, then for each further
+/// vertex its offset from the one before as an x and a y delta.
+///
+/// Each delta is zig-zag encoded, so small negative deltas stay small, and written as a
+/// little-endian base-128 varint, seven bits to a byte with the top bit set on every byte but the
+/// last. Contours whose vertices lie close together take two or three bytes a vertex instead of
+/// the eight of a `Point`. Deltas wrap around, so any vertices can be encoded.
+///
+/// The bytes are checked once by [`new`](Self::new); after that the vertices are decoded as they
+/// are read, through [`StreamingPolygon`], without being collected. The translate offset is read
+/// through [`translate`](Self::translate) and set with [`set_translate`](Self::set_translate), as
+/// for a [`Polygon`](super::Polygon).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressedPolygon<'a> {
+    translate: Point,
+    start: Point,
+    deltas: &'a [u8],
+    len: usize,
+}
+
+impl<'a> CompressedPolygon<'a> {
+    /// Checks `deltas` and creates a polygon starting from vertex `start`.
+    pub fn new(start: Point, deltas: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut vertices = CompressedVertices { next: None, deltas, offset: 0 };
+        let mut len = 1;
+        while vertices.read_delta()?.is_some() {
+            len += 1;
+        }
+        Ok(CompressedPolygon { translate: Point::zero(), start, deltas, len })
+    }
+
+    /// Encodes `vertices` as the starting vertex and the delta bytes [`new`](Self::new) takes.
+    /// Without vertices the start is the origin and there are no bytes, which is a polygon of a
+    /// single vertex.
+    pub fn encode(vertices: &[Point]) -> (Point, Vec<u8>) {
+        let mut bytes = Vec::new();
+        for pair in vertices.windows(2) {
+            write_varint(zigzag(pair[1].x.wrapping_sub(pair[0].x)), &mut bytes);
+            write_varint(zigzag(pair[1].y.wrapping_sub(pair[0].y)), &mut bytes);
+        }
+        (vertices.first().copied().unwrap_or_default(), bytes)
+    }
+
+    /// Returns the number of vertices.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always `false`, as there is at least the starting vertex.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the offset added to every vertex when drawing and measuring.
+    pub const fn translate(&self) -> Point {
+        self.translate
+    }
+
+    pub fn set_translate(&mut self, translate: Point) {
+        self.translate = translate;
+    }
+
+    /// Returns the vertices, without the translate offset.
+    pub fn vertices(&self) -> CompressedVertices<'a> {
+        CompressedVertices { next: Some(self.start), deltas: self.deltas, offset: 0 }
+    }
+
+    /// Returns the polygon as a [`StreamingPolygon`] decoding the same vertices.
+    pub fn streaming(&self) -> StreamingPolygon<CompressedVertices<'a>> {
+        StreamingPolygon { translate: self.translate, vertices: self.vertices() }
+    }
+
+    /// Returns the edges of the closed outline with the translate offset applied, in the same
+    /// way as [`Polygon::edges`](super::Polygon::edges).
+    pub fn edges(&self) -> impl Iterator<Item = Line> + 'a {
+        let translate = self.translate;
+        ring_edges(self.vertices().map(move |v| v + translate))
+    }
+}
+
+impl<'a> Dimensions for CompressedPolygon<'a> {
+    /// Returns the box around the vertices, moved by the translate offset.
+    fn bounding_box(&self) -> Rectangle {
+        let translate = self.translate;
+        vertex_bounds(self.vertices().map(move |v| v + translate))
+    }
+}
+
+impl<'a> Primitive for CompressedPolygon<'a> {}
+
+impl<'a> ContainsPoint for CompressedPolygon<'a> {
+    fn contains(&self, point: Point) -> bool {
+        edges_contain(self.edges(), point)
+    }
+}
+
+impl<'a, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for CompressedPolygon<'a> {
+    type Color = C;
+    type Output = Rectangle;
+
+    /// Draws the polygon as [`StreamingPolygon`] does.
+    fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color = Self::Color> {
+        self.streaming().draw_styled(style, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Dimensions, Point};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{CompressedPolygon, DecodeError, Polygon};
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = StdRng::seed_from_u64(395);
+        for _ in 0..200 {
+            let count = rng.gen_range(1..20);
+            let vertices: Vec<Point> = (0..count).map(|_| Point::new(rng.gen_range(-100..100), rng.gen_range(-100..100))).collect();
+            let (start, bytes) = CompressedPolygon::encode(&vertices);
+            let mut compressed = CompressedPolygon::new(start, &bytes).unwrap();
+            assert_eq!(compressed.vertices().collect::<Vec<_>>(), vertices);
+            assert_eq!(compressed.len(), vertices.len());
+
+            let mut polygon = Polygon::new(&vertices);
+            polygon.set_translate(Point::new(32, 32));
+            compressed.set_translate(polygon.translate());
+            assert_eq!(compressed.translate(), Point::new(32, 32));
+            assert_eq!(compressed.bounding_box(), polygon.bounding_box());
+            assert!((0..64).all(|x| (0..64).all(|y| compressed.contains(Point::new(x, y)) == polygon.contains(Point::new(x, y)))));
+            let style = PrimitiveStyle::with_fill(BinaryColor::On);
+            let (mut expected, mut display) = (MockDisplay::new(), MockDisplay::new());
+            polygon.into_styled(style).draw(&mut expected).unwrap();
+            compressed.into_styled(style).draw(&mut display).unwrap();
+            display.assert_eq(&expected);
+        }
+
+        // the extremes wrap around rather than overflow
+        let extremes = [Point::new(i32::MIN, i32::MAX), Point::new(i32::MAX, i32::MIN), Point::zero()];
+        let (start, bytes) = CompressedPolygon::encode(&extremes);
+        assert_eq!(CompressedPolygon::new(start, &bytes).unwrap().vertices().collect::<Vec<_>>(), extremes);
+        assert_eq!(CompressedPolygon::encode(&[]), (Point::zero(), Vec::new()));
+    }
+
+    #[test]
+    fn test_contour_takes_a_fraction_of_the_space() {
+        // a coastline-like walk of a thousand short steps
+        let mut rng = StdRng::seed_from_u64(1395);
+        let mut at = Point::new(200, 120);
+        let contour: Vec<Point> = (0..1000).map(|_| {
+            at += Point::new(rng.gen_range(-5..=5), rng.gen_range(-5..=5));
+            at
+        }).collect();
+        let (start, bytes) = CompressedPolygon::encode(&contour);
+        assert_eq!(bytes.len(), 2 * 999, "every delta fits a byte");
+        assert_eq!(std::mem::size_of_val(contour.as_slice()), 8000);
+        assert_eq!(CompressedPolygon::new(start, &bytes).unwrap().vertices().collect::<Vec<_>>(), contour);
+    }
+
+    #[test]
+    fn test_corrupt_bytes_are_rejected() {
+        let (start, bytes) = CompressedPolygon::encode(&[Point::zero(), Point::new(300, -2), Point::new(1, 1)]);
+        assert_eq!(bytes, [0xd8, 0x04, 0x03, 0xd5, 0x04, 0x06]);
+        assert!(CompressedPolygon::new(start, &bytes).is_ok());
+        // cut inside a number, and after an x delta
+        assert_eq!(CompressedPolygon::new(start, &bytes[..4]), Err(DecodeError::Truncated { offset: 4 }));
+        assert_eq!(CompressedPolygon::new(start, &bytes[..5]), Err(DecodeError::Truncated { offset: 5 }));
+        assert_eq!(CompressedPolygon::new(start, &bytes[..1]), Err(DecodeError::Truncated { offset: 1 }));
+        // more than 32 bits
+        assert_eq!(CompressedPolygon::new(start, &[0xff, 0xff, 0xff, 0xff, 0x1f, 0x00]), Err(DecodeError::Overflow { offset: 0 }));
+        assert_eq!(CompressedPolygon::new(start, &[0x00, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]), Err(DecodeError::Overflow { offset: 1 }));
+        assert!(CompressedPolygon::new(start, &[0xff, 0xff, 0xff, 0xff, 0x0f, 0x00]).is_ok());
+        assert_eq!(CompressedPolygon::new(start, &[]).map(|polygon| polygon.len()), Ok(1));
+    }
+}
@@ -0,0 +1,109 @@
+//! Containment of one polygon in another.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::{ContainsPoint, Line};
+use super::Polygon;
+use super::geometry::cross;
+
+/// Returns `true` if the segments cross at a single point inside both of them.
+fn crosses(first: Line, second: Line) -> bool {
+    let (d1, d2) = (cross(first.start, first.end, second.start), cross(first.start, first.end, second.end));
+    let (d3, d4) = (cross(second.start, second.end, first.start), cross(second.start, second.end, first.end));
+    d1.signum() * d2.signum() < 0 && d3.signum() * d4.signum() < 0
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns `true` if the filled area of `other` lies entirely within the filled area of this
+    /// polygon, for example to skip drawing a shape hidden behind this one.
+    ///
+    /// Both polygons are treated as closed, as the fill is, so `other` may share boundary points,
+    /// edges or its whole outline with this polygon and still be contained. Checking the vertices
+    /// alone is not enough for a concave polygon, so `other` is also rejected if any of its edges
+    /// crosses this outline or runs outside it between two points where it touches the outline.
+    /// Returns `false` if either polygon is empty.
+    pub fn contains_polygon(&self, other: &Polygon) -> bool {
+        if self.vertices().is_empty() || other.vertices().is_empty() {
+            return false;
+        }
+        if !other.vertices().iter().all(|v| self.contains(*v + other.translate())) {
+            return false;
+        }
+        // at double scale the midpoint between two pixels is a pixel, so pieces of an edge can be
+        // tested with the usual containment
+        let doubled: Vec<Point> = self.vertices().iter().map(|v| (*v + self.translate()) * 2).collect();
+        let doubled = Polygon::new(&doubled);
+        let outline: Vec<Point> = self.vertices().iter().map(|v| *v + self.translate()).collect();
+        let mut cuts = Vec::new();
+        other.edges().all(|edge| {
+            if self.edges().any(|own| crosses(edge, own)) {
+                return false;
+            }
+            // without crossings the edge can only leave the polygon at a vertex lying on it
+            cuts.clear();
+            cuts.extend([edge.start, edge.end]);
+            cuts.extend(outline.iter().filter(|v| {
+                cross(edge.start, edge.end, **v) == 0
+                    && v.x >= edge.start.x.min(edge.end.x) && v.x <= edge.start.x.max(edge.end.x)
+                    && v.y >= edge.start.y.min(edge.end.y) && v.y <= edge.start.y.max(edge.end.y)
+            }));
+            cuts.sort_by_key(|p| (p.x as i64 - edge.start.x as i64).abs() + (p.y as i64 - edge.start.y as i64).abs());
+            cuts.windows(2).all(|piece| doubled.contains(piece[0] + piece[1]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::tests::ring;
+    use crate::polygon::Polygon;
+
+    fn c_shape() -> Vec<Point> {
+        ring(&[[0, 0], [30, 0], [30, 10], [10, 10], [10, 20], [30, 20], [30, 30], [0, 30]])
+    }
+
+    #[test]
+    fn test_contains_convex_polygons() {
+        let outer = ring(&[[0, 0], [20, 0], [20, 20], [0, 20]]);
+        let inner = ring(&[[0, 0], [6, 0], [3, 5]]);
+        let a = Polygon::new(&outer);
+        let mut b = Polygon::new(&inner);
+        b.set_translate(Point::new(7, 7));
+        assert!(a.contains_polygon(&b));
+        assert!(!b.contains_polygon(&a));
+        b.set_translate(Point::new(17, 7));
+        assert!(!a.contains_polygon(&b));
+        assert!(!a.contains_polygon(&Polygon::new(&[])));
+    }
+
+    #[test]
+    fn test_shared_boundary_counts_as_contained() {
+        let outer = ring(&[[0, 0], [20, 0], [20, 20], [0, 20]]);
+        let a = Polygon::new(&outer);
+        assert!(a.contains_polygon(&a));
+        let corner = ring(&[[0, 0], [20, 0], [0, 20]]);
+        assert!(a.contains_polygon(&Polygon::new(&corner)));
+    }
+
+    #[test]
+    fn test_concave_polygon_does_not_contain_its_mouth() {
+        let c = c_shape();
+        let c = Polygon::new(&c);
+        // all four corners of the block are inside the C's bounding box, and none inside the C
+        let block = ring(&[[14, 12], [28, 12], [28, 18], [14, 18]]);
+        assert!(!c.contains_polygon(&Polygon::new(&block)));
+        // vertices in both arms, the edges crossing the mouth
+        let bridge = ring(&[[20, 5], [25, 5], [25, 25], [20, 25]]);
+        assert!(!c.contains_polygon(&Polygon::new(&bridge)));
+        // every vertex inside and no edge crossing, but one edge runs through the mouth between
+        // the inner corner and the lower arm
+        let wedge = ring(&[[5, 5], [20, 20], [5, 20]]);
+        assert!(!c.contains_polygon(&Polygon::new(&wedge)));
+
+        let spine = ring(&[[2, 2], [10, 10], [10, 20], [2, 28]]);
+        assert!(c.contains_polygon(&Polygon::new(&spine)));
+        let arm = ring(&[[5, 2], [30, 2], [30, 10], [10, 10]]);
+        assert!(c.contains_polygon(&Polygon::new(&arm)));
+    }
+}
@@ -0,0 +1,82 @@
+//! Counting the pixels a fill covers without drawing it.
+
+use alloc::vec::Vec;
+use embedded_graphics::primitives::Rectangle;
+use super::Polygon;
+use super::scanline::Scanlines;
+
+impl<'a> Polygon<'a> {
+    /// Returns the number of pixels a fill covers, with the translate offset applied, by summing
+    /// the widths of the fill spans.
+    ///
+    /// This is exactly the number of pixels a filled draw emits into an unbounded target, which
+    /// can differ from the geometric area as the fill covers every pixel on the outline, thin
+    /// slivers included.
+    pub fn pixel_count(&self) -> u64 {
+        self.spans().map(|span| span.len as u64).sum()
+    }
+
+    /// Returns the number of pixels a fill covers within `area`, such as the visible part of the
+    /// screen.
+    ///
+    /// Only the rows of `area` are walked, so a shape reaching far outside it costs no more than
+    /// the part inside.
+    pub fn pixel_count_clipped(&self, area: &Rectangle) -> u64 {
+        let Some(bottom_right) = area.bottom_right() else {
+            return 0;
+        };
+        let mut scanlines = Scanlines::new(self.edges());
+        scanlines.skip_to(area.top_left.y);
+        let mut spans = Vec::new();
+        let mut count = 0;
+        while scanlines.next_row(&mut spans).filter(|y| *y <= bottom_right.y).is_some() {
+            for &(start, end) in spans.iter() {
+                let (start, end) = (start.max(area.top_left.x), end.min(bottom_right.x));
+                if start <= end {
+                    count += end.abs_diff(start) as u64 + 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::{PixelCollector, ring};
+
+    #[test]
+    fn test_count_matches_the_pixels_drawn() {
+        let fixtures = [
+            ring(&[[-6, 3], [50, -4], [70, 30], [31, 23], [35, 52], [2, 40]]),
+            // a sliver with next to no area still covers the pixels along it
+            ring(&[[0, 0], [60, 1], [0, 2]]),
+            ring(&[[0, -20], [10, 50], [20, -20], [0, 30], [20, 30]]),
+            ring(&[[4, 4], [9, 4]]),
+            ring(&[[4, 4]]),
+            ring(&[]),
+        ];
+        let areas = [
+            Rectangle::new(Point::new(0, 0), Size::new(32, 24)),
+            Rectangle::new(Point::new(-100, -100), Size::new(300, 300)),
+            Rectangle::new(Point::new(30, 20), Size::new(1, 1)),
+            Rectangle::new(Point::new(10, 10), Size::new(0, 5)),
+        ];
+        for vertices in fixtures.iter() {
+            let mut polygon = Polygon::new(vertices);
+            polygon.set_translate(Point::new(3, -5));
+            let mut collector = PixelCollector::default();
+            polygon.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+            assert_eq!(polygon.pixel_count(), collector.pixels.len() as u64, "{vertices:?}");
+            for area in areas.iter() {
+                let inside = collector.pixels.iter().filter(|p| area.contains(**p)).count();
+                assert_eq!(polygon.pixel_count_clipped(area), inside as u64, "{vertices:?} in {area:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Per-pixel coverage estimates for compositing and anti-aliasing.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use super::Polygon;
+use super::math;
+use super::scanline::Scanlines;
+
+impl<'a> Polygon<'a> {
+    /// Calls `f` with every pixel the fill covers, translate offset applied, and an estimate of
+    /// how much of the pixel lies inside the polygon, from 1 to 255.
+    ///
+    /// Pixels in the middle of a span report 255. At the ends of a span the coverage is the share
+    /// of the pixel's width that lies between the exact edge crossings of its row, so only
+    /// horizontal partial coverage is accounted for; a pixel whose center is on an edge reports
+    /// half. A pixel of the fill that comes out with no coverage at all, such as one at a vertex
+    /// where both edges meet in the pixel, is skipped, as are pixels outside the fill. The values come from the exact integer
+    /// edge stepping, so the same polygon always produces the same coverage.
+    pub fn for_each_coverage<F: FnMut(Point, u8)>(&self, mut f: F) {
+        let mut scanlines = Scanlines::new(self.edges()).with_crossings();
+        let mut spans = Vec::new();
+        while let Some(y) = scanlines.next_row(&mut spans) {
+            let crossings = scanlines.crossings();
+            for &(start, end) in spans.iter() {
+                // the crossings the span was rounded in from, if it ends on an edge at all
+                let left = crossings.iter().rev().copied().find(|&c| c <= start as f64).filter(|&c| c > start as f64 - 1.0);
+                let right = crossings.iter().copied().find(|&c| c >= end as f64).filter(|&c| c < end as f64 + 1.0);
+                for x in start..=end {
+                    let (pixel_left, pixel_right) = (x as f64 - 0.5, x as f64 + 0.5);
+                    let inside = right.map_or(pixel_right, |c| c.min(pixel_right)) - left.map_or(pixel_left, |c| c.max(pixel_left));
+                    let coverage = math::round(inside.clamp(0.0, 1.0) * 255.0) as u8;
+                    if coverage > 0 {
+                        f(Point::new(x, y), coverage);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn coverage(polygon: &Polygon) -> Vec<(Point, u8)> {
+        let mut pixels = Vec::new();
+        polygon.for_each_coverage(|p, c| pixels.push((p, c)));
+        pixels
+    }
+
+    #[test]
+    fn test_coverage_covers_the_fill() {
+        let vertices = ring(&[[0, 0], [10, 0], [10, 4], [0, 4]]);
+        let pixels = coverage(&Polygon::new(&vertices));
+        assert_eq!(pixels.iter().map(|(p, _)| *p).collect::<Vec<_>>(), fill_pixels(&Polygon::new(&vertices)));
+        // pixel centers on the vertical edges are half covered
+        assert!(pixels.iter().all(|&(p, c)| c == if p.x == 0 || p.x == 10 { 128 } else { 255 }));
+
+        let triangle = ring(&[[0, 0], [7, 0], [0, 10]]);
+        let mut polygon = Polygon::new(&triangle);
+        polygon.set_translate(Point::new(5, 5));
+        let pixels = coverage(&polygon);
+        // toward the bottom vertex both edges meet inside the pixels, which are filled but not
+        // covered and so skipped
+        let (covered, skipped): (Vec<Point>, Vec<Point>) = fill_pixels(&polygon).into_iter().partition(|p| pixels.iter().any(|(q, _)| q == p));
+        assert_eq!(pixels.iter().map(|(p, _)| *p).collect::<Vec<_>>(), covered);
+        assert_eq!(skipped, [Point::new(5, 14), Point::new(5, 15)]);
+        assert!(pixels.iter().all(|(_, c)| *c > 0));
+        // on row 3 the hypotenuse crosses at x = 4.9, covering the last pixel [3.5, 4.5] fully
+        // and leaving the center of the next one out
+        let row: Vec<u8> = pixels.iter().filter(|(p, _)| p.y == 8).map(|(_, c)| *c).collect();
+        assert_eq!(row, [128, 255, 255, 255, 255]);
+        // on row 5 it crosses at x = 3.5, the edge of the last pixel
+        let row: Vec<u8> = pixels.iter().filter(|(p, _)| p.y == 10).map(|(_, c)| *c).collect();
+        assert_eq!(row, [128, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_coverage_is_deterministic() {
+        let fixtures = [
+            ring(&[[0, 0], [37, 5], [29, 41], [13, 17], [-8, 30]]),
+            ring(&[[0, 0], [10, 10], [10, 0], [0, 10]]),
+            ring(&[[3, 1], [4, 90], [5, 2]]),
+        ];
+        // FNV-1a, whose output does not depend on the platform or the standard library
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for vertices in fixtures.iter() {
+            Polygon::new(vertices).for_each_coverage(|p, c| {
+                for byte in p.x.to_le_bytes().into_iter().chain(p.y.to_le_bytes()).chain([c]) {
+                    hash = (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+                }
+            });
+        }
+        assert_eq!(hash, 103839563132352555);
+    }
+}
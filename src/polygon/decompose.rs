@@ -0,0 +1,154 @@
+//! Splitting polygons into triangles and convex pieces.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use super::{Polygon, PolygonOwned};
+use super::geometry::{cross, twice_signed_area};
+
+/// Returns `true` if `p` lies inside or on the triangle `a`, `b`, `c` whose turns have the sign
+/// `orientation`.
+fn in_triangle(p: Point, a: Point, b: Point, c: Point, orientation: i64) -> bool {
+    cross(a, b, p) * orientation >= 0 && cross(b, c, p) * orientation >= 0 && cross(c, a, p) * orientation >= 0
+}
+
+impl<'a> Polygon<'a> {
+    /// Splits a simple polygon into triangles by ear clipping, returned as indices into
+    /// `vertices`.
+    ///
+    /// Triangles without area, such as those formed by collinear vertices, are left out; their
+    /// pixels are still covered by the neighbouring triangles. Self-intersecting polygons give
+    /// unspecified results.
+    pub fn triangulate(&self) -> Vec<[usize; 3]> {
+        let vertices = self.vertices();
+        let orientation = twice_signed_area(vertices).signum() as i64;
+        let mut triangles = Vec::new();
+        if orientation == 0 {
+            return triangles;
+        }
+        let mut remaining: Vec<usize> = (0..vertices.len()).collect();
+        while remaining.len() >= 3 {
+            let m = remaining.len();
+            let corner = |i: usize| (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+            let is_ear = |i: usize| {
+                let (a, b, c) = corner(i);
+                let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+                cross(a, b, c) * orientation > 0 && remaining.iter().map(|j| vertices[*j])
+                    .filter(|p| *p != a && *p != b && *p != c)
+                    .all(|p| !in_triangle(p, a, b, c, orientation))
+            };
+            // collinear corners are clipped as empty triangles when no proper ear is left
+            let ear = (0..m).find(|i| is_ear(*i))
+                .or_else(|| (0..m).find(|i| {
+                    let (a, b, c) = corner(*i);
+                    cross(vertices[a], vertices[b], vertices[c]) == 0
+                }))
+                .unwrap_or(0);
+            let (a, b, c) = corner(ear);
+            if cross(vertices[a], vertices[b], vertices[c]) * orientation > 0 {
+                triangles.push([a, b, c]);
+            }
+            let _ = remaining.remove(ear);
+        }
+        triangles
+    }
+
+    /// Splits a simple polygon into convex pieces that together cover exactly the same area.
+    ///
+    /// Uses the Hertel–Mehlhorn approach: triangulate, then drop every diagonal whose removal
+    /// keeps the merged piece convex. The result is at most four times the optimal piece count.
+    /// Every piece passes [`is_convex`](Self::is_convex) and keeps this polygon's translate
+    /// offset.
+    pub fn decompose_convex(&self) -> Vec<PolygonOwned> {
+        let mut pieces: Vec<Vec<usize>> = self.triangulate().iter().map(|t| t.to_vec()).collect();
+        let positions = |piece: &[usize]| piece.iter().map(|i| self.vertices()[*i]).collect::<Vec<Point>>();
+        let mut merged_any = true;
+        while merged_any {
+            merged_any = false;
+            'search: for i in 0..pieces.len() {
+                for j in (i + 1)..pieces.len() {
+                    if let Some(merged) = merge_along_shared_edge(&pieces[i], &pieces[j]) {
+                        if Polygon::new(&positions(&merged)).is_convex() {
+                            pieces[i] = merged;
+                            let _ = pieces.swap_remove(j);
+                            merged_any = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        pieces.iter().map(|piece| PolygonOwned::with_translate(positions(piece), self.translate())).collect()
+    }
+}
+
+/// Joins two pieces with the same winding that share the edge `a -> b` in `first` and `b -> a`
+/// in `second`, returning the ring without that diagonal.
+fn merge_along_shared_edge(first: &[usize], second: &[usize]) -> Option<Vec<usize>> {
+    let (n, m) = (first.len(), second.len());
+    for i in 0..n {
+        let (a, b) = (first[i], first[(i + 1) % n]);
+        if let Some(j) = (0..m).find(|j| second[*j] == b && second[(j + 1) % m] == a) {
+            // walk first from b round to a, then second from a round to just before b
+            let mut merged: Vec<usize> = (1..=n).map(|k| first[(i + k) % n]).collect();
+            merged.extend((2..m).map(|k| second[(j + k) % m]));
+            return Some(merged);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::Polygon;
+    use crate::polygon::geometry::twice_signed_area;
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn fixtures() -> Vec<Vec<Point>> {
+        vec![
+            ring(&[[0, 0], [30, 0], [30, 30], [20, 30], [20, 10], [10, 10], [10, 30], [0, 30]]),
+            ring(&[[0, 0], [8, 12], [16, 0], [24, 12], [32, 0], [32, 20], [0, 20]]),
+            ring(&[[20, 0], [25, 15], [40, 15], [28, 24], [33, 40], [20, 30], [7, 40], [12, 24], [0, 15], [15, 15]]),
+            ring(&[[0, 0], [10, 0], [20, 0], [20, 10], [15, 4], [10, 10], [0, 10]]),
+            ring(&[[3, 3], [40, 8], [22, 14], [35, 30], [5, 25]]),
+        ]
+    }
+
+    #[test]
+    fn test_triangulate_preserves_area() {
+        for vertices in fixtures() {
+            let triangles = Polygon::new(&vertices).triangulate();
+            let area: i128 = triangles.iter().map(|t| twice_signed_area(&[vertices[t[0]], vertices[t[1]], vertices[t[2]]]).abs()).sum();
+            assert_eq!(area, twice_signed_area(&vertices).abs(), "{vertices:?}");
+            assert!(triangles.len() <= vertices.len() - 2);
+        }
+    }
+
+    #[test]
+    fn test_decompose_convex_covers_polygon_exactly() {
+        for vertices in fixtures() {
+            let mut polygon = Polygon::new(&vertices);
+            polygon.set_translate(Point::new(7, -2));
+            let pieces = polygon.decompose_convex();
+            assert!(pieces.len() < polygon.triangulate().len() || pieces.len() == 1);
+
+            let mut coverage: HashMap<Point, usize> = HashMap::new();
+            for piece in pieces.iter() {
+                assert!(piece.as_polygon().is_convex(), "{piece:?}");
+                for p in fill_pixels(&piece.as_polygon()) {
+                    *coverage.entry(p).or_default() += 1;
+                }
+            }
+            let mut union: Vec<Point> = coverage.keys().copied().collect();
+            union.sort_by_key(|p| (p.y, p.x));
+            assert_eq!(union, fill_pixels(&polygon), "{vertices:?}");
+
+            // pixels drawn more than once may only lie on edges shared between pieces
+            for (p, count) in coverage.iter().filter(|(_, count)| **count > 1) {
+                let on_boundary = pieces.iter().filter(|piece| piece.as_polygon().closest_boundary_point(*p).1 == 0).count();
+                assert_eq!(on_boundary, *count, "{p} is covered by a piece's interior twice");
+            }
+        }
+    }
+}
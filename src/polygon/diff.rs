@@ -0,0 +1,190 @@
+//! Redrawing only the pixels that change when one polygon replaces another.
+
+use alloc::vec::Vec;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::Rectangle;
+use super::Polygon;
+use super::scanline::{Scanlines, Span};
+
+/// The spans of one fill that another does not cover, walking both a row at a time.
+pub(super) struct SpanDifference {
+    kept: Scanlines,
+    removed: Scanlines,
+    /// The last row of `removed` and its spans, once it has been reached.
+    removed_row: Option<i32>,
+    removed_spans: Vec<(i32, i32)>,
+    kept_spans: Vec<(i32, i32)>,
+    /// Spans of the current row still to be returned, the leftmost last.
+    ready: Vec<Span>,
+}
+
+impl SpanDifference {
+    pub(super) fn new(kept: &Polygon, removed: &Polygon) -> Self {
+        SpanDifference {
+            kept: Scanlines::new(kept.edges()),
+            removed: Scanlines::new(removed.edges()),
+            removed_row: None,
+            removed_spans: Vec::new(),
+            kept_spans: Vec::new(),
+            ready: Vec::new(),
+        }
+    }
+}
+
+impl Iterator for SpanDifference {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Span> {
+        while self.ready.is_empty() {
+            let y = self.kept.next_row(&mut self.kept_spans)?;
+            while self.removed_row.is_none_or(|row| row < y) {
+                match self.removed.next_row(&mut self.removed_spans) {
+                    Some(row) => self.removed_row = Some(row),
+                    None => {
+                        self.removed_row = Some(i32::MAX);
+                        self.removed_spans.clear();
+                    }
+                }
+            }
+            let cuts: &[(i32, i32)] = if self.removed_row == Some(y) { &self.removed_spans } else { &[] };
+            // both rows are sorted and their spans never touch, so one pass over the cuts does
+            let mut cuts = cuts.iter().peekable();
+            for &(start, end) in self.kept_spans.iter() {
+                let mut from = start as i64;
+                while let Some(&&(cut_start, cut_end)) = cuts.peek() {
+                    if cut_start as i64 > end as i64 {
+                        break;
+                    }
+                    if cut_start as i64 > from {
+                        self.ready.push(Span { y, x: from as i32, len: (cut_start as i64 - from) as u32 });
+                    }
+                    from = from.max(cut_end as i64 + 1);
+                    if cut_end > end {
+                        break;
+                    }
+                    let _ = cuts.next();
+                }
+                if from <= end as i64 {
+                    self.ready.push(Span { y, x: from as i32, len: (end as i64 - from + 1) as u32 });
+                }
+            }
+            self.ready.reverse();
+        }
+        self.ready.pop()
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the spans to clear, covered by this polygon's fill but not by `new`'s, and the
+    /// spans to set, covered by `new`'s fill but not this one's, both top to bottom and left to
+    /// right, with the translate offsets applied.
+    ///
+    /// Clearing and setting them over a fill of this polygon leaves exactly a fill of `new`,
+    /// touching only the pixels that differ, as wanted on e-paper, where every write shows.
+    /// The two fills are walked a row at a time in step, so nothing is collected up front.
+    pub fn diff(&self, new: &Polygon) -> (impl Iterator<Item = Span>, impl Iterator<Item = Span>) {
+        (SpanDifference::new(self, new), SpanDifference::new(new, self))
+    }
+}
+
+/// Turns a fill of `old` in `fill_color` into a fill of `new`, painting the pixels only `old`
+/// covers in `clear_color` and the ones only `new` covers in `fill_color`, clipped to the target.
+///
+/// See [`Polygon::diff`].
+pub fn draw_diff<C, D>(old: &Polygon, new: &Polygon, clear_color: C, fill_color: C, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+    let (clear, set) = old.diff(new);
+    fill_spans(clear, clear_color, target)?;
+    fill_spans(set, fill_color, target)
+}
+
+/// Fills each span in `color`, clipped to the target.
+pub(super) fn fill_spans<C, D>(spans: impl Iterator<Item = Span>, color: C, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+    let bounds = target.bounding_box();
+    let Some(bottom_right) = bounds.bottom_right() else {
+        return Ok(());
+    };
+    for span in spans {
+        if span.y < bounds.top_left.y || span.y > bottom_right.y {
+            continue;
+        }
+        let end = span.x as i64 + span.len as i64 - 1;
+        let (start, end) = (span.x.max(bounds.top_left.x), end.min(bottom_right.x as i64) as i32);
+        if start <= end {
+            target.fill_solid(&Rectangle::new(Point::new(start, span.y), Size::new(end.abs_diff(start) + 1, 1)), color)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{draw_diff, Polygon, Span};
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn render(polygon: &Polygon) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display.clear(BinaryColor::Off).unwrap();
+        polygon.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+        display
+    }
+
+    fn pixels(spans: impl Iterator<Item = Span>) -> Vec<Point> {
+        spans.flat_map(|span| (0..span.len as i32).map(move |i| Point::new(span.x + i, span.y))).collect()
+    }
+
+    #[test]
+    fn test_diff_over_the_old_render_matches_the_new() {
+        let star = ring(&[[15, -3], [19, 9], [33, 9], [22, 17], [26, 33], [15, 23], [4, 33], [8, 17], [-3, 9], [11, 9]]);
+        let mut rng = StdRng::seed_from_u64(388);
+        for step in 0..60 {
+            let mut old = Polygon::new(&star);
+            old.set_translate(Point::new(rng.gen_range(-20..50), rng.gen_range(-20..50)));
+            let mut new = old;
+            new.set_translate(new.translate() + Point::new(rng.gen_range(-3..=3), rng.gen_range(-3..=3)));
+            let other = ring(&[[10, 10], [40, 5], [30, 40]]);
+            let new = if step % 3 == 0 { Polygon::new(&other) } else { new };
+
+            let mut display = render(&old);
+            draw_diff(&old, &new, BinaryColor::Off, BinaryColor::On, &mut display).unwrap();
+            display.assert_eq(&render(&new));
+
+            // each changed pixel is touched exactly once, and no other
+            let (clear, set) = old.diff(&new);
+            let (clear, set) = (pixels(clear), pixels(set));
+            let (before, after): (BTreeSet<_>, BTreeSet<_>) = (fill_pixels(&old).into_iter().map(|p| (p.y, p.x)).collect(), fill_pixels(&new).into_iter().map(|p| (p.y, p.x)).collect());
+            assert_eq!(clear.iter().map(|p| (p.y, p.x)).collect::<Vec<_>>(), before.difference(&after).copied().collect::<Vec<_>>());
+            assert_eq!(set.iter().map(|p| (p.y, p.x)).collect::<Vec<_>>(), after.difference(&before).copied().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_diff_of_disjoint_and_equal_polygons() {
+        let square = ring(&[[0, 0], [5, 0], [5, 5], [0, 5]]);
+        let polygon = Polygon::new(&square);
+        let (clear, set) = polygon.diff(&polygon);
+        assert_eq!((clear.count(), set.count()), (0, 0));
+
+        let mut far = polygon;
+        far.set_translate(Point::new(20, 3));
+        let (clear, set) = polygon.diff(&far);
+        assert_eq!((pixels(clear), pixels(set)), (fill_pixels(&polygon), fill_pixels(&far)));
+
+        // a notch cut up into the middle of the bottom rows splits their spans
+        let notched = ring(&[[0, 0], [5, 0], [5, 5], [4, 5], [4, 2], [1, 2], [1, 5], [0, 5]]);
+        let (clear, set) = polygon.diff(&Polygon::new(&notched));
+        assert_eq!(clear.collect::<Vec<_>>(), (3..=5).map(|y| Span { y, x: 2, len: 2 }).collect::<Vec<_>>());
+        assert_eq!(set.count(), 0);
+    }
+}
@@ -0,0 +1,303 @@
+//! Distance queries against the polygon outline.
+
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::{ContainsPoint, Line};
+use super::Polygon;
+use super::math;
+use super::geometry::cross;
+
+/// Closest point to `p` on the segment from `a` to `b`, rounded to the nearest pixel.
+pub(crate) fn closest_on_segment(p: Point, a: Point, b: Point) -> Point {
+    let (dx, dy) = (b.x as i64 - a.x as i64, b.y as i64 - a.y as i64);
+    let length_squared = dx * dx + dy * dy;
+    let along = (p.x as i64 - a.x as i64) * dx + (p.y as i64 - a.y as i64) * dy;
+    if length_squared == 0 || along <= 0 {
+        a
+    } else if along >= length_squared {
+        b
+    } else {
+        let offset = |d: i64| (2 * along as i128 * d as i128 + length_squared as i128).div_euclid(2 * length_squared as i128) as i64;
+        Point::new((a.x as i64 + offset(dx)) as i32, (a.y as i64 + offset(dy)) as i32)
+    }
+}
+
+/// Squared distance between two points, saturating at `u32::MAX`.
+pub(crate) fn distance_squared(a: Point, b: Point) -> u32 {
+    let (dx, dy) = (a.x as i64 - b.x as i64, a.y as i64 - b.y as i64);
+    u32::try_from(dx * dx + dy * dy).unwrap_or(u32::MAX)
+}
+
+/// Exact squared distance from `p` to the segment from `a` to `b`.
+fn segment_distance_squared(p: Point, Line { start: a, end: b }: Line) -> f64 {
+    let (dx, dy) = (b.x as f64 - a.x as f64, b.y as f64 - a.y as f64);
+    let (px, py) = (p.x as f64 - a.x as f64, p.y as f64 - a.y as f64);
+    let length_squared = dx * dx + dy * dy;
+    let along = px * dx + py * dy;
+    if length_squared == 0.0 || along <= 0.0 {
+        px * px + py * py
+    } else if along >= length_squared {
+        let (qx, qy) = (p.x as f64 - b.x as f64, p.y as f64 - b.y as f64);
+        qx * qx + qy * qy
+    } else {
+        let perpendicular = px * dy - py * dx;
+        perpendicular * perpendicular / length_squared
+    }
+}
+
+/// Returns `true` if the two segments share at least one point, including touching at an end or
+/// overlapping along a common line.
+pub(crate) fn segments_touch(first: Line, second: Line) -> bool {
+    let within = |p: Point, Line { start, end }: Line| {
+        p.x >= start.x.min(end.x) && p.x <= start.x.max(end.x) && p.y >= start.y.min(end.y) && p.y <= start.y.max(end.y)
+    };
+    let (d1, d2) = (cross(first.start, first.end, second.start), cross(first.start, first.end, second.end));
+    let (d3, d4) = (cross(second.start, second.end, first.start), cross(second.start, second.end, first.end));
+    if d1.signum() * d2.signum() < 0 && d3.signum() * d4.signum() < 0 {
+        return true;
+    }
+    (d1 == 0 && within(second.start, first)) || (d2 == 0 && within(second.end, first))
+        || (d3 == 0 && within(first.start, second)) || (d4 == 0 && within(first.end, second))
+}
+
+/// Index and value of the smallest distance, the first one on ties.
+fn nearest(distances: impl Iterator<Item = u32>) -> Option<(usize, u32)> {
+    distances.enumerate().fold(None, |best, (i, distance)| match best {
+        Some((_, best_distance)) if best_distance <= distance => best,
+        _ => Some((i, distance)),
+    })
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the point on the polygon outline closest to `p` and its squared distance from `p`.
+    ///
+    /// `p` may lie inside or outside the polygon. When the perpendicular projection onto an edge
+    /// falls past its end the nearest vertex is returned, and ties between edges go to the lower
+    /// edge index. An empty polygon has no outline and returns `(p, u32::MAX)`.
+    pub fn closest_boundary_point(&self, p: Point) -> (Point, u32) {
+        let (_, closest, distance) = self.nearest_edge(p);
+        (closest, distance)
+    }
+
+    /// Returns the index of the vertex closest to `p`, with the translate offset applied, and its
+    /// squared distance from `p`.
+    ///
+    /// Ties go to the lower index. An empty polygon returns `(0, u32::MAX)`.
+    pub fn nearest_vertex(&self, p: Point) -> (usize, u32) {
+        let query = p - self.translate();
+        nearest(self.vertices().iter().map(|v| distance_squared(query, *v))).unwrap_or((0, u32::MAX))
+    }
+
+    /// Returns the index of the edge closest to `p`, the closest point on it rounded to the nearest
+    /// pixel, and that point's squared distance from `p`, with the translate offset applied.
+    ///
+    /// Edge `i` runs from vertex `i` to the next, the last closing the ring back to the first.
+    /// Ties go to the lower index. An empty polygon returns `(0, p, u32::MAX)`.
+    pub fn nearest_edge(&self, p: Point) -> (usize, Point, u32) {
+        let query = p - self.translate();
+        let closest = |i: usize| closest_on_segment(query, self.vertices()[i], self.vertices()[(i + 1) % self.vertices().len()]);
+        match nearest((0..self.vertices().len()).map(|i| distance_squared(query, closest(i)))) {
+            Some((i, distance)) => (i, closest(i) + self.translate(), distance),
+            None => (0, p, u32::MAX),
+        }
+    }
+
+    /// Returns `true` if `p` is inside the polygon or within `tolerance` pixels of its outline,
+    /// for picking shapes with an imprecise pointer such as a finger.
+    ///
+    /// The distance is measured to the nearest outline pixel as found by
+    /// [`closest_boundary_point`](Self::closest_boundary_point). A tolerance of 0 is exactly
+    /// [`ContainsPoint::contains`].
+    pub fn hit_test(&self, p: Point, tolerance: u32) -> bool {
+        if self.contains(p) {
+            return true;
+        }
+        tolerance > 0 && !self.vertices().is_empty() && self.closest_boundary_point(p).1 as u64 <= tolerance as u64 * tolerance as u64
+    }
+
+    /// Returns the distance between the filled areas of the two polygons, rounded to the nearest
+    /// pixel, or 0 if they overlap, touch or one lies inside the other.
+    ///
+    /// Every pair of edges is compared, so concave polygons are handled. Returns `u32::MAX` if
+    /// either polygon is empty.
+    pub fn distance_to(&self, other: &Polygon) -> u32 {
+        let (Some(first), Some(other_first)) = (self.vertices().first(), other.vertices().first()) else {
+            return u32::MAX;
+        };
+        if self.contains(*other_first + other.translate()) || other.contains(*first + self.translate()) {
+            return 0;
+        }
+        let mut closest = f64::INFINITY;
+        for edge in self.edges() {
+            for other_edge in other.edges() {
+                if segments_touch(edge, other_edge) {
+                    return 0;
+                }
+                closest = closest
+                    .min(segment_distance_squared(edge.start, other_edge))
+                    .min(segment_distance_squared(edge.end, other_edge))
+                    .min(segment_distance_squared(other_edge.start, edge))
+                    .min(segment_distance_squared(other_edge.end, edge));
+            }
+        }
+        math::round(math::sqrt(closest)).min(u32::MAX as f64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::primitives::ContainsPoint;
+    use crate::polygon::Polygon;
+
+    fn square() -> [Point; 4] {
+        [Point::new(0, 0), Point::new(20, 0), Point::new(20, 20), Point::new(0, 20)]
+    }
+
+    #[test]
+    fn test_closest_boundary_point_from_inside() {
+        let vertices = square();
+        let polygon = Polygon::new(&vertices);
+        assert_eq!(polygon.closest_boundary_point(Point::new(5, 8)), (Point::new(0, 8), 25));
+        assert_eq!(polygon.closest_boundary_point(Point::new(14, 17)), (Point::new(14, 20), 9));
+    }
+
+    #[test]
+    fn test_closest_boundary_point_from_outside() {
+        let vertices = square();
+        let polygon = Polygon::new(&vertices);
+        // projection past the end of both adjacent edges snaps to the shared vertex
+        assert_eq!(polygon.closest_boundary_point(Point::new(25, -3)), (Point::new(20, 0), 34));
+        assert_eq!(polygon.closest_boundary_point(Point::new(-6, 11)), (Point::new(0, 11), 36));
+
+        let triangle = [Point::new(0, 0), Point::new(10, 10), Point::new(0, 10)];
+        let polygon = Polygon::new(&triangle);
+        assert_eq!(polygon.closest_boundary_point(Point::new(8, 2)), (Point::new(5, 5), 18));
+    }
+
+    #[test]
+    fn test_closest_boundary_point_on_edge() {
+        let vertices = square();
+        let mut polygon = Polygon::new(&vertices);
+        assert_eq!(polygon.closest_boundary_point(Point::new(20, 7)), (Point::new(20, 7), 0));
+        assert_eq!(polygon.closest_boundary_point(Point::new(0, 0)), (Point::new(0, 0), 0));
+
+        polygon.set_translate(Point::new(100, 50));
+        assert_eq!(polygon.closest_boundary_point(Point::new(120, 57)), (Point::new(120, 57), 0));
+        assert_eq!(polygon.closest_boundary_point(Point::new(95, 50)), (Point::new(100, 50), 25));
+    }
+
+    #[test]
+    fn test_hit_test_thin_sliver() {
+        let sliver = [Point::new(0, 0), Point::new(40, 0), Point::new(40, 2), Point::new(0, 2)];
+        let mut polygon = Polygon::new(&sliver);
+        polygon.set_translate(Point::new(10, 10));
+        let touch = Point::new(30, 17);
+        assert!(polygon.hit_test(touch, 6));
+        assert!(polygon.hit_test(touch, 5));
+        assert!(!polygon.hit_test(touch, 4));
+        // past the end the distance is to the corner
+        assert!(polygon.hit_test(Point::new(53, 16), 5));
+        assert!(!polygon.hit_test(Point::new(53, 16), 4));
+        assert!(polygon.hit_test(Point::new(25, 11), 0));
+    }
+
+    #[test]
+    fn test_hit_test_without_tolerance_is_containment() {
+        let triangle = [Point::new(0, 0), Point::new(17, 5), Point::new(4, 13)];
+        let polygon = Polygon::new(&triangle);
+        for y in -3..17 {
+            for x in -3..21 {
+                let p = Point::new(x, y);
+                assert_eq!(polygon.hit_test(p, 0), polygon.contains(p), "{p:?}");
+                assert!(!polygon.contains(p) || polygon.hit_test(p, 1));
+            }
+        }
+        assert!(!Polygon::new(&[]).hit_test(Point::zero(), u32::MAX));
+    }
+
+    #[test]
+    fn test_distance_between_separated_polygons() {
+        let first = square();
+        let second = [Point::new(0, 0), Point::new(8, 0), Point::new(8, 8), Point::new(0, 8)];
+        let a = Polygon::new(&first);
+        let mut b = Polygon::new(&second);
+        b.set_translate(Point::new(30, 5));
+        assert_eq!(a.distance_to(&b), 10);
+        assert_eq!(b.distance_to(&a), 10);
+        // corner to corner, 3-4-5
+        b.set_translate(Point::new(23, 24));
+        assert_eq!(a.distance_to(&b), 5);
+        // corner to the middle of a slanted edge
+        let triangle = [Point::new(0, 30), Point::new(30, 0), Point::new(30, 30)];
+        let c = Polygon::new(&triangle);
+        assert_eq!(Polygon::new(&[Point::new(0, 0), Point::new(10, 0), Point::new(0, 10)]).distance_to(&c), 14);
+        assert_eq!(a.distance_to(&Polygon::new(&[])), u32::MAX);
+    }
+
+    #[test]
+    fn test_distance_between_touching_and_overlapping_polygons() {
+        let first = square();
+        let a = Polygon::new(&first);
+        let mut b = Polygon::new(&first);
+        for (translate, what) in [(Point::new(20, 0), "shared edge"), (Point::new(20, 20), "shared corner"), (Point::new(10, 12), "overlap")] {
+            b.set_translate(translate);
+            assert_eq!(a.distance_to(&b), 0, "{what}");
+        }
+        b.set_translate(Point::new(21, 0));
+        assert_eq!(a.distance_to(&b), 1);
+    }
+
+    #[test]
+    fn test_distance_between_nested_polygons() {
+        let outer = square();
+        let inner = [Point::new(0, 0), Point::new(4, 0), Point::new(2, 3)];
+        let a = Polygon::new(&outer);
+        let mut b = Polygon::new(&inner);
+        b.set_translate(Point::new(8, 8));
+        assert_eq!(a.distance_to(&b), 0);
+        assert_eq!(b.distance_to(&a), 0);
+
+        // inside the mouth of a C is outside it
+        let c_shape = [
+            Point::new(0, 0), Point::new(30, 0), Point::new(30, 10), Point::new(10, 10),
+            Point::new(10, 20), Point::new(30, 20), Point::new(30, 30), Point::new(0, 30),
+        ];
+        let c = Polygon::new(&c_shape);
+        b.set_translate(Point::new(16, 13));
+        assert_eq!(c.distance_to(&b), 3);
+    }
+
+    #[test]
+    fn test_nearest_vertex() {
+        let vertices = square();
+        let mut polygon = Polygon::new(&vertices);
+        assert_eq!(polygon.nearest_vertex(Point::new(17, 2)), (1, 13));
+        // equidistant from the two vertices of the top edge
+        assert_eq!(polygon.nearest_vertex(Point::new(10, -4)), (0, 116));
+        assert_eq!(polygon.nearest_vertex(Point::new(10, 10)), (0, 200));
+        polygon.set_translate(Point::new(-50, 30));
+        assert_eq!(polygon.nearest_vertex(Point::new(-31, 49)), (2, 2));
+        assert_eq!(Polygon::new(&[]).nearest_vertex(Point::zero()), (0, u32::MAX));
+    }
+
+    #[test]
+    fn test_nearest_edge() {
+        let vertices = square();
+        let mut polygon = Polygon::new(&vertices);
+        // closer to the middle of the right edge than to any vertex
+        assert_eq!(polygon.nearest_edge(Point::new(26, 9)), (1, Point::new(20, 9), 36));
+        // on the shared corner both edges tie and the lower index wins
+        assert_eq!(polygon.nearest_edge(Point::new(23, 24)), (1, Point::new(20, 20), 25));
+        assert_eq!(polygon.nearest_edge(Point::new(-2, -2)), (0, Point::new(0, 0), 8));
+        // the closing edge from the last vertex back to the first
+        assert_eq!(polygon.nearest_edge(Point::new(3, 12)), (3, Point::new(0, 12), 9));
+        polygon.set_translate(Point::new(5, 5));
+        assert_eq!(polygon.nearest_edge(Point::new(31, 14)), (1, Point::new(25, 14), 36));
+        assert_eq!(Polygon::new(&[]).nearest_edge(Point::new(1, 2)), (0, Point::new(1, 2), u32::MAX));
+    }
+
+    #[test]
+    fn test_closest_boundary_point_of_empty_polygon() {
+        assert_eq!(Polygon::new(&[]).closest_boundary_point(Point::new(3, 4)), (Point::new(3, 4), u32::MAX));
+    }
+}
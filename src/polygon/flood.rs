@@ -0,0 +1,231 @@
+//! Filling a region bounded by pixels already on a target.
+
+use core::fmt;
+use alloc::vec;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::Rectangle;
+
+/// A target whose pixels can be read back, such as a framebuffer, for [`flood_fill`] to find
+/// where a region ends.
+///
+/// embedded-graphics 0.7 has no trait for this, so this one has the shape of the `GetPixel` that
+/// later versions add.
+pub trait GetPixel {
+    type Color: PixelColor;
+
+    /// Returns the pixel at `p`, or `None` outside the target.
+    fn pixel(&self, p: Point) -> Option<Self::Color>;
+}
+
+/// Which pixels a flood fill spreads over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloodMode<C> {
+    /// Every pixel up to one of the given boundary color. Pixels already of the fill color stop
+    /// the fill too, as they cannot be told apart from the ones it wrote.
+    Boundary(C),
+    /// Every pixel of the color found at the seed.
+    Replace,
+}
+
+/// A flood fill stopped before covering its whole region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloodError<E> {
+    /// More runs were waiting to be filled than the stack allowed. The pixels written until then
+    /// are left on the target.
+    StackFull,
+    /// The target failed to draw.
+    Target(E),
+}
+
+impl<E: fmt::Display> fmt::Display for FloodError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloodError::StackFull => write!(f, "flood fill ran out of stack"),
+            FloodError::Target(error) => write!(f, "draw target failed: {error}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for FloodError<E> {}
+
+/// Fills the 4-connected region around `seed` with `color`, reading back what is on the target to
+/// find where the region ends, such as the inside of an outline drawn earlier.
+///
+/// The fill runs a row at a time, writing each run of pixels with a single `fill_solid`, and
+/// keeps the runs still to visit on a stack that grows as needed rather than recursing. It stops
+/// at the edge of the target, where `pixel` returns `None`. Nothing is drawn if `seed` is outside
+/// the target or not part of the region, which includes a seed already of the fill color.
+pub fn flood_fill<C, D>(target: &mut D, seed: Point, mode: FloodMode<C>, color: C) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> + GetPixel<Color = C> {
+    flood_fill_bounded(target, seed, mode, color, usize::MAX).map_err(|error| match error {
+        FloodError::Target(error) => error,
+        FloodError::StackFull => unreachable!("an unbounded stack cannot fill up"),
+    })
+}
+
+/// Like [`flood_fill`], with at most `max_pending` runs waiting on the stack at any time, which
+/// bounds the memory used to `max_pending` points.
+///
+/// Fails with [`FloodError::StackFull`] instead of growing the stack past that, leaving the fill
+/// incomplete. Outlines with many separate pockets along a row need the most.
+pub fn flood_fill_bounded<C, D>(target: &mut D, seed: Point, mode: FloodMode<C>, color: C, max_pending: usize) -> Result<(), FloodError<D::Error>> where C: PixelColor, D: DrawTarget<Color = C> + GetPixel<Color = C> {
+    let Some(found) = target.pixel(seed) else {
+        return Ok(());
+    };
+    let fillable = |pixel: Option<C>| match (mode, pixel) {
+        (_, None) => false,
+        (FloodMode::Boundary(boundary), Some(pixel)) => pixel != boundary && pixel != color,
+        (FloodMode::Replace, Some(pixel)) => pixel == found && pixel != color,
+    };
+    if !fillable(Some(found)) {
+        return Ok(());
+    }
+
+    let mut pending = vec![seed];
+    while let Some(start) = pending.pop() {
+        if !fillable(target.pixel(start)) {
+            // filled through another run since it was pushed
+            continue;
+        }
+        let mut left = start.x;
+        while fillable(target.pixel(Point::new(left - 1, start.y))) {
+            left -= 1;
+        }
+        let mut right = start.x;
+        while fillable(target.pixel(Point::new(right + 1, start.y))) {
+            right += 1;
+        }
+        let run = Rectangle::new(Point::new(left, start.y), Size::new(right.abs_diff(left) + 1, 1));
+        target.fill_solid(&run, color).map_err(FloodError::Target)?;
+
+        // one seed for each run of fillable pixels touching this one above and below
+        for y in [start.y - 1, start.y + 1] {
+            let mut in_run = false;
+            for x in left..=right {
+                let open = fillable(target.pixel(Point::new(x, y)));
+                if open && !in_run {
+                    if pending.len() >= max_pending {
+                        return Err(FloodError::StackFull);
+                    }
+                    pending.push(Point::new(x, y));
+                }
+                in_run = open;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+    use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+    use embedded_graphics::Pixel;
+    use crate::polygon::{flood_fill, flood_fill_bounded, FloodError, FloodMode, GetPixel, Polygon};
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    /// A framebuffer that fails the test on any pixel outside it.
+    struct Framebuffer {
+        size: Size,
+        pixels: Vec<Rgb888>,
+        draws: usize,
+    }
+
+    impl Framebuffer {
+        fn new(width: u32, height: u32) -> Self {
+            Framebuffer { size: Size::new(width, height), pixels: vec![Rgb888::BLACK; (width * height) as usize], draws: 0 }
+        }
+
+        fn points_of(&self, color: Rgb888) -> Vec<Point> {
+            let width = self.size.width as usize;
+            self.pixels.iter().enumerate().filter(|(_, c)| **c == color).map(|(i, _)| Point::new((i % width) as i32, (i / width) as i32)).collect()
+        }
+    }
+
+    impl OriginDimensions for Framebuffer {
+        fn size(&self) -> Size {
+            self.size
+        }
+    }
+
+    impl DrawTarget for Framebuffer {
+        type Color = Rgb888;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+            self.draws += 1;
+            for Pixel(p, color) in pixels {
+                assert!(p.x >= 0 && p.y >= 0 && p.x < self.size.width as i32 && p.y < self.size.height as i32, "{p:?} outside the framebuffer");
+                self.pixels[(p.y * self.size.width as i32 + p.x) as usize] = color;
+            }
+            Ok(())
+        }
+    }
+
+    impl GetPixel for Framebuffer {
+        type Color = Rgb888;
+
+        fn pixel(&self, p: Point) -> Option<Self::Color> {
+            let inside = p.x >= 0 && p.y >= 0 && p.x < self.size.width as i32 && p.y < self.size.height as i32;
+            inside.then(|| self.pixels[(p.y * self.size.width as i32 + p.x) as usize])
+        }
+    }
+
+    #[test]
+    fn test_flood_inside_a_stroked_outline_matches_the_fill() {
+        let vertices = ring(&[[4, 4], [40, 8], [52, 40], [30, 22], [26, 50], [6, 36]]);
+        let polygon = Polygon::new(&vertices);
+        for mode in [FloodMode::Boundary(Rgb888::RED), FloodMode::Replace] {
+            let mut framebuffer = Framebuffer::new(60, 60);
+            polygon.into_styled(PrimitiveStyle::with_stroke(Rgb888::RED, 1)).draw(&mut framebuffer).unwrap();
+            let outline = framebuffer.points_of(Rgb888::RED);
+            flood_fill(&mut framebuffer, Point::new(20, 20), mode, Rgb888::GREEN).unwrap();
+
+            let mut covered = framebuffer.points_of(Rgb888::GREEN);
+            covered.extend(outline.iter().copied());
+            covered.sort_by_key(|p| (p.y, p.x));
+            let mut expected = fill_pixels(&polygon);
+            expected.extend(outline.iter().copied());
+            expected.sort_by_key(|p| (p.y, p.x));
+            expected.dedup();
+            assert_eq!(covered, expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn test_flood_stops_at_the_target_and_on_filled_seeds() {
+        // an empty target fills entirely, without drawing outside it
+        let mut framebuffer = Framebuffer::new(17, 9);
+        flood_fill(&mut framebuffer, Point::new(3, 3), FloodMode::Replace, Rgb888::BLUE).unwrap();
+        assert_eq!(framebuffer.points_of(Rgb888::BLUE).len(), 17 * 9);
+        assert_eq!(framebuffer.draws, 9);
+
+        // seeds already of the fill color, on the boundary or off the target draw nothing
+        let draws = framebuffer.draws;
+        flood_fill(&mut framebuffer, Point::new(3, 3), FloodMode::Replace, Rgb888::BLUE).unwrap();
+        flood_fill(&mut framebuffer, Point::new(0, 0), FloodMode::Boundary(Rgb888::RED), Rgb888::BLUE).unwrap();
+        flood_fill(&mut framebuffer, Point::new(4, 4), FloodMode::Boundary(Rgb888::BLUE), Rgb888::RED).unwrap();
+        flood_fill(&mut framebuffer, Point::new(-1, 4), FloodMode::Replace, Rgb888::RED).unwrap();
+        assert_eq!(framebuffer.draws, draws);
+    }
+
+    #[test]
+    fn test_bounded_stack_reports_running_out() {
+        // a comb, whose teeth each leave a run waiting below the spine
+        let mut framebuffer = Framebuffer::new(21, 10);
+        for x in (1..21).step_by(2) {
+            framebuffer.fill_solid(&Rectangle::new(Point::new(x, 1), Size::new(1, 9)), Rgb888::RED).unwrap();
+        }
+        let mut copy = Framebuffer { size: framebuffer.size, pixels: framebuffer.pixels.clone(), draws: 0 };
+        let result = flood_fill_bounded(&mut framebuffer, Point::zero(), FloodMode::Boundary(Rgb888::RED), Rgb888::GREEN, 4);
+        assert_eq!(result, Err(FloodError::StackFull));
+
+        flood_fill_bounded(&mut copy, Point::zero(), FloodMode::Boundary(Rgb888::RED), Rgb888::GREEN, 11).unwrap();
+        assert_eq!(copy.points_of(Rgb888::GREEN).len(), 21 + 11 * 9);
+    }
+}
@@ -0,0 +1,130 @@
+//! Conversions to and from `geo-types` polygons.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use geo_types::{Coord, LineString};
+use super::{CoordinateMapping, CoordinateOutOfRange, Polygon, PolygonWithHoles};
+
+/// Maps a `geo` ring to pixels, dropping the repeated closing vertex since rings here are
+/// closed implicitly.
+fn ring_to_pixels(ring: &LineString<f64>, mapping: &CoordinateMapping) -> Result<Vec<Point>, CoordinateOutOfRange> {
+    let mut coords = ring.0.as_slice();
+    if let [first, .., last] = coords {
+        if first == last {
+            coords = &coords[..coords.len() - 1];
+        }
+    }
+    coords.iter().map(|c| mapping.to_pixel(c.x, c.y)).collect()
+}
+
+fn ring_from_pixels(ring: &Polygon, mapping: &CoordinateMapping) -> LineString<f64> {
+    // `geo_types::Polygon::new` closes the ring again
+    ring.vertices().iter().map(|v| {
+        let (x, y) = mapping.from_pixel(*v + ring.translate());
+        Coord { x, y }
+    }).collect()
+}
+
+impl PolygonWithHoles {
+    /// Converts a `geo` polygon to pixels, mapping its exterior and interior rings through
+    /// `mapping`.
+    pub fn from_geo(polygon: &geo_types::Polygon<f64>, mapping: &CoordinateMapping) -> Result<Self, CoordinateOutOfRange> {
+        Ok(PolygonWithHoles::new(
+            ring_to_pixels(polygon.exterior(), mapping)?,
+            polygon.interiors().iter().map(|ring| ring_to_pixels(ring, mapping)).collect::<Result<_, _>>()?,
+        ))
+    }
+
+    /// Converts to a `geo` polygon, mapping every pixel, translate offset included, back
+    /// through `mapping`.
+    pub fn to_geo(&self, mapping: &CoordinateMapping) -> geo_types::Polygon<f64> {
+        geo_types::Polygon::new(ring_from_pixels(&self.exterior(), mapping), self.holes().map(|hole| ring_from_pixels(&hole, mapping)).collect())
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Converts to a `geo` polygon without interior rings, mapping every pixel, translate offset
+    /// included, back through `mapping`.
+    pub fn to_geo(&self, mapping: &CoordinateMapping) -> geo_types::Polygon<f64> {
+        geo_types::Polygon::new(ring_from_pixels(self, mapping), Vec::new())
+    }
+}
+
+impl TryFrom<&geo_types::Polygon<f64>> for PolygonWithHoles {
+    type Error = CoordinateOutOfRange;
+
+    /// Converts with [`CoordinateMapping::IDENTITY`].
+    fn try_from(polygon: &geo_types::Polygon<f64>) -> Result<Self, Self::Error> {
+        PolygonWithHoles::from_geo(polygon, &CoordinateMapping::IDENTITY)
+    }
+}
+
+impl From<&PolygonWithHoles> for geo_types::Polygon<f64> {
+    fn from(polygon: &PolygonWithHoles) -> Self {
+        polygon.to_geo(&CoordinateMapping::IDENTITY)
+    }
+}
+
+impl<'a> From<Polygon<'a>> for geo_types::Polygon<f64> {
+    fn from(polygon: Polygon<'a>) -> Self {
+        polygon.to_geo(&CoordinateMapping::IDENTITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use geo_types::{Coord, LineString};
+    use crate::polygon::{CoordinateMapping, CoordinateOutOfRange, Polygon, PolygonWithHoles};
+
+    fn line_string(coords: &[(f64, f64)]) -> LineString<f64> {
+        coords.iter().map(|&(x, y)| Coord { x, y }).collect()
+    }
+
+    fn points(points: &[[i32; 2]]) -> Vec<Point> {
+        points.iter().map(Point::from).collect()
+    }
+
+    #[test]
+    fn test_geo_round_trip_with_hole() {
+        let geo = geo_types::Polygon::new(
+            line_string(&[(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0), (0.0, 0.0)]),
+            vec![line_string(&[(1.0, 1.0), (2.5, 1.0), (2.5, 2.0), (1.0, 1.0)])],
+        );
+        let mapping = CoordinateMapping::new(10.0, (5.0, -5.0));
+        let polygon = PolygonWithHoles::from_geo(&geo, &mapping).unwrap();
+        assert_eq!(polygon.exterior, points(&[[5, -5], [45, -5], [45, 25], [5, 25]]));
+        assert_eq!(polygon.holes, vec![points(&[[15, 5], [30, 5], [30, 15]])]);
+        assert_eq!(polygon.to_geo(&mapping), geo);
+
+        // unclosed geo rings convert the same
+        let open = geo_types::Polygon::new(line_string(&[(0.0, 0.0), (4.0, 0.0), (4.0, 3.0)]), vec![]);
+        assert_eq!(PolygonWithHoles::from_geo(&open, &mapping).unwrap().exterior, points(&[[5, -5], [45, -5], [45, 25]]));
+    }
+
+    #[test]
+    fn test_geo_identity_conversions() {
+        let vertices = points(&[[0, 0], [7, 1], [3, 9]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(2, 2));
+        let geo: geo_types::Polygon<f64> = polygon.into();
+        assert_eq!(geo.exterior(), &line_string(&[(2.0, 2.0), (9.0, 3.0), (5.0, 11.0), (2.0, 2.0)]));
+        let back = PolygonWithHoles::try_from(&geo).unwrap();
+        assert_eq!(back.exterior, points(&[[2, 2], [9, 3], [5, 11]]));
+        assert_eq!(geo_types::Polygon::from(&back), geo);
+
+        // fractional coordinates round to the nearest pixel
+        let fractional = geo_types::Polygon::new(line_string(&[(0.4, 0.6), (5.5, -0.5), (3.0, 3.0)]), vec![]);
+        assert_eq!(PolygonWithHoles::try_from(&fractional).unwrap().exterior, points(&[[0, 1], [6, -1], [3, 3]]));
+    }
+
+    #[test]
+    fn test_geo_out_of_range_coordinates() {
+        let huge = geo_types::Polygon::new(line_string(&[(0.0, 0.0), (1e12, 0.0), (0.0, 1.0)]), vec![]);
+        assert_eq!(PolygonWithHoles::try_from(&huge), Err(CoordinateOutOfRange { x: 1e12, y: 0.0 }));
+        let scaled = geo_types::Polygon::new(line_string(&[(0.0, 0.0), (3e5, 0.0), (0.0, 1.0)]), vec![]);
+        assert!(PolygonWithHoles::from_geo(&scaled, &CoordinateMapping::new(1e5, (0.0, 0.0))).is_err());
+        let hole = geo_types::Polygon::new(line_string(&[(0.0, 0.0), (5.0, 0.0), (0.0, 5.0)]), vec![line_string(&[(1.0, 1.0), (f64::NAN, 1.0), (1.0, 2.0)])]);
+        assert!(PolygonWithHoles::try_from(&hole).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Shape properties derived from the vertex ring.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Line;
+use super::Polygon;
+use super::math;
+
+/// Z component of the cross product of `a - origin` and `b - origin`.
+pub(crate) fn cross(origin: Point, a: Point, b: Point) -> i64 {
+    (a.x as i64 - origin.x as i64) * (b.y as i64 - origin.y as i64) - (a.y as i64 - origin.y as i64) * (b.x as i64 - origin.x as i64)
+}
+
+/// Twice the signed area enclosed by a ring, positive when its turns are positive.
+pub(crate) fn twice_signed_area(vertices: &[Point]) -> i128 {
+    vertices.iter().enumerate().map(|(i, a)| {
+        let b = vertices[(i + 1) % vertices.len()];
+        a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128
+    }).sum()
+}
+
+/// Convex hull of `points` by Andrew's monotone chain, with positive (`cross > 0`) turns and no
+/// collinear points. Collinear input collapses to its two extreme points.
+pub(crate) fn convex_hull(points: impl Iterator<Item = Point>) -> Vec<Point> {
+    let mut points: Vec<Point> = points.collect();
+    points.sort_by_key(|p| (p.x, p.y));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let chain = |points: &mut dyn Iterator<Item = &Point>| {
+        let mut chain: Vec<Point> = Vec::new();
+        for p in points {
+            while chain.len() >= 2 && cross(chain[chain.len() - 2], chain[chain.len() - 1], *p) <= 0 {
+                let _ = chain.pop();
+            }
+            chain.push(*p);
+        }
+        // the last point of each chain starts the other one
+        let _ = chain.pop();
+        chain
+    };
+    let mut hull = chain(&mut points.iter());
+    hull.extend(chain(&mut points.iter().rev()));
+    hull
+}
+
+impl<'a> Polygon<'a> {
+    /// Length of the normals returned by [`edge_normals`](Self::edge_normals), so a normal of
+    /// `(NORMAL_SCALE, 0)` points straight along the x axis.
+    pub const NORMAL_SCALE: i32 = 256;
+
+    /// Returns each edge (see [`edges`](Self::edges)) with its outward-facing normal, in fixed
+    /// point with a length of [`NORMAL_SCALE`](Self::NORMAL_SCALE).
+    ///
+    /// Outward is taken from the sign of the polygon's area, so it does not depend on the winding
+    /// order. Zero-length edges have no direction and are skipped. A polygon without area has no
+    /// outside, its normals all point to the same side of their edges.
+    pub fn edge_normals(&self) -> impl Iterator<Item = (Line, Point)> + 'a {
+        let outward = if twice_signed_area(self.vertices()) < 0 { -1 } else { 1 };
+        self.edges().filter(|edge| edge.start != edge.end).map(move |edge| {
+            let delta = edge.end - edge.start;
+            let (dx, dy) = (delta.x as f64, delta.y as f64);
+            let scale = outward as f64 * Self::NORMAL_SCALE as f64 / math::hypot(dx, dy);
+            (edge, Point::new(math::round(dy * scale) as i32, math::round(-dx * scale) as i32))
+        })
+    }
+
+    /// Returns the corners of the smallest-area rectangle enclosing the polygon, in order around
+    /// the rectangle, found with rotating calipers over the convex hull.
+    ///
+    /// Corners are rounded to the nearest pixel. Collinear vertices give a zero-width box along
+    /// their line, a single point (or an empty polygon, at its translate offset) gives four equal
+    /// corners.
+    pub fn oriented_bounding_box(&self) -> [Point; 4] {
+        let hull = convex_hull(self.vertices().iter().map(|v| *v + self.translate()));
+        let n = hull.len();
+        match n {
+            0 => return [self.translate(); 4],
+            1 => return [hull[0]; 4],
+            2 => return [hull[0], hull[1], hull[1], hull[0]],
+            _ => {}
+        }
+        let dot = |p: Point, d: (i64, i64)| p.x as i64 * d.0 + p.y as i64 * d.1;
+        let (mut far, mut top, mut near) = (1, 1, 1);
+        let mut best: Option<(f64, [(f64, f64); 4])> = None;
+        for i in 0..n {
+            let origin = hull[i];
+            let edge = (hull[(i + 1) % n].x as i64 - origin.x as i64, hull[(i + 1) % n].y as i64 - origin.y as i64);
+            // hull turns are positive, so the left normal points inwards
+            let normal = (-edge.1, edge.0);
+            // the calipers only ever move forwards as the edges rotate
+            while dot(hull[(far + 1) % n], edge) > dot(hull[far], edge) {
+                far = (far + 1) % n;
+            }
+            if i == 0 {
+                top = far;
+            }
+            while dot(hull[(top + 1) % n], normal) > dot(hull[top], normal) {
+                top = (top + 1) % n;
+            }
+            if i == 0 {
+                near = top;
+            }
+            while dot(hull[(near + 1) % n], edge) < dot(hull[near], edge) {
+                near = (near + 1) % n;
+            }
+            let base = dot(origin, edge);
+            let (min_u, max_u) = ((dot(hull[near], edge) - base) as f64, (dot(hull[far], edge) - base) as f64);
+            let max_v = (dot(hull[top], normal) - dot(origin, normal)) as f64;
+            let length_squared = (edge.0 * edge.0 + edge.1 * edge.1) as f64;
+            let area = (max_u - min_u) * max_v / length_squared;
+            if !matches!(best, Some((best_area, _)) if best_area <= area) {
+                let corner = |u: f64, v: f64| {
+                    (origin.x as f64 + (u * edge.0 as f64 + v * normal.0 as f64) / length_squared,
+                     origin.y as f64 + (u * edge.1 as f64 + v * normal.1 as f64) / length_squared)
+                };
+                best = Some((area, [corner(min_u, 0.0), corner(max_u, 0.0), corner(max_u, max_v), corner(min_u, max_v)]));
+            }
+        }
+        let corners = best.map(|(_, corners)| corners).unwrap_or_default();
+        corners.map(|(x, y)| Point::new(math::round(x) as i32, math::round(y) as i32))
+    }
+
+    /// Returns `true` if the polygon is convex: every turn along the outline bends the same way
+    /// and the outline winds around its interior only once.
+    ///
+    /// Repeated vertices and straight-through (collinear) vertices are ignored, so a polygon whose
+    /// vertices all lie on one line also counts as convex.
+    pub fn is_convex(&self) -> bool {
+        let mut ring = self.vertices().to_vec();
+        ring.dedup();
+        while ring.len() > 1 && ring.first() == ring.last() {
+            let _ = ring.pop();
+        }
+        let n = ring.len();
+        if n < 3 {
+            return true;
+        }
+        let mut turn_sign = 0;
+        for i in 0..n {
+            let turn = cross(ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]).signum();
+            if turn != 0 {
+                if turn_sign != 0 && turn != turn_sign {
+                    return false;
+                }
+                turn_sign = turn;
+            }
+        }
+        // a convex outline changes horizontal and vertical direction exactly twice each
+        let flips = |component: fn(Point) -> i32| {
+            let signs: Vec<i32> = (0..n).map(|i| (component(ring[(i + 1) % n]) - component(ring[i])).signum()).filter(|s| *s != 0).collect();
+            (0..signs.len()).filter(|i| signs[*i] != signs[(i + 1) % signs.len()]).count()
+        };
+        let (x_flips, y_flips) = (flips(|p| p.x), flips(|p| p.y));
+        x_flips <= 2 && y_flips <= 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::{Dimensions, Point};
+    use embedded_graphics::primitives::ContainsPoint;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::ring;
+
+    #[test]
+    fn test_edge_normals_point_outwards() {
+        let fixtures = [
+            ring(&[[0, 0], [10, 0], [10, 10], [0, 10]]),
+            ring(&[[0, 0], [40, 8], [22, 14], [35, 30], [5, 25]]),
+            ring(&[[0, 0], [30, 0], [30, 30], [20, 30], [20, 10], [10, 10], [10, 30], [0, 30]]),
+            ring(&[[20, 0], [25, 15], [40, 15], [28, 24], [33, 40], [20, 30], [7, 40], [12, 24], [0, 15], [15, 15]]),
+        ];
+        for vertices in fixtures.iter() {
+            let mut reversed = vertices.clone();
+            reversed.reverse();
+            for vertices in [vertices, &reversed] {
+                let mut polygon = Polygon::new(vertices);
+                polygon.set_translate(Point::new(-6, 9));
+                assert_eq!(polygon.edge_normals().count(), vertices.len());
+                for (edge, normal) in polygon.edge_normals() {
+                    let length = ((normal.x * normal.x + normal.y * normal.y) as f64).sqrt();
+                    assert!((length - Polygon::NORMAL_SCALE as f64).abs() <= 1.0, "{normal}");
+                    // step two pixels out from the middle of the edge
+                    let outside = Point::new(
+                        (edge.start.x + edge.end.x) / 2 + (2 * normal.x + Polygon::NORMAL_SCALE / 2).div_euclid(Polygon::NORMAL_SCALE),
+                        (edge.start.y + edge.end.y) / 2 + (2 * normal.y + Polygon::NORMAL_SCALE / 2).div_euclid(Polygon::NORMAL_SCALE),
+                    );
+                    assert!(!polygon.contains(outside), "{vertices:?} {edge:?} {normal}");
+                }
+            }
+        }
+        let repeated = ring(&[[0, 0], [10, 0], [10, 0], [10, 10]]);
+        assert_eq!(Polygon::new(&repeated).edge_normals().map(|(_, normal)| normal).collect::<Vec<_>>(), [Point::new(0, -256), Point::new(256, 0), Point::new(-181, 181)]);
+    }
+
+    #[test]
+    fn test_is_convex() {
+        assert!(Polygon::new(&ring(&[[0, 0], [10, 0], [10, 10], [0, 10]])).is_convex());
+        assert!(Polygon::new(&ring(&[[0, 10], [10, 10], [10, 0], [0, 0]])).is_convex());
+        assert!(Polygon::new(&ring(&[[0, 0], [5, 0], [10, 0], [10, 10], [10, 10], [0, 10]])).is_convex());
+        assert!(Polygon::new(&ring(&[[0, 0], [5, 5], [10, 10]])).is_convex());
+        assert!(!Polygon::new(&ring(&[[0, 0], [10, 0], [5, 3], [10, 10], [0, 10]])).is_convex());
+        // a pentagram turns the same way at every vertex but winds twice
+        assert!(!Polygon::new(&ring(&[[10, 0], [16, 19], [0, 7], [20, 7], [4, 19]])).is_convex());
+        assert!(!Polygon::new(&ring(&[[0, 0], [10, 10], [10, 0], [0, 10]])).is_convex());
+    }
+
+    fn area(corners: &[Point; 4]) -> f64 {
+        (0..4).map(|i| {
+            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+            a.x as f64 * b.y as f64 - b.x as f64 * a.y as f64
+        }).sum::<f64>().abs() / 2.0
+    }
+
+    /// Smallest enclosing rectangle area over a sweep of orientations.
+    fn sampled_min_area(vertices: &[Point]) -> f64 {
+        (0..3600).map(|step| {
+            let angle = (step as f64 / 20.0).to_radians();
+            let (sin, cos) = angle.sin_cos();
+            let (mut min_u, mut max_u, mut min_v, mut max_v) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+            for p in vertices {
+                let (u, v) = (p.x as f64 * cos + p.y as f64 * sin, -(p.x as f64) * sin + p.y as f64 * cos);
+                min_u = min_u.min(u);
+                max_u = max_u.max(u);
+                min_v = min_v.min(v);
+                max_v = max_v.max(v);
+            }
+            (max_u - min_u) * (max_v - min_v)
+        }).fold(f64::MAX, f64::min)
+    }
+
+    #[test]
+    fn test_axis_aligned_rectangle_matches_bounding_box() {
+        let vertices = ring(&[[3, 4], [23, 4], [23, 14], [13, 14], [3, 14]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(-2, 5));
+        let mut corners = polygon.oriented_bounding_box();
+        corners.sort_by_key(|p| (p.x, p.y));
+        let bounds = polygon.bounding_box();
+        let (top_left, bottom_right) = (bounds.top_left, bounds.bottom_right().unwrap());
+        assert_eq!(corners, [top_left, Point::new(top_left.x, bottom_right.y), Point::new(bottom_right.x, top_left.y), bottom_right]);
+    }
+
+    #[test]
+    fn test_oriented_bounding_box_is_area_optimal() {
+        let fixtures = [
+            ring(&[[0, 0], [40, 30], [36, 36], [-4, 6]]),
+            ring(&[[5, 2], [30, 8], [28, 20], [10, 25], [1, 14], [12, 11]]),
+            ring(&[[0, 0], [50, 10], [52, 14], [2, 4]]),
+            ring(&[[10, 0], [16, 19], [0, 7], [20, 7], [4, 19]]),
+        ];
+        for vertices in fixtures.iter() {
+            let corners = Polygon::new(vertices).oriented_bounding_box();
+            let sampled = sampled_min_area(vertices);
+            // rounding the corners to pixels moves each side by at most half a pixel
+            let perimeter = (0..4).map(|i| {
+                let d = corners[(i + 1) % 4] - corners[i];
+                ((d.x * d.x + d.y * d.y) as f64).sqrt()
+            }).sum::<f64>();
+            assert!(area(&corners) <= sampled + perimeter, "{vertices:?}: {} > {sampled}", area(&corners));
+            assert!(area(&corners) >= sampled - perimeter, "{vertices:?}: {} < {sampled}", area(&corners));
+        }
+    }
+
+    #[test]
+    fn test_collinear_points_give_zero_width_box() {
+        let vertices = ring(&[[0, 0], [5, 5], [10, 10], [3, 3]]);
+        assert_eq!(Polygon::new(&vertices).oriented_bounding_box(), [Point::new(0, 0), Point::new(10, 10), Point::new(10, 10), Point::new(0, 0)]);
+        assert_eq!(Polygon::new(&ring(&[[4, 2]])).oriented_bounding_box(), [Point::new(4, 2); 4]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Finding a point well inside a polygon, e.g. to anchor a label.
+
+use core::cmp::Ordering;
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::ContainsPoint;
+use super::Polygon;
+use super::math;
+use super::scanline::Scanlines;
+
+/// A square search cell, ordered by the best distance any point inside it could reach.
+struct Cell {
+    x: f32,
+    y: f32,
+    half: f32,
+    distance: f32,
+    potential: f32,
+}
+
+impl Cell {
+    fn new(x: f32, y: f32, half: f32, vertices: &[Point]) -> Self {
+        let distance = signed_distance(vertices, x, y);
+        Cell {
+            x,
+            y,
+            half,
+            distance,
+            potential: distance + half * core::f32::consts::SQRT_2,
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.potential.total_cmp(&other.potential)
+    }
+}
+
+/// Distance from `(x, y)` to the polygon outline, positive inside and negative outside.
+fn signed_distance(vertices: &[Point], x: f32, y: f32) -> f32 {
+    let mut inside = false;
+    let mut min_squared = f32::INFINITY;
+    for (i, a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        let (ax, ay, bx, by) = (a.x as f32, a.y as f32, b.x as f32, b.y as f32);
+        if (ay > y) != (by > y) && x < (bx - ax) * (y - ay) / (by - ay) + ax {
+            inside = !inside;
+        }
+        let (dx, dy) = (bx - ax, by - ay);
+        let length_squared = dx * dx + dy * dy;
+        let t = if length_squared > 0.0 { (((x - ax) * dx + (y - ay) * dy) / length_squared).clamp(0.0, 1.0) } else { 0.0 };
+        let (px, py) = (ax + t * dx - x, ay + t * dy - y);
+        min_squared = min_squared.min(px * px + py * py);
+    }
+    let distance = math::sqrt(min_squared);
+    if inside { distance } else { -distance }
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns a point inside the polygon that is far from its outline, suitable for placing a
+    /// label. Unlike the centroid it stays inside concave shapes.
+    ///
+    /// Equivalent to [`representative_point_with_precision`](Self::representative_point_with_precision)
+    /// with a precision of one pixel.
+    pub fn representative_point(&self) -> Point {
+        self.representative_point_with_precision(1)
+    }
+
+    /// Approximates the pole of inaccessibility, the interior point furthest from the outline, by
+    /// subdividing the bounding box until no cell can beat the best candidate by more than
+    /// `precision` pixels.
+    ///
+    /// The result always satisfies [`ContainsPoint`], including for shapes too thin to contain a
+    /// point away from their outline. An empty polygon returns its translate offset.
+    pub fn representative_point_with_precision(&self, precision: u32) -> Point {
+        let vertices = self.vertices();
+        let Some(first) = vertices.first() else {
+            return self.translate();
+        };
+        let (min, max) = vertices.iter().fold((*first, *first), |(min, max), v| (min.component_min(*v), max.component_max(*v)));
+        let (width, height) = ((max.x - min.x) as f32, (max.y - min.y) as f32);
+        let cell_size = width.min(height);
+        let untranslated = if cell_size == 0.0 {
+            *first
+        } else {
+            let precision = precision.max(1) as f32;
+            let half = cell_size / 2.0;
+            let mut cells = BinaryHeap::new();
+            let mut y = min.y as f32;
+            while y < max.y as f32 {
+                let mut x = min.x as f32;
+                while x < max.x as f32 {
+                    cells.push(Cell::new(x + half, y + half, half, vertices));
+                    x += cell_size;
+                }
+                y += cell_size;
+            }
+
+            let mut best = Cell::new(min.x as f32 + width / 2.0, min.y as f32 + height / 2.0, 0.0, vertices);
+            while let Some(cell) = cells.pop() {
+                if cell.distance > best.distance {
+                    best = Cell::new(cell.x, cell.y, 0.0, vertices);
+                }
+                if cell.potential - best.distance <= precision {
+                    continue;
+                }
+                let half = cell.half / 2.0;
+                for (dx, dy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                    cells.push(Cell::new(cell.x + dx * half, cell.y + dy * half, half, vertices));
+                }
+            }
+            self.nearest_contained(best.x, best.y)
+        };
+        untranslated + self.translate()
+    }
+
+    /// Picks the lattice point next to `(x, y)` that lies furthest inside the polygon, falling
+    /// back to the middle of the widest fill span when none of them are covered.
+    fn nearest_contained(&self, x: f32, y: f32) -> Point {
+        let untranslated = Polygon::new(self.vertices());
+        let (floor_x, floor_y) = (math::floor(x) as i32, math::floor(y) as i32);
+        let candidates = [(0, 0), (1, 0), (0, 1), (1, 1)].iter()
+            .map(|(dx, dy)| Point::new(floor_x + dx, floor_y + dy))
+            .filter(|p| untranslated.contains(*p))
+            .max_by(|a, b| {
+                signed_distance(self.vertices(), a.x as f32, a.y as f32).total_cmp(&signed_distance(self.vertices(), b.x as f32, b.y as f32))
+            });
+        candidates.unwrap_or_else(|| {
+            let mut scanlines = Scanlines::new(untranslated.edges());
+            let mut spans = Vec::new();
+            let mut widest = (self.vertices()[0], 0);
+            while let Some(y) = scanlines.next_row(&mut spans) {
+                for &(start, end) in spans.iter() {
+                    if end.abs_diff(start) >= widest.1 {
+                        widest = (Point::new(start + (end - start) / 2, y), end.abs_diff(start));
+                    }
+                }
+            }
+            widest.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::primitives::ContainsPoint;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::ring;
+    use super::signed_distance;
+
+    #[test]
+    fn test_representative_point_of_u_shape() {
+        let vertices = ring(&[[0, 0], [30, 0], [30, 30], [20, 30], [20, 10], [10, 10], [10, 30], [0, 30]]);
+        let polygon = Polygon::new(&vertices);
+        let point = polygon.representative_point();
+        assert!(polygon.contains(point), "{point}");
+        assert!(signed_distance(&vertices, point.x as f32, point.y as f32) >= 4.0, "{point}");
+    }
+
+    #[test]
+    fn test_representative_point_of_thin_sliver() {
+        let vertices = ring(&[[0, 0], [60, 1], [0, 2]]);
+        let polygon = Polygon::new(&vertices);
+        assert!(polygon.contains(polygon.representative_point()));
+
+        let needle = ring(&[[3, 5], [80, 6], [4, 6]]);
+        let polygon = Polygon::new(&needle);
+        assert!(polygon.contains(polygon.representative_point()));
+    }
+
+    #[test]
+    fn test_representative_point_of_donut_ring() {
+        // outer square joined to a reversed inner square by a zero-width bridge
+        let vertices = ring(&[
+            [0, 20], [0, 0], [40, 0], [40, 40], [0, 40], [0, 20],
+            [10, 20], [10, 30], [30, 30], [30, 10], [10, 10], [10, 20],
+        ]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(5, -3));
+        let point = polygon.representative_point();
+        assert!(polygon.contains(point), "{point}");
+        let untranslated = point - polygon.translate();
+        assert!(!(11..30).contains(&untranslated.x) || !(11..30).contains(&untranslated.y), "{point} is in the hole");
+        assert!(signed_distance(&vertices, untranslated.x as f32, untranslated.y as f32) >= 3.0, "{point}");
+    }
+}
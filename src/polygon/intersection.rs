@@ -0,0 +1,169 @@
+//! Intersections between a polygon outline and line segments.
+
+use core::cmp::Ordering;
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Line;
+use super::Polygon;
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn delta(from: Point, to: Point) -> (i128, i128) {
+    (to.x as i128 - from.x as i128, to.y as i128 - from.y as i128)
+}
+
+/// A position along a segment as the exact fraction `num / den`, with `den > 0`.
+#[derive(Debug, Clone, Copy)]
+struct Fraction {
+    num: i128,
+    den: i128,
+}
+
+impl Fraction {
+    fn new(num: i128, den: i128) -> Self {
+        if den < 0 { Fraction { num: -num, den: -den } } else { Fraction { num, den } }
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+
+    fn in_unit_range(&self) -> bool {
+        self.num >= 0 && self.num <= self.den
+    }
+}
+
+/// Rounds `start + t * delta` to the nearest pixel.
+fn lerp(start: Point, (dx, dy): (i128, i128), t: Fraction) -> Point {
+    let offset = |d: i128| (2 * t.num * d + t.den).div_euclid(2 * t.den);
+    Point::new((start.x as i128 + offset(dx)) as i32, (start.y as i128 + offset(dy)) as i32)
+}
+
+/// Appends the points where segment `a`-`b` meets `line`, keyed by their position along `line`.
+fn intersect_edge(line: &Line, a: Point, b: Point, hits: &mut Vec<(Fraction, Point)>) {
+    let r = delta(line.start, line.end);
+    let s = delta(a, b);
+    let to_edge = delta(line.start, a);
+    let denominator = cross(r, s);
+    if denominator != 0 {
+        let t = Fraction::new(cross(to_edge, s), denominator);
+        let u = Fraction::new(cross(to_edge, r), denominator);
+        if t.in_unit_range() && u.in_unit_range() {
+            hits.push((t, lerp(line.start, r, t)));
+        }
+    } else if cross(to_edge, r) == 0 {
+        let length_squared = r.0 * r.0 + r.1 * r.1;
+        if length_squared == 0 {
+            // a degenerate line is a single point, which only hits edges passing through it
+            let p = line.start;
+            if cross(to_edge, s) == 0 && (a.x.min(b.x)..=a.x.max(b.x)).contains(&p.x) && (a.y.min(b.y)..=a.y.max(b.y)).contains(&p.y) {
+                hits.push((Fraction::new(0, 1), p));
+            }
+            return;
+        }
+        // collinear, the overlap is bounded by the line and edge endpoints
+        let project = |p: Point| {
+            let d = delta(line.start, p);
+            Fraction::new(d.0 * r.0 + d.1 * r.1, length_squared)
+        };
+        let (mut enter, mut exit) = ((project(a), a), (project(b), b));
+        if exit.0.cmp(&enter.0) == Ordering::Less {
+            core::mem::swap(&mut enter, &mut exit);
+        }
+        if enter.0.num < 0 {
+            enter = (Fraction::new(0, 1), line.start);
+        }
+        if exit.0.num > exit.0.den {
+            exit = (Fraction::new(1, 1), line.end);
+        }
+        if enter.0.cmp(&exit.0) != Ordering::Greater {
+            hits.push(enter);
+            hits.push(exit);
+        }
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the points where `line` crosses or touches the polygon outline, ordered from the
+    /// line's start to its end.
+    ///
+    /// A line grazing a vertex yields that vertex once, and a line running along an edge yields
+    /// the two ends of the shared stretch. A line entirely inside or outside the polygon yields
+    /// nothing. Points are rounded to the nearest pixel.
+    pub fn intersect_segment(&self, line: &Line) -> impl Iterator<Item = Point> {
+        let mut hits = Vec::new();
+        for edge in self.edges() {
+            intersect_edge(line, edge.start, edge.end, &mut hits);
+        }
+        hits.sort_by(|a, b| a.0.cmp(&b.0));
+        hits.dedup_by(|a, b| a.1 == b.1);
+        hits.into_iter().map(|(_, point)| point)
+    }
+
+    /// Returns the first point along `line` where it meets the polygon outline, as used for
+    /// raycasts.
+    pub fn first_intersection(&self, line: &Line) -> Option<Point> {
+        self.intersect_segment(line).next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::primitives::Line;
+    use itertools::Itertools;
+    use crate::polygon::Polygon;
+
+    fn u_shape() -> Vec<Point> {
+        [[0, 0], [30, 0], [30, 30], [20, 30], [20, 10], [10, 10], [10, 30], [0, 30]].iter().map(Point::from).collect()
+    }
+
+    #[test]
+    fn test_segment_crossing_concave_polygon_four_times() {
+        let vertices = u_shape();
+        let polygon = Polygon::new(&vertices);
+        let line = Line::new(Point::new(-5, 20), Point::new(35, 20));
+        let expected = [Point::new(0, 20), Point::new(10, 20), Point::new(20, 20), Point::new(30, 20)];
+        assert_eq!(polygon.intersect_segment(&line).collect_vec(), expected);
+
+        let reversed = Line::new(line.end, line.start);
+        assert_eq!(polygon.intersect_segment(&reversed).collect_vec(), expected.iter().rev().copied().collect_vec());
+        assert_eq!(polygon.first_intersection(&reversed), Some(Point::new(30, 20)));
+    }
+
+    #[test]
+    fn test_segment_inside_has_no_hits() {
+        let vertices = u_shape();
+        let polygon = Polygon::new(&vertices);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(2, 2), Point::new(8, 25))).count(), 0);
+        assert_eq!(polygon.first_intersection(&Line::new(Point::new(12, 15), Point::new(18, 29))), None);
+    }
+
+    #[test]
+    fn test_segment_starting_inside_grazing_and_collinear() {
+        let vertices = u_shape();
+        let mut polygon = Polygon::new(&vertices);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(5, 5), Point::new(5, 40))).collect_vec(), [Point::new(5, 30)]);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(25, -5), Point::new(35, 5))).collect_vec(), [Point::new(30, 0)]);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(-5, 0), Point::new(40, 0))).collect_vec(), [Point::new(0, 0), Point::new(30, 0)]);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(12, 0), Point::new(18, 0))).collect_vec(), [Point::new(12, 0), Point::new(18, 0)]);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(7, 13), Point::new(13, 7))).collect_vec(), [Point::new(10, 10)]);
+
+        polygon.set_translate(Point::new(100, 0));
+        assert_eq!(polygon.first_intersection(&Line::new(Point::new(90, 20), Point::new(140, 20))), Some(Point::new(100, 20)));
+    }
+
+    #[test]
+    fn test_diagonal_segment_rounds_to_nearest_pixel() {
+        let vertices = [Point::new(0, 0), Point::new(10, 0), Point::new(10, 10), Point::new(0, 10)];
+        let polygon = Polygon::new(&vertices);
+        let line = Line::new(Point::new(-3, 1), Point::new(13, 4));
+        assert_eq!(polygon.intersect_segment(&line).collect_vec(), [Point::new(0, 2), Point::new(10, 3)]);
+
+        let point = Line::new(Point::new(10, 4), Point::new(10, 4));
+        assert_eq!(polygon.intersect_segment(&point).collect_vec(), [Point::new(10, 4)]);
+        assert_eq!(polygon.intersect_segment(&Line::new(Point::new(5, 5), Point::new(5, 5))).count(), 0);
+    }
+}
@@ -0,0 +1,155 @@
+//! Fills spread over several calls.
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use super::Polygon;
+use super::scanline::{Span, Spans};
+
+/// Whether a [`FillJob`] has drawn every pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progress {
+    /// Pixels are left to draw.
+    Incomplete,
+    /// The fill is finished, and further steps draw nothing.
+    Complete,
+}
+
+/// A fill of a polygon that draws a limited number of pixels at a time, as returned by
+/// [`Polygon::fill_job`].
+///
+/// The job holds its own edge table, so it does not borrow the polygon.
+pub struct FillJob<C> {
+    spans: Spans,
+    color: Option<C>,
+    /// The rest of a span cut short by the last step's budget, or the next span to draw.
+    pending: Option<Span>,
+    started: bool,
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns a job filling the polygon in the fill color of `style`, a few pixels per
+    /// [`step`](FillJob::step), so that a large fill over a slow bus can be spread over several
+    /// passes of a main loop.
+    ///
+    /// Only the fill is drawn, and only for a style a plain fill would be drawn with, having a fill
+    /// color and no stroke; for any other style the job is complete from the start.
+    pub fn fill_job<C: PixelColor>(&self, style: PrimitiveStyle<C>) -> FillJob<C> {
+        let color = style.fill_color.filter(|_| style.stroke_width == 0);
+        FillJob { spans: self.spans(), color, pending: None, started: false }
+    }
+}
+
+impl<C: PixelColor> FillJob<C> {
+    /// Draws up to `max_pixels` more pixels of the fill, clipped to the target, and returns
+    /// whether any are left.
+    ///
+    /// Pixels outside the target count for nothing. A span is cut wherever the budget runs out
+    /// and carried on from there by the next step, so every step but the last draws exactly
+    /// `max_pixels`, and all steps together draw the same pixels in the same order as filling in
+    /// one go, provided the target's bounds stay the same in between.
+    pub fn step<D>(&mut self, target: &mut D, max_pixels: usize) -> Result<Progress, D::Error> where D: DrawTarget<Color = C> {
+        let Some(color) = self.color else {
+            return Ok(Progress::Complete);
+        };
+        let bounds = target.bounding_box();
+        let Some(bottom_right) = bounds.bottom_right() else {
+            return Ok(Progress::Complete);
+        };
+        if !self.started {
+            self.spans.skip_to(bounds.top_left.y);
+            self.started = true;
+        }
+        let mut budget = max_pixels;
+        loop {
+            let Some(span) = self.pending.take().or_else(|| self.spans.next()).filter(|span| span.y <= bottom_right.y) else {
+                self.color = None;
+                return Ok(Progress::Complete);
+            };
+            let end = span.x as i64 + span.len as i64 - 1;
+            let (start, end) = (span.x.max(bounds.top_left.x) as i64, end.min(bottom_right.x as i64));
+            if span.y < bounds.top_left.y || start > end {
+                continue;
+            }
+            if budget == 0 {
+                self.pending = Some(Span { y: span.y, x: start as i32, len: (end - start + 1) as u32 });
+                return Ok(Progress::Incomplete);
+            }
+            let drawn = (end - start + 1).min(budget.try_into().unwrap_or(i64::MAX));
+            target.fill_solid(&Rectangle::new(Point::new(start as i32, span.y), Size::new(drawn as u32, 1)), color)?;
+            budget -= drawn as usize;
+            if start + drawn <= end {
+                self.pending = Some(Span { y: span.y, x: (start + drawn) as i32, len: (end - start - drawn + 1) as u32 });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+    use crate::polygon::{Polygon, Progress};
+    use crate::polygon::tests::{PixelCollector, ring};
+
+    fn star() -> Vec<Point> {
+        ring(&[[15, -3], [19, 9], [33, 9], [22, 17], [26, 33], [15, 23], [4, 33], [8, 17], [-3, 9], [11, 9]])
+    }
+
+    #[test]
+    fn test_steps_draw_the_one_shot_fill() {
+        let star = star();
+        let polygon = Polygon::new(&star);
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let mut whole = PixelCollector::default();
+        polygon.into_styled(style).draw(&mut whole).unwrap();
+
+        for budget in [1, 2, 3, 7, 16, 100, 10_000, usize::MAX] {
+            let mut job = polygon.fill_job(style);
+            let mut collector = PixelCollector::default();
+            let mut steps = 0;
+            loop {
+                let before = collector.pixels.len();
+                let progress = job.step(&mut collector, budget).unwrap();
+                let drawn = collector.pixels.len() - before;
+                steps += 1;
+                if progress == Progress::Complete {
+                    assert!(drawn <= budget);
+                    break;
+                }
+                assert_eq!(drawn, budget, "budget {budget}, step {steps}");
+            }
+            assert_eq!(collector.pixels, whole.pixels, "budget {budget}");
+            assert_eq!(steps, whole.pixels.len().div_ceil(budget).max(1), "budget {budget}");
+            assert_eq!(job.step(&mut collector, budget), Ok(Progress::Complete));
+            assert_eq!(collector.pixels.len(), whole.pixels.len());
+        }
+    }
+
+    #[test]
+    fn test_steps_clip_to_the_target() {
+        let star = star();
+        let mut polygon = Polygon::new(&star);
+        polygon.set_translate(Point::new(40, 45));
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let mut expected = MockDisplay::new();
+        polygon.into_styled(style).draw(&mut expected).unwrap();
+
+        for budget in [1, 5, 64] {
+            let mut display = MockDisplay::new();
+            let mut job = polygon.fill_job(style);
+            while let Progress::Incomplete = job.step(&mut display, budget).unwrap() {}
+            display.assert_eq(&expected);
+        }
+
+        // outlines are not filled, and a job with nothing to fill is already done
+        let mut display: MockDisplay<BinaryColor> = MockDisplay::new();
+        assert_eq!(polygon.fill_job(PrimitiveStyle::with_stroke(BinaryColor::On, 1)).step(&mut display, 10), Ok(Progress::Complete));
+        assert_eq!(Polygon::new(&[]).fill_job(style).step(&mut display, 0), Ok(Progress::Complete));
+        assert_eq!(display, MockDisplay::new());
+    }
+}
@@ -0,0 +1,63 @@
+//! Mapping between real-valued coordinates and pixels, for the interchange formats.
+
+use core::fmt;
+use embedded_graphics::geometry::Point;
+use super::math;
+
+/// How real-valued coordinates from other formats map onto pixels: each axis is scaled and then
+/// offset, and the result rounded to the nearest pixel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoordinateMapping {
+    pub scale: (f64, f64),
+    pub offset: (f64, f64),
+}
+
+impl CoordinateMapping {
+    /// Takes coordinates as pixels unchanged, rounding any fractional part.
+    pub const IDENTITY: CoordinateMapping = CoordinateMapping { scale: (1.0, 1.0), offset: (0.0, 0.0) };
+
+    pub fn new(scale: f64, offset: (f64, f64)) -> Self {
+        CoordinateMapping {
+            scale: (scale, scale),
+            offset,
+        }
+    }
+
+    /// Maps a coordinate to the nearest pixel, failing if it is not finite or falls outside the
+    /// `i32` range.
+    pub fn to_pixel(&self, x: f64, y: f64) -> Result<Point, CoordinateOutOfRange> {
+        let (px, py) = (math::round(x * self.scale.0 + self.offset.0), math::round(y * self.scale.1 + self.offset.1));
+        let range = i32::MIN as f64..=i32::MAX as f64;
+        if range.contains(&px) && range.contains(&py) {
+            Ok(Point::new(px as i32, py as i32))
+        } else {
+            Err(CoordinateOutOfRange { x, y })
+        }
+    }
+
+    /// Maps a pixel back to the coordinate it was rounded from.
+    pub fn from_pixel(&self, point: Point) -> (f64, f64) {
+        ((point.x as f64 - self.offset.0) / self.scale.0, (point.y as f64 - self.offset.1) / self.scale.1)
+    }
+}
+
+impl Default for CoordinateMapping {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// A coordinate that has no pixel under the mapping in use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoordinateOutOfRange {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl fmt::Display for CoordinateOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "coordinate ({}, {}) does not map to a pixel", self.x, self.y)
+    }
+}
+
+impl core::error::Error for CoordinateOutOfRange {}
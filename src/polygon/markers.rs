@@ -0,0 +1,121 @@
+//! Marking the vertices of a polygon, for editors and debug overlays.
+
+use embedded_graphics::Drawable;
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{Circle, Line, Primitive, PrimitiveStyle, Rectangle};
+use super::Polygon;
+
+/// The shape drawn at each vertex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexMarker {
+    /// A filled circle.
+    Circle,
+    /// A filled square.
+    Square,
+    /// A horizontal and a vertical line, one pixel wide.
+    Cross,
+}
+
+/// The color of each vertex marker.
+#[derive(Clone, Copy, Debug)]
+pub enum MarkerColor<C> {
+    /// Every marker in the same color.
+    Single(C),
+    /// The color for each marker from the index of its vertex.
+    PerIndex(fn(usize) -> C),
+}
+
+/// How [`Polygon::draw_vertices`] marks each vertex.
+#[derive(Clone, Copy, Debug)]
+pub struct VertexMarkerStyle<C> {
+    pub marker: VertexMarker,
+    /// Width and height of each marker in pixels. Odd sizes are centered exactly on the vertex.
+    pub size: u32,
+    pub color: MarkerColor<C>,
+}
+
+impl<C> VertexMarkerStyle<C> {
+    pub fn new(marker: VertexMarker, size: u32, color: C) -> Self {
+        VertexMarkerStyle { marker, size, color: MarkerColor::Single(color) }
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Draws a marker centered on each vertex, with the translate offset applied so the markers
+    /// follow the drawn polygon.
+    ///
+    /// Markers are clipped to the target, so vertices at or past its edges are safe to mark.
+    pub fn draw_vertices<C, D>(&self, target: &mut D, style: VertexMarkerStyle<C>) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+        let area = target.bounding_box();
+        let mut target = target.clipped(&area);
+        let reach = (style.size.saturating_sub(1) / 2) as i32;
+        for (i, vertex) in self.vertices().iter().enumerate() {
+            let center = *vertex + self.translate();
+            let color = match style.color {
+                MarkerColor::Single(color) => color,
+                MarkerColor::PerIndex(color) => color(i),
+            };
+            match style.marker {
+                VertexMarker::Circle => Circle::with_center(center, style.size).into_styled(PrimitiveStyle::with_fill(color)).draw(&mut target)?,
+                VertexMarker::Square => Rectangle::with_center(center, Size::new(style.size, style.size)).into_styled(PrimitiveStyle::with_fill(color)).draw(&mut target)?,
+                VertexMarker::Cross => {
+                    let stroke = PrimitiveStyle::with_stroke(color, 1);
+                    Line::new(center - Point::new(reach, 0), center + Point::new(reach, 0)).into_styled(stroke).draw(&mut target)?;
+                    Line::new(center - Point::new(0, reach), center + Point::new(0, reach)).into_styled(stroke).draw(&mut target)?;
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::{Dimensions, Point};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::PointsIter;
+    use crate::polygon::{MarkerColor, Polygon, VertexMarker, VertexMarkerStyle};
+    use crate::polygon::tests::ring;
+
+    #[test]
+    fn test_markers_are_centered_on_translated_vertices() {
+        let vertices = ring(&[[5, 5], [30, 8], [18, 27]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(10, 20));
+        for marker in [VertexMarker::Circle, VertexMarker::Square, VertexMarker::Cross] {
+            for size in [1, 5, 7] {
+                let mut display = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                polygon.draw_vertices(&mut display, VertexMarkerStyle::new(marker, size, BinaryColor::On)).unwrap();
+                let set: Vec<Point> = display.bounding_box().points().filter(|p| display.get_pixel(*p).is_some()).collect();
+                for vertex in vertices.iter().map(|v| *v + polygon.translate()) {
+                    let around: Vec<Point> = set.iter().copied().filter(|p| (*p - vertex).x.abs() <= 4 && (*p - vertex).y.abs() <= 4).collect();
+                    assert!(around.contains(&vertex), "{marker:?} {size}");
+                    // symmetric about the vertex
+                    assert!(around.iter().all(|p| around.contains(&(vertex * 2 - *p))), "{marker:?} {size}");
+                    assert!(around.iter().all(|p| (*p - vertex).x.abs() <= size as i32 / 2 && (*p - vertex).y.abs() <= size as i32 / 2));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_markers_are_clipped_and_colored_per_index() {
+        let vertices = ring(&[[0, 0], [63, 0], [63, 63], [0, 63], [70, 30]]);
+        let polygon = Polygon::new(&vertices);
+        let style = VertexMarkerStyle {
+            marker: VertexMarker::Square,
+            size: 5,
+            color: MarkerColor::PerIndex(|i| if i % 2 == 0 { BinaryColor::On } else { BinaryColor::Off }),
+        };
+        let mut display = MockDisplay::new();
+        polygon.draw_vertices(&mut display, style).unwrap();
+        assert_eq!(display.get_pixel(Point::new(1, 2)), Some(BinaryColor::On));
+        assert_eq!(display.get_pixel(Point::new(62, 0)), Some(BinaryColor::Off));
+        assert_eq!(display.get_pixel(Point::new(63, 63)), Some(BinaryColor::On));
+        assert_eq!(display.get_pixel(Point::new(62, 30)), None);
+    }
+}
@@ -0,0 +1,292 @@
+//! Float functions behind a backend chosen at compile time.
+//!
+//! Every float call in the crate goes through here rather than the methods `std` puts on `f32`
+//! and `f64`, so the crate does not lean on `std` for its math. The `libm` feature takes them from
+//! the `libm` crate, which gives the same results as `std`. The `micromath` feature takes sines,
+//! cosines, arc tangents and exponentials from `micromath`, whose approximations are off by up to
+//! about 0.003, enough to move a vertex of a large circle or gear by a pixel; rounding and square
+//! roots are done exactly here instead, as `micromath` only estimates them. If both are enabled
+//! `libm` wins. Without either the `std` methods are used, so a build without the `std` feature
+//! needs one of them.
+
+#[cfg(not(any(feature = "std", feature = "libm", feature = "micromath")))]
+compile_error!("building without the `std` feature needs the `libm` or `micromath` feature for float math");
+
+/// The operations the crate needs from `f32` and `f64`.
+pub(crate) trait Real: Copy {
+    fn round(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn atan2(self, other: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    #[cfg_attr(not(feature = "3d"), allow(dead_code))]
+    fn exp(self) -> Self;
+}
+
+/// Rounds to the nearest integer, halfway cases away from zero.
+pub(crate) fn round<F: Real>(x: F) -> F {
+    x.round()
+}
+
+pub(crate) fn floor<F: Real>(x: F) -> F {
+    x.floor()
+}
+
+pub(crate) fn ceil<F: Real>(x: F) -> F {
+    x.ceil()
+}
+
+pub(crate) fn abs<F: Real>(x: F) -> F {
+    x.abs()
+}
+
+pub(crate) fn sqrt<F: Real>(x: F) -> F {
+    x.sqrt()
+}
+
+/// `sqrt(x * x + y * y)`.
+pub(crate) fn hypot<F: Real>(x: F, y: F) -> F {
+    x.hypot(y)
+}
+
+/// `(sin(x), cos(x))`.
+pub(crate) fn sin_cos<F: Real>(x: F) -> (F, F) {
+    x.sin_cos()
+}
+
+/// The angle of `(x, y)` from the positive x axis, in `-pi..=pi`.
+pub(crate) fn atan2<F: Real>(y: F, x: F) -> F {
+    y.atan2(x)
+}
+
+pub(crate) fn powi<F: Real>(x: F, n: i32) -> F {
+    x.powi(n)
+}
+
+/// `e` to the power `x`.
+#[cfg_attr(not(feature = "3d"), allow(dead_code))]
+pub(crate) fn exp<F: Real>(x: F) -> F {
+    x.exp()
+}
+
+/// `x` to the power `n` by repeated squaring, as `std` does.
+#[cfg(any(feature = "libm", feature = "micromath"))]
+macro_rules! powi_by_squaring {
+    ($x:expr, $n:expr) => {{
+        let (mut base, mut power, mut result) = ($x, $n.unsigned_abs(), 1.0);
+        while power > 0 {
+            if power & 1 == 1 {
+                result *= base;
+            }
+            power >>= 1;
+            base *= base;
+        }
+        if $n < 0 { 1.0 / result } else { result }
+    }};
+}
+
+#[cfg(all(feature = "std", not(any(feature = "libm", feature = "micromath"))))]
+macro_rules! real_std {
+    ($t:ty) => {
+        impl Real for $t {
+            fn round(self) -> Self { <$t>::round(self) }
+            fn floor(self) -> Self { <$t>::floor(self) }
+            fn ceil(self) -> Self { <$t>::ceil(self) }
+            fn abs(self) -> Self { <$t>::abs(self) }
+            fn sqrt(self) -> Self { <$t>::sqrt(self) }
+            fn hypot(self, other: Self) -> Self { <$t>::hypot(self, other) }
+            fn sin_cos(self) -> (Self, Self) { <$t>::sin_cos(self) }
+            fn atan2(self, other: Self) -> Self { <$t>::atan2(self, other) }
+            fn powi(self, n: i32) -> Self { <$t>::powi(self, n) }
+            fn exp(self) -> Self { <$t>::exp(self) }
+        }
+    };
+}
+
+#[cfg(all(feature = "std", not(any(feature = "libm", feature = "micromath"))))]
+real_std!(f32);
+#[cfg(all(feature = "std", not(any(feature = "libm", feature = "micromath"))))]
+real_std!(f64);
+
+#[cfg(feature = "libm")]
+macro_rules! real_libm {
+    ($t:ty, $round:ident, $floor:ident, $ceil:ident, $abs:ident, $sqrt:ident, $hypot:ident, $sin_cos:ident, $atan2:ident, $exp:ident) => {
+        impl Real for $t {
+            fn round(self) -> Self { libm::$round(self) }
+            fn floor(self) -> Self { libm::$floor(self) }
+            fn ceil(self) -> Self { libm::$ceil(self) }
+            fn abs(self) -> Self { libm::$abs(self) }
+            fn sqrt(self) -> Self { libm::$sqrt(self) }
+            fn hypot(self, other: Self) -> Self { libm::$hypot(self, other) }
+            fn sin_cos(self) -> (Self, Self) { libm::$sin_cos(self) }
+            fn atan2(self, other: Self) -> Self { libm::$atan2(self, other) }
+            fn powi(self, n: i32) -> Self { powi_by_squaring!(self, n) }
+            fn exp(self) -> Self { libm::$exp(self) }
+        }
+    };
+}
+
+#[cfg(feature = "libm")]
+real_libm!(f32, roundf, floorf, ceilf, fabsf, sqrtf, hypotf, sincosf, atan2f, expf);
+#[cfg(feature = "libm")]
+real_libm!(f64, round, floor, ceil, fabs, sqrt, hypot, sincos, atan2, exp);
+
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+macro_rules! real_micromath {
+    ($t:ty, $bits:ty, $mantissa_bits:expr, $one:expr, $newton_steps:expr) => {
+        impl Real for $t {
+            fn round(self) -> Self {
+                let truncated = self.trunc_exact();
+                if Real::abs(self - truncated) >= 0.5 { truncated + (1.0 as $t).with_sign_of(self) } else { truncated }
+            }
+
+            fn floor(self) -> Self {
+                let truncated = self.trunc_exact();
+                if truncated > self { truncated - 1.0 } else { truncated }
+            }
+
+            fn ceil(self) -> Self {
+                let truncated = self.trunc_exact();
+                if truncated < self { truncated + 1.0 } else { truncated }
+            }
+
+            fn abs(self) -> Self {
+                <$t>::from_bits(self.to_bits() & !(1 << (<$bits>::BITS - 1)))
+            }
+
+            fn sqrt(self) -> Self {
+                if self.is_nan() || self < 0.0 {
+                    return <$t>::NAN;
+                }
+                if self == 0.0 || self.is_infinite() {
+                    return self;
+                }
+                // halving the exponent estimates the root within a few percent, and each Newton
+                // step doubles the digits that are right
+                let mut root = <$t>::from_bits((self.to_bits() + $one) >> 1);
+                for _ in 0..$newton_steps {
+                    root = 0.5 * (root + self / root);
+                }
+                root
+            }
+
+            fn hypot(self, other: Self) -> Self {
+                Real::sqrt(self * self + other * other)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                let (sin, cos) = micromath::F32Ext::sin_cos(self as f32);
+                (sin as $t, cos as $t)
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                micromath::F32Ext::atan2(self as f32, other as f32) as $t
+            }
+
+            fn powi(self, n: i32) -> Self {
+                powi_by_squaring!(self, n)
+            }
+
+            fn exp(self) -> Self {
+                micromath::F32Ext::exp(self as f32) as $t
+            }
+        }
+
+        impl TruncExact for $t {
+            fn trunc_exact(self) -> Self {
+                // from here on every float is a whole number, and NaN and the infinities stay put
+                if self.is_nan() || Real::abs(self) >= (1u64 << $mantissa_bits) as $t {
+                    return self;
+                }
+                (self as i64 as $t).with_sign_of(self)
+            }
+
+            fn with_sign_of(self, sign: Self) -> Self {
+                let sign_bit = 1 << (<$bits>::BITS - 1);
+                <$t>::from_bits(self.to_bits() & !sign_bit | sign.to_bits() & sign_bit)
+            }
+        }
+    };
+}
+
+/// What `core` lacks for rounding without `std`.
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+trait TruncExact {
+    /// Drops the fraction, keeping the sign of zero.
+    fn trunc_exact(self) -> Self;
+    fn with_sign_of(self, sign: Self) -> Self;
+}
+
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+real_micromath!(f32, u32, 23, 0x3f80_0000, 4);
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+real_micromath!(f64, u64, 52, 0x3ff0_0000_0000_0000, 5);
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::Polygon;
+
+    /// How far sines, cosines and arc tangents may be from `std`.
+    const TRIG_TOLERANCE: f64 = if cfg!(all(feature = "micromath", not(feature = "libm"))) { 3e-3 } else { 1e-12 };
+
+    fn samples() -> impl Iterator<Item = f64> {
+        let special = [0.0, -0.0, 0.5, -0.5, 1.5, -1.5, 2.5, -2.5, 0.49999999999999994, 4503599627370495.5, 1e300, -1e300];
+        special.into_iter().chain((-2000..=2000).map(|i| i as f64 * 0.37))
+    }
+
+    #[test]
+    fn test_rounding_matches_std() {
+        for x in samples() {
+            assert_eq!(super::round(x).to_bits(), x.round().to_bits(), "round {x}");
+            assert_eq!(super::floor(x).to_bits(), x.floor().to_bits(), "floor {x}");
+            assert_eq!(super::ceil(x).to_bits(), x.ceil().to_bits(), "ceil {x}");
+            assert_eq!(super::abs(x).to_bits(), x.abs().to_bits(), "abs {x}");
+            assert_eq!(super::powi(x, 2), x.powi(2), "powi {x}");
+            let x = x as f32;
+            assert_eq!(super::round(x).to_bits(), x.round().to_bits(), "round {x}");
+            assert_eq!(super::floor(x).to_bits(), x.floor().to_bits(), "floor {x}");
+            assert_eq!(super::ceil(x).to_bits(), x.ceil().to_bits(), "ceil {x}");
+            assert_eq!(super::powi(x, 2), x.powi(2), "powi {x}");
+        }
+        assert!(super::round(f64::NAN).is_nan() && super::floor(f32::NAN).is_nan());
+        assert_eq!(super::ceil(f64::NEG_INFINITY), f64::NEG_INFINITY);
+        assert_eq!(super::powi(2.0f64, -3), 0.125);
+    }
+
+    #[test]
+    fn test_roots_and_trig_match_std() {
+        for x in samples().filter(|x| x.abs() < 1e10) {
+            let root = super::sqrt(x.abs());
+            assert!((root - x.abs().sqrt()).abs() <= x.abs().sqrt() * 1e-15, "sqrt {x}: {root}");
+            assert!((super::hypot(x, 3.0) - x.hypot(3.0)).abs() <= x.hypot(3.0) * 1e-15, "hypot {x}");
+            let (sin, cos) = super::sin_cos(x / 100.0);
+            assert!((sin - (x / 100.0).sin()).abs() <= TRIG_TOLERANCE && (cos - (x / 100.0).cos()).abs() <= TRIG_TOLERANCE, "sin_cos {x}");
+            assert!((super::atan2(x, 7.0) - x.atan2(7.0)).abs() <= TRIG_TOLERANCE, "atan2 {x}");
+            assert!((super::exp(x / 100.0) - (x / 100.0).exp()).abs() <= (x / 100.0).exp() * TRIG_TOLERANCE, "exp {x}");
+        }
+        assert!(super::sqrt(-1.0f64).is_nan());
+        assert_eq!(super::sqrt(0.0f32), 0.0);
+    }
+
+    #[test]
+    fn test_shapes_rasterize_like_std() {
+        // vertices worked out with the `std` methods; through micromath a vertex may move by a pixel
+        let slack = if cfg!(all(feature = "micromath", not(feature = "libm"))) { 1 } else { 0 };
+        let center = Point::new(7, -3);
+        for radius in [1, 5, 40, 250] {
+            for segments in [3, 7, 64] {
+                let shape = Polygon::sector(center, radius, 0.3, 6.0, segments).unwrap();
+                for (i, v) in shape.vertices().iter().enumerate().skip(1) {
+                    let angle = 0.3f32 as f64 + 6.0 * (i - 1) as f64 / segments as f64;
+                    let expected = center + Point::new((radius as f64 * angle.cos()).round() as i32, (radius as f64 * angle.sin()).round() as i32);
+                    assert!((v.x - expected.x).abs() <= slack && (v.y - expected.y).abs() <= slack, "{radius} {segments} {i}: {v:?} {expected:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Interpolating between two polygon outlines.
+
+use alloc::{vec, vec::Vec};
+use embedded_graphics::geometry::Point;
+use super::{Polygon, PolygonOwned};
+use super::math;
+use super::geometry::twice_signed_area;
+
+/// Resamples a ring to `count` vertices by keeping every original vertex and inserting evenly
+/// spaced points along the edges, more on longer edges.
+fn resample(vertices: &[Point], count: usize) -> Vec<Point> {
+    let n = vertices.len();
+    let edge = |i: usize| (vertices[i], vertices[(i + 1) % n]);
+    let lengths: Vec<f64> = (0..n).map(|i| {
+        let (a, b) = edge(i);
+        math::sqrt(((b.x as i64 - a.x as i64).pow(2) + (b.y as i64 - a.y as i64).pow(2)) as f64)
+    }).collect();
+    let perimeter: f64 = lengths.iter().sum();
+    let extra = count.saturating_sub(n);
+
+    // share the extra points out by edge length, handing leftovers to the largest remainders
+    let mut inserted = vec![0; n];
+    if perimeter > 0.0 {
+        let quotas: Vec<f64> = lengths.iter().map(|length| extra as f64 * length / perimeter).collect();
+        for (slot, quota) in inserted.iter_mut().zip(quotas.iter()) {
+            *slot = math::floor(*quota) as usize;
+        }
+        let mut by_remainder: Vec<usize> = (0..n).collect();
+        by_remainder.sort_by(|a, b| (quotas[*b] - math::floor(quotas[*b])).total_cmp(&(quotas[*a] - math::floor(quotas[*a]))));
+        let leftover = extra - inserted.iter().sum::<usize>();
+        for i in by_remainder.into_iter().take(leftover) {
+            inserted[i] += 1;
+        }
+    } else {
+        inserted[0] = extra;
+    }
+
+    let mut resampled = Vec::with_capacity(count.max(n));
+    for (i, k) in inserted.into_iter().enumerate() {
+        let (a, b) = edge(i);
+        resampled.push(a);
+        resampled.extend((1..=k).map(|j| {
+            let t = j as f64 / (k + 1) as f64;
+            Point::new(a.x + math::round((b.x - a.x) as f64 * t) as i32, a.y + math::round((b.y - a.y) as f64 * t) as i32)
+        }));
+    }
+    resampled
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    let mix = |a: i32, b: i32| a + math::round((b as i64 - a as i64) as f64 * t as f64) as i32;
+    Point::new(mix(a.x, b.x), mix(a.y, b.y))
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the shape `t` of the way from this polygon to `other`, for `t` in `0.0..=1.0`.
+    ///
+    /// Both outlines are resampled to the same vertex count by inserting points along their
+    /// edges in proportion to edge length, `other` is reversed if it winds the other way, and its
+    /// starting vertex is rotated to minimise the total distance travelled before the vertex
+    /// positions and translate offsets are interpolated. At `t = 0.0` and `t = 1.0` the result
+    /// covers the same pixels as the inputs, up to the rounding of the inserted edge points.
+    /// `t` is clamped to `0.0..=1.0`. If either polygon has no vertices there is nothing to
+    /// interpolate and the nearer input is returned.
+    pub fn morph_to(&self, other: &Polygon, t: f32) -> PolygonOwned {
+        let t = t.clamp(0.0, 1.0);
+        if self.vertices().is_empty() || other.vertices().is_empty() {
+            return if t < 0.5 { (*self).into() } else { (*other).into() };
+        }
+        let count = self.vertices().len().max(other.vertices().len());
+        let from = resample(self.vertices(), count);
+        let mut to = resample(other.vertices(), count);
+        if twice_signed_area(self.vertices()).signum() * twice_signed_area(other.vertices()).signum() < 0 {
+            to.reverse();
+        }
+        let travel = |offset: usize| -> i128 {
+            from.iter().enumerate().map(|(i, a)| {
+                let b = to[(i + offset) % count] + other.translate() - self.translate();
+                (b.x as i128 - a.x as i128).pow(2) + (b.y as i128 - a.y as i128).pow(2)
+            }).sum()
+        };
+        let offset = (0..count).min_by_key(|offset| travel(*offset)).unwrap_or(0);
+        let vertices = from.iter().enumerate().map(|(i, a)| lerp(*a, to[(i + offset) % count], t)).collect();
+        PolygonOwned::with_translate(vertices, lerp(self.translate(), other.translate(), t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn regular(count: usize, radius: f64, center: Point) -> Vec<Point> {
+        (0..count).map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / count as f64;
+            center + Point::new((radius * angle.cos()).round() as i32, (radius * angle.sin()).round() as i32)
+        }).collect()
+    }
+
+    #[test]
+    fn test_morph_endpoints_reproduce_inputs() {
+        let square = ring(&[[0, 0], [40, 0], [40, 40], [0, 40]]);
+        let star = ring(&[[20, 0], [25, 15], [40, 15], [28, 24], [33, 40], [20, 30], [7, 40], [12, 24], [0, 15], [15, 15]]);
+        let (mut from, to) = (Polygon::new(&square), Polygon::new(&star));
+        from.set_translate(Point::new(5, -3));
+
+        let start = from.morph_to(&to, 0.0);
+        assert_eq!(start.vertices().len(), star.len());
+        assert_eq!(fill_pixels(&start.as_polygon()), fill_pixels(&from));
+        assert_eq!(fill_pixels(&from.morph_to(&to, 1.0).as_polygon()), fill_pixels(&to));
+        assert_eq!(from.morph_to(&to, 7.0), from.morph_to(&to, 1.0));
+    }
+
+    #[test]
+    fn test_morph_aligns_rotated_and_reversed_rings() {
+        let square = ring(&[[0, 0], [20, 0], [20, 20], [0, 20]]);
+        let rotated = ring(&[[30, 20], [30, 40], [50, 40], [50, 20]]);
+        // without alignment the halfway shape would twist into a bow tie
+        let halfway = Polygon::new(&square).morph_to(&Polygon::new(&rotated), 0.5);
+        let mut expected = Polygon::new(&square);
+        expected.set_translate(Point::new(15, 10));
+        assert_eq!(fill_pixels(&halfway.as_polygon()), fill_pixels(&expected));
+    }
+
+    #[test]
+    fn test_morph_between_very_different_vertex_counts() {
+        let triangle = ring(&[[0, 0], [30, 0], [15, 25]]);
+        let circle = regular(20, 20.0, Point::new(15, 10));
+        let (from, to) = (Polygon::new(&triangle), Polygon::new(&circle));
+        for step in 0..=10 {
+            let shape = from.morph_to(&to, step as f32 / 10.0);
+            assert_eq!(shape.vertices().len(), 20);
+            // every intermediate shape stays solid and between the two inputs
+            let pixels = fill_pixels(&shape.as_polygon());
+            assert!(pixels.len() > 300, "{step}: {shape:?}");
+            assert!(pixels.iter().all(|p| (-5..=35).contains(&p.x) && (-10..=30).contains(&p.y)), "{step}: {shape:?}");
+        }
+        // points inserted along the slanted edges round to the nearest pixel, moving the outline
+        // by at most one pixel
+        let start = from.morph_to(&to, 0.0);
+        let (start_pixels, from_pixels) = (fill_pixels(&start.as_polygon()), fill_pixels(&from));
+        for p in start_pixels.iter().filter(|p| !from_pixels.contains(p)).chain(from_pixels.iter().filter(|p| !start_pixels.contains(p))) {
+            assert!(from.closest_boundary_point(*p).1 <= 1, "{p}");
+        }
+        assert_eq!(to.morph_to(&from, 0.0).vertices(), circle);
+
+        let degenerate = ring(&[[4, 4], [4, 4]]);
+        assert_eq!(Polygon::new(&degenerate).morph_to(&to, 1.0).vertices().len(), 20);
+        assert_eq!(Polygon::new(&[]).morph_to(&to, 0.2).vertices().len(), 0);
+    }
+}
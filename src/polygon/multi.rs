@@ -0,0 +1,198 @@
+//! Sets of polygons handled as one shape.
+
+use core::iter::FromIterator;
+use core::slice;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
+use embedded_graphics::transform::Transform;
+use super::{vertex_bounds, PolygonOwned, PolygonWithHoles};
+use super::affected::AffectedArea;
+
+/// Several polygons, each possibly with holes, drawn and tested as one shape, such as a country
+/// with its islands or the pieces left by clipping.
+///
+/// Every part keeps its own translate offset. Parts are filled one after another rather than
+/// together, so where two overlap the overlap is covered once more instead of cancelling out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MultiPolygon {
+    pub parts: Vec<PolygonWithHoles>,
+}
+
+impl MultiPolygon {
+    pub fn new(parts: Vec<PolygonWithHoles>) -> Self {
+        MultiPolygon { parts }
+    }
+
+    /// Iterates over the parts.
+    pub fn iter(&self) -> slice::Iter<'_, PolygonWithHoles> {
+        self.parts.iter()
+    }
+
+    /// Returns the number of parts.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Returns `true` if there are no parts.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+impl From<PolygonWithHoles> for MultiPolygon {
+    fn from(part: PolygonWithHoles) -> Self {
+        MultiPolygon { parts: vec![part] }
+    }
+}
+
+impl From<PolygonOwned> for MultiPolygon {
+    fn from(polygon: PolygonOwned) -> Self {
+        MultiPolygon::from(PolygonWithHoles::from(polygon))
+    }
+}
+
+impl FromIterator<PolygonWithHoles> for MultiPolygon {
+    fn from_iter<I: IntoIterator<Item = PolygonWithHoles>>(parts: I) -> Self {
+        MultiPolygon { parts: parts.into_iter().collect() }
+    }
+}
+
+impl FromIterator<PolygonOwned> for MultiPolygon {
+    fn from_iter<I: IntoIterator<Item = PolygonOwned>>(parts: I) -> Self {
+        parts.into_iter().map(PolygonWithHoles::from).collect()
+    }
+}
+
+impl IntoIterator for MultiPolygon {
+    type Item = PolygonWithHoles;
+    type IntoIter = alloc::vec::IntoIter<PolygonWithHoles>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MultiPolygon {
+    type Item = &'a PolygonWithHoles;
+    type IntoIter = slice::Iter<'a, PolygonWithHoles>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts.iter()
+    }
+}
+
+impl Dimensions for MultiPolygon {
+    /// Returns the box around every part, or a zero-sized one if no part has any vertices.
+    fn bounding_box(&self) -> Rectangle {
+        let corners = self.parts.iter().filter(|part| !part.exterior.is_empty()).flat_map(|part| {
+            let bounds = part.bounding_box();
+            [bounds.top_left, bounds.bottom_right().unwrap_or(bounds.top_left)]
+        });
+        match corners.clone().next() {
+            Some(_) => vertex_bounds(corners),
+            None => Rectangle::zero(),
+        }
+    }
+}
+
+impl Primitive for MultiPolygon {}
+
+impl ContainsPoint for MultiPolygon {
+    /// Returns `true` if any part contains `point`.
+    fn contains(&self, point: Point) -> bool {
+        self.parts.iter().any(|part| part.contains(point))
+    }
+}
+
+impl Transform for MultiPolygon {
+    /// Returns a copy with every part moved by `by`.
+    fn translate(&self, by: Point) -> Self {
+        let mut moved = self.clone();
+        moved.translate_mut(by);
+        moved
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        for part in self.parts.iter_mut() {
+            part.translate += by;
+        }
+        self
+    }
+}
+
+impl<C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for MultiPolygon {
+    type Color = C;
+    type Output = Rectangle;
+
+    /// Draws every part in turn and returns the affected area as [`Polygon`](super::Polygon)
+    /// does.
+    fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color = Self::Color> {
+        let mut target = AffectedArea::new(target);
+        for part in self.parts.iter() {
+            part.draw_styled(style, &mut target)?;
+        }
+        Ok(target.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Dimensions, Point, Size};
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle, Rectangle};
+    use embedded_graphics::transform::Transform;
+    use crate::polygon::{MultiPolygon, PolygonOwned, PolygonWithHoles};
+    use crate::polygon::tests::{fill_pixels, PixelCollector, ring};
+
+    fn islands() -> MultiPolygon {
+        let mut lagoon = PolygonWithHoles::new(ring(&[[0, 0], [12, 0], [12, 10], [0, 10]]), vec![ring(&[[4, 3], [8, 3], [8, 7], [4, 7]])]);
+        lagoon.translate = Point::new(2, 1);
+        let mut rock = PolygonOwned::new(ring(&[[0, 0], [6, 4], [0, 8]]));
+        rock.set_translate(Point::new(30, 20));
+        MultiPolygon::new(vec![lagoon, rock.into()])
+    }
+
+    fn draw(multi: &MultiPolygon, style: PrimitiveStyle<BinaryColor>) -> (Vec<Point>, Rectangle) {
+        let mut collector = PixelCollector::default();
+        let area = multi.clone().into_styled(style).draw(&mut collector).unwrap();
+        collector.pixels.sort_by_key(|p| (p.y, p.x));
+        (collector.pixels, area)
+    }
+
+    #[test]
+    fn test_both_islands_render() {
+        let multi = islands();
+        let (pixels, area) = draw(&multi, PrimitiveStyle::with_fill(BinaryColor::On));
+        let lagoon = &multi.parts[0];
+        let rock = multi.parts[1].exterior();
+        let mut expected: Vec<Point> = fill_pixels(&lagoon.exterior()).into_iter().filter(|p| lagoon.contains(*p)).chain(fill_pixels(&rock)).collect();
+        expected.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(pixels, expected);
+        assert!(!pixels.contains(&Point::new(8, 6)), "the lagoon stays open");
+        assert_eq!(area, Rectangle::new(Point::new(2, 1), Size::new(35, 28)));
+
+        // the box covers both islands, the same pixels the fill reached
+        let bounds = multi.bounding_box();
+        assert_eq!(bounds, area);
+        assert!(pixels.iter().all(|p| p.x >= bounds.top_left.x && p.y >= bounds.top_left.y && p.x <= 36 && p.y <= 28));
+        assert!(pixels.iter().all(|p| multi.contains(*p)));
+        assert!(!multi.contains(Point::new(20, 15)));
+
+        let (outline, _) = draw(&multi, PrimitiveStyle::with_stroke(BinaryColor::On, 1));
+        assert!(outline.contains(&Point::new(2, 1)) && outline.contains(&Point::new(36, 24)) && outline.contains(&Point::new(6, 4)));
+    }
+
+    #[test]
+    fn test_translate_moves_every_part() {
+        let multi = islands();
+        let moved = multi.translate(Point::new(-5, 7));
+        assert_eq!(moved.iter().map(|part| part.translate).collect::<Vec<_>>(), [Point::new(-3, 8), Point::new(25, 27)]);
+        assert_eq!(moved.bounding_box(), Rectangle::new(Point::new(-3, 8), Size::new(35, 28)));
+        assert_eq!(MultiPolygon::default().bounding_box(), Rectangle::zero());
+        assert_eq!(draw(&MultiPolygon::default(), PrimitiveStyle::with_fill(BinaryColor::On)).0, []);
+    }
+}
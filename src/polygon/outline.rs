@@ -0,0 +1,131 @@
+//! Anti-aliased outlines.
+
+use alloc::collections::BTreeMap;
+use embedded_graphics::Pixel;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888, RgbColor};
+use embedded_graphics::primitives::Line;
+use super::Polygon;
+
+/// Adds the coverage of the pixels along `line`, as drawn by Wu's algorithm, to `coverage`,
+/// keyed by `(y, x)`. Where a pixel is already covered the larger value is kept.
+fn wu_line(line: Line, coverage: &mut BTreeMap<(i32, i32), u8>) {
+    let (dx, dy) = (line.end.x as i64 - line.start.x as i64, line.end.y as i64 - line.start.y as i64);
+    // step along the longer axis, one pixel per step, splitting the other between two pixels
+    let steep = dy.abs() > dx.abs();
+    let (major, minor) = if steep { (dy, dx) } else { (dx, dy) };
+    let steps = major.abs();
+    let (start_major, start_minor) = if steep { (line.start.y, line.start.x) } else { (line.start.x, line.start.y) };
+    for i in 0..=steps {
+        // the exact minor coordinate is start + minor * i / steps, kept as a whole part and a
+        // remainder so long edges do not drift
+        let numerator = minor * i;
+        let (whole, remainder) = if steps == 0 { (0, 0) } else { (numerator.div_euclid(steps), numerator.rem_euclid(steps)) };
+        let along = start_major as i64 + i * major.signum();
+        let across = start_minor as i64 + whole;
+        let far = if steps == 0 { 0 } else { ((remainder * 255 + steps / 2) / steps) as u8 };
+        for (offset, value) in [(0, 255 - far), (1, far)] {
+            if value == 0 {
+                continue;
+            }
+            let (x, y) = if steep { (across + offset, along) } else { (along, across + offset) };
+            let pixel = coverage.entry((y as i32, x as i32)).or_insert(0);
+            *pixel = (*pixel).max(value);
+        }
+    }
+}
+
+/// `background` blended towards `color` by `coverage` out of 255.
+pub(crate) fn blend<C: Into<Rgb888>>(color: C, background: C, coverage: u8) -> Rgb888 {
+    let (color, background): (Rgb888, Rgb888) = (color.into(), background.into());
+    let mix = |to: u8, from: u8| ((from as u32 * (255 - coverage as u32) + to as u32 * coverage as u32 + 127) / 255) as u8;
+    Rgb888::new(mix(color.r(), background.r()), mix(color.g(), background.g()), mix(color.b(), background.b()))
+}
+
+impl<'a> Polygon<'a> {
+    /// Draws the outline with every edge as an anti-aliased line in the style of Wu's algorithm,
+    /// with the translate offset applied.
+    ///
+    /// The target cannot be read back, so each pixel is blended from `background` towards
+    /// `color` by how much the line covers it, and drawing over anything but `background` leaves
+    /// fringes of the wrong shade. Where edges meet or pass close to each other a pixel takes the
+    /// coverage of the edge covering it most, rather than being blended once per edge, so each
+    /// pixel is drawn exactly once. Vertices lie on pixel centers and are drawn in `color`.
+    /// Pixels outside the target are skipped.
+    pub fn draw_outline_aa<C, D>(&self, color: C, background: C, target: &mut D) -> Result<(), D::Error> where C: PixelColor + Into<Rgb888> + From<Rgb888>, D: DrawTarget<Color = C> {
+        let mut coverage = BTreeMap::new();
+        for edge in self.edges() {
+            wu_line(edge, &mut coverage);
+        }
+        let bounds = target.bounding_box();
+        target.draw_iter(coverage.into_iter().map(|((y, x), value)| Pixel(Point::new(x, y), C::from(blend(color, background, value)))).filter(|Pixel(p, _)| bounds.contains(*p)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use embedded_graphics::Pixel;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{Dimensions, Point, Size};
+    use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+    use embedded_graphics::primitives::Rectangle;
+    use crate::polygon::tests::ring;
+    use crate::polygon::Polygon;
+
+    #[derive(Default)]
+    struct Recorder {
+        pixels: Vec<(Point, Rgb888)>,
+    }
+
+    impl Dimensions for Recorder {
+        fn bounding_box(&self) -> Rectangle {
+            Rectangle::new(Point::new(-100, -100), Size::new(200, 200))
+        }
+    }
+
+    impl DrawTarget for Recorder {
+        type Color = Rgb888;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+            self.pixels.extend(pixels.into_iter().map(|Pixel(p, c)| (p, c)));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_shallow_edge_blends_and_corners_draw_once() {
+        // the first edge rises at 30 degrees, 15 / 26 being within 0.1% of tan 30
+        let triangle = ring(&[[0, 0], [26, 15], [0, 15]]);
+        let mut polygon = Polygon::new(&triangle);
+        polygon.set_translate(Point::new(-5, 3));
+        let mut recorder = Recorder::default();
+        polygon.draw_outline_aa(Rgb888::WHITE, Rgb888::BLACK, &mut recorder).unwrap();
+
+        let mut counts: HashMap<Point, usize> = HashMap::new();
+        for (p, _) in recorder.pixels.iter() {
+            *counts.entry(*p).or_default() += 1;
+        }
+        assert!(counts.values().all(|count| *count == 1));
+        let shade: HashMap<Point, u8> = recorder.pixels.iter().map(|(p, c)| (*p, c.r())).collect();
+        for corner in triangle.iter() {
+            assert_eq!(shade[&(*corner + polygon.translate())], 255);
+        }
+
+        // along the sloped edge each column is split between two pixels, in between shades
+        let sloped: Vec<u8> = (1..26).flat_map(|x| (0..=15).map(move |y| Point::new(x, y))).filter_map(|p| shade.get(&(p + polygon.translate())).copied()).filter(|s| *s < 255).collect();
+        assert!(sloped.iter().filter(|s| (64..=192).contains(*s)).count() >= 10, "{sloped:?}");
+        // up to where the bottom edge is near, each column's shades add up to one full pixel
+        for x in 1..24 {
+            let column: u32 = (0..15).filter_map(|y| shade.get(&(Point::new(x, y) + polygon.translate()))).map(|s| *s as u32).sum();
+            assert!((254..=256).contains(&column), "column {x}: {column}");
+        }
+
+        // the axis aligned edges are solid
+        assert!((0..=26).all(|x| shade[&(Point::new(x, 15) + polygon.translate())] == 255));
+        assert!((0..=15).all(|y| shade[&(Point::new(0, y) + polygon.translate())] == 255));
+    }
+}
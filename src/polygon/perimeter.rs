@@ -0,0 +1,75 @@
+//! Walking the polygon outline by arc length.
+
+use embedded_graphics::geometry::Point;
+use super::Polygon;
+use super::math;
+
+impl<'a> Polygon<'a> {
+    /// Returns points spaced `spacing` pixels apart along the closed outline, measured along the
+    /// edges and starting at the first vertex, with the translate offset applied.
+    ///
+    /// When the perimeter is not a multiple of `spacing` the final interval, back to the first
+    /// vertex, is simply shorter; the first vertex is never repeated at the end. Points are
+    /// rounded to the nearest pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spacing` is 0.
+    pub fn points_along_perimeter(&self, spacing: u32) -> impl Iterator<Item = Point> + 'a {
+        assert!(spacing > 0, "perimeter spacing must be positive");
+        let spacing = spacing as f64;
+        self.edges()
+            .scan(0.0, |walked: &mut f64, edge| {
+                let start = *walked;
+                let delta = edge.end - edge.start;
+                let length = math::hypot(delta.x as f64, delta.y as f64);
+                *walked += length;
+                Some((edge, start, length))
+            })
+            .flat_map(move |(edge, start, length)| {
+                // each sample belongs to the edge whose half-open range [start, start + length) holds it
+                let first = math::ceil(start / spacing) as u64;
+                let end = math::ceil((start + length) / spacing) as u64;
+                (first..end).map(move |k| {
+                    let t = (k as f64 * spacing - start) / length;
+                    let delta = edge.end - edge.start;
+                    edge.start + Point::new(math::round(delta.x as f64 * t) as i32, math::round(delta.y as f64 * t) as i32)
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::tests::ring;
+    use crate::polygon::Polygon;
+
+    #[test]
+    fn test_spacing_dividing_the_perimeter() {
+        let vertices = ring(&[[0, 0], [10, 0], [10, 5], [0, 5]]);
+        let points: Vec<Point> = Polygon::new(&vertices).points_along_perimeter(5).collect();
+        assert_eq!(points, ring(&[[0, 0], [5, 0], [10, 0], [10, 5], [5, 5], [0, 5]]));
+        assert_eq!(Polygon::new(&vertices).points_along_perimeter(1).count(), 30);
+        assert_eq!(Polygon::new(&vertices).points_along_perimeter(30).collect::<Vec<_>>(), [Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn test_spacing_leaving_a_shorter_last_interval() {
+        let vertices = ring(&[[0, 0], [10, 0], [10, 5], [0, 5]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(20, -10));
+        let points: Vec<Point> = polygon.points_along_perimeter(7).collect();
+        assert_eq!(points, ring(&[[20, -10], [27, -10], [30, -6], [24, -5], [20, -8]]));
+        assert_eq!(polygon.points_along_perimeter(100).collect::<Vec<_>>(), [Point::new(20, -10)]);
+    }
+
+    #[test]
+    fn test_slanted_edges_and_degenerate_rings() {
+        let vertices = ring(&[[0, 0], [6, 8], [0, 8]]);
+        let points: Vec<Point> = Polygon::new(&vertices).points_along_perimeter(5).collect();
+        assert_eq!(points, ring(&[[0, 0], [3, 4], [6, 8], [1, 8], [0, 4]]));
+        assert_eq!(Polygon::new(&[]).points_along_perimeter(5).count(), 0);
+        assert_eq!(Polygon::new(&ring(&[[3, 3]])).points_along_perimeter(5).count(), 0);
+    }
+}
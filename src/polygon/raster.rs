@@ -0,0 +1,230 @@
+//! Filling straight into packed monochrome framebuffers and occupancy masks.
+
+use embedded_graphics::geometry::Point;
+use super::Polygon;
+
+/// How a 1bpp framebuffer packs pixels into bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Rows of `ceil(width / 8)` bytes, top to bottom, with the leftmost pixel of each byte in its
+    /// most significant bit.
+    MsbFirstRowMajor,
+    /// Pages of 8 rows as used by the SSD1306 and similar controllers: one byte per column of each
+    /// page, left to right, with the top pixel of the column in the least significant bit.
+    Ssd1306Pages,
+}
+
+impl BitOrder {
+    /// Number of bytes a `width` by `height` framebuffer takes in this layout.
+    pub fn buffer_len(self, width: usize, height: usize) -> usize {
+        match self {
+            BitOrder::MsbFirstRowMajor => width.div_ceil(8) * height,
+            BitOrder::Ssd1306Pages => width * height.div_ceil(8),
+        }
+    }
+}
+
+/// Sets or clears the bits `mask` of `byte`.
+fn apply(byte: &mut u8, mask: u8, value: bool) {
+    if value {
+        *byte |= mask;
+    } else {
+        *byte &= !mask;
+    }
+}
+
+/// Sets or clears pixels `start..=end` of an MSB-first row.
+fn fill_row(row: &mut [u8], start: usize, end: usize, value: bool) {
+    let (first, last) = (start / 8, end / 8);
+    let head = 0xffu8 >> (start % 8);
+    let tail = 0xffu8 << (7 - end % 8);
+    if first == last {
+        apply(&mut row[first], head & tail, value);
+    } else {
+        apply(&mut row[first], head, value);
+        row[first + 1..last].fill(if value { 0xff } else { 0 });
+        apply(&mut row[last], tail, value);
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Sets the pixels the fill covers to `value` in a packed 1bpp framebuffer of `width` by
+    /// `height` pixels, leaving every other pixel untouched.
+    ///
+    /// Pixels outside the framebuffer are clipped. In [`BitOrder::MsbFirstRowMajor`] the whole
+    /// bytes in the middle of each span are written at once and only the bytes at its ends are
+    /// masked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than [`BitOrder::buffer_len`].
+    pub fn fill_1bpp(&self, buf: &mut [u8], width: usize, height: usize, order: BitOrder, value: bool) {
+        assert!(buf.len() >= order.buffer_len(width, height), "framebuffer too small for {width}x{height}");
+        for (y, start, end) in self.clipped_spans(width, height, Point::zero()) {
+            match order {
+                BitOrder::MsbFirstRowMajor => {
+                    let stride = width.div_ceil(8);
+                    fill_row(&mut buf[y * stride..(y + 1) * stride], start, end, value);
+                }
+                BitOrder::Ssd1306Pages => {
+                    let page = &mut buf[y / 8 * width..(y / 8 + 1) * width];
+                    page[start..=end].iter_mut().for_each(|column| apply(column, 1 << (y % 8), value));
+                }
+            }
+        }
+    }
+
+    /// Marks the pixels the fill covers as `true` in a row-major `width` by `height` mask whose
+    /// first element is the pixel at `origin`, leaving every other element untouched.
+    ///
+    /// Pixels outside the mask are clipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is shorter than `width * height`.
+    pub fn rasterize_mask(&self, mask: &mut [bool], width: usize, height: usize, origin: Point) {
+        assert!(mask.len() >= width * height, "mask too small for {width}x{height}");
+        for (y, start, end) in self.clipped_spans(width, height, origin) {
+            mask[y * width + start..=y * width + end].fill(true);
+        }
+    }
+
+    /// Like [`rasterize_mask`](Self::rasterize_mask), but sets bits of a mask packed as
+    /// [`BitOrder::MsbFirstRowMajor`], an eighth of the size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is shorter than [`BitOrder::buffer_len`].
+    pub fn rasterize_mask_packed(&self, mask: &mut [u8], width: usize, height: usize, origin: Point) {
+        let order = BitOrder::MsbFirstRowMajor;
+        assert!(mask.len() >= order.buffer_len(width, height), "mask too small for {width}x{height}");
+        let stride = width.div_ceil(8);
+        for (y, start, end) in self.clipped_spans(width, height, origin) {
+            fill_row(&mut mask[y * stride..(y + 1) * stride], start, end, true);
+        }
+    }
+
+    /// The fill spans relative to `origin` as inclusive `(y, start_x, end_x)`, clipped to a
+    /// `width` by `height` area.
+    fn clipped_spans(&self, width: usize, height: usize, origin: Point) -> impl Iterator<Item = (usize, usize, usize)> + 'a {
+        self.spans().filter_map(move |span| {
+            let y = usize::try_from(span.y as i64 - origin.y as i64).ok().filter(|&y| y < height)?;
+            let start = (span.x as i64 - origin.x as i64).max(0);
+            let end = (span.x as i64 - origin.x as i64 + span.len as i64 - 1).min(width as i64 - 1);
+            (start <= end).then_some((y, start as usize, end as usize))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{BitOrder, Polygon};
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn unpack(buf: &[u8], width: usize, height: usize, order: BitOrder) -> Vec<Point> {
+        let mut points = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let set = match order {
+                    BitOrder::MsbFirstRowMajor => buf[y * width.div_ceil(8) + x / 8] & (0x80 >> (x % 8)) != 0,
+                    BitOrder::Ssd1306Pages => buf[y / 8 * width + x] & (1 << (y % 8)) != 0,
+                };
+                if set {
+                    points.push(Point::new(x as i32, y as i32));
+                }
+            }
+        }
+        points
+    }
+
+    fn rendered(polygon: &Polygon) -> Vec<Point> {
+        let mut display = MockDisplay::new();
+        polygon.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+        let mut points = Vec::new();
+        for y in 0..64 {
+            for x in 0..64 {
+                if display.get_pixel(Point::new(x, y)) == Some(BinaryColor::On) {
+                    points.push(Point::new(x, y));
+                }
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_fill_1bpp_matches_rendering() {
+        // spans starting and ending mid-byte, within a single byte, and across several bytes
+        let fixtures = [
+            ring(&[[3, 1], [60, 9], [37, 45], [21, 20], [5, 50]]),
+            ring(&[[9, 2], [13, 2], [13, 30], [9, 30]]),
+            ring(&[[0, 0], [30, 30], [30, 0], [0, 30]]),
+        ];
+        let (width, height) = (61, 53);
+        for vertices in fixtures.iter() {
+            let polygon = Polygon::new(vertices);
+            let expected = rendered(&polygon);
+            for order in [BitOrder::MsbFirstRowMajor, BitOrder::Ssd1306Pages] {
+                let mut buf = vec![0u8; order.buffer_len(width, height)];
+                polygon.fill_1bpp(&mut buf, width, height, order, true);
+                assert_eq!(unpack(&buf, width, height, order), expected, "{order:?} {vertices:?}");
+
+                // clearing leaves exactly the complement set
+                let mut buf = vec![0xffu8; order.buffer_len(width, height)];
+                polygon.fill_1bpp(&mut buf, width, height, order, false);
+                let cleared = unpack(&buf, width, height, order);
+                assert_eq!(cleared.len(), width * height - expected.len());
+                assert!(cleared.iter().all(|p| !expected.contains(p)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_1bpp_clips_to_the_framebuffer() {
+        let vertices = ring(&[[-10, -10], [30, -10], [30, 30], [-10, 30]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(-5, 0));
+        let (width, height) = (20, 12);
+        for order in [BitOrder::MsbFirstRowMajor, BitOrder::Ssd1306Pages] {
+            let mut buf = vec![0u8; order.buffer_len(width, height)];
+            polygon.fill_1bpp(&mut buf, width, height, order, true);
+            assert_eq!(unpack(&buf, width, height, order).len(), width * height);
+        }
+        assert_eq!(BitOrder::MsbFirstRowMajor.buffer_len(20, 12), 36);
+        assert_eq!(BitOrder::Ssd1306Pages.buffer_len(128, 64), 1024);
+    }
+
+    #[test]
+    fn test_rasterize_mask_matches_containment() {
+        let vertices = ring(&[[0, 0], [40, 0], [40, 30], [25, 30], [25, 10], [15, 10], [15, 30], [0, 30]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(-6, 3));
+        let (width, height, origin) = (30, 40, Point::new(-10, -2));
+        let mut mask = vec![false; width * height];
+        polygon.rasterize_mask(&mut mask, width, height, origin);
+        let mut packed = vec![0u8; BitOrder::MsbFirstRowMajor.buffer_len(width, height)];
+        polygon.rasterize_mask_packed(&mut packed, width, height, origin);
+        assert_eq!(unpack(&packed, width, height, BitOrder::MsbFirstRowMajor).iter().map(|p| p.y as usize * width + p.x as usize).collect::<Vec<_>>(),
+            (0..width * height).filter(|&i| mask[i]).collect::<Vec<_>>());
+
+        // exactly the filled pixels inside the mask
+        let inside: Vec<Point> = fill_pixels(&polygon).into_iter()
+            .map(|p| p - origin)
+            .filter(|p| (0..width as i32).contains(&p.x) && (0..height as i32).contains(&p.y))
+            .collect();
+        let marked: Vec<Point> = (0..width * height).filter(|&i| mask[i]).map(|i| Point::new((i % width) as i32, (i / width) as i32)).collect();
+        assert_eq!(marked, inside);
+
+        let mut rng = StdRng::seed_from_u64(352);
+        for _ in 0..500 {
+            let p = Point::new(rng.gen_range(0..width as i32), rng.gen_range(0..height as i32));
+            assert_eq!(mask[p.y as usize * width + p.x as usize], polygon.contains(p + origin), "{p:?}");
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! A slow reference rasterizer to check the fill against.
+//!
+//! Every pixel of the polygon's extent is tested on its own with a plain crossing count, sharing
+//! no code with the scanline fill, so the two can be compared on arbitrary shapes.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Line;
+use super::Polygon;
+
+pub use super::FillRule;
+
+fn on_edge(p: Point, Line { start: a, end: b }: Line) -> bool {
+    let cross = (b.x as i64 - a.x as i64) * (p.y as i64 - a.y as i64) - (p.x as i64 - a.x as i64) * (b.y as i64 - a.y as i64);
+    cross == 0 && p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+/// How many times the outline winds around `p`, counting each edge crossing the ray to the right
+/// of `p` as +1 going down and -1 going up. Each edge covers its rows half-open, so a vertex on the
+/// ray is counted once.
+fn winding(p: Point, edges: impl Iterator<Item = Line>) -> i32 {
+    edges.map(|Line { start: a, end: b }| {
+        let cross = (b.x as i64 - a.x as i64) * (p.y as i64 - a.y as i64) - (p.x as i64 - a.x as i64) * (b.y as i64 - a.y as i64);
+        if a.y <= p.y && p.y < b.y && cross > 0 {
+            1
+        } else if b.y <= p.y && p.y < a.y && cross < 0 {
+            -1
+        } else {
+            0
+        }
+    }).sum()
+}
+
+/// Returns every pixel the polygon covers under `rule`, row by row from the top and left to right
+/// within a row, with the translate offset applied.
+///
+/// As with the fill, pixels exactly on the outline are covered and a lone vertex covers nothing.
+/// This tests every pixel of the polygon's extent against every edge and is only meant for
+/// verification.
+pub fn fill_points(polygon: &Polygon, rule: FillRule) -> impl Iterator<Item = Point> {
+    let corners: Vec<Point> = polygon.vertices().iter().map(|v| *v + polygon.translate()).collect();
+    // every vertex to the next, closing the ring from the last back to the first, and a single
+    // vertex has no outline at all
+    let edges: Vec<Line> = (0..corners.len()).filter(|_| corners.len() > 1).map(|i| Line::new(corners[i], corners[(i + 1) % corners.len()])).collect();
+    let (min, max) = corners.iter().copied().fold((Point::new(i32::MAX, i32::MAX), Point::new(i32::MIN, i32::MIN)), |(min, max), v| {
+        (min.component_min(v), max.component_max(v))
+    });
+    (min.y..=max.y)
+        .flat_map(move |y| (min.x..=max.x).map(move |x| Point::new(x, y)))
+        .filter(move |p| {
+            if edges.iter().any(|edge| on_edge(*p, *edge)) {
+                return true;
+            }
+            let winding = winding(*p, edges.iter().copied());
+            match rule {
+                FillRule::EvenOdd => winding % 2 != 0,
+                FillRule::NonZero => winding != 0,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::Primitive;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{Polygon, PolygonStyle};
+    use crate::polygon::reference::{fill_points, FillRule};
+    use crate::polygon::tests::{fill_pixels, PixelCollector, ring};
+
+    #[test]
+    fn test_fill_matches_reference_on_awkward_shapes() {
+        let fixtures = [
+            // vertices and horizontal edges sharing scanlines
+            ring(&[[16, 20], [28, 10], [28, 16], [22, 10], [10, 10], [10, 16]]),
+            ring(&[[0, 0], [4, 4], [8, 0], [12, 4], [12, 8], [8, 4], [4, 8], [0, 4]]),
+            ring(&[[0, 0], [10, 10], [10, 0], [0, 10]]),
+            ring(&[[0, 0], [1, 7], [2, 0]]),
+            ring(&[[3, 3], [9, 5]]),
+            ring(&[[3, 3]]),
+        ];
+        for vertices in fixtures.iter() {
+            let mut polygon = Polygon::new(vertices);
+            polygon.set_translate(Point::new(-4, 9));
+            assert_eq!(fill_pixels(&polygon), fill_points(&polygon, FillRule::EvenOdd).collect::<Vec<_>>(), "{vertices:?}");
+        }
+    }
+
+    #[test]
+    fn test_fill_matches_reference_on_random_polygons() {
+        let mut rng = StdRng::seed_from_u64(367);
+        for _ in 0..500 {
+            let count = rng.gen_range(3..12);
+            let vertices: Vec<Point> = (0..count).map(|_| Point::new(rng.gen_range(-20..40), rng.gen_range(-20..40))).collect();
+            let polygon = Polygon::new(&vertices);
+            assert_eq!(fill_pixels(&polygon), fill_points(&polygon, FillRule::EvenOdd).collect::<Vec<_>>(), "{vertices:?}");
+        }
+    }
+
+    #[test]
+    fn test_non_zero_fill_matches_reference_on_random_polygons() {
+        let mut rng = StdRng::seed_from_u64(392);
+        for _ in 0..500 {
+            let count = rng.gen_range(2..12);
+            let vertices: Vec<Point> = (0..count).map(|_| Point::new(rng.gen_range(-20..40), rng.gen_range(-20..40))).collect();
+            let polygon = Polygon::new(&vertices);
+            let mut collector = PixelCollector::default();
+            let style = PolygonStyle { fill_rule: FillRule::NonZero, ..PolygonStyle::with_fill(BinaryColor::On) };
+            polygon.into_styled(style).draw(&mut collector).unwrap();
+            collector.pixels.sort_by_key(|p| (p.y, p.x));
+            assert_eq!(collector.pixels, fill_points(&polygon, FillRule::NonZero).collect::<Vec<_>>(), "{vertices:?}");
+        }
+    }
+
+    #[test]
+    fn test_non_zero_fills_the_middle_of_a_star() {
+        let star = ring(&[[10, 0], [16, 20], [0, 7], [20, 7], [4, 20]]);
+        let polygon = Polygon::new(&star);
+        let center = Point::new(10, 11);
+        assert!(!fill_points(&polygon, FillRule::EvenOdd).any(|p| p == center));
+        assert!(fill_points(&polygon, FillRule::NonZero).any(|p| p == center));
+        let even_odd: Vec<Point> = fill_points(&polygon, FillRule::EvenOdd).collect();
+        assert!(even_odd.iter().all(|p| fill_points(&polygon, FillRule::NonZero).any(|q| q == *p)));
+        assert_eq!(fill_points(&Polygon::new(&[]), FillRule::NonZero).count(), 0);
+    }
+}
@@ -0,0 +1,130 @@
+//! Run-length encoding of the fill, for display controllers and e-paper panels that take spans.
+
+use core::fmt;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888, RgbColor};
+use super::{Polygon, Span};
+
+/// Why the fill could not be written as run-length records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RleError {
+    /// The buffer ran out; `written` bytes of complete records were written before it did.
+    InsufficientSpace { written: usize },
+    /// A span does not fit the record fields.
+    OutOfRange(Span),
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RleError::InsufficientSpace { written } => write!(f, "insufficient space after {written} bytes of runs"),
+            RleError::OutOfRange(span) => write!(f, "span at ({}, {}) of length {} does not fit a run record", span.x, span.y, span.len),
+        }
+    }
+}
+
+impl core::error::Error for RleError {}
+
+impl<'a> Polygon<'a> {
+    /// Size of one run record written by [`write_rle`](Self::write_rle).
+    pub const RLE_RECORD_SIZE: usize = 9;
+
+    /// Returns the runs of the fill in row-major order, with runs touching on the same row merged,
+    /// ready to be written with [`write_rle`](Self::write_rle).
+    pub fn to_rle(&self) -> impl Iterator<Item = Span> + 'a {
+        self.spans()
+    }
+
+    /// Writes the runs of the fill in `color` into `buf` and returns the number of bytes written.
+    ///
+    /// Each run is a record of [`RLE_RECORD_SIZE`](Self::RLE_RECORD_SIZE) bytes: `y` and `x` as
+    /// little-endian `i16`, `len` as a little-endian `u16`, then the red, green and blue bytes of
+    /// `color` as [`Rgb888`]. Records are written in the order of [`to_rle`](Self::to_rle), so the
+    /// runs of several polygons, each in its own color, can be written one after another.
+    pub fn write_rle<C>(&self, color: C, buf: &mut [u8]) -> Result<usize, RleError> where C: PixelColor + Into<Rgb888> {
+        let color: Rgb888 = color.into();
+        let mut written = 0;
+        for span in self.to_rle() {
+            let (Ok(y), Ok(x), Ok(len)) = (i16::try_from(span.y), i16::try_from(span.x), u16::try_from(span.len)) else {
+                return Err(RleError::OutOfRange(span));
+            };
+            let record = buf.get_mut(written..written + Self::RLE_RECORD_SIZE).ok_or(RleError::InsufficientSpace { written })?;
+            record[0..2].copy_from_slice(&y.to_le_bytes());
+            record[2..4].copy_from_slice(&x.to_le_bytes());
+            record[4..6].copy_from_slice(&len.to_le_bytes());
+            record[6..9].copy_from_slice(&[color.r(), color.g(), color.b()]);
+            written += Self::RLE_RECORD_SIZE;
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565, Rgb888, RgbColor};
+    use crate::polygon::{Polygon, RleError, Span};
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn decode(bytes: &[u8]) -> Vec<(Point, Rgb888)> {
+        bytes.chunks_exact(Polygon::RLE_RECORD_SIZE).flat_map(|record| {
+            let y = i16::from_le_bytes([record[0], record[1]]) as i32;
+            let x = i16::from_le_bytes([record[2], record[3]]) as i32;
+            let len = u16::from_le_bytes([record[4], record[5]]) as i32;
+            let color = Rgb888::new(record[6], record[7], record[8]);
+            (x..x + len).map(move |x| (Point::new(x, y), color))
+        }).collect()
+    }
+
+    fn points(runs: Vec<(Point, Rgb888)>) -> Vec<Point> {
+        runs.into_iter().map(|(p, _)| p).collect()
+    }
+
+    #[test]
+    fn test_rle_reconstructs_the_fill() {
+        let fixtures = [
+            ring(&[[0, 0], [20, 3], [14, 18], [6, 9], [-4, 15]]),
+            // holes in the middle of rows, and a bowtie whose halves touch at a point
+            ring(&[[0, 0], [12, 0], [12, 12], [0, 12], [0, 0], [4, 4], [8, 4], [8, 8], [4, 8], [4, 4]]),
+            ring(&[[0, 0], [10, 10], [10, 0], [0, 10]]),
+        ];
+        for vertices in fixtures.iter() {
+            let mut polygon = Polygon::new(vertices);
+            polygon.set_translate(Point::new(-7, 30));
+            let spans: Vec<Span> = polygon.to_rle().collect();
+            assert!(spans.windows(2).all(|w| (w[0].y, w[0].x + w[0].len as i32) < (w[1].y, w[1].x)), "{spans:?}");
+
+            let mut buf = [0u8; 1024];
+            let written = polygon.write_rle(Rgb888::new(10, 200, 30), &mut buf).unwrap();
+            assert_eq!(written, spans.len() * Polygon::RLE_RECORD_SIZE);
+            let runs = decode(&buf[..written]);
+            assert!(runs.iter().all(|(_, color)| *color == Rgb888::new(10, 200, 30)));
+            assert_eq!(points(runs), fill_pixels(&polygon));
+        }
+    }
+
+    #[test]
+    fn test_rle_records_carry_each_polygon_color() {
+        // two polygons in different colors written into one list
+        let (left, right) = (ring(&[[0, 0], [3, 0], [3, 2], [0, 2]]), ring(&[[6, 0], [8, 0], [8, 2]]));
+        let mut buf = [0u8; 256];
+        let written = Polygon::new(&left).write_rle(Rgb565::RED, &mut buf).unwrap();
+        let written = written + Polygon::new(&right).write_rle(BinaryColor::On, &mut buf[written..]).unwrap();
+        let runs = decode(&buf[..written]);
+        assert_eq!(runs.len(), fill_pixels(&Polygon::new(&left)).len() + fill_pixels(&Polygon::new(&right)).len());
+        assert!(runs.iter().all(|(p, color)| *color == if p.x < 6 { Rgb888::RED } else { Rgb888::WHITE }), "{runs:?}");
+    }
+
+    #[test]
+    fn test_rle_errors() {
+        let vertices = ring(&[[0, 0], [4, 0], [4, 4], [0, 4]]);
+        let polygon = Polygon::new(&vertices);
+        let mut buf = [0u8; 30];
+        assert_eq!(polygon.write_rle(BinaryColor::On, &mut buf), Err(RleError::InsufficientSpace { written: 27 }));
+        assert_eq!(points(decode(&buf[..27])), fill_pixels(&polygon)[..15]);
+        assert_eq!(polygon.write_rle(BinaryColor::On, &mut []), Err(RleError::InsufficientSpace { written: 0 }));
+
+        let far = ring(&[[40_000, 0], [40_004, 0], [40_004, 4]]);
+        assert_eq!(Polygon::new(&far).write_rle(BinaryColor::On, &mut buf), Err(RleError::OutOfRange(Span { y: 0, x: 40_000, len: 5 })));
+        assert_eq!(Polygon::new(&[]).write_rle(BinaryColor::On, &mut []), Ok(0));
+    }
+}
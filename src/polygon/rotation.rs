@@ -0,0 +1,109 @@
+//! Rotating polygons without floating point.
+
+use embedded_graphics::geometry::Point;
+use super::{Polygon, PolygonOwned};
+
+/// A binary angle, where 65536 is a full turn.
+const QUARTER_TURN: u32 = 1 << 14;
+
+/// `sin` of each 1/256 turn over the first quarter, as Q15 fixed point.
+const QUARTER_SINE: [i32; 65] = [
+    0, 804, 1608, 2411, 3212, 4011, 4808, 5602, 6393, 7180, 7962, 8740, 9512, 10279, 11039, 11793,
+    12540, 13279, 14010, 14733, 15447, 16151, 16846, 17531, 18205, 18868, 19520, 20160, 20788, 21403, 22006, 22595,
+    23170, 23732, 24279, 24812, 25330, 25833, 26320, 26791, 27246, 27684, 28106, 28511, 28899, 29269, 29622, 29957,
+    30274, 30572, 30853, 31114, 31357, 31581, 31786, 31972, 32138, 32286, 32413, 32522, 32610, 32679, 32729, 32758,
+    32768,
+];
+
+/// `sin` of an angle within the first quarter turn, interpolated between table entries.
+fn quarter_sine(angle: u32) -> i32 {
+    let (index, fraction) = ((angle >> 8) as usize, (angle & 0xff) as i32);
+    match QUARTER_SINE.get(index + 1) {
+        Some(next) => QUARTER_SINE[index] + (((next - QUARTER_SINE[index]) * fraction + 0x80) >> 8),
+        None => QUARTER_SINE[index],
+    }
+}
+
+/// `sin` of a binary angle as Q15 fixed point, mirrored from the quarter wave.
+fn sine(angle: u16) -> i32 {
+    let angle = angle as u32;
+    let within = angle % QUARTER_TURN;
+    match angle / QUARTER_TURN {
+        0 => quarter_sine(within),
+        1 => quarter_sine(QUARTER_TURN - within),
+        2 => -quarter_sine(within),
+        _ => -quarter_sine(QUARTER_TURN - within),
+    }
+}
+
+/// `cos` of a binary angle as Q15 fixed point.
+fn cosine(angle: u16) -> i32 {
+    sine(angle.wrapping_add(QUARTER_TURN as u16))
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the polygon rotated by `angle` about `pivot`, using only integer arithmetic so it
+    /// suits targets without an FPU.
+    ///
+    /// The angle is binary, 65536 being a full turn, and turns clockwise on screen as y points
+    /// down. `pivot` is in drawn coordinates, with the translate offset included, and the offset is
+    /// carried over. Sine and cosine come from a quarter wave table in Q15 fixed point, which
+    /// keeps every vertex within a pixel of the exact rotation for shapes a few hundred pixels
+    /// across.
+    pub fn rotate_binary(&self, angle: u16, pivot: Point) -> PolygonOwned {
+        let (sin, cos) = (sine(angle) as i64, cosine(angle) as i64);
+        let translate = self.translate();
+        let vertices = self.vertices().iter().map(|v| {
+            let (dx, dy) = ((v.x as i64 + translate.x as i64 - pivot.x as i64), (v.y as i64 + translate.y as i64 - pivot.y as i64));
+            let x = (dx * cos - dy * sin + (1 << 14)) >> 15;
+            let y = (dx * sin + dy * cos + (1 << 14)) >> 15;
+            Point::new((pivot.x as i64 + x - translate.x as i64) as i32, (pivot.y as i64 + y - translate.y as i64) as i32)
+        }).collect();
+        PolygonOwned::with_translate(vertices, translate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::ring;
+    use super::{cosine, sine};
+
+    #[test]
+    fn test_table_matches_float_trig() {
+        for angle in (0..=u16::MAX).step_by(7) {
+            let radians = angle as f32 / 65536.0 * TAU;
+            assert!((sine(angle) as f32 / 32768.0 - radians.sin()).abs() < 2e-4, "sin {angle}");
+            assert!((cosine(angle) as f32 / 32768.0 - radians.cos()).abs() < 2e-4, "cos {angle}");
+        }
+        assert_eq!([sine(0), sine(16384), sine(32768), sine(49152)], [0, 32768, 0, -32768]);
+    }
+
+    #[test]
+    fn test_rotation_within_a_pixel_of_float() {
+        let vertices = ring(&[[0, 0], [255, 0], [255, 255], [128, 90], [0, 255], [40, 200]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(-30, 12));
+        // about the middle of a shape 256 pixels across, a corner and a point off to the side
+        for pivot in [Point::new(98, 140), Point::new(-30, 12), Point::new(120, -60)] {
+            for angle in (0..=u16::MAX).step_by(331).chain([16384, 32768, 49152, u16::MAX]) {
+                let rotated = polygon.rotate_binary(angle, pivot);
+                assert_eq!(rotated.translate(), polygon.translate());
+                let (sin, cos) = (angle as f32 / 65536.0 * TAU).sin_cos();
+                for (v, r) in vertices.iter().zip(rotated.vertices()) {
+                    let d = *v + polygon.translate() - pivot;
+                    let (x, y) = (d.x as f32 * cos - d.y as f32 * sin, d.x as f32 * sin + d.y as f32 * cos);
+                    let r = *r + polygon.translate() - pivot;
+                    assert!((r.x as f32 - x).abs() <= 1.0 && (r.y as f32 - y).abs() <= 1.0, "{v:?} by {angle} about {pivot:?}: {r:?}");
+                }
+            }
+        }
+
+        // quarter turns are exact, clockwise on screen
+        let square = ring(&[[0, 0], [10, 0], [10, 4], [0, 4]]);
+        let turned = Polygon::new(&square).rotate_binary(16384, Point::zero());
+        assert_eq!(turned.vertices(), ring(&[[0, 0], [0, 10], [-4, 10], [-4, 0]]));
+    }
+}
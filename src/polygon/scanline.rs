@@ -0,0 +1,536 @@
+//! Scanline span generation shared by the polygon fill paths.
+//!
+//! A pixel `(x, y)` is filled when the point `(x, y)` lies inside the polygon or on its boundary.
+//! Sampling the closed polygon means two polygons sharing an edge both cover the pixels on that
+//! edge, so any tiling of a region leaves no cracks, and a sliver with a nonzero area still emits
+//! every pixel its outline passes exactly through.
+//!
+//! Edge intersections are stepped with exact integer arithmetic, so the produced spans do not
+//! depend on float rounding.
+
+use core::cmp::{Ordering, Reverse};
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Line;
+use super::FillRule;
+
+/// A non-horizontal polygon edge stored from its top endpoint to its bottom endpoint.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    top: Point,
+    bottom: Point,
+    /// 1 if the ring runs down the edge, -1 if it runs up.
+    winding: i32,
+}
+
+/// An edge crossing the current scanline.
+///
+/// The exact intersection is `x + rem / dy` with `0 <= rem < dy`.
+#[derive(Debug, Clone, Copy)]
+struct ActiveEdge {
+    x: i64,
+    rem: i64,
+    step: i64,
+    rem_step: i64,
+    dy: i64,
+    bottom: Point,
+    winding: i32,
+}
+
+impl ActiveEdge {
+    /// The edge at scanline `y`, advancing `rows` scanlines at a time.
+    fn stepping(edge: &Edge, y: i32, rows: i64) -> Self {
+        let dx = edge.bottom.x as i64 - edge.top.x as i64;
+        let dy = edge.bottom.y as i64 - edge.top.y as i64;
+        let numerator = edge.top.x as i128 * dy as i128 + dx as i128 * (y as i64 - edge.top.y as i64) as i128;
+        ActiveEdge {
+            x: numerator.div_euclid(dy as i128) as i64,
+            rem: numerator.rem_euclid(dy as i128) as i64,
+            step: (dx * rows).div_euclid(dy),
+            rem_step: (dx * rows).rem_euclid(dy),
+            dy,
+            bottom: edge.bottom,
+            winding: edge.winding,
+        }
+    }
+
+    fn advance(&mut self) {
+        self.x += self.step;
+        self.rem += self.rem_step;
+        if self.rem >= self.dy {
+            self.rem -= self.dy;
+            self.x += 1;
+        }
+    }
+
+    /// Leftmost pixel at or right of the intersection.
+    fn ceil(&self) -> i64 {
+        self.x + (self.rem != 0) as i64
+    }
+
+    /// The intersection as a float, for coverage estimates.
+    fn exact(&self) -> f64 {
+        self.x as f64 + self.rem as f64 / self.dy as f64
+    }
+
+    /// Rightmost pixel at or left of the intersection.
+    fn floor(&self) -> i64 {
+        self.x
+    }
+
+    fn cmp_x(&self, other: &Self) -> Ordering {
+        self.x.cmp(&other.x).then_with(|| (self.rem as i128 * other.dy as i128).cmp(&(other.rem as i128 * self.dy as i128)))
+    }
+}
+
+/// Walks a closed ring of vertices one scanline at a time, top to bottom.
+pub(crate) struct Scanlines {
+    /// Edges not yet reached, the one with the lowest top last.
+    pending: Vec<Edge>,
+    /// Horizontal edges as `(y, min_x, max_x)`, the lowest last.
+    horizontal: Vec<(i32, i32, i32)>,
+    active: Vec<ActiveEdge>,
+    y: i32,
+    max_x: i32,
+    /// Units per pixel of the edge coordinates, only every `scale`th scanline being a pixel row.
+    scale: i32,
+    rule: FillRule,
+    /// Exact edge intersections of the last scanline, sorted, when recording was requested.
+    crossings: Option<Vec<f64>>,
+}
+
+impl Scanlines {
+    /// Builds the edge table from the edges of a closed ring, as produced by `Polygon::edges`.
+    pub(crate) fn new(edges: impl Iterator<Item = Line>) -> Self {
+        let mut pending = Vec::new();
+        let mut horizontal = Vec::new();
+        let mut count = 0;
+        for Line { start, end } in edges {
+            count += 1;
+            match start.y.cmp(&end.y) {
+                Ordering::Less => pending.push(Edge { top: start, bottom: end, winding: 1 }),
+                Ordering::Greater => pending.push(Edge { top: end, bottom: start, winding: -1 }),
+                Ordering::Equal => horizontal.push((start.y, start.x.min(end.x), start.x.max(end.x))),
+            }
+        }
+        // a lone segment is a two-vertex ring, which runs along it and back again
+        if count == 1 {
+            pending.extend(pending.clone().into_iter().map(|edge| Edge { winding: -edge.winding, ..edge }));
+        }
+        pending.sort_by_key(|e| Reverse(e.top.y));
+        horizontal.sort_by_key(|h| Reverse(h.0));
+        let y = pending.iter().map(|e| e.top.y).chain(horizontal.iter().map(|h| h.0)).min().unwrap_or(0);
+        let max_x = pending.iter().map(|e| e.top.x.max(e.bottom.x)).chain(horizontal.iter().map(|h| h.2)).max().unwrap_or(0);
+        Scanlines {
+            pending,
+            horizontal,
+            active: Vec::new(),
+            y,
+            max_x,
+            scale: 1,
+            rule: FillRule::EvenOdd,
+            crossings: None,
+        }
+    }
+
+    /// Builds the edge table from edges in units of `1 / scale` pixels, sampling only the rows and
+    /// columns falling on whole pixels, so spans follow the exact sub-pixel outline.
+    pub(crate) fn subpixel(edges: impl Iterator<Item = Line>, scale: i32) -> Self {
+        let mut scanlines = Scanlines::new(edges);
+        scanlines.scale = scale;
+        scanlines.y = scanlines.pixel_row_at_or_below(scanlines.y as i64);
+        scanlines
+    }
+
+    /// The first scanline at or below `y` that falls on a pixel row.
+    fn pixel_row_at_or_below(&self, y: i64) -> i32 {
+        let scale = self.scale as i64;
+        let row = y.div_euclid(scale) + (y.rem_euclid(scale) != 0) as i64;
+        (row * scale).clamp(i32::MIN as i64 / scale * scale, i32::MAX as i64 / scale * scale) as i32
+    }
+
+    /// Fills by `rule` instead of the even-odd rule.
+    pub(crate) fn with_rule(mut self, rule: FillRule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Records the exact edge intersections of each scanline for [`crossings`](Self::crossings).
+    pub(crate) fn with_crossings(mut self) -> Self {
+        self.crossings = Some(Vec::new());
+        self
+    }
+
+    /// The sorted exact x coordinates where edges cross the scanline last returned by
+    /// [`next_row`](Self::next_row), including the bottom vertices of edges ending on it.
+    pub(crate) fn crossings(&self) -> &[f64] {
+        self.crossings.as_deref().unwrap_or(&[])
+    }
+
+    /// Jumps ahead so that the next scanline returned is the first at or below `y`, computing
+    /// where the edges cross it directly rather than stepping through the rows above.
+    ///
+    /// Only valid before the first call to [`next_row`](Self::next_row).
+    pub(crate) fn skip_to(&mut self, y: i32) {
+        debug_assert!(self.active.is_empty(), "skip_to after rows were produced");
+        let y = self.pixel_row_at_or_below(y as i64 * self.scale as i64);
+        if y <= self.y {
+            return;
+        }
+        while let Some(edge) = self.pending.last().copied().filter(|e| e.top.y < y) {
+            if edge.bottom.y >= y {
+                self.active.push(ActiveEdge::stepping(&edge, y, self.scale as i64));
+            }
+            let _ = self.pending.pop();
+        }
+        while self.horizontal.last().is_some_and(|h| h.0 < y) {
+            let _ = self.horizontal.pop();
+        }
+        self.y = y;
+    }
+
+    /// Writes the inclusive `(start_x, end_x)` spans of the next scanline into `spans`, sorted and
+    /// non-overlapping, and returns its y coordinate. Returns `None` once the ring is exhausted.
+    pub(crate) fn next_row(&mut self, spans: &mut Vec<(i32, i32)>) -> Option<i32> {
+        spans.clear();
+        if self.active.is_empty() {
+            // skip any gap between disconnected parts of the ring
+            let top = self.pending.last().map(|e| e.top.y).into_iter()
+                .chain(self.horizontal.last().map(|h| h.0))
+                .min()?;
+            self.y = self.pixel_row_at_or_below(top as i64);
+        }
+        let y = self.y;
+
+        // with sub-pixel edges some start, or even end, between two pixel rows
+        while let Some(edge) = self.pending.last().copied().filter(|e| e.top.y <= y) {
+            if edge.bottom.y >= y {
+                self.active.push(ActiveEdge::stepping(&edge, y, self.scale as i64));
+            }
+            let _ = self.pending.pop();
+        }
+        while self.horizontal.last().is_some_and(|h| h.0 < y) {
+            let _ = self.horizontal.pop();
+        }
+
+        // edges ending on this scanline no longer cross it, only their bottom vertex is covered
+        if let Some(crossings) = self.crossings.as_mut() {
+            crossings.clear();
+        }
+        self.active.retain(|edge| {
+            // retire edges at or above their bottom, so no edge outlives the last row of the ring
+            if edge.bottom.y <= y {
+                if edge.bottom.y == y {
+                    spans.push((edge.bottom.x, edge.bottom.x));
+                    if let Some(crossings) = self.crossings.as_mut() {
+                        crossings.push(edge.bottom.x as f64 / self.scale as f64);
+                    }
+                }
+                false
+            } else {
+                true
+            }
+        });
+
+        sort_active(&mut self.active);
+        if let Some(crossings) = self.crossings.as_mut() {
+            crossings.extend(self.active.iter().map(|edge| edge.exact() / self.scale as f64));
+            crossings.sort_by(f64::total_cmp);
+        }
+        // each span runs from an edge entering the inside to the next edge leaving it
+        let mut open = None;
+        let mut winding = 0;
+        for edge in self.active.iter() {
+            let inside = match self.rule {
+                FillRule::EvenOdd => {
+                    winding ^= 1;
+                    winding != 0
+                }
+                FillRule::NonZero => {
+                    winding += edge.winding;
+                    winding != 0
+                }
+            };
+            match open {
+                None if inside => open = Some(edge.ceil()),
+                Some(start) if !inside => {
+                    if start <= edge.floor() {
+                        spans.push((start as i32, edge.floor() as i32));
+                    }
+                    open = None;
+                }
+                _ => {}
+            }
+        }
+        // a closed ring always crosses a scanline an even number of times, and its windings
+        // cancel out, but if that is ever violated close the unpaired edge at the polygon's right
+        // extent rather than dropping it
+        if let Some(start) = open {
+            #[cfg(feature = "debug-fill")]
+            eprintln!("unclosed span among {} active edges on scanline {y}", self.active.len());
+            if start <= self.max_x as i64 {
+                spans.push((start as i32, self.max_x));
+            }
+        }
+
+        while let Some(&(_, min_x, max_x)) = self.horizontal.last().filter(|h| h.0 == y) {
+            spans.push((min_x, max_x));
+            let _ = self.horizontal.pop();
+        }
+
+        if self.scale > 1 {
+            // only the whole pixels within each span are covered
+            let scale = self.scale as i64;
+            spans.retain_mut(|(start, end)| {
+                let (first, last) = ((*start as i64 + scale - 1).div_euclid(scale), (*end as i64).div_euclid(scale));
+                (*start, *end) = (first as i32, last as i32);
+                first <= last
+            });
+        }
+        merge_spans(spans);
+        self.active.iter_mut().for_each(ActiveEdge::advance);
+        match y.checked_add(self.scale) {
+            Some(next) => self.y = next,
+            // nothing lies below the last representable scanline
+            None => {
+                self.active.clear();
+                self.pending.clear();
+                self.horizontal.clear();
+            }
+        }
+        Some(y / self.scale)
+    }
+}
+
+/// A horizontal run of `len` filled pixels starting at `(x, y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub y: i32,
+    pub x: i32,
+    pub len: u32,
+}
+
+/// The fill spans of a polygon in row-major order, as returned by
+/// [`Polygon::spans`](super::Polygon::spans).
+pub struct Spans {
+    scanlines: Scanlines,
+    row: Vec<(i32, i32)>,
+    next: usize,
+    y: i32,
+}
+
+impl Spans {
+    pub(crate) fn new(edges: impl Iterator<Item = Line>) -> Self {
+        Spans { scanlines: Scanlines::new(edges), row: Vec::new(), next: 0, y: 0 }
+    }
+
+    /// Jumps ahead to the first row at or below `y`, as [`Scanlines::skip_to`] does.
+    ///
+    /// Only valid before the first span is taken.
+    pub(crate) fn skip_to(&mut self, y: i32) {
+        self.scanlines.skip_to(y);
+    }
+}
+
+impl Iterator for Spans {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Span> {
+        while self.next >= self.row.len() {
+            self.y = self.scanlines.next_row(&mut self.row)?;
+            self.next = 0;
+        }
+        let (start, end) = self.row[self.next];
+        self.next += 1;
+        Some(Span { y: self.y, x: start, len: end.abs_diff(start) + 1 })
+    }
+}
+
+/// Sorts the active edges by their intersection with the scanline.
+///
+/// Edges only change order where they cross, which most shapes never do, and newly reached edges
+/// are few, so an insertion sort is linear on almost every scanline.
+fn sort_active(active: &mut [ActiveEdge]) {
+    for i in 1..active.len() {
+        let mut j = i;
+        while j > 0 && active[j - 1].cmp_x(&active[j]) == Ordering::Greater {
+            active.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Sorts spans and joins any that overlap or touch.
+fn merge_spans(spans: &mut Vec<(i32, i32)>) {
+    spans.sort_unstable();
+    let mut merged = 0;
+    for i in 0..spans.len() {
+        let (start, end) = spans[i];
+        if merged > 0 && start as i64 <= spans[merged - 1].1 as i64 + 1 {
+            spans[merged - 1].1 = spans[merged - 1].1.max(end);
+        } else {
+            spans[merged] = (start, end);
+            merged += 1;
+        }
+    }
+    spans.truncate(merged);
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{Dimensions, Point, Size};
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle, Rectangle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::Polygon;
+    use embedded_graphics_core::Pixel;
+    use crate::polygon::tests::{fill_pixels, ring};
+    use super::{ActiveEdge, Edge, Scanlines};
+
+    #[test]
+    fn test_active_edge_count_is_even_on_every_scanline() {
+        let fixtures = [
+            // vertices landing on scanlines shared with horizontal edges
+            ring(&[[16, 20], [28, 10], [28, 16], [22, 10], [10, 10], [10, 16]]),
+            // local minimum and maximum vertices on the same scanline as pass-through vertices
+            ring(&[[0, 0], [4, 4], [8, 0], [12, 4], [12, 8], [8, 4], [4, 8], [0, 4]]),
+            // self-intersecting bowtie
+            ring(&[[0, 0], [10, 10], [10, 0], [0, 10]]),
+        ];
+        for vertices in fixtures.iter() {
+            let mut scanlines = Scanlines::new(Polygon::new(vertices).edges());
+            let mut spans = Vec::new();
+            while scanlines.next_row(&mut spans).is_some() {
+                assert_eq!(scanlines.active.len() % 2, 0, "{vertices:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_odd_active_edge_count_closes_at_max_x() {
+        let vertices = ring(&[[0, 0], [10, 0], [0, 10]]);
+        let mut scanlines = Scanlines::new(Polygon::new(&vertices).edges());
+        let mut spans = Vec::new();
+        assert_eq!(scanlines.next_row(&mut spans), Some(0));
+        assert_eq!(spans, vec![(0, 10)]);
+
+        // inject an unpaired edge as a parity bug would, the span after it must still be drawn
+        scanlines.active.push(ActiveEdge::stepping(&Edge { top: Point::new(5, 0), bottom: Point::new(5, 10), winding: 1 }, 1, 1));
+        assert_eq!(scanlines.next_row(&mut spans), Some(1));
+        assert_eq!(spans, vec![(0, 5), (9, 10)]);
+    }
+
+    #[test]
+    fn test_active_edges_pair_up_left_to_right() {
+        let comb = ring(&[[0, 0], [30, 0], [30, 10], [24, 10], [24, 4], [18, 4], [18, 10], [12, 10], [12, 4], [6, 4], [6, 10], [0, 10]]);
+        let mut scanlines = Scanlines::new(Polygon::new(&comb).edges());
+        scanlines.skip_to(6);
+        let mut spans = Vec::new();
+        assert_eq!(scanlines.next_row(&mut spans), Some(6));
+        assert_eq!(spans, vec![(0, 6), (12, 18), (24, 30)]);
+
+        // with an unpaired edge the last one left over still closes at the right extent
+        scanlines.active.insert(5, ActiveEdge::stepping(&Edge { top: Point::new(27, 0), bottom: Point::new(27, 10), winding: 1 }, 7, 1));
+        assert_eq!(scanlines.next_row(&mut spans), Some(7));
+        assert_eq!(spans, vec![(0, 6), (12, 18), (24, 27), (30, 30)]);
+    }
+
+    #[test]
+    fn test_crossing_edges_are_reordered() {
+        let fixtures = [
+            ring(&[[0, 0], [40, 30], [40, 0], [0, 30]]),
+            // edges crossing between scanlines, and several crossing on the same row
+            ring(&[[0, 0], [37, 23], [41, 2], [3, 29], [20, -5], [25, 31]]),
+        ];
+        for vertices in fixtures.iter() {
+            let polygon = Polygon::new(vertices);
+            let expected: Vec<Point> = (-5..=31).flat_map(|y| (0..=41).map(move |x| Point::new(x, y)))
+                .filter(|p| polygon.contains(*p))
+                .collect();
+            assert_eq!(fill_pixels(&polygon), expected, "{vertices:?}");
+        }
+    }
+
+    #[test]
+    fn test_near_horizontal_edges_do_not_streak() {
+        // edges running 100 pixels across for each row down, meeting in a notch
+        let vertices = ring(&[[0, 0], [200, 2], [200, 10], [100, 9], [0, 10]]);
+        let polygon = Polygon::new(&vertices);
+        let expected: Vec<Point> = (0..=10).flat_map(|y| (0..=200).map(move |x| Point::new(x, y)))
+            .filter(|p| polygon.contains(*p))
+            .collect();
+        assert_eq!(fill_pixels(&polygon), expected);
+        assert!(!expected.contains(&Point::new(150, 1)) && !expected.contains(&Point::new(100, 10)));
+    }
+
+    /// Counts every pixel drawn to it, inside its bounds or not.
+    struct Attempts {
+        bounds: Rectangle,
+        inside: usize,
+        outside: usize,
+    }
+
+    impl Dimensions for Attempts {
+        fn bounding_box(&self) -> Rectangle {
+            self.bounds
+        }
+    }
+
+    impl DrawTarget for Attempts {
+        type Color = BinaryColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+            for Pixel(p, _) in pixels {
+                if self.bounds.contains(p) {
+                    self.inside += 1;
+                } else {
+                    self.outside += 1;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_malformed_rings_fill_within_a_pixel_budget() {
+        let huge = i32::MAX;
+        let mut fixtures = vec![
+            ring(&[[3, 3], [3, 3], [3, 3]]),
+            ring(&[[0, 0], [10, 10], [0, 0], [10, 10]]),
+            ring(&[[10, 10], [0, 10], [5, 0]]),
+            ring(&[[0, 0], [0, 20], [0, 5], [0, 40]]),
+            ring(&[[-huge, -huge], [huge, -huge], [huge, huge], [-huge - 1, huge]]),
+            ring(&[[0, -huge - 1], [1, huge], [-1, huge]]),
+            ring(&[[huge, huge], [huge, huge - 1], [huge - 1, huge]]),
+            ring(&[[-huge - 1, 0], [huge, 0]]),
+        ];
+        let screen = Rectangle::new(Point::new(-64, -64), Size::new(128, 128));
+        let mut rng = StdRng::seed_from_u64(379);
+        for _ in 0..200 {
+            let count = rng.gen_range(0..9);
+            let range = [8, 1 << 12, huge][rng.gen_range(0..3)];
+            let mut vertices: Vec<Point> = (0..count).map(|_| Point::new(rng.gen_range(-range..=range), rng.gen_range(-range..=range))).collect();
+            if count > 1 && rng.gen_bool(0.3) {
+                vertices.push(vertices[rng.gen_range(0..count)]);
+            }
+            fixtures.push(vertices);
+        }
+        for vertices in fixtures.iter() {
+            for reversed in [false, true] {
+                let mut vertices = vertices.clone();
+                if reversed {
+                    vertices.reverse();
+                }
+                let mut target = Attempts { bounds: screen, inside: 0, outside: 0 };
+                let drawn = Polygon::new(&vertices).into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut target).unwrap();
+                assert_eq!(drawn.intersection(&screen), drawn, "{vertices:?}");
+                assert!(target.inside <= 128 * 128 && target.outside == 0, "{vertices:?}: {} inside, {} outside", target.inside, target.outside);
+            }
+        }
+    }
+}
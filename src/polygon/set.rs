@@ -0,0 +1,275 @@
+//! Many polygons behind a grid index, for finding the ones under a point or a rectangle.
+
+use alloc::borrow::Cow;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::{ContainsPoint, Line, Rectangle};
+use super::{vertex_bounds, Polygon, PolygonOwned};
+use super::distance::segments_touch;
+use super::math;
+
+/// Most cells along each side of the grid.
+const MAX_CELLS: usize = 64;
+
+/// The corners of a polygon's bounding box, both inside it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Bounds {
+    min: Point,
+    max: Point,
+}
+
+impl Bounds {
+    fn union(self, other: Bounds) -> Bounds {
+        Bounds { min: self.min.component_min(other.min), max: self.max.component_max(other.max) }
+    }
+
+    fn within(self, other: Bounds) -> bool {
+        self.union(other) == other
+    }
+}
+
+/// A uniform grid of cells over the union of the bounding boxes, each cell listing the ids whose
+/// bounding box reaches it, in ascending order.
+#[derive(Debug, Default)]
+struct Grid {
+    bounds: Option<Bounds>,
+    /// Cells along each side.
+    side: usize,
+    cell_width: i64,
+    cell_height: i64,
+    cells: Vec<Vec<usize>>,
+    /// How many polygons the grid was sized for.
+    built_for: usize,
+}
+
+impl Grid {
+    fn new(boxes: &[Option<Bounds>]) -> Grid {
+        let Some(bounds) = boxes.iter().flatten().copied().reduce(Bounds::union) else {
+            return Grid { built_for: boxes.len(), ..Grid::default() };
+        };
+        // about one polygon a cell if they were spread evenly
+        let side = (math::ceil(math::sqrt(boxes.len() as f64)) as usize).clamp(1, MAX_CELLS);
+        let (width, height) = (bounds.max.x as i64 - bounds.min.x as i64 + 1, bounds.max.y as i64 - bounds.min.y as i64 + 1);
+        let mut grid = Grid {
+            bounds: Some(bounds),
+            side,
+            cell_width: (width + side as i64 - 1) / side as i64,
+            cell_height: (height + side as i64 - 1) / side as i64,
+            cells: vec![Vec::new(); side * side],
+            built_for: boxes.len(),
+        };
+        for (id, polygon_bounds) in boxes.iter().enumerate() {
+            if let Some(polygon_bounds) = polygon_bounds {
+                grid.insert(id, *polygon_bounds);
+            }
+        }
+        grid
+    }
+
+    /// The columns and rows of the cells `area` reaches, or `None` when it misses the grid.
+    fn cell_range(&self, area: Bounds) -> Option<((usize, usize), (usize, usize))> {
+        let bounds = self.bounds?;
+        if area.max.x < bounds.min.x || area.max.y < bounds.min.y || area.min.x > bounds.max.x || area.min.y > bounds.max.y {
+            return None;
+        }
+        let column = |x: i32| ((x.clamp(bounds.min.x, bounds.max.x) as i64 - bounds.min.x as i64) / self.cell_width) as usize;
+        let row = |y: i32| ((y.clamp(bounds.min.y, bounds.max.y) as i64 - bounds.min.y as i64) / self.cell_height) as usize;
+        Some(((column(area.min.x), column(area.max.x)), (row(area.min.y), row(area.max.y))))
+    }
+
+    /// Adds `id` to every cell its box reaches. Ids arrive in ascending order, which keeps each
+    /// cell sorted.
+    fn insert(&mut self, id: usize, area: Bounds) {
+        if let Some(((left, right), (top, bottom))) = self.cell_range(area) {
+            for row in top..=bottom {
+                for column in left..=right {
+                    self.cells[row * self.side + column].push(id);
+                }
+            }
+        }
+    }
+}
+
+/// Polygons kept together with a grid index over their bounding boxes, answering which of them
+/// lie under a point or overlap a rectangle without testing every one.
+///
+/// Each polygon gets an id when it is added, counting up from 0 in the order they were added.
+/// Queries return ids in ascending order. The polygons may borrow their vertices or own them.
+///
+/// The grid spans the union of the bounding boxes with about one cell per polygon, and is rebuilt
+/// when a polygon reaches outside it or the count has doubled since it was last sized, so adding
+/// polygons one at a time stays cheap overall.
+#[derive(Debug, Default)]
+pub struct PolygonSet<'a> {
+    polygons: Vec<(Point, Cow<'a, [Point]>)>,
+    /// Bounding box corners of each polygon, `None` for one without vertices.
+    boxes: Vec<Option<Bounds>>,
+    grid: Grid,
+}
+
+impl<'a> PolygonSet<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a polygon borrowing its vertices and returns its id.
+    pub fn push(&mut self, polygon: Polygon<'a>) -> usize {
+        self.insert(polygon.translate(), Cow::Borrowed(polygon.vertices()))
+    }
+
+    /// Adds a polygon owning its vertices and returns its id.
+    pub fn push_owned(&mut self, polygon: PolygonOwned) -> usize {
+        let translate = polygon.translate();
+        self.insert(translate, Cow::Owned(polygon.into_vertices()))
+    }
+
+    fn insert(&mut self, translate: Point, vertices: Cow<'a, [Point]>) -> usize {
+        let id = self.polygons.len();
+        let area = (!vertices.is_empty()).then(|| {
+            let bounds = vertex_bounds(vertices.iter().map(|v| *v + translate));
+            Bounds { min: bounds.top_left, max: bounds.bottom_right().unwrap_or(bounds.top_left) }
+        });
+        self.polygons.push((translate, vertices));
+        self.boxes.push(area);
+        match area {
+            Some(area) if self.grid.bounds.is_some_and(|bounds| area.within(bounds)) && self.polygons.len() <= 2 * self.grid.built_for => self.grid.insert(id, area),
+            Some(_) => self.grid = Grid::new(&self.boxes),
+            None => {}
+        }
+        id
+    }
+
+    /// Returns the number of polygons.
+    pub fn len(&self) -> usize {
+        self.polygons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    /// Returns the polygon with the given id, if there is one.
+    pub fn get(&self, id: usize) -> Option<Polygon<'_>> {
+        self.polygons.get(id).map(|(translate, vertices)| Polygon::with_translate(vertices, *translate))
+    }
+
+    /// Returns the polygons in id order.
+    pub fn iter(&self) -> impl Iterator<Item = Polygon<'_>> {
+        self.polygons.iter().map(|(translate, vertices)| Polygon::with_translate(vertices, *translate))
+    }
+
+    /// Returns the ids of the polygons containing `point`, in the sense of
+    /// [`contains`](ContainsPoint::contains), so including their outlines. Only the polygons
+    /// listed in the cell holding the point are tested.
+    pub fn query_point(&self, point: Point) -> impl Iterator<Item = usize> + '_ {
+        let candidates = match self.grid.cell_range(Bounds { min: point, max: point }) {
+            Some(((column, _), (row, _))) => self.grid.cells[row * self.grid.side + column].as_slice(),
+            None => &[],
+        };
+        candidates.iter().copied().filter(move |&id| self.get(id).is_some_and(|polygon| polygon.contains(point)))
+    }
+
+    /// Returns the ids of the polygons sharing at least one point with `rect`, outlines and the
+    /// rectangle's edges included. A rectangle of zero size overlaps nothing. Only the polygons
+    /// listed in the cells the rectangle reaches are tested.
+    pub fn query_rect(&self, rect: Rectangle) -> impl Iterator<Item = usize> + '_ {
+        let mut candidates = Vec::new();
+        if let Some(bottom_right) = rect.bottom_right() {
+            if let Some(((left, right), (top, bottom))) = self.grid.cell_range(Bounds { min: rect.top_left, max: bottom_right }) {
+                for row in top..=bottom {
+                    for column in left..=right {
+                        candidates.extend_from_slice(&self.grid.cells[row * self.grid.side + column]);
+                    }
+                }
+            }
+            candidates.sort_unstable();
+            candidates.dedup();
+        }
+        candidates.into_iter().filter(move |&id| self.get(id).is_some_and(|polygon| overlaps_rect(&polygon, rect)))
+    }
+}
+
+/// Returns `true` if the polygon and the rectangle share a point: either one contains a corner
+/// of the other, or an edge crosses one of the rectangle's sides.
+fn overlaps_rect(polygon: &Polygon, rect: Rectangle) -> bool {
+    let Some(bottom_right) = rect.bottom_right() else {
+        return false;
+    };
+    if polygon.vertices().is_empty() {
+        return false;
+    }
+    let top_left = rect.top_left;
+    let corners = [top_left, Point::new(bottom_right.x, top_left.y), bottom_right, Point::new(top_left.x, bottom_right.y)];
+    let sides = [0, 1, 2, 3].map(|i| Line::new(corners[i], corners[(i + 1) % 4]));
+    polygon.contains(top_left)
+        || polygon.vertices().iter().any(|v| rect.contains(*v + polygon.translate()))
+        || polygon.edges().any(|edge| sides.iter().any(|side| segments_touch(edge, *side)))
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::primitives::{ContainsPoint, Rectangle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{Polygon, PolygonOwned, PolygonSet};
+    use super::overlaps_rect;
+
+    fn random_polygon(rng: &mut StdRng) -> Vec<Point> {
+        let count = rng.gen_range(3..8);
+        let size = rng.gen_range(2..30);
+        (0..count).map(|_| Point::new(rng.gen_range(0..size), rng.gen_range(0..size))).collect()
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(396);
+        let shapes: Vec<Vec<Point>> = (0..150).map(|_| random_polygon(&mut rng)).collect();
+        let mut set = PolygonSet::new();
+        for (i, shape) in shapes.iter().enumerate() {
+            let translate = Point::new(rng.gen_range(-100..100), rng.gen_range(-100..100));
+            // a mix of borrowed and owned, growing the bounds as they are added
+            let id = match i % 3 {
+                0 => set.push_owned(PolygonOwned::with_translate(shape.clone(), translate)),
+                _ => set.push(Polygon::with_translate(shape, translate)),
+            };
+            assert_eq!(id, i);
+
+            if i % 10 == 9 {
+                for _ in 0..200 {
+                    let point = Point::new(rng.gen_range(-110..140), rng.gen_range(-110..140));
+                    let expected: Vec<usize> = set.iter().enumerate().filter(|(_, polygon)| polygon.contains(point)).map(|(id, _)| id).collect();
+                    assert_eq!(set.query_point(point).collect::<Vec<_>>(), expected, "{point:?}");
+
+                    let rect = Rectangle::new(point, Size::new(rng.gen_range(0..40), rng.gen_range(0..40)));
+                    let expected: Vec<usize> = set.iter().enumerate().filter(|(_, polygon)| overlaps_rect(polygon, rect)).map(|(id, _)| id).collect();
+                    assert_eq!(set.query_rect(rect).collect::<Vec<_>>(), expected, "{rect:?}");
+                }
+            }
+        }
+        assert_eq!(set.len(), 150);
+        assert_eq!(set.get(4).unwrap(), Polygon::with_translate(&shapes[4], set.get(4).unwrap().translate()));
+    }
+
+    #[test]
+    fn test_rect_overlap() {
+        let triangle = [Point::new(0, 0), Point::new(20, 0), Point::new(0, 20)];
+        let polygon = Polygon::new(&triangle);
+        let rect = |x, y, w, h| Rectangle::new(Point::new(x, y), Size::new(w, h));
+        // inside, around, crossing an edge, touching a corner
+        assert!(overlaps_rect(&polygon, rect(2, 2, 3, 3)));
+        assert!(overlaps_rect(&polygon, rect(-5, -5, 40, 40)));
+        assert!(overlaps_rect(&polygon, rect(8, 8, 10, 10)));
+        assert!(overlaps_rect(&polygon, rect(20, -3, 4, 4)));
+        // beyond the hypotenuse, and empty
+        assert!(!overlaps_rect(&polygon, rect(12, 12, 5, 5)));
+        assert!(!overlaps_rect(&polygon, rect(2, 2, 0, 3)));
+
+        let mut set = PolygonSet::new();
+        set.push(Polygon::new(&[]));
+        set.push(polygon);
+        assert_eq!(set.query_point(Point::new(1, 1)).collect::<Vec<_>>(), [1]);
+        assert_eq!(set.query_rect(rect(-5, -5, 40, 40)).collect::<Vec<_>>(), [1]);
+        assert_eq!(set.query_point(Point::new(-1, 1)).count(), 0);
+    }
+}
@@ -0,0 +1,109 @@
+//! Drop shadows under polygons.
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, StyledDrawable};
+use super::Polygon;
+use super::affected::AffectedArea;
+use super::diff::{fill_spans, SpanDifference};
+
+/// How [`Polygon::draw_with_shadow`] draws the shadow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShadowStyle<C> {
+    /// How far the shadow is moved from the polygon, such as `(2, 2)` for light from the top left.
+    pub offset: Point,
+    pub color: C,
+    /// How many copies make up the shadow, the `k`th moved by `k` times `offset`, so more than
+    /// one smears it out into a glow. Zero draws no shadow.
+    pub layers: u32,
+}
+
+impl<C> ShadowStyle<C> {
+    /// A shadow of a single layer.
+    pub fn new(offset: Point, color: C) -> Self {
+        ShadowStyle { offset, color, layers: 1 }
+    }
+}
+
+impl<'a> Polygon<'a> {
+    /// Fills the shadow of the polygon in `shadow.color`, then draws the polygon itself in
+    /// `style`, and returns the area affected by both as [`Polygon`]'s `draw_styled` does.
+    ///
+    /// Every layer of the shadow is filled whatever the style, but where `style` fills the polygon
+    /// the shadow pixels the fill would paint over are skipped rather than written twice. For a
+    /// glow that fades out, draw the farther layers first with single layer shadows in lighter
+    /// colors, then this with the nearest.
+    pub fn draw_with_shadow<C, D>(&self, style: PrimitiveStyle<C>, shadow: ShadowStyle<C>, target: &mut D) -> Result<Rectangle, D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+        let mut target = AffectedArea::new(target);
+        // only a plain fill paints the inside, wider strokes draw just the outline
+        let covered = match (style.stroke_width, style.fill_color) {
+            (0, Some(_)) => *self,
+            _ => Polygon::new(&[]),
+        };
+        for layer in (1..=shadow.layers as i32).rev() {
+            let mut cast = *self;
+            cast.set_translate(cast.translate() + shadow.offset * layer);
+            fill_spans(SpanDifference::new(&cast, &covered), shadow.color, &mut target)?;
+        }
+        self.draw_styled(&style, &mut target)?;
+        Ok(target.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565, RgbColor};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+    use crate::polygon::{Polygon, ShadowStyle};
+    use crate::polygon::tests::{fill_pixels, PixelCollector, ring};
+
+    #[test]
+    fn test_shadow_only_outside_the_fill() {
+        let arrow = ring(&[[3, 3], [20, 10], [12, 12], [10, 22]]);
+        let mut polygon = Polygon::new(&arrow);
+        polygon.set_translate(Point::new(4, 1));
+        let mut display = MockDisplay::new();
+        let shadow = ShadowStyle::new(Point::new(2, 2), Rgb565::BLUE);
+        let area = polygon.draw_with_shadow(PrimitiveStyle::with_fill(Rgb565::WHITE), shadow, &mut display).unwrap();
+
+        // the mock display panics on overdraw, so nothing was written twice
+        let fill: BTreeSet<Point> = fill_pixels(&polygon).into_iter().collect();
+        let mut cast = polygon;
+        cast.set_translate(cast.translate() + Point::new(2, 2));
+        let shade: BTreeSet<Point> = fill_pixels(&cast).into_iter().filter(|p| !fill.contains(p)).collect();
+        assert!(!shade.is_empty());
+        for y in 0..64 {
+            for x in 0..64 {
+                let p = Point::new(x, y);
+                let expected = if fill.contains(&p) { Some(Rgb565::WHITE) } else if shade.contains(&p) { Some(Rgb565::BLUE) } else { None };
+                assert_eq!(display.get_pixel(p), expected, "{p:?}");
+            }
+        }
+        assert_eq!(area, Rectangle::new(Point::new(7, 4), Size::new(20, 22)));
+    }
+
+    #[test]
+    fn test_layers_and_outlines() {
+        let square = ring(&[[0, 0], [4, 0], [4, 4], [0, 4]]);
+        let polygon = Polygon::new(&square);
+        let mut collector = PixelCollector::default();
+        let shadow = ShadowStyle { offset: Point::new(1, 0), color: BinaryColor::Off, layers: 3 };
+        polygon.draw_with_shadow(PrimitiveStyle::with_fill(BinaryColor::On), shadow, &mut collector).unwrap();
+        // the layers reach three pixels right of the square, and stop short of the fill
+        let shade: BTreeSet<(i32, i32)> = collector.pixels.iter().take(collector.pixels.len() - 25).map(|p| (p.x, p.y)).collect();
+        assert_eq!(shade, (5..=7).flat_map(|x| (0..=4).map(move |y| (x, y))).collect());
+
+        // an outline leaves the inside open, so the shadow shows through it
+        let mut collector = PixelCollector::default();
+        let shadow = ShadowStyle { layers: 0, ..shadow };
+        polygon.draw_with_shadow(PrimitiveStyle::with_stroke(BinaryColor::On, 1), shadow, &mut collector).unwrap();
+        assert_eq!(collector.pixels.len(), 18, "the outline writes its corners twice");
+        let mut collector = PixelCollector::default();
+        polygon.draw_with_shadow(PrimitiveStyle::with_stroke(BinaryColor::On, 1), ShadowStyle::new(Point::new(1, 1), BinaryColor::Off), &mut collector).unwrap();
+        assert_eq!(collector.pixels.len(), 25 + 18);
+    }
+}
@@ -0,0 +1,448 @@
+//! Constructors approximating common shapes with polygons.
+
+use core::fmt;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::geometry::Point;
+use super::{Polygon, PolygonOwned};
+use super::math;
+
+/// Reasons a shape constructor could not build a polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    /// Fewer than three segments were requested, which cannot enclose an area.
+    TooFewSegments,
+    /// A gear needs at least three teeth.
+    TooFewTeeth,
+    /// A gear's root radius must be smaller than its outer radius.
+    RootNotInsideOuter,
+    /// A gear's teeth must take up more than none and at most all of each pitch.
+    ToothFractionOutOfRange,
+    /// A radar chart needs at least three axes.
+    TooFewAxes,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::TooFewSegments => write!(f, "a closed shape needs at least 3 segments"),
+            ShapeError::TooFewTeeth => write!(f, "a gear needs at least 3 teeth"),
+            ShapeError::RootNotInsideOuter => write!(f, "a gear's root radius must be smaller than its outer radius"),
+            ShapeError::ToothFractionOutOfRange => write!(f, "a gear's tooth fraction must be above 0 and at most 1"),
+            ShapeError::TooFewAxes => write!(f, "a radar chart needs at least 3 axes"),
+        }
+    }
+}
+
+impl core::error::Error for ShapeError {}
+
+impl<'a> Polygon<'a> {
+    /// Returns a polygon with `segments` evenly spaced vertices on the circle of `radius` around
+    /// `center`, starting on the positive x axis.
+    pub fn circle(center: Point, radius: u32, segments: u32) -> Result<PolygonOwned, ShapeError> {
+        Self::ellipse(center, radius, radius, segments, 0.0)
+    }
+
+    /// Returns a polygon with `segments` vertices on the ellipse with radii `rx` and `ry` around
+    /// `center`, evenly spaced in angle and starting on the positive x axis before the whole
+    /// ellipse is rotated by `rotation` radians.
+    ///
+    /// Without rotation the rounded vertices are exactly mirror symmetric about the horizontal
+    /// axis, and also about the vertical axis when `segments` is even.
+    pub fn ellipse(center: Point, rx: u32, ry: u32, segments: u32, rotation: f32) -> Result<PolygonOwned, ShapeError> {
+        if segments < 3 {
+            return Err(ShapeError::TooFewSegments);
+        }
+        let n = segments as u64;
+        let (sin_r, cos_r) = math::sin_cos(rotation as f64);
+        let vertices = (0..n).map(|i| {
+            // fold the angle into the first quadrant so mirrored vertices round identically
+            let j = i.min(n - i);
+            let half_turns = (2 * j).min(n - 2 * j);
+            let (sin, cos) = math::sin_cos(core::f64::consts::PI * half_turns as f64 / n as f64);
+            let x = if 2 * j > n - 2 * j { -cos } else { cos } * rx as f64;
+            let y = if i > j { -sin } else { sin } * ry as f64;
+            center + Point::new(math::round(x * cos_r - y * sin_r) as i32, math::round(x * sin_r + y * cos_r) as i32)
+        }).collect();
+        Ok(PolygonOwned::new(vertices))
+    }
+
+    /// Returns a pie wedge of the circle of `radius` around `center`: the center, then `segments`
+    /// straight segments along the arc from `start` sweeping through `sweep` radians, and back to
+    /// the center.
+    ///
+    /// Positive sweeps turn from the x axis towards the y axis, negative sweeps the other way.
+    /// Sweeps over half a turn give a concave wedge, and a sweep of a full turn or more gives the
+    /// whole [`circle`](Self::circle) with `segments` vertices.
+    pub fn sector(center: Point, radius: u32, start: f32, sweep: f32, segments: u32) -> Result<PolygonOwned, ShapeError> {
+        if math::abs(sweep) >= core::f32::consts::TAU {
+            return Self::circle(center, radius, segments);
+        }
+        if segments < 1 {
+            return Err(ShapeError::TooFewSegments);
+        }
+        let (start, sweep) = (start as f64, sweep as f64);
+        let arc = (0..=segments).map(|i| {
+            let (sin, cos) = math::sin_cos(start + sweep * i as f64 / segments as f64);
+            center + Point::new(math::round(radius as f64 * cos) as i32, math::round(radius as f64 * sin) as i32)
+        });
+        Ok(PolygonOwned::new(core::iter::once(center).chain(arc).collect()))
+    }
+
+    /// Returns an arrow from `from` with its tip exactly on `to`: a shaft of `shaft_width`
+    /// followed by a triangular head `head_width` across and `head_length` long.
+    ///
+    /// The head is clamped to the length of the arrow, leaving a plain triangle when it would be
+    /// longer, and to at least the shaft width so its barbs never fold back into the shaft. A
+    /// zero-length arrow is just its tip.
+    pub fn arrow(from: Point, to: Point, shaft_width: u32, head_width: u32, head_length: u32) -> PolygonOwned {
+        let delta = to - from;
+        let length = math::hypot(delta.x as f64, delta.y as f64);
+        if length == 0.0 {
+            return PolygonOwned::new(vec![to]);
+        }
+        let (ux, uy) = (delta.x as f64 / length, delta.y as f64 / length);
+        // offsets from an integer base round the same way on both sides of the axis
+        let at = |along: f64, across: f64| from + Point::new(math::round(ux * along - uy * across) as i32, math::round(uy * along + ux * across) as i32);
+        let head_base = length - (head_length as f64).min(length);
+        let (shaft, head) = (shaft_width as f64 / 2.0, (head_width.max(shaft_width)) as f64 / 2.0);
+        let vertices = if head_base == 0.0 {
+            vec![at(0.0, -head), to, at(0.0, head)]
+        } else {
+            vec![at(0.0, -shaft), at(head_base, -shaft), at(head_base, -head), to, at(head_base, head), at(head_base, shaft), at(0.0, shaft)]
+        };
+        PolygonOwned::new(vertices)
+    }
+
+    /// Returns a chevron fitting a `size` square around `center`, pointing `direction` radians
+    /// from the positive x axis, with arms `thickness` pixels thick along that direction.
+    ///
+    /// The thickness is clamped to `size`, at which point the chevron becomes a solid triangle.
+    pub fn chevron(center: Point, size: u32, direction: f32, thickness: u32) -> PolygonOwned {
+        let (uy, ux) = math::sin_cos(direction as f64);
+        let at = |along: f64, across: f64| center + Point::new(math::round(ux * along - uy * across) as i32, math::round(uy * along + ux * across) as i32);
+        let (half, thickness) = (size as f64 / 2.0, thickness.min(size) as f64);
+        let vertices = if thickness >= size as f64 {
+            vec![at(half, 0.0), at(-half, half), at(-half, -half)]
+        } else {
+            vec![at(half, 0.0), at(-half, half), at(-half + thickness, half), at(half - thickness, 0.0), at(-half + thickness, -half), at(-half, -half)]
+        };
+        PolygonOwned::new(vertices)
+    }
+
+    /// Returns a gear around `center` with `teeth` trapezoidal teeth reaching from `root_radius`
+    /// out to `outer_radius`, the whole gear turned by `rotation` radians so it can spin from one
+    /// frame to the next.
+    ///
+    /// Each tooth takes up `tooth_fraction` of its pitch, the angle from one tooth to the next, at
+    /// the root and half that at the tip, with straight flanks between. The first tooth is centered
+    /// on the positive x axis before rotation.
+    pub fn gear(center: Point, outer_radius: u32, root_radius: u32, teeth: u32, tooth_fraction: f32, rotation: f32) -> Result<PolygonOwned, ShapeError> {
+        if teeth < 3 {
+            return Err(ShapeError::TooFewTeeth);
+        }
+        if root_radius >= outer_radius {
+            return Err(ShapeError::RootNotInsideOuter);
+        }
+        if !(tooth_fraction > 0.0 && tooth_fraction <= 1.0) {
+            return Err(ShapeError::ToothFractionOutOfRange);
+        }
+        let pitch = core::f64::consts::TAU / teeth as f64;
+        let half_root = pitch * tooth_fraction as f64 / 2.0;
+        let at = |angle: f64, radius: u32| {
+            let (sin, cos) = math::sin_cos(angle);
+            center + Point::new(math::round(radius as f64 * cos) as i32, math::round(radius as f64 * sin) as i32)
+        };
+        let mut vertices: Vec<Point> = (0..teeth).flat_map(|i| {
+            let middle = rotation as f64 + pitch * i as f64;
+            [
+                at(middle - half_root, root_radius),
+                at(middle - half_root / 2.0, outer_radius),
+                at(middle + half_root / 2.0, outer_radius),
+                at(middle + half_root, root_radius),
+            ]
+        }).collect();
+        // teeth filling the whole pitch meet at the root
+        vertices.dedup();
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            let _ = vertices.pop();
+        }
+        Ok(PolygonOwned::new(vertices))
+    }
+
+    /// Returns a radar chart outline around `center` with one vertex per value, on evenly spaced
+    /// axes `radius` long. Each value is the fraction of its axis out from the center, clamped to
+    /// `0..=1`.
+    ///
+    /// The first axis is `rotation` radians from the positive x axis and the rest follow towards
+    /// the y axis, so a rotation of -π/2 puts it straight up. Values of 0 land on the center, which
+    /// is kept only once where several such values follow each other. The axis ends are returned by
+    /// [`radar_axes`](Self::radar_axes).
+    pub fn radar(center: Point, radius: u32, values: &[f32], rotation: f32) -> Result<PolygonOwned, ShapeError> {
+        if values.len() < 3 {
+            return Err(ShapeError::TooFewAxes);
+        }
+        let mut vertices: Vec<Point> = values.iter().enumerate().map(|(i, value)| {
+            // NaN clamps to the center as well
+            let value = if *value > 0.0 { value.min(1.0) as f64 } else { 0.0 };
+            radar_point(center, radius as f64 * value, rotation, i, values.len())
+        }).collect();
+        vertices.dedup();
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            let _ = vertices.pop();
+        }
+        Ok(PolygonOwned::new(vertices))
+    }
+
+    /// Returns the ends of the `axes` axes of a [`radar`](Self::radar) chart with the same
+    /// `center`, `radius` and `rotation`, for drawing its grid: the outline of all values 1 and a
+    /// line from the center to each end.
+    pub fn radar_axes(center: Point, radius: u32, axes: usize, rotation: f32) -> Vec<Point> {
+        (0..axes).map(|i| radar_point(center, radius as f64, rotation, i, axes)).collect()
+    }
+}
+
+/// The point `distance` out from `center` along axis `i` of `axes`.
+fn radar_point(center: Point, distance: f64, rotation: f32, i: usize, axes: usize) -> Point {
+    let (sin, cos) = math::sin_cos(rotation as f64 + core::f64::consts::TAU * i as f64 / axes as f64);
+    center + Point::new(math::round(distance * cos) as i32, math::round(distance * sin) as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Circle, ContainsPoint, PointsIter, Primitive, PrimitiveStyle, Rectangle};
+    use crate::polygon::{Polygon, ShapeError};
+    use crate::polygon::tests::fill_pixels;
+
+    #[test]
+    fn test_circle_matches_circle_primitive() {
+        let center = Point::new(40, 30);
+        let circle = Polygon::circle(center, 20, 64).unwrap();
+        assert_eq!(circle.vertices().len(), 64);
+        let filled: HashSet<Point> = fill_pixels(&circle.as_polygon()).into_iter().collect();
+        let reference: HashSet<Point> = Circle::with_center(center, 41).points().collect();
+        // the two only disagree about pixels right on the outline
+        for p in filled.symmetric_difference(&reference) {
+            assert!(circle.as_polygon().closest_boundary_point(*p).1 <= 1, "{p}");
+        }
+        assert!(filled.symmetric_difference(&reference).count() < reference.len() / 20);
+    }
+
+    #[test]
+    fn test_axis_aligned_ellipse_is_symmetric() {
+        let center = Point::new(7, -3);
+        for segments in [12, 30, 64, 99] {
+            let ellipse = Polygon::ellipse(center, 31, 13, segments, 0.0).unwrap();
+            let filled: HashSet<Point> = fill_pixels(&ellipse.as_polygon()).into_iter().collect();
+            for p in filled.iter() {
+                let offset = *p - center;
+                assert!(filled.contains(&(center + Point::new(offset.x, -offset.y))), "{segments}: {p}");
+                if segments % 2 == 0 {
+                    assert!(filled.contains(&(center + Point::new(-offset.x, offset.y))), "{segments}: {p}");
+                }
+            }
+        }
+        let rotated = Polygon::ellipse(Point::zero(), 20, 5, 4, std::f32::consts::FRAC_PI_2).unwrap();
+        assert_eq!(rotated.vertices(), [Point::new(0, 20), Point::new(-5, 0), Point::new(0, -20), Point::new(5, 0)]);
+    }
+
+    /// Checks that `wedge` fills the pixels of `circle` selected by `keep`, apart from pixels on
+    /// either outline.
+    fn assert_wedge_of_circle(wedge: &Polygon, circle: &Polygon, keep: impl Fn(Point) -> bool) {
+        let filled: HashSet<Point> = fill_pixels(wedge).into_iter().collect();
+        let expected: HashSet<Point> = fill_pixels(circle).into_iter().filter(|p| keep(*p)).collect();
+        for p in filled.symmetric_difference(&expected) {
+            let on_outline = wedge.closest_boundary_point(*p).1 <= 1 || circle.closest_boundary_point(*p).1 <= 1;
+            assert!(on_outline, "{p}");
+        }
+    }
+
+    #[test]
+    fn test_sector_quadrants() {
+        use std::f32::consts::{FRAC_PI_2, PI, TAU};
+        let center = Point::new(30, 30);
+        let circle = Polygon::circle(center, 20, 64).unwrap();
+        let circle = circle.as_polygon();
+
+        let quarter = Polygon::sector(center, 20, 0.0, FRAC_PI_2, 16).unwrap();
+        assert_eq!(quarter.vertices().len(), 18);
+        assert_wedge_of_circle(&quarter.as_polygon(), &circle, |p| p.x >= center.x && p.y >= center.y);
+        let quarter = fill_pixels(&quarter.as_polygon());
+        assert!(quarter.len() * 4 >= fill_pixels(&circle).len());
+
+        let reversed = Polygon::sector(center, 20, 0.0, -FRAC_PI_2, 16).unwrap();
+        assert_wedge_of_circle(&reversed.as_polygon(), &circle, |p| p.x >= center.x && p.y <= center.y);
+
+        // three quarters are concave at the center
+        let pacman = Polygon::sector(center, 20, PI, 3.0 * FRAC_PI_2, 48).unwrap();
+        assert_wedge_of_circle(&pacman.as_polygon(), &circle, |p| !(p.x < center.x && p.y > center.y));
+
+        assert_eq!(Polygon::sector(center, 20, 1.0, TAU, 64).unwrap(), Polygon::circle(center, 20, 64).unwrap());
+        assert_eq!(Polygon::sector(center, 20, 1.0, -7.0, 64).unwrap(), Polygon::circle(center, 20, 64).unwrap());
+    }
+
+    /// Reflects `p` about the line through `origin` along `(dx, dy)`, exact for axis-aligned and
+    /// diagonal lines.
+    fn reflect(p: Point, origin: Point, (dx, dy): (i32, i32)) -> Point {
+        let offset = p - origin;
+        let (dx, dy, length_squared) = (dx as f64, dy as f64, (dx * dx + dy * dy) as f64);
+        let along = (offset.x as f64 * dx + offset.y as f64 * dy) / length_squared;
+        origin + Point::new((2.0 * along * dx - offset.x as f64).round() as i32, (2.0 * along * dy - offset.y as f64).round() as i32)
+    }
+
+    fn assert_symmetric(polygon: &Polygon, origin: Point, axis: (i32, i32)) {
+        for v in polygon.vertices() {
+            assert!(polygon.vertices().contains(&reflect(*v, origin, axis)), "{v} in {:?}", polygon.vertices());
+        }
+        let filled: HashSet<Point> = fill_pixels(polygon).into_iter().collect();
+        assert!(filled.iter().all(|p| filled.contains(&reflect(*p, origin, axis))));
+    }
+
+    #[test]
+    fn test_arrow_at_right_angles_and_diagonals() {
+        let from = Point::new(20, 20);
+        for direction in [(1, 0), (0, 1), (1, 1), (-1, 1), (-1, 0)] {
+            let to = from + Point::new(direction.0, direction.1) * 30;
+            let arrow = Polygon::arrow(from, to, 5, 14, 10);
+            let polygon = arrow.as_polygon();
+            assert_eq!(polygon.vertices().len(), 7);
+            assert_eq!(polygon.vertices()[3], to);
+            assert_symmetric(&polygon, from, direction);
+            assert!(polygon.contains(from) && polygon.contains(from + Point::new(direction.0, direction.1) * 15));
+            // a sound outline covers every pixel the exact containment test does
+            assert_eq!(fill_pixels(&polygon).len(), Rectangle::new(Point::new(-20, -20), Size::new(90, 90)).points().filter(|p| polygon.contains(*p)).count());
+        }
+    }
+
+    #[test]
+    fn test_arrow_clamps_degenerate_parameters() {
+        let (from, to) = (Point::new(0, 0), Point::new(8, 0));
+        assert_eq!(Polygon::arrow(from, to, 2, 6, 20).vertices(), [Point::new(0, -3), to, Point::new(0, 3)]);
+        // a head narrower than the shaft is widened to it
+        assert_eq!(Polygon::arrow(from, to, 6, 2, 4).vertices(), [
+            Point::new(0, -3), Point::new(4, -3), Point::new(4, -3), to, Point::new(4, 3), Point::new(4, 3), Point::new(0, 3),
+        ]);
+        assert_eq!(Polygon::arrow(to, to, 2, 6, 4).vertices(), [to]);
+    }
+
+    #[test]
+    fn test_chevron() {
+        let center = Point::new(10, 10);
+        let chevron = Polygon::chevron(center, 12, 0.0, 4);
+        assert_eq!(chevron.vertices(), [Point::new(16, 10), Point::new(4, 16), Point::new(8, 16), Point::new(12, 10), Point::new(8, 4), Point::new(4, 4)]);
+        assert_symmetric(&chevron.as_polygon(), center, (1, 0));
+        assert!(!chevron.as_polygon().contains(center));
+
+        let down = Polygon::chevron(center, 12, std::f32::consts::FRAC_PI_2, 4);
+        assert_eq!(down.vertices()[0], Point::new(10, 16));
+        assert_symmetric(&down.as_polygon(), center, (0, 1));
+        let diagonal = Polygon::chevron(center, 12, std::f32::consts::FRAC_PI_4, 3);
+        assert_symmetric(&diagonal.as_polygon(), center, (1, 1));
+        assert_eq!(Polygon::chevron(center, 12, 0.0, 40).vertices().len(), 3);
+    }
+
+    #[test]
+    fn test_gear() {
+        let gear = Polygon::gear(Point::new(10, 10), 10, 6, 8, 0.5, 0.0).unwrap();
+        assert_eq!(gear.vertices().len(), 32);
+        let mut display = MockDisplay::new();
+        gear.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut display).unwrap();
+        display.assert_pattern(&[
+            "         ###         ",
+            "         ###         ",
+            "    #    ###    #    ",
+            "   ###   ###   ###   ",
+            "  #####  ###  #####  ",
+            "   ###############   ",
+            "    #############    ",
+            "     ###########     ",
+            "     ###########     ",
+            "#####################",
+            "#####################",
+            "#####################",
+            "     ###########     ",
+            "     ###########     ",
+            "    #############    ",
+            "   ###############   ",
+            "  #####  ###  #####  ",
+            "   ###   ###   ###   ",
+            "    #    ###    #    ",
+            "         ###         ",
+            "         ###         ",
+        ]);
+
+        // turning by one pitch lands every tooth where the next one was
+        let pitch = std::f32::consts::TAU / 8.0;
+        for rotation in [0.0, 0.3] {
+            let gear = Polygon::gear(Point::new(30, 30), 24, 18, 8, 0.6, rotation).unwrap();
+            let turned = Polygon::gear(Point::new(30, 30), 24, 18, 8, 0.6, rotation + pitch).unwrap();
+            assert_eq!(fill_pixels(&gear.as_polygon()), fill_pixels(&turned.as_polygon()));
+        }
+
+        // teeth taking up the whole pitch share their root corners
+        assert_eq!(Polygon::gear(Point::zero(), 20, 10, 6, 1.0, 0.0).unwrap().vertices().len(), 18);
+    }
+
+    #[test]
+    fn test_gear_parameters_are_checked() {
+        assert_eq!(Polygon::gear(Point::zero(), 10, 6, 2, 0.5, 0.0), Err(ShapeError::TooFewTeeth));
+        assert_eq!(Polygon::gear(Point::zero(), 10, 10, 8, 0.5, 0.0), Err(ShapeError::RootNotInsideOuter));
+        assert_eq!(Polygon::gear(Point::zero(), 10, 12, 8, 0.5, 0.0), Err(ShapeError::RootNotInsideOuter));
+        for fraction in [0.0, -0.5, 1.5, f32::NAN] {
+            assert_eq!(Polygon::gear(Point::zero(), 10, 6, 8, fraction, 0.0), Err(ShapeError::ToothFractionOutOfRange));
+        }
+    }
+
+    #[test]
+    fn test_too_few_segments() {
+        assert_eq!(Polygon::circle(Point::zero(), 10, 2), Err(ShapeError::TooFewSegments));
+        assert_eq!(Polygon::ellipse(Point::zero(), 10, 4, 0, 1.0), Err(ShapeError::TooFewSegments));
+        assert_eq!(Polygon::circle(Point::new(1, 1), 10, 3).unwrap().vertices().len(), 3);
+        assert_eq!(Polygon::sector(Point::zero(), 10, 0.0, 1.0, 0), Err(ShapeError::TooFewSegments));
+        assert_eq!(Polygon::sector(Point::zero(), 10, 0.0, 1.0, 1).unwrap().vertices().len(), 3);
+    }
+
+    #[test]
+    fn test_radar_vertex_placement() {
+        let center = Point::new(30, 30);
+        let up = -std::f32::consts::FRAC_PI_2;
+        // a triangle of full values pointing up
+        assert_eq!(Polygon::radar(center, 20, &[1.0; 3], up).unwrap().vertices(), [Point::new(30, 10), Point::new(47, 40), Point::new(13, 40)]);
+        assert_eq!(Polygon::radar_axes(center, 20, 3, up), [Point::new(30, 10), Point::new(47, 40), Point::new(13, 40)]);
+
+        // five axes, each value its fraction of the radius along its own axis
+        let values = [1.0, 0.5, 0.25, 0.75, 0.6];
+        let radar = Polygon::radar(center, 20, &values, up).unwrap();
+        let axes = Polygon::radar_axes(center, 20, 5, up);
+        for ((vertex, end), value) in radar.vertices().iter().zip(&axes).zip(values) {
+            let (along, full) = (*vertex - center, *end - center);
+            let expected = (full.x as f32 * value, full.y as f32 * value);
+            assert!((along.x as f32 - expected.0).abs() <= 1.0 && (along.y as f32 - expected.1).abs() <= 1.0, "{vertex:?} {end:?} {value}");
+        }
+
+        // eight axes fall on the compass points, out-of-range values clamp
+        let radar = Polygon::radar(center, 10, &[1.0, 2.0, 0.5, -1.0, 1.0, 0.3, 0.5, 1.0], 0.0).unwrap();
+        assert_eq!(radar.vertices(), [Point::new(40, 30), Point::new(37, 37), Point::new(30, 35), Point::new(30, 30), Point::new(20, 30), Point::new(28, 28), Point::new(30, 25), Point::new(37, 23)]);
+        assert_eq!(Polygon::radar_axes(center, 10, 8, 0.0)[2], Point::new(30, 40));
+        assert_eq!(Polygon::radar(center, 10, &[1.0, 1.0], 0.0), Err(ShapeError::TooFewAxes));
+    }
+
+    #[test]
+    fn test_radar_zero_values_collapse_to_the_center() {
+        let center = Point::new(20, 20);
+        let radar = Polygon::radar(center, 15, &[1.0, 0.0, 0.0, 1.0, f32::NAN, 0.0, 1.0, 0.8], 0.0).unwrap();
+        assert_eq!(radar.vertices().iter().filter(|v| **v == center).count(), 2);
+        assert!(radar.vertices().windows(2).all(|pair| pair[0] != pair[1]));
+        let pixels = fill_pixels(&radar.as_polygon());
+        assert!(pixels.contains(&center) && pixels.contains(&Point::new(35, 20)));
+
+        // zeros at both ends of the list are one vertex as well
+        let wrapped = Polygon::radar(center, 15, &[0.0, 1.0, 1.0, 1.0, 0.0], 0.0).unwrap();
+        assert_eq!(wrapped.vertices().len(), 4);
+        assert_eq!(Polygon::radar(center, 15, &[0.0; 5], 0.0).unwrap().vertices(), [center]);
+    }
+}
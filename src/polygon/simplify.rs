@@ -0,0 +1,181 @@
+//! Ramer–Douglas–Peucker simplification of closed rings.
+
+use alloc::{vec, vec::Vec};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, StyledDrawable};
+use super::{Polygon, PolygonOwned, PolygonWithHoles};
+use super::math;
+
+/// Squared distance from `p` to the segment `a`-`b`.
+fn segment_distance_squared(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = ((b.x - a.x) as f64, (b.y - a.y) as f64);
+    let (px, py) = ((p.x - a.x) as f64, (p.y - a.y) as f64);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 { 0.0 } else { ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0) };
+    let (ex, ey) = (px - t * dx, py - t * dy);
+    ex * ex + ey * ey
+}
+
+/// Simplifies a closed ring, keeping every vertex farther than `epsilon` from the simplified
+/// outline.
+pub(crate) fn simplify_ring(vertices: &[Point], epsilon: f32) -> Vec<Point> {
+    if vertices.len() < 3 {
+        return vertices.to_vec();
+    }
+    // split the ring at the vertex farthest from the first, which both halves keep
+    let far = (1..vertices.len())
+        .max_by_key(|&i| {
+            let delta = vertices[i] - vertices[0];
+            (delta.x as i64).pow(2) + (delta.y as i64).pow(2)
+        })
+        .unwrap_or(0);
+    if vertices[far] == vertices[0] {
+        // every vertex is the same point, which still covers its pixel
+        return vec![vertices[0]; 2];
+    }
+    let closed: Vec<Point> = vertices.iter().chain(vertices.first()).copied().collect();
+    let tolerance = math::powi(epsilon.max(0.0) as f64, 2);
+    let mut keep = vec![false; closed.len()];
+    let mut stack = vec![(0, far), (far, vertices.len())];
+    while let Some((start, end)) = stack.pop() {
+        keep[start] = true;
+        keep[end] = true;
+        let farthest = (start + 1..end)
+            .map(|i| (i, segment_distance_squared(closed[i], closed[start], closed[end])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, _)) = farthest.filter(|&(_, distance)| distance > tolerance) {
+            stack.push((start, i));
+            stack.push((i, end));
+        }
+    }
+    vertices.iter().zip(keep).filter(|(_, keep)| *keep).map(|(v, _)| *v).collect()
+}
+
+impl<'a> Polygon<'a> {
+    /// Simplifies the outline with the Ramer–Douglas–Peucker algorithm, dropping vertices that
+    /// lie within `epsilon` pixels of the simplified outline.
+    ///
+    /// An `epsilon` of 0 drops only vertices in the middle of straight runs. The translate offset
+    /// is carried over.
+    pub fn simplify(&self, epsilon: f32) -> PolygonOwned {
+        PolygonOwned::with_translate(simplify_ring(self.vertices(), epsilon), self.translate())
+    }
+}
+
+impl PolygonOwned {
+    /// Draws the outline simplified as by [`simplify`](Polygon::simplify) with an epsilon of
+    /// `max_error`, in pixels, and returns the affected area.
+    ///
+    /// Meant for detailed outlines drawn small, such as a coastline of thousands of vertices that
+    /// ends up a few dozen pixels tall, where every vertex costs time but most land on the same
+    /// pixels. No pixel moves by more than about `max_error` from where the full outline puts it.
+    ///
+    /// The simplified vertices are kept and reused for as long as the same `max_error` is asked
+    /// for; a different error simplifies again, and changing the vertices drops them. The
+    /// translate offset does not affect them.
+    pub fn draw_simplified<C, D>(&self, style: &PrimitiveStyle<C>, max_error: f32, target: &mut D) -> Result<Rectangle, D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+        let mut cached = self.simplified.borrow_mut();
+        if cached.as_ref().map(|(bits, _)| *bits) != Some(max_error.to_bits()) {
+            *cached = Some((max_error.to_bits(), simplify_ring(self.vertices(), max_error)));
+        }
+        let vertices = cached.as_ref().map_or(&[][..], |(_, vertices)| vertices);
+        Polygon::with_translate(vertices, self.translate()).draw_styled(style, target)
+    }
+
+    /// Returns the number of vertices the last [`draw_simplified`](Self::draw_simplified) drew,
+    /// or `None` if none are cached.
+    pub fn simplified_len(&self) -> Option<usize> {
+        self.simplified.borrow().as_ref().map(|(_, vertices)| vertices.len())
+    }
+}
+
+impl PolygonWithHoles {
+    /// Simplifies the exterior and every hole as [`Polygon::simplify`] does.
+    pub fn simplify(&self, epsilon: f32) -> PolygonWithHoles {
+        PolygonWithHoles {
+            translate: self.translate,
+            exterior: simplify_ring(&self.exterior, epsilon),
+            holes: self.holes.iter().map(|hole| simplify_ring(hole, epsilon)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::PrimitiveStyle;
+    use crate::polygon::{Polygon, PolygonOwned};
+    use crate::polygon::tests::{fill_pixels, PixelCollector, ring};
+
+    #[test]
+    fn test_simplify() {
+        // straight runs collapse at epsilon 0
+        let square = ring(&[[0, 0], [5, 0], [10, 0], [10, 5], [10, 10], [5, 10], [0, 10], [0, 5]]);
+        assert_eq!(Polygon::new(&square).simplify(0.0).vertices(), ring(&[[0, 0], [10, 0], [10, 10], [0, 10]]));
+
+        let wobbly = ring(&[[0, 0], [5, 1], [10, 0], [11, 5], [10, 10], [0, 10]]);
+        assert_eq!(Polygon::new(&wobbly).simplify(0.5).vertices(), wobbly);
+        assert_eq!(Polygon::new(&wobbly).simplify(1.5).vertices(), ring(&[[0, 0], [10, 0], [10, 10], [0, 10]]));
+
+        // a line traced out and back keeps both ends
+        let line = ring(&[[0, 0], [1, 0], [2, 0], [1, 0]]);
+        assert_eq!(Polygon::new(&line).simplify(0.0).vertices(), ring(&[[0, 0], [2, 0]]));
+        assert_eq!(Polygon::new(&ring(&[[3, 3], [3, 3], [3, 3]])).simplify(1.0).vertices(), ring(&[[3, 3], [3, 3]]));
+
+        let mut polygon = Polygon::new(&square);
+        polygon.set_translate(Point::new(4, 4));
+        assert_eq!(polygon.simplify(0.0).translate(), Point::new(4, 4));
+    }
+
+    /// A wobbly ring of `count` vertices about 30 pixels across.
+    fn coastline(count: usize) -> PolygonOwned {
+        let vertices = (0..count).map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / count as f64;
+            let radius = 1500.0 + 60.0 * (angle * 37.0).sin() + 25.0 * (angle * 211.0).cos();
+            Point::new((radius * angle.cos()).round() as i32, (radius * angle.sin()).round() as i32)
+        }).collect();
+        let mut polygon = PolygonOwned::new(vertices);
+        polygon.scale(0.01);
+        polygon
+    }
+
+    #[test]
+    fn test_draw_simplified_stays_close_and_caches() {
+        let mut polygon = coastline(3000);
+        polygon.set_translate(Point::new(20, 20));
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let full = fill_pixels(&polygon.as_polygon());
+        assert_eq!(polygon.simplified_len(), None);
+
+        let mut collector = PixelCollector::default();
+        polygon.draw_simplified(&style, 0.5, &mut collector).unwrap();
+        let retained = polygon.simplified_len().unwrap();
+        assert!(retained < 300, "{retained} of 3000 vertices kept");
+        let mut pixels = collector.pixels;
+        pixels.sort_by_key(|p| (p.y, p.x));
+        // every pixel the fills disagree on is next to the full outline
+        let outline = polygon.as_polygon();
+        let differing: Vec<Point> = pixels.iter().filter(|p| full.binary_search_by_key(&(p.y, p.x), |f| (f.y, f.x)).is_err())
+            .chain(full.iter().filter(|p| pixels.binary_search_by_key(&(p.y, p.x), |f| (f.y, f.x)).is_err())).copied().collect();
+        assert!(differing.iter().all(|p| outline.closest_boundary_point(*p).1 <= 2), "{differing:?}");
+        assert!(differing.len() < full.len() / 20);
+
+        // the same error reuses the cached outline, a coarser one simplifies further
+        polygon.draw_simplified(&style, 0.5, &mut PixelCollector::default()).unwrap();
+        assert_eq!(polygon.simplified_len(), Some(retained));
+        polygon.draw_simplified(&style, 2.0, &mut PixelCollector::default()).unwrap();
+        assert!(polygon.simplified_len().unwrap() < retained);
+
+        // moving keeps the cache, editing the vertices drops it
+        polygon.translate_mut(Point::new(5, 0));
+        assert!(polygon.simplified_len().is_some());
+        polygon.vertices_mut().truncate(4);
+        assert_eq!(polygon.simplified_len(), None);
+        let mut collector = PixelCollector::default();
+        polygon.draw_simplified(&style, 2.0, &mut collector).unwrap();
+        assert!(polygon.simplified_len().unwrap() <= 4);
+    }
+}
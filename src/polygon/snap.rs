@@ -0,0 +1,112 @@
+//! Snapping vertices onto a pixel grid.
+
+use core::fmt;
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use super::{Polygon, PolygonOwned};
+
+/// How each coordinate moves onto the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapMode {
+    /// To the nearest grid line, halfway coordinates moving towards positive infinity.
+    Round,
+    /// To the grid line at or below.
+    Floor,
+    /// To the grid line at or above.
+    Ceil,
+}
+
+impl SnapMode {
+    fn snap(self, value: i32, cell: i64) -> i32 {
+        let value = value as i64;
+        let snapped = match self {
+            SnapMode::Round => (value + cell / 2).div_euclid(cell) * cell,
+            SnapMode::Floor => value.div_euclid(cell) * cell,
+            SnapMode::Ceil => -(-value).div_euclid(cell) * cell,
+        };
+        snapped.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+}
+
+/// Snapping collapsed the polygon to fewer than three distinct vertices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapCollapsed {
+    /// The number of distinct vertices left after snapping.
+    pub distinct: usize,
+}
+
+impl fmt::Display for SnapCollapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "snapping left {} distinct vertices, a polygon needs at least 3", self.distinct)
+    }
+}
+
+impl core::error::Error for SnapCollapsed {}
+
+impl<'a> Polygon<'a> {
+    /// Moves every vertex onto the grid of `cell` pixel squares, with the translate offset
+    /// included so that the drawn vertices land on the grid, and drops vertices that collapse onto
+    /// their predecessor.
+    ///
+    /// The translate offset is carried over. Fails if fewer than three distinct vertices remain,
+    /// as the snapped ring would no longer enclose an area.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell` is 0.
+    pub fn snap_to_grid(&self, cell: u32, mode: SnapMode) -> Result<PolygonOwned, SnapCollapsed> {
+        assert!(cell > 0, "grid cell size must be positive");
+        let translate = self.translate();
+        let mut vertices: Vec<Point> = self.vertices().iter().map(|v| {
+            let v = *v + translate;
+            Point::new(mode.snap(v.x, cell as i64), mode.snap(v.y, cell as i64)) - translate
+        }).collect();
+        vertices.dedup();
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            let _ = vertices.pop();
+        }
+
+        let mut distinct = vertices.clone();
+        distinct.sort_by_key(|v| (v.x, v.y));
+        distinct.dedup();
+        if distinct.len() < 3 {
+            return Err(SnapCollapsed { distinct: distinct.len() });
+        }
+        Ok(PolygonOwned::with_translate(vertices, translate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::tests::ring;
+    use crate::polygon::{Polygon, SnapCollapsed, SnapMode};
+
+    #[test]
+    fn test_snap_jittery_rectangle() {
+        let jittery = ring(&[[1, -1], [11, 1], [13, -1], [11, 11], [1, 13], [-1, 10], [0, 1]]);
+        let snapped = Polygon::new(&jittery).snap_to_grid(4, SnapMode::Round).unwrap();
+        assert_eq!(snapped.vertices(), ring(&[[0, 0], [12, 0], [12, 12], [0, 12]]));
+
+        let square = ring(&[[1, 1], [7, 1], [7, 7], [1, 7]]);
+        assert_eq!(Polygon::new(&square).snap_to_grid(4, SnapMode::Floor).unwrap().vertices(), ring(&[[0, 0], [4, 0], [4, 4], [0, 4]]));
+        assert_eq!(Polygon::new(&square).snap_to_grid(4, SnapMode::Ceil).unwrap().vertices(), ring(&[[4, 4], [8, 4], [8, 8], [4, 8]]));
+
+        // the translated vertices land on the grid and the offset is kept
+        let mut polygon = Polygon::new(&square);
+        polygon.set_translate(Point::new(2, -3));
+        let snapped = polygon.snap_to_grid(4, SnapMode::Floor).unwrap();
+        assert_eq!(snapped.translate(), Point::new(2, -3));
+        assert!(snapped.vertices().iter().all(|v| (*v + snapped.translate()).x.rem_euclid(4) == 0 && (*v + snapped.translate()).y.rem_euclid(4) == 0));
+    }
+
+    #[test]
+    fn test_snap_collapses_are_reported() {
+        let sliver = ring(&[[0, 0], [10, 1], [20, 0], [10, -1]]);
+        assert_eq!(Polygon::new(&sliver).snap_to_grid(16, SnapMode::Round), Err(SnapCollapsed { distinct: 2 }));
+
+        let tiny = ring(&[[1, 1], [2, 1], [2, 2]]);
+        assert_eq!(Polygon::new(&tiny).snap_to_grid(16, SnapMode::Floor), Err(SnapCollapsed { distinct: 1 }));
+        assert_eq!(Polygon::new(&[]).snap_to_grid(4, SnapMode::Round), Err(SnapCollapsed { distinct: 0 }));
+    }
+}
@@ -0,0 +1,126 @@
+//! Cutting a polygon in two along a straight line.
+
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Line;
+use super::{MultiPolygon, Polygon, PolygonOwned};
+use super::clip::gcd;
+
+impl<'a> Polygon<'a> {
+    /// Cuts the polygon along the infinite line through `line`, returning the pieces on the right
+    /// of the line as drawn on screen, looking from its start to its end, and then the pieces on
+    /// its left.
+    ///
+    /// Each side is clipped as by [`clip_to`](Self::clip_to) against a half plane, so the polygon
+    /// must be simple, a concave one may leave several pieces on a side, and the pieces carry the
+    /// translate offset in their vertices. Parts lying on the line only touch it and go to neither
+    /// side. A line that misses the polygon leaves it whole on one side, and a line whose ends
+    /// coincide has no sides and returns the polygon unchanged as the first bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the half planes, which reach past the polygon, do not fit in `i32` coordinates.
+    pub fn split(&self, line: Line) -> (MultiPolygon, MultiPolygon) {
+        let (dx, dy) = (line.end.x as i64 - line.start.x as i64, line.end.y as i64 - line.start.y as i64);
+        if (dx, dy) == (0, 0) {
+            return (MultiPolygon::from(PolygonOwned::from(*self)), MultiPolygon::default());
+        }
+        let divisor = gcd(dx as i128, dy as i128) as i64;
+        let (dx, dy) = (dx / divisor, dy / divisor);
+        // twice the furthest any vertex lies from the start of the line, so the half planes
+        // overshoot the polygon even along a diagonal
+        let reach = self.vertices().iter().map(|v| {
+            let v = *v + self.translate();
+            (v.x as i64 - line.start.x as i64).abs().max((v.y as i64 - line.start.y as i64).abs())
+        }).max().unwrap_or(0) * 2 + 1;
+        let at = |along: i64, across: i64| {
+            let coordinate = |start: i32, d: i64, n: i64| i32::try_from(start as i64 + d * along + n * across).expect("split line reaches past the coordinate range");
+            Point::new(coordinate(line.start.x, dx, -dy), coordinate(line.start.y, dy, dx))
+        };
+        let right = [at(-reach, 0), at(reach, 0), at(reach, reach), at(-reach, reach)];
+        let left = [at(-reach, 0), at(reach, 0), at(reach, -reach), at(-reach, -reach)];
+        (self.clip_to(&Polygon::new(&right)), self.clip_to(&Polygon::new(&left)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::primitives::Line;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{MultiPolygon, Polygon, PolygonOwned};
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    type Pixels = BTreeSet<(i32, i32)>;
+
+    fn pixels(pieces: &MultiPolygon) -> Pixels {
+        pieces.iter().flat_map(|piece| fill_pixels(&piece.exterior())).map(|p| (p.x, p.y)).collect()
+    }
+
+    fn original(polygon: &Polygon) -> Pixels {
+        fill_pixels(polygon).into_iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn test_split_concave_polygon_into_several_pieces() {
+        // a U opening towards positive y, cut across its legs
+        let u_shape = ring(&[[0, 0], [30, 0], [30, 30], [20, 30], [20, 10], [10, 10], [10, 30], [0, 30]]);
+        let mut polygon = Polygon::new(&u_shape);
+        polygon.set_translate(Point::new(4, -3));
+        let (below, above) = polygon.split(Line::new(Point::new(-5, 17), Point::new(40, 17)));
+        assert_eq!((below.len(), above.len()), (2, 1));
+        let (below, above) = (pixels(&below), pixels(&above));
+        assert!(below.iter().all(|p| p.1 >= 17) && above.iter().all(|p| p.1 <= 17));
+        assert_eq!(below.union(&above).copied().collect::<Pixels>(), original(&polygon));
+
+        // the same line walked the other way swaps the sides
+        let (first, second) = polygon.split(Line::new(Point::new(40, 17), Point::new(-5, 17)));
+        assert_eq!((first.len(), second.len()), (1, 2));
+    }
+
+    #[test]
+    fn test_split_through_vertices_and_missing() {
+        let diamond = ring(&[[10, 0], [20, 10], [10, 20], [0, 10]]);
+        let polygon = Polygon::new(&diamond);
+        let (right, left) = polygon.split(Line::new(Point::new(10, 3), Point::new(10, 5)));
+        assert_eq!((right.len(), left.len()), (1, 1));
+        assert_eq!(right.parts[0].exterior.len(), 3);
+        assert!(pixels(&right).iter().all(|p| p.0 <= 10) && pixels(&left).iter().all(|p| p.0 >= 10));
+        assert_eq!(pixels(&right).union(&pixels(&left)).copied().collect::<Pixels>(), original(&polygon));
+
+        // along an edge, everything is on one side
+        let (right, left) = polygon.split(Line::new(Point::new(10, 0), Point::new(20, 10)));
+        assert_eq!((right.len(), left.len()), (1, 0));
+        let (right, left) = polygon.split(Line::new(Point::new(50, 0), Point::new(50, 1)));
+        assert_eq!((right.len(), left.len()), (1, 0));
+        assert_eq!(pixels(&right), original(&polygon));
+        let (right, left) = polygon.split(Line::new(Point::new(3, 3), Point::new(3, 3)));
+        assert_eq!((right, left), (MultiPolygon::from(PolygonOwned::from(polygon)), MultiPolygon::default()));
+    }
+
+    #[test]
+    fn test_split_pieces_reproduce_the_fill() {
+        let mut rng = StdRng::seed_from_u64(374);
+        for _ in 0..200 {
+            // star shaped around the origin, so simple but often concave, and spread out enough
+            // not to be a sliver that rounding could cut away entirely
+            let count = rng.gen_range(3..10);
+            let vertices: Vec<Point> = (0..count).map(|i| {
+                let angle = (i as f64 + rng.gen_range(0.0..0.6)) * std::f64::consts::TAU / count as f64;
+                let radius = rng.gen_range(8.0..30.0);
+                Point::new((angle.cos() * radius).round() as i32, (angle.sin() * radius).round() as i32)
+            }).collect();
+            let polygon = Polygon::new(&vertices);
+            let line = Line::new(Point::new(rng.gen_range(-20..20), rng.gen_range(-20..20)), Point::new(rng.gen_range(-20..20), rng.gen_range(-20..20)));
+            let (right, left) = polygon.split(line);
+            let split = pixels(&right).union(&pixels(&left)).copied().collect::<Pixels>();
+            let expected = original(&polygon);
+            // rounding the cut points only moves the outline next to them by up to a pixel
+            let near = |set: &Pixels, (x, y): (i32, i32)| (-1..=1).any(|dx| (-1..=1).any(|dy| set.contains(&(x + dx, y + dy))));
+            assert!(split.difference(&expected).all(|p| near(&expected, *p)), "{vertices:?} {line:?}");
+            assert!(expected.difference(&split).all(|p| near(&split, *p)), "{vertices:?} {line:?}");
+            assert!(expected.len() < 100 || split.intersection(&expected).count() * 10 >= expected.len() * 9, "{vertices:?} {line:?}");
+        }
+    }
+}
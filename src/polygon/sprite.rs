@@ -0,0 +1,222 @@
+//! Rasterizing a polygon once and drawing the result many times.
+
+use core::convert::Infallible;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics::image::ImageDrawable;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, StyledDrawable};
+use embedded_graphics::Pixel;
+use super::Polygon;
+
+/// Collects every pixel a draw produces and the color it was drawn in, in drawing order.
+struct Recorder<C: PixelColor> {
+    pixels: Vec<Pixel<C>>,
+}
+
+impl<C: PixelColor> Dimensions for Recorder<C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::new(-(1 << 29), -(1 << 29)), Size::new(1 << 30, 1 << 30))
+    }
+}
+
+impl<C: PixelColor> DrawTarget for Recorder<C> {
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+        self.pixels.extend(pixels);
+        Ok(())
+    }
+}
+
+/// A polygon drawn once into 1-bit masks, one for each color the style draws in, to be drawn
+/// again at any position by copying the masks instead of rasterizing the outline.
+///
+/// The masks cover the smallest rectangle around the drawn pixels and unset pixels are skipped.
+/// Each pixel is in the mask of the color it was drawn in last, such as the stroke where it
+/// covers the fill, so drawing the sprite writes exactly the pixels, in the same colors, that
+/// drawing the polygon at the same position would.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolygonSprite<C> {
+    /// Top left corner of the masks relative to the polygon's origin.
+    offset: Point,
+    size: Size,
+    /// A color and the pixels drawn in it, with rows packed as
+    /// [`BitOrder::MsbFirstRowMajor`](super::BitOrder::MsbFirstRowMajor), in the order the colors
+    /// were first drawn.
+    layers: Vec<(C, Vec<u8>)>,
+}
+
+impl<C: PixelColor> PolygonSprite<C> {
+    /// Draws `polygon` with `style` into a new sprite. The translate offset of `polygon` is
+    /// ignored, the position is given each time the sprite is drawn.
+    pub fn render(polygon: &Polygon, style: &PrimitiveStyle<C>) -> Self {
+        let mut recorder = Recorder { pixels: Vec::new() };
+        let origin = Polygon::new(polygon.vertices());
+        origin.draw_styled(style, &mut recorder).unwrap_or_else(|never| match never {});
+
+        let Some((min, max)) = recorder.pixels.iter().fold(None, |bounds: Option<(Point, Point)>, Pixel(p, _)| {
+            Some(bounds.map_or((*p, *p), |(min, max)| (min.component_min(*p), max.component_max(*p))))
+        }) else {
+            return PolygonSprite { offset: Point::zero(), size: Size::zero(), layers: Vec::new() };
+        };
+        let size = Size::new(min.x.abs_diff(max.x) + 1, min.y.abs_diff(max.y) + 1);
+        // the color each pixel ends up in, as later pixels are drawn over earlier ones
+        let mut last = vec![None; size.width as usize * size.height as usize];
+        for Pixel(p, color) in recorder.pixels.iter() {
+            last[(p.y - min.y) as usize * size.width as usize + (p.x - min.x) as usize] = Some(*color);
+        }
+        let stride = (size.width as usize).div_ceil(8);
+        let mut layers: Vec<(C, Vec<u8>)> = Vec::new();
+        for (i, color) in last.into_iter().enumerate() {
+            let Some(color) = color else {
+                continue;
+            };
+            let layer = match layers.iter().position(|(c, _)| *c == color) {
+                Some(layer) => layer,
+                None => {
+                    layers.push((color, vec![0; stride * size.height as usize]));
+                    layers.len() - 1
+                }
+            };
+            let (x, y) = (i % size.width as usize, i / size.width as usize);
+            layers[layer].1[y * stride + x / 8] |= 0x80 >> (x % 8);
+        }
+        PolygonSprite { offset: min, size, layers }
+    }
+
+    /// Top left corner of the sprite when drawn at `position`.
+    pub fn top_left(&self, position: Point) -> Point {
+        position + self.offset
+    }
+
+    /// Draws the sprite where the polygon would be drawn with its translate offset set to
+    /// `position`.
+    ///
+    /// Each run of set pixels in a row of a color's mask is written with a single `fill_solid`.
+    pub fn draw_at<D>(&self, position: Point, target: &mut D) -> Result<(), D::Error> where D: DrawTarget<Color = C> {
+        let top_left = self.top_left(position);
+        let stride = (self.size.width as usize).div_ceil(8);
+        for (color, mask) in self.layers.iter() {
+            for (y, row) in mask.chunks(stride.max(1)).enumerate() {
+                let set = |x: u32| row[x as usize / 8] & (0x80 >> (x % 8)) != 0;
+                let mut x = 0;
+                while x < self.size.width {
+                    if !set(x) {
+                        x += 1;
+                        continue;
+                    }
+                    let start = x;
+                    while x < self.size.width && set(x) {
+                        x += 1;
+                    }
+                    target.fill_solid(&Rectangle::new(top_left + Point::new(start as i32, y as i32), Size::new(x - start, 1)), *color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C> OriginDimensions for PolygonSprite<C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C: PixelColor> ImageDrawable for PolygonSprite<C> {
+    type Color = C;
+
+    /// Draws the sprite with its top left corner at the origin.
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error> where D: DrawTarget<Color = C> {
+        self.draw_at(-self.offset, target)
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error> where D: DrawTarget<Color = C> {
+        self.draw(&mut target.clipped(&Rectangle::new(Point::zero(), area.size)).translated(-area.top_left))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+    use embedded_graphics::image::Image;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565, RgbColor};
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle, PrimitiveStyleBuilder};
+    use crate::polygon::tests::ring;
+    use crate::polygon::{Polygon, PolygonSprite};
+
+    #[test]
+    fn test_sprite_blit_matches_direct_draw() {
+        // concave, so the sprite has unset pixels to skip over
+        let vertices = ring(&[[0, 0], [20, 4], [9, 9], [21, 17], [2, 19], [-3, 8]]);
+        let styles = [PrimitiveStyle::with_fill(BinaryColor::On), PrimitiveStyle::with_stroke(BinaryColor::On, 3)];
+        for style in styles {
+            let sprite = PolygonSprite::render(&Polygon::new(&vertices), &style);
+            for position in [Point::new(5, 4), Point::new(30, 20)] {
+                let mut expected = MockDisplay::new();
+                expected.clear(BinaryColor::Off).unwrap();
+                expected.set_allow_overdraw(true);
+                let mut polygon = Polygon::new(&vertices);
+                polygon.set_translate(position);
+                polygon.into_styled(style).draw(&mut expected).unwrap();
+
+                let mut display = MockDisplay::new();
+                display.clear(BinaryColor::Off).unwrap();
+                display.set_allow_overdraw(true);
+                sprite.draw_at(position, &mut display).unwrap();
+                display.assert_eq(&expected);
+
+                // as an image placed at the sprite's top left corner
+                let mut image = MockDisplay::new();
+                image.clear(BinaryColor::Off).unwrap();
+                image.set_allow_overdraw(true);
+                Image::new(&sprite, sprite.top_left(position)).draw(&mut image).unwrap();
+                image.assert_eq(&expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sprite_keeps_fill_and_stroke_colors() {
+        let vertices = ring(&[[0, 0], [20, 4], [9, 9], [21, 17], [2, 19], [-3, 8]]);
+        let styles = [
+            PrimitiveStyleBuilder::new().fill_color(Rgb565::BLUE).stroke_color(Rgb565::YELLOW).stroke_width(3).build(),
+            PrimitiveStyleBuilder::new().fill_color(Rgb565::GREEN).stroke_color(Rgb565::RED).stroke_width(1).build(),
+        ];
+        for style in styles {
+            let sprite = PolygonSprite::render(&Polygon::new(&vertices), &style);
+            let position = Point::new(8, 6);
+            let mut expected = MockDisplay::new();
+            expected.set_allow_overdraw(true);
+            let mut polygon = Polygon::new(&vertices);
+            polygon.set_translate(position);
+            polygon.into_styled(style).draw(&mut expected).unwrap();
+
+            let mut display = MockDisplay::new();
+            sprite.draw_at(position, &mut display).unwrap();
+            display.assert_eq(&expected);
+        }
+    }
+
+    #[test]
+    fn test_sprite_size() {
+        let vertices = ring(&[[2, 3], [12, 3], [12, 8], [2, 8]]);
+        let mut polygon = Polygon::new(&vertices);
+        polygon.set_translate(Point::new(40, 40));
+        let sprite = PolygonSprite::render(&polygon, &PrimitiveStyle::with_fill(BinaryColor::On));
+        assert_eq!(sprite.size(), Size::new(11, 6));
+        assert_eq!(sprite.top_left(Point::new(1, 1)), Point::new(3, 4));
+
+        let transparent = PolygonSprite::render(&polygon, &PrimitiveStyleBuilder::<BinaryColor>::new().build());
+        assert_eq!(transparent.size(), Size::zero());
+        let mut display = MockDisplay::<BinaryColor>::new();
+        transparent.draw_at(Point::zero(), &mut display).unwrap();
+        assert_eq!(display, MockDisplay::new());
+    }
+}
@@ -0,0 +1,315 @@
+//! Widening a path into the outline of its stroke.
+
+use core::f64::consts::{FRAC_PI_4, PI};
+use alloc::{vec, vec::Vec};
+use embedded_graphics::geometry::Point;
+use super::{MultiPolygon, PolygonOwned};
+use super::math;
+use super::geometry::cross;
+
+/// How [`stroke_polyline`] joins two segments on the outside of a turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinStyle {
+    /// The outer edges extended until they meet, beveled instead where they would meet more than
+    /// twice the width from the corner.
+    Miter,
+    /// A straight edge cut across the corner.
+    Bevel,
+    /// An arc around the corner.
+    Round,
+}
+
+/// How [`stroke_polyline`] finishes the ends of the path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CapStyle {
+    /// Cut square, right at the end points.
+    Butt,
+    /// A half circle around each end point.
+    Round,
+}
+
+/// A point or direction in pixels.
+type Vector = (f64, f64);
+
+/// The outline of `points` drawn as a line `width` pixels wide, ready to be filled, offset or
+/// clipped, such as a route on a map.
+///
+/// The outline runs along one side of the path, around the far end, back along the other side
+/// and around the start. Segments meet with `join` on the outside of each turn, while on the
+/// inside the two edges are cut off where they cross, so the corner leaves no notch. An axis
+/// aligned segment covers exactly `width` rows or columns, the extra pixel of an even width
+/// falling below or right of the path. As the outline is rounded to whole pixels, diagonal
+/// strokes only a pixel or two wide come out thinner in places than a wide `Polyline`.
+///
+/// Where the outline would cross or touch itself, as a path doubling back over itself or turning
+/// sharply next to a short segment does, its even-odd fill would leave the overlap empty. The
+/// stroke is then returned in pieces instead, one for each segment, outer join and cap, which
+/// cover the overlaps when drawn together. Otherwise it is a single polygon. A path of one point
+/// is a dot with round caps and nothing with butt caps, and a zero width draws nothing.
+pub fn stroke_polyline(points: &[Point], width: u32, join: JoinStyle, cap: CapStyle) -> MultiPolygon {
+    let mut path: Vec<Vector> = points.iter().map(|p| (p.x as f64, p.y as f64)).collect();
+    path.dedup();
+    if width == 0 || path.is_empty() {
+        return MultiPolygon::default();
+    }
+    let half = (width - 1) as f64 / 2.0;
+    if path.len() == 1 {
+        return match cap {
+            CapStyle::Butt => MultiPolygon::default(),
+            CapStyle::Round => MultiPolygon::from(PolygonOwned::new(rounded_ring(arc(path[0], (1.0, 0.0), 2.0 * PI, half)))),
+        };
+    }
+    let directions: Vec<Vector> = path.windows(2).map(|pair| {
+        let (dx, dy) = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+        let length = math::hypot(dx, dy);
+        (dx / length, dy / length)
+    }).collect();
+    let last = path.len() - 1;
+    let (start_normal, end_normal) = (normal(directions[0], 1.0), normal(directions[last - 1], 1.0));
+    let caps = match cap {
+        CapStyle::Butt => None,
+        CapStyle::Round => Some((arc(path[last], end_normal, -PI, half), arc(path[0], scaled(start_normal, -1.0), -PI, half))),
+    };
+
+    let mut outline = Vec::new();
+    for side in [1.0, -1.0] {
+        let mut chain = vec![along(path[0], normal(directions[0], side), half)];
+        for i in 1..last {
+            match outer_join(path[i], directions[i - 1], directions[i], side, half, join) {
+                Some(points) => chain.extend(points),
+                None => {
+                    // the two edges on the inside of the turn cross at the miter point
+                    let (a, b) = (normal(directions[i - 1], side), normal(directions[i], side));
+                    let scale = half / (1.0 + a.0 * b.0 + a.1 * b.1);
+                    chain.push((path[i].0 + (a.0 + b.0) * scale, path[i].1 + (a.1 + b.1) * scale));
+                }
+            }
+        }
+        chain.push(along(path[last], normal(directions[last - 1], side), half));
+        if side < 0.0 {
+            chain.reverse();
+        }
+        outline.extend(chain);
+        if let Some((end, start)) = &caps {
+            outline.extend(if side > 0.0 { end } else { start });
+        }
+    }
+    let outline = rounded_ring(outline);
+    if is_simple(&outline) {
+        return MultiPolygon::from(PolygonOwned::new(outline));
+    }
+
+    let mut pieces = Vec::new();
+    for (i, direction) in directions.iter().enumerate() {
+        let (left, right) = (normal(*direction, 1.0), normal(*direction, -1.0));
+        pieces.push(vec![along(path[i], left, half), along(path[i + 1], left, half), along(path[i + 1], right, half), along(path[i], right, half)]);
+    }
+    for i in 1..last {
+        for side in [1.0, -1.0] {
+            if let Some(points) = outer_join(path[i], directions[i - 1], directions[i], side, half, join) {
+                pieces.push(core::iter::once(path[i]).chain(points).collect());
+            }
+        }
+    }
+    if let Some((end, start)) = caps {
+        pieces.extend([end, start]);
+    }
+    pieces.into_iter().map(|piece| PolygonOwned::new(rounded_ring(piece))).collect()
+}
+
+/// The unit normal of `direction` on `side`, the positive side being clockwise on screen.
+fn normal(direction: Vector, side: f64) -> Vector {
+    (-direction.1 * side, direction.0 * side)
+}
+
+fn scaled(v: Vector, factor: f64) -> Vector {
+    (v.0 * factor, v.1 * factor)
+}
+
+fn along(p: Vector, direction: Vector, distance: f64) -> Vector {
+    (p.0 + direction.0 * distance, p.1 + direction.1 * distance)
+}
+
+/// The points around the corner at `p` on `side` of the path, from the edge of the segment
+/// along `incoming` to the edge of the one along `outgoing`, or `None` if that side is the
+/// inside of the turn.
+fn outer_join(p: Vector, incoming: Vector, outgoing: Vector, side: f64, half: f64, join: JoinStyle) -> Option<Vec<Vector>> {
+    let turn = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+    let reverses = turn.abs() < 1e-12 && incoming.0 * outgoing.0 + incoming.1 * outgoing.1 < 0.0;
+    if turn * side >= 0.0 && !reverses {
+        return None;
+    }
+    let (a, b) = (normal(incoming, side), normal(outgoing, side));
+    let bevel = vec![along(p, a, half), along(p, b, half)];
+    Some(match join {
+        JoinStyle::Bevel => bevel,
+        JoinStyle::Miter => {
+            let sum = (a.0 + b.0, a.1 + b.1);
+            let length_squared = sum.0 * sum.0 + sum.1 * sum.1;
+            // the miter point lies 2 / |a + b| half widths out, so within twice the width
+            if length_squared < 0.25 {
+                bevel
+            } else {
+                vec![along(p, sum, 2.0 * half / length_squared)]
+            }
+        }
+        JoinStyle::Round => {
+            let sweep = if reverses { -PI * side } else { math::atan2(a.0 * b.1 - a.1 * b.0, a.0 * b.0 + a.1 * b.1) };
+            arc(p, a, sweep, half)
+        }
+    })
+}
+
+/// Points along the arc of `radius` around `center`, starting in `from` and turning through
+/// `sweep` radians, close enough together that the chords stay within a quarter pixel of it.
+fn arc(center: Vector, from: Vector, sweep: f64, radius: f64) -> Vec<Vector> {
+    let step = math::sqrt(2.0 / radius).min(FRAC_PI_4);
+    let steps = math::ceil(math::abs(sweep) / step).max(1.0) as usize;
+    let start = math::atan2(from.1, from.0);
+    (0..=steps).map(|i| {
+        let (sin, cos) = math::sin_cos(start + sweep * i as f64 / steps as f64);
+        (center.0 + cos * radius, center.1 + sin * radius)
+    }).collect()
+}
+
+/// The ring through `points` on whole pixels, halfway coordinates rounding towards positive
+/// infinity so both sides of an axis aligned segment round the same way.
+fn rounded_ring(points: Vec<Vector>) -> Vec<Point> {
+    let mut ring: Vec<Point> = points.into_iter().map(|(x, y)| Point::new(math::floor(x + 0.5) as i32, math::floor(y + 0.5) as i32)).collect();
+    ring.dedup();
+    while ring.len() > 1 && ring.first() == ring.last() {
+        let _ = ring.pop();
+    }
+    ring
+}
+
+/// Returns `true` if no two edges of the ring meet other than neighbours at their shared vertex.
+fn is_simple(ring: &[Point]) -> bool {
+    let n = ring.len();
+    let edge = |i: usize| (ring[i], ring[(i + 1) % n]);
+    let on_segment = |(a, b): (Point, Point), p: Point| cross(a, b, p) == 0 && p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y);
+    for i in 0..n {
+        // neighbours doubling back along each other
+        let (a, b, c) = (ring[i], ring[(i + 1) % n], ring[(i + 2) % n]);
+        if n > 2 && cross(a, b, c) == 0 && (b.x - a.x) as i64 * (c.x - b.x) as i64 + (b.y - a.y) as i64 * (c.y - b.y) as i64 <= 0 {
+            return false;
+        }
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let ((p, q), (r, s)) = (edge(i), edge(j));
+            let (d1, d2, d3, d4) = (cross(p, q, r), cross(p, q, s), cross(r, s, p), cross(r, s, q));
+            let crossing = d1.signum() * d2.signum() < 0 && d3.signum() * d4.signum() < 0;
+            if crossing || on_segment((p, q), r) || on_segment((p, q), s) || on_segment((r, s), p) || on_segment((r, s), q) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::polygon::{stroke_polyline, CapStyle, JoinStyle, MultiPolygon};
+    use crate::polygon::tests::{PixelCollector, ring};
+
+    type Pixels = BTreeSet<(i32, i32)>;
+
+    fn pixels(stroke: &MultiPolygon) -> Pixels {
+        let mut collector = PixelCollector::default();
+        stroke.clone().into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+        collector.pixels.into_iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    fn distance_to_path(path: &[Point], (x, y): (i32, i32)) -> f64 {
+        let to_segment = |a: Point, b: Point| {
+            let (dx, dy) = ((b.x - a.x) as f64, (b.y - a.y) as f64);
+            let length_squared = dx * dx + dy * dy;
+            let t = if length_squared == 0.0 { 0.0 } else { (((x - a.x) as f64 * dx + (y - a.y) as f64 * dy) / length_squared).clamp(0.0, 1.0) };
+            (a.x as f64 + dx * t - x as f64).hypot(a.y as f64 + dy * t - y as f64)
+        };
+        path.iter().zip(path.iter().skip(1).chain(path.last())).map(|(a, b)| to_segment(*a, *b)).fold(f64::INFINITY, f64::min)
+    }
+
+    fn block(x: std::ops::RangeInclusive<i32>, y: std::ops::RangeInclusive<i32>) -> Pixels {
+        x.flat_map(|x| y.clone().map(move |y| (x, y))).collect()
+    }
+
+    #[test]
+    fn test_l_path_has_no_notch() {
+        let path = ring(&[[5, 5], [30, 5], [30, 30]]);
+        let miter = stroke_polyline(&path, 6, JoinStyle::Miter, CapStyle::Butt);
+        assert_eq!(miter.len(), 1);
+        assert_eq!(miter.parts[0].exterior, ring(&[[5, 8], [28, 8], [28, 30], [33, 30], [33, 3], [5, 3]]));
+        // six rows along the first leg and six columns down the second, the corner filled in
+        let expected: Pixels = block(5..=33, 3..=8).union(&block(28..=33, 3..=30)).copied().collect();
+        let miter = pixels(&miter);
+        assert_eq!(miter, expected);
+
+        // bevels and arcs round the outer corner off, and only that
+        let bevel = pixels(&stroke_polyline(&path, 6, JoinStyle::Bevel, CapStyle::Butt));
+        let round = pixels(&stroke_polyline(&path, 6, JoinStyle::Round, CapStyle::Butt));
+        assert!(bevel.is_subset(&round) && round.is_subset(&miter));
+        assert!(!round.contains(&(33, 3)) && round.contains(&(31, 4)));
+        assert!(miter.difference(&bevel).all(|(x, y)| *x > 30 && *y < 5));
+    }
+
+    #[test]
+    fn test_caps_and_dots() {
+        let path = ring(&[[10, 10], [30, 10]]);
+        let butt = pixels(&stroke_polyline(&path, 5, JoinStyle::Miter, CapStyle::Butt));
+        assert_eq!(butt, block(10..=30, 8..=12));
+        let round = pixels(&stroke_polyline(&path, 5, JoinStyle::Miter, CapStyle::Round));
+        assert!(butt.is_subset(&round));
+        assert!(round.contains(&(8, 10)) && round.contains(&(32, 10)) && !round.contains(&(33, 10)) && !round.contains(&(8, 8)));
+
+        let dot = pixels(&stroke_polyline(&[Point::new(4, 4), Point::new(4, 4)], 7, JoinStyle::Round, CapStyle::Round));
+        assert!(dot.contains(&(1, 4)) && dot.contains(&(4, 7)) && !dot.contains(&(1, 1)));
+        assert!(stroke_polyline(&[Point::new(4, 4)], 7, JoinStyle::Round, CapStyle::Butt).is_empty());
+        assert!(stroke_polyline(&path, 0, JoinStyle::Round, CapStyle::Round).is_empty());
+        assert!(stroke_polyline(&[], 3, JoinStyle::Round, CapStyle::Round).is_empty());
+    }
+
+    #[test]
+    fn test_crossing_paths_cover_the_overlap() {
+        // a figure eight whose outline would cross itself in the middle
+        let path = ring(&[[0, 0], [20, 20], [20, 0], [0, 20]]);
+        let stroke = stroke_polyline(&path, 4, JoinStyle::Round, CapStyle::Round);
+        assert!(stroke.len() > 1);
+        assert!(pixels(&stroke).contains(&(10, 10)));
+
+        let mut rng = StdRng::seed_from_u64(387);
+        for _ in 0..100 {
+            let path: Vec<Point> = (0..rng.gen_range(1..8)).map(|_| Point::new(rng.gen_range(-30..30), rng.gen_range(-30..30))).collect();
+            let width = rng.gen_range(1..12);
+            let half = (width - 1) as f64 / 2.0;
+            for (join, cap) in [(JoinStyle::Miter, CapStyle::Butt), (JoinStyle::Bevel, CapStyle::Round), (JoinStyle::Round, CapStyle::Round)] {
+                let covered = pixels(&stroke_polyline(&path, width, join, cap));
+                // rounding moves the outline by up to 0.71 pixels, and wherever the stroke overlaps
+                // itself everything near the path is covered
+                for x in -45..45 {
+                    for y in -45..45 {
+                        let distance = distance_to_path(&path, (x, y));
+                        // butt caps and bevels cut into the circle around the vertex they end
+                        let near = |v: &Point| ((v.x - x) as f64).hypot((v.y - y) as f64) <= half + 1.0;
+                        let cut = (cap == CapStyle::Butt && (near(&path[0]) || near(&path[path.len() - 1]))) || (join != JoinStyle::Round && path.iter().any(near));
+                        if distance <= half - 0.75 && !cut {
+                            assert!(covered.contains(&(x, y)), "({x}, {y}) of {path:?} at {width}, {join:?}");
+                        }
+                        if join == JoinStyle::Round && distance > half + 0.75 {
+                            assert!(!covered.contains(&(x, y)), "({x}, {y}) of {path:?} at {width}, {join:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! A drawing style with the options `PrimitiveStyle` has no room for.
+
+use alloc::{vec, vec::Vec};
+use embedded_graphics::Drawable;
+use embedded_graphics::Pixel;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
+use embedded_graphics::primitives::{Line, Polyline, Primitive, PrimitiveStyle, Rectangle, StrokeAlignment, StyledDrawable};
+use super::{edges_locate, fill_scanlines, fill_scanlines_masked, math, Polygon};
+use super::affected::AffectedArea;
+use super::scanline::Scanlines;
+
+/// Which points a self-overlapping outline encloses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside where a ray from the point crosses the outline an odd number of times. This is the
+    /// rule plain fills use.
+    #[default]
+    EvenOdd,
+    /// Inside where the outline winds around the point a nonzero number of times.
+    NonZero,
+}
+
+/// Dashes along an outline, `on` pixels drawn and then `off` pixels skipped, measured along the
+/// outline from the first vertex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DashPattern {
+    pub on: u32,
+    pub off: u32,
+}
+
+impl DashPattern {
+    pub fn new(on: u32, off: u32) -> Self {
+        DashPattern { on, off }
+    }
+}
+
+/// How a [`Polygon`] is drawn, with the options `PrimitiveStyle` lacks.
+///
+/// Unlike with `PrimitiveStyle`, where a polygon with a stroke is only outlined, the fill is drawn
+/// first and the stroke over it, so both can be had in one draw.
+#[derive(Clone, Copy, Debug)]
+pub struct PolygonStyle<C> {
+    /// Fill color, or `None` for no fill.
+    pub fill_color: Option<C>,
+    pub fill_rule: FillRule,
+    /// Pixels of the fill for which this returns `false` are left alone, for hatching and
+    /// stipples.
+    pub fill_pattern: Option<fn(Point) -> bool>,
+    /// Stroke color, or `None` for no stroke. No stroke is drawn either if `stroke_width` is 0.
+    pub stroke_color: Option<C>,
+    pub stroke_width: u32,
+    /// Whether the stroke is centered on the outline or lies all inside or outside of the fill.
+    pub stroke_alignment: StrokeAlignment,
+    pub stroke_dash: Option<DashPattern>,
+    /// Draws a solid one pixel stroke anti-aliased, blended towards this color, as
+    /// [`Polygon::draw_outline_aa`] does.
+    pub antialias_background: Option<C>,
+}
+
+impl<C> Default for PolygonStyle<C> {
+    fn default() -> Self {
+        PolygonStyle {
+            fill_color: None,
+            fill_rule: FillRule::EvenOdd,
+            fill_pattern: None,
+            stroke_color: None,
+            stroke_width: 0,
+            stroke_alignment: StrokeAlignment::Center,
+            stroke_dash: None,
+            antialias_background: None,
+        }
+    }
+}
+
+impl<C> PolygonStyle<C> {
+    pub fn with_fill(color: C) -> Self {
+        PolygonStyle { fill_color: Some(color), ..Default::default() }
+    }
+
+    pub fn with_stroke(color: C, width: u32) -> Self {
+        PolygonStyle { stroke_color: Some(color), stroke_width: width, ..Default::default() }
+    }
+}
+
+impl<C: PixelColor> From<PrimitiveStyle<C>> for PolygonStyle<C> {
+    /// Takes the colors, stroke width and alignment, leaving the other options off.
+    fn from(style: PrimitiveStyle<C>) -> Self {
+        PolygonStyle {
+            fill_color: style.fill_color,
+            stroke_color: style.stroke_color,
+            stroke_width: style.stroke_width,
+            stroke_alignment: style.stroke_alignment,
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds a [`PolygonStyle`] one option at a time, starting from no fill and no stroke.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PolygonStyleBuilder<C> {
+    style: PolygonStyle<C>,
+}
+
+impl<C> PolygonStyleBuilder<C> {
+    pub fn new() -> Self {
+        PolygonStyleBuilder { style: PolygonStyle::default() }
+    }
+
+    pub fn fill_color(mut self, color: C) -> Self {
+        self.style.fill_color = Some(color);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.style.fill_rule = rule;
+        self
+    }
+
+    pub fn fill_pattern(mut self, pattern: fn(Point) -> bool) -> Self {
+        self.style.fill_pattern = Some(pattern);
+        self
+    }
+
+    pub fn stroke_color(mut self, color: C) -> Self {
+        self.style.stroke_color = Some(color);
+        self
+    }
+
+    pub fn stroke_width(mut self, width: u32) -> Self {
+        self.style.stroke_width = width;
+        self
+    }
+
+    pub fn stroke_alignment(mut self, alignment: StrokeAlignment) -> Self {
+        self.style.stroke_alignment = alignment;
+        self
+    }
+
+    pub fn stroke_dash(mut self, dash: DashPattern) -> Self {
+        self.style.stroke_dash = Some(dash);
+        self
+    }
+
+    /// Anti-aliases a solid one pixel stroke, blending it towards `background`.
+    pub fn antialias(mut self, background: C) -> Self {
+        self.style.antialias_background = Some(background);
+        self
+    }
+
+    pub fn build(self) -> PolygonStyle<C> {
+        self.style
+    }
+}
+
+/// Forwards the pixels for which `keep` returns `true`.
+struct Keep<'d, D, F> {
+    target: &'d mut D,
+    keep: F,
+}
+
+impl<'d, D: DrawTarget, F> Dimensions for Keep<'d, D, F> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<'d, D: DrawTarget, F: FnMut(Point) -> bool> DrawTarget for Keep<'d, D, F> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+        let Keep { target, keep } = self;
+        target.draw_iter(pixels.into_iter().filter(|Pixel(p, _)| keep(*p)))
+    }
+}
+
+/// Splits the path through `points` into the stretches `dash` draws, each of `dash.on` pixels
+/// along a straight run.
+fn dashes(points: &[Point], dash: DashPattern) -> Vec<Vec<Point>> {
+    if dash.off == 0 || points.len() < 2 {
+        return vec![points.to_vec()];
+    }
+    let mut along = vec![0.0];
+    for pair in points.windows(2) {
+        let delta = pair[1] - pair[0];
+        along.push(along[along.len() - 1] + math::hypot(delta.x as f64, delta.y as f64));
+    }
+    let length = along[along.len() - 1];
+    let at = |distance: f64| {
+        let i = along.partition_point(|d| *d <= distance).clamp(1, points.len() - 1);
+        let (a, delta) = (points[i - 1], points[i] - points[i - 1]);
+        let t = if along[i] > along[i - 1] { (distance - along[i - 1]) / (along[i] - along[i - 1]) } else { 0.0 };
+        a + Point::new(math::round(delta.x as f64 * t) as i32, math::round(delta.y as f64 * t) as i32)
+    };
+    // a dash of `on` pixels runs `on - 1` pixels from its first to its last
+    let period = dash.on as f64 + dash.off as f64;
+    let mut pieces = Vec::new();
+    let mut start = 0.0;
+    while dash.on > 0 && start <= length {
+        let end = (start + dash.on as f64 - 1.0).min(length);
+        let mut piece = vec![at(start)];
+        piece.extend(points.iter().zip(along.iter()).filter(|(_, d)| **d > start && **d < end).map(|(p, _)| *p));
+        piece.push(at(end));
+        piece.dedup();
+        pieces.push(piece);
+        start += period;
+    }
+    pieces
+}
+
+/// Strokes each stretch of outline, drawing a stretch of a single point as a dot.
+fn draw_pieces<C, D>(pieces: &[Vec<Point>], color: C, width: u32, target: &mut D) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> {
+    let stroke = PrimitiveStyle::with_stroke(color, width);
+    for piece in pieces.iter() {
+        match piece.as_slice() {
+            [dot] => Pixel(*dot, color).draw(target)?,
+            piece => Polyline::new(piece).into_styled(stroke).draw(target)?,
+        }
+    }
+    Ok(())
+}
+
+impl<'a, C> StyledDrawable<PolygonStyle<C>> for Polygon<'a> where C: PixelColor + Into<Rgb888> + From<Rgb888> {
+    type Color = C;
+    type Output = Rectangle;
+
+    /// Draws the fill and then the stroke, and returns the affected area as with a
+    /// `PrimitiveStyle`.
+    ///
+    /// Strokes aligned inside or outside are drawn twice as wide around the outline and cut back
+    /// to the inside or outside of the fill under the style's fill rule; a one pixel stroke stays
+    /// on the outline whatever its alignment. Dashed and wider strokes are never anti-aliased.
+    fn draw_styled<D>(&self, style: &PolygonStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color = Self::Color> {
+        let mut target = AffectedArea::new(target);
+        if let Some(color) = style.fill_color {
+            let scanlines = Scanlines::new(self.edges()).with_rule(style.fill_rule);
+            match style.fill_pattern {
+                Some(pattern) => fill_scanlines_masked(scanlines, color, pattern, &mut target)?,
+                None => fill_scanlines(scanlines, color, &mut target)?,
+            }
+        }
+        let Some(color) = style.stroke_color.filter(|_| style.stroke_width > 0) else {
+            return Ok(target.area());
+        };
+        if let (Some(background), 1, None) = (style.antialias_background, style.stroke_width, style.stroke_dash) {
+            self.draw_outline_aa(color, background, &mut target)?;
+            return Ok(target.area());
+        }
+
+        let ring: Vec<Point> = self.edges().map(|edge| edge.start).chain(self.vertices().first().map(|v| *v + self.translate())).collect();
+        let pieces = match style.stroke_dash {
+            // a lone vertex has no outline
+            _ if ring.len() < 2 => Vec::new(),
+            Some(dash) => dashes(&ring, dash),
+            None => vec![ring],
+        };
+        let (width, keep): (u32, fn(Option<bool>) -> bool) = match style.stroke_alignment {
+            _ if style.stroke_width == 1 => return draw_pieces(&pieces, color, 1, &mut target).map(|_| target.area()),
+            StrokeAlignment::Center => return draw_pieces(&pieces, color, style.stroke_width, &mut target).map(|_| target.area()),
+            // the outline itself belongs to both sides
+            StrokeAlignment::Inside => (style.stroke_width * 2 - 1, |at| at != Some(false)),
+            StrokeAlignment::Outside => (style.stroke_width * 2 - 1, |at| at != Some(true)),
+        };
+        let edges: Vec<Line> = self.edges().collect();
+        draw_pieces(&pieces, color, width, &mut Keep { target: &mut target, keep: |p| keep(edges_locate(edges.iter().copied(), p, style.fill_rule)) })?;
+        Ok(target.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565, RgbColor};
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle, StrokeAlignment};
+    use crate::polygon::{DashPattern, FillRule, Polygon, PolygonStyle, PolygonStyleBuilder};
+    use crate::polygon::tests::{fill_pixels, ring};
+
+    fn render<S>(polygon: &Polygon, style: S) -> MockDisplay<Rgb565> where S: Into<PolygonStyle<Rgb565>> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        polygon.into_styled(style.into()).draw(&mut display).unwrap();
+        display
+    }
+
+    #[test]
+    fn test_non_zero_fill_with_dashed_outline() {
+        let star = ring(&[[30, 2], [48, 60], [2, 22], [58, 22], [12, 60]]);
+        let polygon = Polygon::new(&star);
+        let style = PolygonStyleBuilder::new()
+            .fill_color(Rgb565::BLUE)
+            .fill_rule(FillRule::NonZero)
+            .stroke_color(Rgb565::WHITE)
+            .stroke_width(1)
+            .stroke_dash(DashPattern::new(4, 3))
+            .build();
+        let display = render(&polygon, style);
+
+        // the pentagon in the middle is filled, where the even-odd rule leaves a hole
+        let center = Point::new(30, 33);
+        assert_eq!(display.get_pixel(center), Some(Rgb565::BLUE));
+        assert_eq!(render(&polygon, PolygonStyle { fill_rule: FillRule::EvenOdd, ..style }).get_pixel(center), None);
+
+        // along the top edge of the star, runs of four outline pixels alternate with gaps of three
+        let row: String = (2..=58).map(|x| match display.get_pixel(Point::new(x, 22)) {
+            Some(Rgb565::WHITE) => '#',
+            Some(Rgb565::BLUE) => '.',
+            _ => ' ',
+        }).collect();
+        assert!(row.starts_with("####...####") || row.contains("...####...####..."), "{row}");
+        assert!(row.chars().all(|c| c != ' '), "{row}");
+        let solid = render(&polygon, PolygonStyle { stroke_dash: None, ..style });
+        let outline = |display: &MockDisplay<Rgb565>| (0..64).flat_map(|y| (0..64).map(move |x| Point::new(x, y))).filter(|p| display.get_pixel(*p) == Some(Rgb565::WHITE)).count();
+        let (dashed, solid) = (outline(&display), outline(&solid));
+        assert!(dashed * 7 > solid * 4 - 40 && dashed * 7 < solid * 4 + 40, "{dashed} of {solid}");
+    }
+
+    #[test]
+    fn test_primitive_styles_convert() {
+        let square = ring(&[[2, 2], [12, 2], [12, 12], [2, 12]]);
+        let polygon = Polygon::new(&square);
+        let fill = PolygonStyle::from(PrimitiveStyle::with_fill(Rgb565::RED));
+        assert_eq!((fill.fill_color, fill.stroke_color, fill.fill_rule), (Some(Rgb565::RED), None, FillRule::EvenOdd));
+        let mut expected = MockDisplay::new();
+        polygon.into_styled(PrimitiveStyle::with_fill(Rgb565::RED)).draw(&mut expected).unwrap();
+        render(&polygon, fill).assert_eq(&expected);
+
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        polygon.into_styled(PrimitiveStyle::with_stroke(Rgb565::RED, 1)).draw(&mut expected).unwrap();
+        render(&polygon, PrimitiveStyle::with_stroke(Rgb565::RED, 1)).assert_eq(&expected);
+    }
+
+    #[test]
+    fn test_aligned_strokes_stay_on_their_side() {
+        let square = ring(&[[10, 10], [30, 10], [30, 30], [10, 30]]);
+        let polygon = Polygon::new(&square);
+        let inside: Vec<Point> = fill_pixels(&polygon);
+        let stroked = |alignment| {
+            let display = render(&polygon, PolygonStyleBuilder::new().stroke_color(Rgb565::GREEN).stroke_width(3).stroke_alignment(alignment).build());
+            (0..64).flat_map(|y| (0..64).map(move |x| Point::new(x, y))).filter(|p| display.get_pixel(*p).is_some()).collect::<Vec<_>>()
+        };
+        let within = stroked(StrokeAlignment::Inside);
+        assert!(within.iter().all(|p| inside.contains(p)));
+        assert!(within.contains(&Point::new(12, 20)) && !within.contains(&Point::new(13, 20)));
+        let without = stroked(StrokeAlignment::Outside);
+        assert!(without.iter().all(|p| !inside.contains(p) || p.x == 10 || p.x == 30 || p.y == 10 || p.y == 30));
+        assert!(without.contains(&Point::new(8, 20)) && !without.contains(&Point::new(7, 20)) && !without.contains(&Point::new(11, 20)));
+
+        // a pattern leaves every other pixel of the fill alone
+        let checkered = PolygonStyleBuilder::new().fill_color(BinaryColor::On).fill_pattern(|p| (p.x + p.y) % 2 == 0).build();
+        let mut display = MockDisplay::new();
+        polygon.into_styled(checkered).draw(&mut display).unwrap();
+        assert_eq!((display.get_pixel(Point::new(12, 12)), display.get_pixel(Point::new(12, 13))), (Some(BinaryColor::On), None));
+    }
+}
@@ -0,0 +1,151 @@
+//! Polygons with vertices between pixels.
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{Line, Primitive, PrimitiveStyle, Rectangle, StyledDrawable};
+use super::{fill_scanlines, ring_edges, vertex_bounds, PolygonOwned};
+use super::math;
+use super::affected::AffectedArea;
+use super::scanline::Scanlines;
+
+/// Units per pixel of [`SubpixelPolygon`] vertices, which are 26.6 fixed point.
+pub const SUBPIXEL_SCALE: i32 = 64;
+
+/// A closed polygon over a borrowed list of vertices in 1/64 pixel units, for shapes that move
+/// by less than a pixel at a time, such as one turning slowly.
+///
+/// The fill covers the same pixels as a [`Polygon`](super::Polygon) through the exact sub-pixel outline would:
+/// every pixel whose coordinate lies inside or on the boundary. Span ends are found from the
+/// fractional vertices and only rounded to whole pixels as they are drawn, so edges creep across
+/// the screen rather than jumping whenever a vertex rounds to the next pixel. A stroke is drawn
+/// through the vertices rounded to the nearest pixel. The translate offset is in whole pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubpixelPolygon<'a> {
+    pub translate: Point,
+    pub vertices: &'a [Point],
+}
+
+impl<'a> SubpixelPolygon<'a> {
+    /// Creates a polygon without offset.
+    pub const fn new(vertices: &'a [Point]) -> Self {
+        SubpixelPolygon { translate: Point::zero(), vertices }
+    }
+
+    /// The vertex at `(x, y)` pixels, rounded to the nearest 1/64 pixel.
+    pub fn vertex(x: f32, y: f32) -> Point {
+        Point::new(math::round(x * SUBPIXEL_SCALE as f32) as i32, math::round(y * SUBPIXEL_SCALE as f32) as i32)
+    }
+
+    /// Returns the edges of the closed ring in 1/64 pixel units, with the translate offset
+    /// applied.
+    pub fn edges(&self) -> impl Iterator<Item = Line> + 'a {
+        let translate = self.translate * SUBPIXEL_SCALE;
+        ring_edges(self.vertices.iter().map(move |v| *v + translate))
+    }
+
+    /// Returns the polygon with every vertex rounded to the nearest pixel, halfway coordinates
+    /// rounding towards positive infinity. The translate offset is carried over.
+    pub fn rounded(&self) -> PolygonOwned {
+        let round = |v: i32| (v as i64 + SUBPIXEL_SCALE as i64 / 2).div_euclid(SUBPIXEL_SCALE as i64) as i32;
+        PolygonOwned::with_translate(self.vertices.iter().map(|v| Point::new(round(v.x), round(v.y))).collect(), self.translate)
+    }
+}
+
+impl<'a> Dimensions for SubpixelPolygon<'a> {
+    /// Returns the box around the vertices in pixels, rounded outwards, moved by the translate
+    /// offset.
+    fn bounding_box(&self) -> Rectangle {
+        let fine = vertex_bounds(self.vertices.iter().copied());
+        let scale = SUBPIXEL_SCALE as i64;
+        let floor = |v: i64| v.div_euclid(scale) as i32;
+        let ceil = |v: i64| (v + scale - 1).div_euclid(scale) as i32;
+        let (min, max) = (fine.top_left, fine.bottom_right().unwrap_or(fine.top_left));
+        vertex_bounds([
+            Point::new(floor(min.x as i64), floor(min.y as i64)) + self.translate,
+            Point::new(ceil(max.x as i64), ceil(max.y as i64)) + self.translate,
+        ].into_iter())
+    }
+}
+
+impl<'a> Primitive for SubpixelPolygon<'a> {}
+
+impl<'a, C: PixelColor> StyledDrawable<PrimitiveStyle<C>> for SubpixelPolygon<'a> {
+    type Color = C;
+    type Output = Rectangle;
+
+    /// Draws the polygon and returns the smallest rectangle holding every pixel written inside the
+    /// target's bounds, or a zero-sized rectangle if there were none.
+    fn draw_styled<D>(&self, style: &PrimitiveStyle<C>, target: &mut D) -> Result<Self::Output, D::Error> where D: DrawTarget<Color = Self::Color> {
+        if style.stroke_width > 0 {
+            return self.rounded().as_polygon().draw_styled(style, target);
+        }
+        let mut target = AffectedArea::new(target);
+        if let Some(color) = style.fill_color {
+            fill_scanlines(Scanlines::subpixel(self.edges(), SUBPIXEL_SCALE), color, &mut target)?;
+        }
+        Ok(target.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::{Dimensions, Point};
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{ContainsPoint, Primitive, PrimitiveStyle};
+    use crate::polygon::{Polygon, SubpixelPolygon, SUBPIXEL_SCALE};
+    use crate::polygon::tests::{fill_pixels, PixelCollector, ring};
+
+    fn subpixel_fill(polygon: &SubpixelPolygon) -> Vec<Point> {
+        let mut collector = PixelCollector::default();
+        polygon.into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+        collector.pixels.sort_by_key(|p| (p.y, p.x));
+        collector.pixels
+    }
+
+    #[test]
+    fn test_whole_pixel_vertices_fill_like_a_polygon() {
+        let fixtures = [
+            ring(&[[-6, 3], [50, -4], [70, 30], [31, 23], [35, 52], [2, 40]]),
+            ring(&[[0, 0], [20, 0], [20, 8], [10, 8], [10, 16], [30, 24], [0, 24]]),
+            ring(&[[0, -20], [10, 50], [20, -20], [0, 30], [20, 30]]),
+            ring(&[[3, 3], [9, 3]]),
+        ];
+        for vertices in fixtures.iter() {
+            let mut polygon = Polygon::new(vertices);
+            polygon.set_translate(Point::new(3, -5));
+            let fine: Vec<Point> = vertices.iter().map(|v| *v * SUBPIXEL_SCALE).collect();
+            let mut subpixel = SubpixelPolygon::new(&fine);
+            subpixel.translate = polygon.translate();
+            assert_eq!(subpixel_fill(&subpixel), fill_pixels(&polygon), "{vertices:?}");
+            assert_eq!(subpixel.bounding_box(), polygon.bounding_box());
+            assert_eq!(subpixel.rounded().vertices(), vertices.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_slow_rotation_moves_edges_smoothly() {
+        // a triangle turning a degree at a time, where its edges cross row 45 moves at most a pixel
+        // per step, the right end steadily inwards
+        let mut previous: Option<(i32, i32)> = None;
+        for step in 0..=40 {
+            let angle = (step as f32).to_radians();
+            let vertices: Vec<Point> = [0.0f32, 120.0, 240.0].iter().map(|corner| {
+                let (sin, cos) = (angle + corner.to_radians()).sin_cos();
+                SubpixelPolygon::vertex(50.0 + 40.0 * cos, 50.0 + 40.0 * sin)
+            }).collect();
+            let pixels = subpixel_fill(&SubpixelPolygon::new(&vertices));
+            let exact = Polygon::new(&vertices);
+            let expected: Vec<Point> = (0..=100).flat_map(|y| (0..=100).map(move |x| Point::new(x, y))).filter(|p| exact.contains(*p * SUBPIXEL_SCALE)).collect();
+            assert_eq!(pixels, expected, "{step} degrees");
+            let row: Vec<i32> = pixels.iter().filter(|p| p.y == 45).map(|p| p.x).collect();
+            let ends = (row[0], row[row.len() - 1]);
+            if let Some((left, right)) = previous {
+                assert!(left.abs_diff(ends.0) <= 1 && (0..=1).contains(&(right - ends.1)), "{previous:?} to {ends:?} at {step} degrees");
+            }
+            previous = Some(ends);
+        }
+        assert_eq!(previous, Some((29, 69)));
+    }
+}
@@ -0,0 +1,242 @@
+//! Parsing the straight-line subset of SVG path data.
+
+use core::fmt;
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use super::{CoordinateMapping, PolygonOwned};
+
+/// What went wrong while parsing path data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SvgPathErrorKind {
+    /// The path data does not start with a moveto (`M` or `m`).
+    MissingMoveTo,
+    /// A command letter was expected.
+    ExpectedCommand,
+    /// A coordinate was expected.
+    ExpectedNumber,
+    /// A curve or arc command, which has no straight-line equivalent.
+    UnsupportedCommand(char),
+    /// A coordinate does not map to a pixel.
+    OutOfRange,
+}
+
+/// An error in SVG path data, at `offset` bytes into the string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SvgPathError {
+    pub offset: usize,
+    pub kind: SvgPathErrorKind,
+}
+
+impl fmt::Display for SvgPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SvgPathErrorKind::MissingMoveTo => write!(f, "path data must start with a moveto")?,
+            SvgPathErrorKind::ExpectedCommand => write!(f, "expected a path command")?,
+            SvgPathErrorKind::ExpectedNumber => write!(f, "expected a number")?,
+            SvgPathErrorKind::UnsupportedCommand(command) => write!(f, "unsupported path command '{command}', only straight lines are supported")?,
+            SvgPathErrorKind::OutOfRange => write!(f, "coordinate does not map to a pixel")?,
+        }
+        write!(f, " at byte {}", self.offset)
+    }
+}
+
+impl core::error::Error for SvgPathError {}
+
+struct Parser<'d> {
+    data: &'d str,
+    pos: usize,
+}
+
+impl<'d> Parser<'d> {
+    fn error(&self, kind: SvgPathErrorKind) -> SvgPathError {
+        SvgPathError { offset: self.pos, kind }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.as_bytes().get(self.pos).copied()
+    }
+
+    /// Skips whitespace and commas, which separate everything in path data.
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0c' | b',')) {
+            self.pos += 1;
+        }
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.peek(), Some(b'0'..=b'9' | b'+' | b'-' | b'.'))
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    /// Reads a number, which ends at anything that cannot continue it, so `1.5.5-2` is `1.5`,
+    /// `.5` and `-2`.
+    fn number(&mut self) -> Result<f64, SvgPathError> {
+        self.skip_separators();
+        let start = self.pos;
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.pos = start;
+            return Err(self.error(SvgPathErrorKind::ExpectedNumber));
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+        self.data[start..self.pos].parse().map_err(|_| SvgPathError { offset: start, kind: SvgPathErrorKind::ExpectedNumber })
+    }
+}
+
+impl PolygonOwned {
+    /// Parses the straight-line subset of an SVG path `d` attribute into one polygon per
+    /// subpath, mapping coordinates to pixels through `mapping`.
+    ///
+    /// Supports `M`, `L`, `H`, `V` and `Z` in absolute and relative (lowercase) form, including
+    /// implicitly repeated commands and the implicit lineto after a moveto. Subpaths are closed
+    /// whether or not they end in `Z`, as SVG does when filling, and subpaths of a lone moveto are
+    /// left out. Curves and arcs are rejected with [`SvgPathErrorKind::UnsupportedCommand`].
+    pub fn from_svg_path(data: &str, mapping: &CoordinateMapping) -> Result<Vec<PolygonOwned>, SvgPathError> {
+        let mut parser = Parser { data, pos: 0 };
+        let mut polygons = Vec::new();
+        let mut subpath: Vec<Point> = Vec::new();
+        let finish = |subpath: &mut Vec<Point>, polygons: &mut Vec<PolygonOwned>| {
+            if subpath.len() > 1 && subpath.first() == subpath.last() {
+                let _ = subpath.pop();
+            }
+            if subpath.len() > 1 {
+                polygons.push(PolygonOwned::new(core::mem::take(subpath)));
+            }
+            subpath.clear();
+        };
+
+        let (mut current, mut start) = ((0.0, 0.0), (0.0, 0.0));
+        let mut previous: Option<u8> = None;
+        loop {
+            parser.skip_separators();
+            let offset = parser.pos;
+            let command = match parser.peek() {
+                None => break,
+                Some(letter) if letter.is_ascii_alphabetic() => {
+                    parser.pos += 1;
+                    letter
+                }
+                // numbers after a command repeat it, a moveto repeats as a lineto
+                Some(_) => match previous {
+                    Some(b'M') if parser.at_number() => b'L',
+                    Some(b'm') if parser.at_number() => b'l',
+                    Some(command) if command != b'Z' && parser.at_number() => command,
+                    _ => return Err(parser.error(SvgPathErrorKind::ExpectedCommand)),
+                },
+            };
+            if previous.is_none() && !matches!(command, b'M' | b'm') {
+                return Err(SvgPathError { offset, kind: SvgPathErrorKind::MissingMoveTo });
+            }
+            let relative = command.is_ascii_lowercase();
+            let base = if relative { current } else { (0.0, 0.0) };
+            let target = match command.to_ascii_uppercase() {
+                b'M' | b'L' => (base.0 + parser.number()?, base.1 + parser.number()?),
+                b'H' => (base.0 + parser.number()?, current.1),
+                b'V' => (current.0, base.1 + parser.number()?),
+                b'Z' => {
+                    finish(&mut subpath, &mut polygons);
+                    current = start;
+                    // a closepath takes no numbers, so it is never repeated implicitly
+                    previous = Some(b'Z');
+                    continue;
+                }
+                b'C' | b'S' | b'Q' | b'T' | b'A' => return Err(SvgPathError { offset, kind: SvgPathErrorKind::UnsupportedCommand(command as char) }),
+                _ => return Err(SvgPathError { offset, kind: SvgPathErrorKind::ExpectedCommand }),
+            };
+            if command.eq_ignore_ascii_case(&b'M') {
+                finish(&mut subpath, &mut polygons);
+                start = target;
+            } else if subpath.is_empty() {
+                // drawing on after a closepath starts a new subpath at the old start point
+                subpath.push(mapping.to_pixel(current.0, current.1).map_err(|_| SvgPathError { offset, kind: SvgPathErrorKind::OutOfRange })?);
+            }
+            subpath.push(mapping.to_pixel(target.0, target.1).map_err(|_| SvgPathError { offset, kind: SvgPathErrorKind::OutOfRange })?);
+            current = target;
+            previous = Some(command);
+        }
+        finish(&mut subpath, &mut polygons);
+        Ok(polygons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::tests::ring;
+    use crate::polygon::{CoordinateMapping, PolygonOwned, SvgPathError, SvgPathErrorKind};
+
+    fn parse(data: &str) -> Result<Vec<Vec<Point>>, SvgPathError> {
+        PolygonOwned::from_svg_path(data, &CoordinateMapping::IDENTITY).map(|polygons| polygons.into_iter().map(PolygonOwned::into_vertices).collect())
+    }
+
+    #[test]
+    fn test_material_icons() {
+        // "home"
+        assert_eq!(parse("M10 20v-6h4v6h5v-8h3L12 3 2 12h3v8z").unwrap(), vec![
+            ring(&[[10, 20], [10, 14], [14, 14], [14, 20], [19, 20], [19, 12], [22, 12], [12, 3], [2, 12], [5, 12], [5, 20]]),
+        ]);
+
+        // "view_module", where each relative moveto starts from the previous subpath's start
+        let view_module = parse("M4 11h5V5H4v6zm0 7h5v-6H4v6zm6 0h5v-6h-5v6zm6 0h5v-6h-5v6zm-6-7h5V5h-5v6zm6-6v6h5V5h-5z").unwrap();
+        assert_eq!(view_module.len(), 6);
+        assert_eq!(view_module[0], ring(&[[4, 11], [9, 11], [9, 5], [4, 5]]));
+        assert_eq!(view_module[1], ring(&[[4, 18], [9, 18], [9, 12], [4, 12]]));
+        assert_eq!(view_module[4], ring(&[[10, 11], [15, 11], [15, 5], [10, 5]]));
+        assert_eq!(view_module[5], ring(&[[16, 5], [16, 11], [21, 11], [21, 5]]));
+    }
+
+    #[test]
+    fn test_number_and_separator_forms() {
+        let mapping = CoordinateMapping::new(2.0, (0.0, 0.0));
+        let polygons = PolygonOwned::from_svg_path(" M0.5.5L10-5 , 1e1,2E+0\n l-1-1 ", &mapping).unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].vertices(), ring(&[[1, 1], [20, -10], [20, 4], [18, 2]]));
+
+        // an unclosed subpath is closed for filling, a lone moveto is dropped
+        assert_eq!(parse("M0 0 10 0 10 10 M5 5 m 1 1 h 3 v 3").unwrap(), vec![
+            ring(&[[0, 0], [10, 0], [10, 10]]),
+            ring(&[[6, 6], [9, 6], [9, 9]]),
+        ]);
+        // drawing on after a closepath starts from the closed subpath's start
+        assert_eq!(parse("M2 2 h4 v4 z l-2 3 h4").unwrap(), vec![ring(&[[2, 2], [6, 2], [6, 6]]), ring(&[[2, 2], [0, 5], [4, 5]])]);
+    }
+
+    #[test]
+    fn test_malformed_path_data() {
+        let error = |offset, kind| Err(SvgPathError { offset, kind });
+        assert_eq!(parse("L1 1"), error(0, SvgPathErrorKind::MissingMoveTo));
+        assert_eq!(parse("M0 0 C1 1 2 2 3 3"), error(5, SvgPathErrorKind::UnsupportedCommand('C')));
+        assert_eq!(parse("M0 0 a1 1 0 0 1 3 3"), error(5, SvgPathErrorKind::UnsupportedCommand('a')));
+        assert_eq!(parse("M0 0 L1"), error(7, SvgPathErrorKind::ExpectedNumber));
+        assert_eq!(parse("M0 0 L1 x"), error(8, SvgPathErrorKind::ExpectedNumber));
+        assert_eq!(parse("M0 0 h1 z 4"), error(10, SvgPathErrorKind::ExpectedCommand));
+        assert_eq!(parse("M0 0 K 1"), error(5, SvgPathErrorKind::ExpectedCommand));
+        assert_eq!(parse("M0 0 L1e10 0"), error(5, SvgPathErrorKind::OutOfRange));
+        assert_eq!(parse("").unwrap(), Vec::<Vec<Point>>::new());
+        assert_eq!(parse("M0 0 L1 x").unwrap_err().to_string(), "expected a number at byte 8");
+    }
+}
@@ -0,0 +1,121 @@
+//! Drawing one polygon across several draw targets that each hold part of the screen.
+
+use alloc::vec::Vec;
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, StyledDrawable};
+use super::Polygon;
+
+impl<'a> Polygon<'a> {
+    /// Draws the polygon across `tiles`, each a target holding the part of the screen whose top
+    /// left corner is at the given origin, so a pixel at `p` is drawn at `p - origin` in its tile.
+    ///
+    /// A fill is rasterized once, with each span cut up between the tiles it crosses, and covers
+    /// exactly the pixels a single draw covers. A stroke is drawn into each tile in turn, clipped
+    /// to the tile.
+    pub fn draw_tiled<'t, C, D>(&self, style: &PrimitiveStyle<C>, tiles: impl IntoIterator<Item = (Point, &'t mut D)>) -> Result<(), D::Error> where C: PixelColor, D: DrawTarget<Color = C> + 't {
+        let mut tiles: Vec<(Point, &mut D)> = tiles.into_iter().collect();
+        if style.stroke_width > 0 {
+            for (origin, target) in tiles {
+                let area = target.bounding_box();
+                self.draw_styled(style, &mut target.translated(-origin).clipped(&Rectangle::new(area.top_left + origin, area.size)))?;
+            }
+            return Ok(());
+        }
+        let Some(color) = style.fill_color else {
+            return Ok(());
+        };
+        for span in self.spans() {
+            let end = span.x as i64 + span.len as i64 - 1;
+            for (origin, target) in tiles.iter_mut() {
+                let area = target.bounding_box();
+                let (top_left, size) = (area.top_left + *origin, area.size);
+                if span.y < top_left.y || span.y as i64 >= top_left.y as i64 + size.height as i64 {
+                    continue;
+                }
+                let start = span.x.max(top_left.x);
+                let end = end.min(top_left.x as i64 + size.width as i64 - 1);
+                if start as i64 <= end {
+                    let run = Rectangle::new(Point::new(start, span.y) - *origin, Size::new((end - start as i64) as u32 + 1, 1));
+                    target.fill_solid(&run, color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use embedded_graphics::Drawable;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+    use embedded_graphics::Pixel;
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::PixelCollector;
+
+    /// A tile that fails the test on any pixel outside it.
+    struct Tile {
+        size: Size,
+        pixels: Vec<Point>,
+    }
+
+    impl OriginDimensions for Tile {
+        fn size(&self) -> Size {
+            self.size
+        }
+    }
+
+    impl DrawTarget for Tile {
+        type Color = BinaryColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+            for Pixel(p, _) in pixels {
+                assert!(p.x >= 0 && p.y >= 0 && p.x < self.size.width as i32 && p.y < self.size.height as i32, "{p:?} outside the tile");
+                self.pixels.push(p);
+            }
+            Ok(())
+        }
+    }
+
+    fn one_shot(polygon: &Polygon, style: PrimitiveStyle<BinaryColor>) -> Vec<Point> {
+        let mut collector = PixelCollector::default();
+        polygon.into_styled(style).draw(&mut collector).unwrap();
+        let mut pixels = collector.pixels;
+        pixels.retain(|p| (0..64).contains(&p.x) && (0..48).contains(&p.y));
+        pixels.sort_by_key(|p| (p.y, p.x));
+        pixels.dedup();
+        pixels
+    }
+
+    fn composite(polygon: &Polygon, style: PrimitiveStyle<BinaryColor>) -> Vec<Point> {
+        let mut tiles: Vec<(Point, Tile)> = [(0, 0), (32, 0), (0, 24), (32, 24)].iter()
+            .map(|&(x, y)| (Point::new(x, y), Tile { size: Size::new(32, 24), pixels: Vec::new() }))
+            .collect();
+        polygon.draw_tiled(&style, tiles.iter_mut().map(|(origin, tile)| (*origin, tile))).unwrap();
+        let mut pixels: Vec<Point> = tiles.iter().flat_map(|(origin, tile)| tile.pixels.iter().map(move |p| *p + *origin)).collect();
+        pixels.sort_by_key(|p| (p.y, p.x));
+        pixels.dedup();
+        pixels
+    }
+
+    #[test]
+    fn test_tiles_stitch_like_a_single_draw() {
+        let vertices = [[-6, 3], [50, -4], [70, 30], [31, 23], [35, 52], [2, 40]].iter().map(Point::from).collect::<Vec<_>>();
+        let mut polygon = Polygon::new(&vertices);
+        for translate in [Point::zero(), Point::new(-9, 5)] {
+            polygon.set_translate(translate);
+            let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+            let pixels = composite(&polygon, fill);
+            assert!(pixels.iter().any(|p| p.x == 31) && pixels.iter().any(|p| p.y == 24));
+            assert_eq!(pixels, one_shot(&polygon, fill));
+            let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+            assert_eq!(composite(&polygon, stroke), one_shot(&polygon, stroke));
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Tracing the outlines of binary images into polygons.
+//!
+//! Borders are followed with the Suzuki–Abe algorithm, which walks the centers of the set pixels
+//! on each border and tells the outer borders of 8-connected regions apart from the borders of the
+//! holes inside them. Since the fill covers the boundary, filling a traced polygon reproduces its
+//! region exactly.
+
+use core::convert::Infallible;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+use embedded_graphics::image::ImageDrawable;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+use super::PolygonWithHoles;
+use super::simplify::simplify_ring;
+
+/// The 8 neighbours as `(dx, dy)`, clockwise on screen starting east.
+const NEIGHBOURS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BorderKind {
+    Outer,
+    Hole,
+}
+
+struct Border {
+    kind: BorderKind,
+    parent: usize,
+    ring: Vec<Point>,
+}
+
+/// Border labels over the image with a one pixel frame of background around it.
+struct Labels {
+    width: i32,
+    labels: Vec<i32>,
+}
+
+impl Labels {
+    fn index(&self, p: Point) -> usize {
+        (p.y * self.width + p.x) as usize
+    }
+
+    fn get(&self, p: Point) -> i32 {
+        self.labels[self.index(p)]
+    }
+
+    fn set(&mut self, p: Point, label: i32) {
+        let index = self.index(p);
+        self.labels[index] = label;
+    }
+
+    /// Follows the border through `start`, entering from the background pixel `from`, and labels
+    /// it `border`.
+    fn follow(&mut self, start: Point, from: Point, border: i32) -> Vec<Point> {
+        let direction = |center: Point, neighbour: Point| NEIGHBOURS.iter().position(|&(dx, dy)| center + Point::new(dx, dy) == neighbour).unwrap();
+        let step = |center: Point, direction: usize| center + Point::new(NEIGHBOURS[direction].0, NEIGHBOURS[direction].1);
+
+        let from = direction(start, from);
+        let Some(last) = (0..8).map(|k| step(start, (from + k) % 8)).find(|&p| self.get(p) != 0) else {
+            // an isolated pixel, kept as a zero-length edge so it still fills
+            self.set(start, -border);
+            return vec![start; 2];
+        };
+        let (mut previous, mut current) = (last, start);
+        let mut ring = Vec::new();
+        loop {
+            ring.push(current);
+            let from = direction(current, previous);
+            let mut east_is_background = false;
+            let mut next = current;
+            for k in 1..=8 {
+                let d = (from + 8 - k) % 8;
+                next = step(current, d);
+                if self.get(next) != 0 {
+                    break;
+                }
+                east_is_background |= d == 0;
+            }
+            if east_is_background {
+                self.set(current, -border);
+            } else if self.get(current) == 1 {
+                self.set(current, border);
+            }
+            if next == start && current == last {
+                return ring;
+            }
+            previous = current;
+            current = next;
+        }
+    }
+}
+
+impl PolygonWithHoles {
+    /// Traces the regions of set pixels within `area`, as decided by `is_set`, into one polygon
+    /// per 8-connected region, with a hole for every background region enclosed by it.
+    ///
+    /// Vertices are the centers of the pixels on each border, with straight runs merged, so the
+    /// fill of the returned polygons covers exactly the set pixels. Regions inside holes come
+    /// back as polygons of their own. A lone pixel becomes a two-vertex ring of that pixel twice.
+    /// Use [`simplify`](Self::simplify) to trade that exactness for fewer vertices.
+    pub fn trace(area: Rectangle, mut is_set: impl FnMut(Point) -> bool) -> Vec<PolygonWithHoles> {
+        let (width, height) = (area.size.width as i32 + 2, area.size.height as i32 + 2);
+        let mut labels = Labels { width, labels: vec![0; (width * height) as usize] };
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if is_set(area.top_left + Point::new(x - 1, y - 1)) {
+                    labels.set(Point::new(x, y), 1);
+                }
+            }
+        }
+
+        // border 1 is the frame, a hole around everything
+        let mut borders = vec![Border { kind: BorderKind::Hole, parent: 0, ring: Vec::new() }];
+        for y in 1..height - 1 {
+            let mut last_border = 1;
+            for x in 1..width - 1 {
+                let p = Point::new(x, y);
+                let label = labels.get(p);
+                let start = if label == 1 && labels.get(p - Point::new(1, 0)) == 0 {
+                    Some((BorderKind::Outer, p - Point::new(1, 0)))
+                } else if label >= 1 && labels.get(p + Point::new(1, 0)) == 0 {
+                    if label > 1 {
+                        last_border = label;
+                    }
+                    Some((BorderKind::Hole, p + Point::new(1, 0)))
+                } else {
+                    None
+                };
+                if let Some((kind, from)) = start {
+                    let previous = &borders[last_border as usize - 1];
+                    let parent = if previous.kind == kind { previous.parent } else { last_border as usize };
+                    let ring = labels.follow(p, from, borders.len() as i32 + 1);
+                    borders.push(Border { kind, parent, ring });
+                }
+                let label = labels.get(p);
+                if label != 0 && label != 1 {
+                    last_border = label.abs();
+                }
+            }
+        }
+
+        let offset = area.top_left - Point::new(1, 1);
+        let ring = |border: &Border| simplify_ring(&border.ring.iter().map(|p| *p + offset).collect::<Vec<_>>(), 0.0);
+        borders.iter().enumerate().filter(|(_, border)| border.kind == BorderKind::Outer).map(|(i, outer)| {
+            let holes = borders.iter().filter(|hole| hole.kind == BorderKind::Hole && hole.parent == i + 1).map(ring).collect();
+            PolygonWithHoles::new(ring(outer), holes)
+        }).collect()
+    }
+
+    /// Traces the `On` pixels of a binary image as [`trace`](Self::trace) does, with the image's
+    /// top left pixel at the origin.
+    pub fn trace_image<I: ImageDrawable<Color = BinaryColor>>(image: &I) -> Vec<PolygonWithHoles> {
+        let mut mask = Mask { size: image.size(), set: vec![false; image.size().width as usize * image.size().height as usize] };
+        image.draw(&mut mask).unwrap_or_else(|never| match never {});
+        PolygonWithHoles::trace(Rectangle::new(Point::zero(), mask.size), |p| mask.set[p.y as usize * mask.size.width as usize + p.x as usize])
+    }
+}
+
+/// Records the `On` pixels an image draws.
+struct Mask {
+    size: Size,
+    set: Vec<bool>,
+}
+
+impl OriginDimensions for Mask {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for Mask {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error> where I: IntoIterator<Item = Pixel<Self::Color>> {
+        for Pixel(p, color) in pixels {
+            if p.x >= 0 && p.y >= 0 && (p.x as u32) < self.size.width && (p.y as u32) < self.size.height {
+                self.set[p.y as usize * self.size.width as usize + p.x as usize] = color == BinaryColor::On;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::draw_target::DrawTarget;
+    use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+    use embedded_graphics::image::ImageDrawable;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{PointsIter, Primitive, PrimitiveStyle, Rectangle};
+    use embedded_graphics::Pixel;
+    use crate::polygon::PolygonWithHoles;
+    use crate::polygon::tests::PixelCollector;
+
+    /// A sprite drawn as text, `#` for set pixels.
+    struct Sprite(&'static [&'static str]);
+
+    impl Sprite {
+        fn pixels(&self) -> Vec<Point> {
+            let mut pixels: Vec<Point> = self.0.iter().enumerate().flat_map(|(y, row)| {
+                row.bytes().enumerate().filter(|(_, c)| *c == b'#').map(move |(x, _)| Point::new(x as i32, y as i32))
+            }).collect();
+            pixels.sort_by_key(|p| (p.y, p.x));
+            pixels
+        }
+    }
+
+    impl OriginDimensions for Sprite {
+        fn size(&self) -> Size {
+            Size::new(self.0[0].len() as u32, self.0.len() as u32)
+        }
+    }
+
+    impl ImageDrawable for Sprite {
+        type Color = BinaryColor;
+
+        fn draw<D>(&self, target: &mut D) -> Result<(), D::Error> where D: DrawTarget<Color = BinaryColor> {
+            target.draw_iter(self.pixels().into_iter().map(|p| Pixel(p, BinaryColor::On)))
+        }
+
+        fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error> where D: DrawTarget<Color = BinaryColor> {
+            target.draw_iter(self.pixels().into_iter().filter(|p| area.contains(*p)).map(|p| Pixel(p - area.top_left, BinaryColor::On)))
+        }
+    }
+
+    fn filled(polygons: &[PolygonWithHoles]) -> Vec<Point> {
+        let mut collector = PixelCollector::default();
+        for polygon in polygons {
+            polygon.clone().into_styled(PrimitiveStyle::with_fill(BinaryColor::On)).draw(&mut collector).unwrap();
+        }
+        collector.pixels.sort_by_key(|p| (p.y, p.x));
+        collector.pixels.dedup();
+        collector.pixels
+    }
+
+    const SPRITES: [&[&str]; 3] = [
+        // a ring around a block, a diagonal line, a lone pixel and a dash
+        &[
+            "..........#...",
+            ".#######...#..",
+            ".#.....##...#.",
+            ".#.###..#....#",
+            ".#.###..#.....",
+            ".#......#.....",
+            ".########.....",
+            "..............",
+            "...#......##..",
+        ],
+        // a space invader
+        &[
+            "..#.....#..",
+            "...#...#...",
+            "..#######..",
+            ".##.###.##.",
+            "###########",
+            "#.#######.#",
+            "#.#.....#.#",
+            "...##.##...",
+        ],
+        // a heart
+        &[
+            ".###...###.",
+            "#####.#####",
+            "###########",
+            "###########",
+            ".#########.",
+            "..#######..",
+            "...#####...",
+            "....###....",
+            ".....#.....",
+        ],
+    ];
+
+    #[test]
+    fn test_tracing_reproduces_the_mask() {
+        for rows in SPRITES.iter() {
+            let sprite = Sprite(rows);
+            let polygons = PolygonWithHoles::trace_image(&sprite);
+            assert_eq!(filled(&polygons), sprite.pixels(), "{rows:?}");
+
+            let simplified: Vec<PolygonWithHoles> = polygons.iter().map(|p| p.simplify(0.5)).collect();
+            let original = sprite.pixels();
+            let recovered = filled(&simplified);
+            let kept = original.iter().filter(|p| recovered.contains(p)).count();
+            assert!(kept * 100 >= original.len() * 98, "{kept} of {} in {rows:?}", original.len());
+        }
+    }
+
+    #[test]
+    fn test_tracing_structure() {
+        let polygons = PolygonWithHoles::trace_image(&Sprite(SPRITES[0]));
+        // in scan order: the diagonal, the ring, the block inside its hole, the lone pixel and the dash
+        assert_eq!(polygons.len(), 5);
+        assert_eq!(polygons[0].exterior, vec![Point::new(10, 0), Point::new(13, 3)]);
+        assert_eq!(polygons[1].exterior.len(), 5);
+        assert_eq!(polygons[1].holes.len(), 1);
+        assert_eq!(polygons[2], PolygonWithHoles::new(vec![Point::new(3, 3), Point::new(3, 4), Point::new(5, 4), Point::new(5, 3)], vec![]));
+        assert_eq!(polygons[3].exterior, vec![Point::new(3, 8); 2]);
+        assert_eq!(polygons[4].exterior, vec![Point::new(10, 8), Point::new(11, 8)]);
+
+        // a lone pixel, and tracing an offset area through a predicate
+        let area = Rectangle::new(Point::new(-20, 10), Size::new(5, 5));
+        let polygons = PolygonWithHoles::trace(area, |p| p == Point::new(-18, 12));
+        assert_eq!(polygons, vec![PolygonWithHoles::new(vec![Point::new(-18, 12); 2], vec![])]);
+        assert_eq!(PolygonWithHoles::trace(area, |_| false), vec![]);
+        assert_eq!(filled(&PolygonWithHoles::trace(area, |_| true)), area.points().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,161 @@
+//! The region visible from a point among occluding polygons.
+
+use alloc::vec::Vec;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::{ContainsPoint, Rectangle};
+use super::{Polygon, PolygonOwned};
+use super::math;
+use super::geometry::cross as turn;
+
+type Segment = ((f64, f64), (f64, f64));
+
+fn cross(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+/// Distance along the ray from `origin` in `direction` to the nearest segment it meets.
+fn cast(origin: (f64, f64), direction: (f64, f64), segments: &[Segment]) -> Option<f64> {
+    segments.iter().filter_map(|&(a, b)| {
+        let edge = (b.0 - a.0, b.1 - a.1);
+        let denominator = cross(direction, edge);
+        if math::abs(denominator) < 1e-12 {
+            return None;
+        }
+        let to_start = (a.0 - origin.0, a.1 - origin.1);
+        let along_ray = cross(to_start, edge) / denominator;
+        let along_edge = cross(to_start, direction) / denominator;
+        (along_ray >= 0.0 && (0.0..=1.0).contains(&along_edge)).then_some(along_ray)
+    }).min_by(f64::total_cmp)
+}
+
+/// Drops every point lying in line with its neighbours, as the rays either side of a vertex
+/// behind a face stop on that face in line with its corners.
+fn without_collinear(ring: Vec<Point>) -> Vec<Point> {
+    let mut kept: Vec<Point> = Vec::with_capacity(ring.len());
+    for p in ring {
+        while kept.len() >= 2 && turn(kept[kept.len() - 2], kept[kept.len() - 1], p) == 0 {
+            let _ = kept.pop();
+        }
+        kept.push(p);
+    }
+    // and around the join between the end of the ring and its start
+    while kept.len() >= 3 {
+        let n = kept.len();
+        if turn(kept[n - 2], kept[n - 1], kept[0]) == 0 {
+            let _ = kept.pop();
+        } else if turn(kept[n - 1], kept[0], kept[1]) == 0 {
+            let _ = kept.remove(0);
+        } else {
+            break;
+        }
+    }
+    kept
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the region of `bounds` visible from `viewpoint` when looking past `occluders`,
+    /// such as the area lit by a light or seen by a player in a top down game.
+    ///
+    /// A ray is cast towards every vertex of the occluders and of `bounds`, and two more just
+    /// either side of it, so rays that graze a corner also find what lies behind it. The region is
+    /// the ring through where the rays stop, in order of angle, rounded to the nearest pixel and
+    /// without points in the middle of straight runs.
+    /// Occluder faces facing the viewpoint lie on its boundary. Returns an empty polygon if
+    /// `viewpoint` is outside `bounds` or inside an occluder.
+    pub fn visibility(viewpoint: Point, bounds: &Rectangle, occluders: &[Polygon]) -> PolygonOwned {
+        let Some(bottom_right) = bounds.bottom_right() else {
+            return PolygonOwned::default();
+        };
+        if !bounds.contains(viewpoint) || occluders.iter().any(|occluder| occluder.contains(viewpoint)) {
+            return PolygonOwned::default();
+        }
+        let corners = [bounds.top_left, Point::new(bottom_right.x, bounds.top_left.y), bottom_right, Point::new(bounds.top_left.x, bottom_right.y)];
+        let to_f64 = |p: Point| (p.x as f64, p.y as f64);
+        let mut segments: Vec<Segment> = Vec::new();
+        for i in 0..corners.len() {
+            segments.push((to_f64(corners[i]), to_f64(corners[(i + 1) % corners.len()])));
+        }
+        for occluder in occluders {
+            segments.extend(occluder.edges().map(|edge| (to_f64(edge.start), to_f64(edge.end))));
+        }
+
+        let origin = to_f64(viewpoint);
+        let mut hits: Vec<(f64, Point)> = Vec::new();
+        let targets = occluders.iter().flat_map(|occluder| occluder.vertices().iter().map(move |v| *v + occluder.translate())).chain(corners);
+        for target in targets.filter(|target| *target != viewpoint) {
+            let (dx, dy) = (target.x as f64 - origin.0, target.y as f64 - origin.1);
+            let length = math::hypot(dx, dy);
+            let (cos, sin) = (dx / length, dy / length);
+            let angle = math::atan2(dy, dx);
+            for turn in [-1e-6, 0.0, 1e-6] {
+                let direction = (cos - turn * sin, sin + turn * cos);
+                let angle = angle + turn;
+                if let Some(distance) = cast(origin, direction, &segments) {
+                    let hit = Point::new(math::round(origin.0 + direction.0 * distance) as i32, math::round(origin.1 + direction.1 * distance) as i32);
+                    hits.push((angle, hit));
+                }
+            }
+        }
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut ring: Vec<Point> = hits.into_iter().map(|(_, hit)| hit).collect();
+        ring.dedup();
+        while ring.len() > 1 && ring.first() == ring.last() {
+            let _ = ring.pop();
+        }
+        PolygonOwned::new(without_collinear(ring))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::{Point, Size};
+    use embedded_graphics::primitives::{ContainsPoint, Line, PointsIter, Rectangle};
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::ring;
+    use crate::polygon::distance::segments_touch;
+
+    #[test]
+    fn test_two_boxes_cast_known_shadows() {
+        let bounds = Rectangle::new(Point::zero(), Size::new(101, 61));
+        let right = ring(&[[60, 25], [70, 25], [70, 35], [60, 35]]);
+        let left = ring(&[[20, 20], [30, 20], [30, 40], [20, 40]]);
+        let occluders = [Polygon::new(&right), Polygon::new(&left)];
+        let viewpoint = Point::new(50, 30);
+        let visible = Polygon::visibility(viewpoint, &bounds, &occluders);
+        let vertices = visible.vertices();
+
+        // the shadows run from the near corners of each box out to the edges of the bounds
+        for corner in [[60, 25], [60, 35], [100, 5], [100, 55], [30, 20], [30, 40], [0, 5], [0, 55]] {
+            assert!(vertices.contains(&Point::from(corner)), "{corner:?} in {vertices:?}");
+        }
+        for corner in [[0, 0], [100, 0], [100, 60], [0, 60]] {
+            assert!(vertices.contains(&Point::from(corner)));
+        }
+        assert_eq!(vertices.len(), 12, "{vertices:?}");
+        for lit in [[90, 5], [90, 55], [65, 20], [10, 3], [10, 57], [50, 0]] {
+            assert!(visible.contains(Point::from(lit)), "{lit:?}");
+        }
+        for shadowed in [[90, 15], [80, 30], [100, 45], [10, 30], [25, 30], [0, 10]] {
+            assert!(!visible.contains(Point::from(shadowed)), "{shadowed:?}");
+        }
+
+        // everywhere clear of the outline, visible exactly when the sight line meets no occluder
+        let visible = visible.as_polygon();
+        for p in bounds.points() {
+            if visible.closest_boundary_point(p).1 > 2 {
+                let blocked = occluders.iter().any(|occluder| occluder.edges().any(|edge| segments_touch(edge, Line::new(viewpoint, p))));
+                assert_eq!(visible.contains(p), !blocked, "{p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_nothing_is_visible_from_outside() {
+        let bounds = Rectangle::new(Point::zero(), Size::new(20, 20));
+        let block = ring(&[[5, 5], [10, 5], [10, 10], [5, 10]]);
+        assert!(Polygon::visibility(Point::new(30, 5), &bounds, &[]).vertices().is_empty());
+        assert!(Polygon::visibility(Point::new(7, 7), &bounds, &[Polygon::new(&block)]).vertices().is_empty());
+        // with nothing in the way the whole of the bounds is visible
+        assert_eq!(Polygon::visibility(Point::new(3, 12), &bounds, &[]).vertices().len(), 4);
+    }
+}
@@ -0,0 +1,101 @@
+//! Polygons filling under sampled signals.
+
+use embedded_graphics::geometry::Point;
+use super::{Polygon, StreamingPolygon};
+
+/// The points of `samples` as a polyline, one every `x_step` pixels from `x_start`.
+fn sample_points(samples: &[i32], x_start: i32, x_step: i32) -> impl DoubleEndedIterator<Item = Point> + Clone + '_ {
+    samples.iter().enumerate().map(move |(i, y)| Point::new(x_start + i as i32 * x_step, *y))
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns the area between the polyline through `samples` and the row `baseline_y`: a
+    /// vertex for each sample, one every `x_step` pixels from `x_start`, then the baseline below
+    /// the last sample and below the first.
+    ///
+    /// The vertices are made as they are read rather than collected, so signals of any length
+    /// fill without an intermediate buffer. Samples on either side of the baseline both fill
+    /// towards it; without samples the polygon has no vertices.
+    pub fn area_under_curve(samples: &[i32], x_start: i32, x_step: i32, baseline_y: i32) -> StreamingPolygon<impl Iterator<Item = Point> + Clone + '_> {
+        let last_x = x_start + samples.len().saturating_sub(1) as i32 * x_step;
+        let baseline = (!samples.is_empty()).then_some([Point::new(last_x, baseline_y), Point::new(x_start, baseline_y)]);
+        StreamingPolygon::new(sample_points(samples, x_start, x_step).chain(baseline.into_iter().flatten()))
+    }
+
+    /// Returns the band between two signals sampled at the same columns, as for a range chart:
+    /// `upper` from left to right, then `lower` back from right to left, one sample every
+    /// `x_step` pixels from `x_start`.
+    ///
+    /// Only as many samples as the shorter signal has are used. Like
+    /// [`area_under_curve`](Self::area_under_curve) the vertices are made as they are read.
+    pub fn area_between_curves<'s>(upper: &'s [i32], lower: &'s [i32], x_start: i32, x_step: i32) -> StreamingPolygon<impl Iterator<Item = Point> + Clone + 's> {
+        let len = upper.len().min(lower.len());
+        StreamingPolygon::new(sample_points(&upper[..len], x_start, x_step).chain(sample_points(&lower[..len], x_start, x_step).rev()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::Drawable;
+    use embedded_graphics::geometry::Point;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+    use crate::polygon::Polygon;
+    use crate::polygon::tests::PixelCollector;
+
+    fn sine(count: usize, amplitude: f64, middle: i32) -> Vec<i32> {
+        (0..count).map(|i| middle + (amplitude * (i as f64 * 0.2).sin()).round() as i32).collect()
+    }
+
+    /// The filled rows of column `x`, as `(top, bottom)`, if it is one unbroken run.
+    fn column(pixels: &[Point], x: i32) -> Option<(i32, i32)> {
+        let mut rows: Vec<i32> = pixels.iter().filter(|p| p.x == x).map(|p| p.y).collect();
+        rows.sort();
+        let (top, bottom) = (*rows.first()?, *rows.last()?);
+        rows.windows(2).all(|pair| pair[1] == pair[0] + 1).then_some((top, bottom))
+    }
+
+    fn fill(polygon: impl Drawable<Color = BinaryColor>) -> Vec<Point> {
+        let mut collector = PixelCollector::default();
+        polygon.draw(&mut collector).unwrap();
+        collector.pixels
+    }
+
+    #[test]
+    fn test_area_under_sine() {
+        let samples = sine(64, 10.0, 20);
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let pixels = fill(Polygon::area_under_curve(&samples, 5, 1, 40).into_styled(style));
+        for (i, sample) in samples.iter().enumerate() {
+            assert_eq!(column(&pixels, 5 + i as i32), Some((*sample, 40)), "column {i}");
+        }
+        assert!(pixels.iter().all(|p| (5..69).contains(&p.x)));
+
+        // every second column holds a sample, the ones between follow the line joining them
+        let pixels = fill(Polygon::area_under_curve(&samples, 0, 2, 40).into_styled(style));
+        for (i, sample) in samples.iter().enumerate() {
+            assert_eq!(column(&pixels, 2 * i as i32), Some((*sample, 40)));
+        }
+        let (left, right) = (samples[10], samples[11]);
+        let (top, bottom) = column(&pixels, 21).unwrap();
+        assert_eq!(bottom, 40);
+        assert!(top >= left.min(right) && top <= left.max(right));
+
+        // a signal dipping below the baseline fills up to it
+        let pixels = fill(Polygon::area_under_curve(&[5, 15, 5], 0, 1, 10).into_styled(style));
+        assert_eq!((column(&pixels, 0), column(&pixels, 1), column(&pixels, 2)), (Some((5, 10)), Some((10, 15)), Some((5, 10))));
+        assert_eq!(Polygon::area_under_curve(&[], 0, 1, 10).vertices.count(), 0);
+    }
+
+    #[test]
+    fn test_area_between_curves() {
+        let upper = sine(50, 8.0, 20);
+        let lower: Vec<i32> = upper.iter().map(|y| y + 5 + (*y % 3)).collect();
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let pixels = fill(Polygon::area_between_curves(&upper, &lower[..40], 0, 1).into_styled(style));
+        for i in 0..40 {
+            assert_eq!(column(&pixels, i as i32), Some((upper[i], lower[i])), "column {i}");
+        }
+        assert!(pixels.iter().all(|p| p.x < 40));
+    }
+}
@@ -0,0 +1,319 @@
+//! Reading and writing polygons as Well-Known Text.
+
+use core::fmt;
+use alloc::{vec, vec::Vec};
+use embedded_graphics::geometry::Point;
+use super::{CoordinateMapping, Polygon, PolygonOwned, PolygonWithHoles};
+
+/// What went wrong while parsing WKT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WktErrorKind {
+    /// The geometry is neither `POLYGON` nor `MULTIPOLYGON`.
+    UnsupportedGeometry,
+    /// The given character was expected.
+    Expected(char),
+    /// A coordinate was expected.
+    ExpectedNumber,
+    /// A coordinate does not map to a pixel.
+    OutOfRange,
+    /// Something other than whitespace follows the geometry.
+    TrailingCharacters,
+}
+
+/// An error in WKT, at `offset` bytes into the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WktError {
+    pub offset: usize,
+    pub kind: WktErrorKind,
+}
+
+impl fmt::Display for WktError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            WktErrorKind::UnsupportedGeometry => write!(f, "expected POLYGON or MULTIPOLYGON")?,
+            WktErrorKind::Expected(c) => write!(f, "expected '{c}'")?,
+            WktErrorKind::ExpectedNumber => write!(f, "expected a coordinate")?,
+            WktErrorKind::OutOfRange => write!(f, "coordinate does not map to a pixel")?,
+            WktErrorKind::TrailingCharacters => write!(f, "unexpected text after the geometry")?,
+        }
+        write!(f, " at byte {}", self.offset)
+    }
+}
+
+impl core::error::Error for WktError {}
+
+/// A geometry read from WKT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WktGeometry {
+    Polygon(PolygonWithHoles),
+    MultiPolygon(Vec<PolygonWithHoles>),
+}
+
+impl WktGeometry {
+    /// Returns the polygons of the geometry, a single one for `POLYGON`.
+    pub fn into_polygons(self) -> Vec<PolygonWithHoles> {
+        match self {
+            WktGeometry::Polygon(polygon) => vec![polygon],
+            WktGeometry::MultiPolygon(polygons) => polygons,
+        }
+    }
+
+    /// Writes the geometry as WKT, mapping pixels back to coordinates through `mapping`.
+    pub fn to_wkt(&self, mapping: &CoordinateMapping, mut out: impl fmt::Write) -> fmt::Result {
+        match self {
+            WktGeometry::Polygon(polygon) => polygon.to_wkt(mapping, out),
+            WktGeometry::MultiPolygon(polygons) if polygons.is_empty() => out.write_str("MULTIPOLYGON EMPTY"),
+            WktGeometry::MultiPolygon(polygons) => {
+                out.write_str("MULTIPOLYGON (")?;
+                for (i, polygon) in polygons.iter().enumerate() {
+                    if i > 0 {
+                        out.write_str(", ")?;
+                    }
+                    write_polygon_text(polygon, mapping, &mut out)?;
+                }
+                out.write_str(")")
+            }
+        }
+    }
+}
+
+struct Parser<'t> {
+    text: &'t str,
+    pos: usize,
+    mapping: &'t CoordinateMapping,
+}
+
+impl<'t> Parser<'t> {
+    fn error(&self, kind: WktErrorKind) -> WktError {
+        WktError { offset: self.pos, kind }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos += self.text[self.pos..].len() - self.text[self.pos..].trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.text[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), WktError> {
+        if self.eat(c) { Ok(()) } else { Err(self.error(WktErrorKind::Expected(c))) }
+    }
+
+    /// Reads the next word, as used by geometry keywords and `EMPTY`.
+    fn word(&mut self) -> &'t str {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let length = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        self.pos += length;
+        &rest[..length]
+    }
+
+    fn eat_empty(&mut self) -> bool {
+        let start = self.pos;
+        if self.word().eq_ignore_ascii_case("EMPTY") {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, WktError> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let length = rest.find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'))).unwrap_or(rest.len());
+        let value = rest[..length].parse().map_err(|_| self.error(WktErrorKind::ExpectedNumber))?;
+        self.pos += length;
+        Ok(value)
+    }
+
+    /// Reads a parenthesised ring, dropping the closing vertex that repeats the first.
+    fn ring(&mut self) -> Result<Vec<Point>, WktError> {
+        self.expect('(')?;
+        let mut ring = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let offset = self.pos;
+            let (x, y) = (self.number()?, self.number()?);
+            ring.push(self.mapping.to_pixel(x, y).map_err(|_| WktError { offset, kind: WktErrorKind::OutOfRange })?);
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(')')?;
+        if ring.len() > 1 && ring.first() == ring.last() {
+            let _ = ring.pop();
+        }
+        Ok(ring)
+    }
+
+    fn polygon(&mut self) -> Result<PolygonWithHoles, WktError> {
+        if self.eat_empty() {
+            return Ok(PolygonWithHoles::default());
+        }
+        self.expect('(')?;
+        let exterior = self.ring()?;
+        let mut holes = Vec::new();
+        while self.eat(',') {
+            holes.push(self.ring()?);
+        }
+        self.expect(')')?;
+        Ok(PolygonWithHoles::new(exterior, holes))
+    }
+
+    fn multi_polygon(&mut self) -> Result<Vec<PolygonWithHoles>, WktError> {
+        if self.eat_empty() {
+            return Ok(Vec::new());
+        }
+        self.expect('(')?;
+        let mut polygons = vec![self.polygon()?];
+        while self.eat(',') {
+            polygons.push(self.polygon()?);
+        }
+        self.expect(')')?;
+        Ok(polygons)
+    }
+}
+
+fn write_ring(ring: &Polygon, mapping: &CoordinateMapping, out: &mut impl fmt::Write) -> fmt::Result {
+    out.write_str("(")?;
+    // WKT rings repeat their first vertex to close
+    for (i, v) in ring.vertices().iter().chain(ring.vertices().first()).enumerate() {
+        let (x, y) = mapping.from_pixel(*v + ring.translate());
+        write!(out, "{}{x} {y}", if i > 0 { ", " } else { "" })?;
+    }
+    out.write_str(")")
+}
+
+fn write_polygon_text(polygon: &PolygonWithHoles, mapping: &CoordinateMapping, out: &mut impl fmt::Write) -> fmt::Result {
+    if polygon.exterior.is_empty() {
+        return out.write_str("EMPTY");
+    }
+    out.write_str("(")?;
+    write_ring(&polygon.exterior(), mapping, out)?;
+    for hole in polygon.holes() {
+        out.write_str(", ")?;
+        write_ring(&hole, mapping, out)?;
+    }
+    out.write_str(")")
+}
+
+impl PolygonOwned {
+    /// Parses a WKT `POLYGON` or `MULTIPOLYGON`, mapping coordinates to pixels through `mapping`.
+    ///
+    /// Keywords are case-insensitive and `EMPTY` geometries are accepted. Interior rings become
+    /// the holes of a [`PolygonWithHoles`], and the closing vertex each WKT ring repeats is
+    /// dropped.
+    pub fn from_wkt(text: &str, mapping: &CoordinateMapping) -> Result<WktGeometry, WktError> {
+        let mut parser = Parser { text, pos: 0, mapping };
+        parser.skip_whitespace();
+        let start = parser.pos;
+        let keyword = parser.word();
+        let geometry = if keyword.eq_ignore_ascii_case("POLYGON") {
+            WktGeometry::Polygon(parser.polygon()?)
+        } else if keyword.eq_ignore_ascii_case("MULTIPOLYGON") {
+            WktGeometry::MultiPolygon(parser.multi_polygon()?)
+        } else {
+            return Err(WktError { offset: start, kind: WktErrorKind::UnsupportedGeometry });
+        };
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error(WktErrorKind::TrailingCharacters));
+        }
+        Ok(geometry)
+    }
+
+    /// Writes the polygon as a WKT `POLYGON`, mapping pixels, translate offset included, back to
+    /// coordinates through `mapping`.
+    pub fn to_wkt(&self, mapping: &CoordinateMapping, out: impl fmt::Write) -> fmt::Result {
+        PolygonWithHoles::from(self.clone()).to_wkt(mapping, out)
+    }
+}
+
+impl PolygonWithHoles {
+    /// Writes the polygon as a WKT `POLYGON` with one interior ring per hole, mapping pixels,
+    /// translate offset included, back to coordinates through `mapping`.
+    pub fn to_wkt(&self, mapping: &CoordinateMapping, mut out: impl fmt::Write) -> fmt::Result {
+        out.write_str("POLYGON ")?;
+        write_polygon_text(self, mapping, &mut out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::geometry::Point;
+    use crate::polygon::tests::ring;
+    use crate::polygon::{CoordinateMapping, PolygonOwned, PolygonWithHoles, WktError, WktErrorKind, WktGeometry};
+
+    fn parse(text: &str) -> Result<WktGeometry, WktError> {
+        PolygonOwned::from_wkt(text, &CoordinateMapping::IDENTITY)
+    }
+
+    #[test]
+    fn test_wkt_with_holes() {
+        let polygon = parse("POLYGON ((35 10, 45 45, 15 40, 10 20, 35 10),(20 30, 35 35, 30 20, 20 30))").unwrap();
+        assert_eq!(polygon, WktGeometry::Polygon(PolygonWithHoles::new(
+            ring(&[[35, 10], [45, 45], [15, 40], [10, 20]]),
+            vec![ring(&[[20, 30], [35, 35], [30, 20]])],
+        )));
+
+        let multi = parse("MULTIPOLYGON (((40 40, 20 45, 45 30, 40 40)),\n((20 35, 10 30, 10 10, 30 5, 45 20, 20 35),\n(30 20, 20 15, 20 25, 30 20)))").unwrap();
+        let polygons = multi.into_polygons();
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[0], PolygonWithHoles::new(ring(&[[40, 40], [20, 45], [45, 30]]), vec![]));
+        assert_eq!(polygons[1].holes, vec![ring(&[[30, 20], [20, 15], [20, 25]])]);
+
+        // geographic coordinates mapped to pixels
+        let mapping = CoordinateMapping { scale: (1000.0, -1000.0), offset: (122_500.0, 37_800.0) };
+        let geofence = PolygonOwned::from_wkt("polygon((-122.4194 37.7749,-122.4 37.78,-122.41 37.79,-122.4194 37.7749))", &mapping).unwrap();
+        assert_eq!(geofence.into_polygons()[0].exterior, ring(&[[81, 25], [100, 20], [90, 10]]));
+
+        assert_eq!(parse("POLYGON EMPTY").unwrap(), WktGeometry::Polygon(PolygonWithHoles::default()));
+        assert_eq!(parse(" MultiPolygon empty ").unwrap(), WktGeometry::MultiPolygon(vec![]));
+    }
+
+    #[test]
+    fn test_wkt_round_trip() {
+        let mut polygon = PolygonWithHoles::new(ring(&[[0, 0], [40, 0], [40, 30], [0, 30]]), vec![ring(&[[5, 5], [15, 5], [10, 12]]), ring(&[[20, 20], [30, 20], [25, 25]])]);
+        polygon.translate = Point::new(3, 4);
+        let mapping = CoordinateMapping::new(4.0, (1.0, 0.0));
+        let mut text = String::new();
+        polygon.to_wkt(&mapping, &mut text).unwrap();
+        assert_eq!(text, "POLYGON ((0.5 1, 10.5 1, 10.5 8.5, 0.5 8.5, 0.5 1), (1.75 2.25, 4.25 2.25, 3 4, 1.75 2.25), (5.5 6, 8 6, 6.75 7.25, 5.5 6))");
+        let mut moved = polygon.clone();
+        moved.exterior.iter_mut().chain(moved.holes.iter_mut().flatten()).for_each(|v| *v += moved.translate);
+        moved.translate = Point::zero();
+        assert_eq!(PolygonOwned::from_wkt(&text, &mapping).unwrap(), WktGeometry::Polygon(moved.clone()));
+
+        let multi = WktGeometry::MultiPolygon(vec![moved.clone(), PolygonWithHoles::new(ring(&[[-3, -3], [-1, -3], [-2, -1]]), vec![])]);
+        let mut text = String::new();
+        multi.to_wkt(&CoordinateMapping::IDENTITY, &mut text).unwrap();
+        assert_eq!(parse(&text).unwrap(), multi);
+
+        let mut text = String::new();
+        PolygonOwned::new(ring(&[[1, 2], [3, 4], [5, 0]])).to_wkt(&CoordinateMapping::IDENTITY, &mut text).unwrap();
+        assert_eq!(text, "POLYGON ((1 2, 3 4, 5 0, 1 2))");
+    }
+
+    #[test]
+    fn test_malformed_wkt() {
+        let error = |offset, kind| Err(WktError { offset, kind });
+        assert_eq!(parse("LINESTRING (0 0, 1 1)"), error(0, WktErrorKind::UnsupportedGeometry));
+        assert_eq!(parse("POLYGON (0 0, 1 1)"), error(9, WktErrorKind::Expected('(')));
+        assert_eq!(parse("POLYGON ((0 0, 1 1, 2)"), error(21, WktErrorKind::ExpectedNumber));
+        assert_eq!(parse("POLYGON ((0 0, 1 1 2 2))"), error(19, WktErrorKind::Expected(')')));
+        assert_eq!(parse("POLYGON ((0 0, 1 x, 0 0))"), error(17, WktErrorKind::ExpectedNumber));
+        assert_eq!(parse("POLYGON ((0 0, 1 1, 0 0)"), error(24, WktErrorKind::Expected(')')));
+        assert_eq!(parse("POLYGON ((0 0, 1 1, 0 0)) x"), error(26, WktErrorKind::TrailingCharacters));
+        assert_eq!(parse("POLYGON ((0 0, 1e20 1, 0 0))"), error(15, WktErrorKind::OutOfRange));
+        assert_eq!(parse("POLYGON ((0 0, 1 1, 0 0)) x").unwrap_err().to_string(), "unexpected text after the geometry at byte 26");
+        assert_eq!(parse("POLYGON ((0 0, 1 ").unwrap_err().kind, WktErrorKind::ExpectedNumber);
+    }
+}